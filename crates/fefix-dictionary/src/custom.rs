@@ -0,0 +1,428 @@
+use super::{
+    ComponentData, DatatypeData, Dictionary, FieldData, FixDatatype, FixmlComponentAttributes,
+    LayoutItemData, LayoutItemKindData, TagU32,
+};
+use smartstring::alias::String as SmartString;
+use std::fmt;
+
+/// The definition of a custom field, for [`Dictionary::add_field`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDef {
+    /// The tag of the field, e.g. a user-defined one (`5000` to `9999`).
+    pub tag: TagU32,
+    /// The name of the field, which must be unique across the
+    /// [`Dictionary`].
+    pub name: String,
+    /// The datatype of the field. [`FixDatatype::NumInGroup`] fields can
+    /// start custom repeating groups (see [`Dictionary::add_group`]).
+    pub datatype: FixDatatype,
+}
+
+/// The definition of a custom component, i.e. a named sequence of fields, for
+/// [`Dictionary::add_component`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentDef {
+    /// The name of the component, which must be unique across the
+    /// [`Dictionary`].
+    pub name: String,
+    /// The tags of all fields of the component, in order. The `NumInGroup`
+    /// tags of custom repeating groups stand for the whole group.
+    pub fields: Vec<u32>,
+}
+
+/// The definition of a custom repeating group, for [`Dictionary::add_group`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupDef {
+    /// The `NumInGroup` field that holds the number of entries.
+    pub count_tag: u32,
+    /// The first field of every entry.
+    pub delimiter_tag: u32,
+    /// The tags of all other fields of every entry, in order. The `NumInGroup`
+    /// tags of other custom repeating groups stand for nested groups.
+    pub fields: Vec<u32>,
+}
+
+/// The type returned in the event of an error by [`Dictionary::add_field`],
+/// [`Dictionary::add_component`], [`Dictionary::add_group`], and related
+/// methods. The [`Dictionary`] is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DefinitionError {
+    /// A field with the same tag or name already exists.
+    DuplicateField(u32),
+    /// A component with the same name already exists.
+    DuplicateComponent(String),
+    /// The `NumInGroup` field already starts another repeating group.
+    DuplicateGroup(u32),
+    /// This tag appears more than once within the same definition.
+    RepeatedTag(u32),
+    /// There's no field with this tag.
+    UnknownField(u32),
+    /// There's no component with this name.
+    UnknownComponent(String),
+    /// There's no message with this `MsgType <35>`.
+    UnknownMessage(String),
+    /// The field with this tag is not a `NumInGroup` field, so it can't start
+    /// a repeating group.
+    NotNumInGroup(u32),
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateField(tag) => write!(f, "Field {} is already defined.", tag),
+            Self::DuplicateComponent(name) => {
+                write!(f, "Component '{}' is already defined.", name)
+            }
+            Self::DuplicateGroup(tag) => {
+                write!(f, "Field {} already starts a repeating group.", tag)
+            }
+            Self::RepeatedTag(tag) => write!(f, "Field {} appears more than once.", tag),
+            Self::UnknownField(tag) => write!(f, "Field {} is not defined.", tag),
+            Self::UnknownComponent(name) => write!(f, "Component '{}' is not defined.", name),
+            Self::UnknownMessage(msg_type) => write!(f, "Message '{}' is not defined.", msg_type),
+            Self::NotNumInGroup(tag) => write!(f, "Field {} is not a `NumInGroup` field.", tag),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+/// Runtime customization, e.g. for proprietary extensions of FIX messages.
+///
+/// Encoders and decoders look up definitions when they're created, so they must
+/// be created after all customizations.
+impl Dictionary {
+    /// Adds a custom field to `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix_dictionary::{Dictionary, FieldDef, FixDatatype, TagU32};
+    ///
+    /// let mut dict = Dictionary::fix44();
+    /// dict.add_field(FieldDef {
+    ///     tag: TagU32::new(5001).unwrap(),
+    ///     name: "StrategyName".to_string(),
+    ///     datatype: FixDatatype::String,
+    /// })
+    /// .unwrap();
+    /// assert_eq!(dict.field_by_name("StrategyName").unwrap().tag().get(), 5001);
+    /// assert!(dict.version_info().is_customized);
+    /// ```
+    pub fn add_field(&mut self, def: FieldDef) -> Result<(), DefinitionError> {
+        let tag = def.tag.get();
+        if self.field_by_tag(tag).is_some() || self.field_by_name(&def.name).is_some() {
+            return Err(DefinitionError::DuplicateField(tag));
+        }
+        if self.datatype_by_name(def.datatype.name()).is_none() {
+            self.insert_datatype(DatatypeData {
+                datatype: def.datatype,
+                description: String::new(),
+                examples: Vec::new(),
+            });
+        }
+        self.insert_field(FieldData {
+            name: def.name.as_str().into(),
+            tag,
+            data_type_name: def.datatype.name().into(),
+            associated_data_tag: None,
+            value_restrictions: None,
+            abbr_name: None,
+            base_category_id: None,
+            base_category_abbr_name: None,
+            required: false,
+            description: None,
+        });
+        self.is_customized = true;
+        Ok(())
+    }
+
+    /// Adds a custom component to `self`, which can then be referenced by
+    /// [`Dictionary::add_component_to_message`].
+    pub fn add_component(&mut self, def: ComponentDef) -> Result<(), DefinitionError> {
+        if self.component_by_name(&def.name).is_some() {
+            return Err(DefinitionError::DuplicateComponent(def.name));
+        }
+        let layout_items = self.custom_layout_items(&def.fields)?;
+        self.insert_custom_component(def.name.as_str().into(), false, layout_items);
+        Ok(())
+    }
+
+    /// Adds a custom repeating group to `self`, which can then be referenced
+    /// by [`Dictionary::add_group_to_message`], or by the `fields` of other
+    /// custom components and groups. Decoders and encoders handle custom
+    /// repeating groups just like built-in ones.
+    ///
+    /// The group is registered as a repeating [`Component`](super::Component)
+    /// named after its `NumInGroup` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix_dictionary::{DefinitionError, Dictionary, FieldDef, FixDatatype, GroupDef, TagU32};
+    ///
+    /// let mut dict = Dictionary::fix44();
+    /// for (tag, name, datatype) in [
+    ///     (5000, "NoLimits", FixDatatype::NumInGroup),
+    ///     (5001, "LimitType", FixDatatype::String),
+    ///     (5002, "LimitAmt", FixDatatype::Amt),
+    /// ] {
+    ///     let tag = TagU32::new(tag).unwrap();
+    ///     let name = name.to_string();
+    ///     dict.add_field(FieldDef { tag, name, datatype }).unwrap();
+    /// }
+    /// let group = GroupDef {
+    ///     count_tag: 5000,
+    ///     delimiter_tag: 5001,
+    ///     fields: vec![5002],
+    /// };
+    /// dict.add_group(group.clone()).unwrap();
+    /// dict.add_group_to_message("D", 5000).unwrap();
+    /// assert!(dict.component_by_name("NoLimits").unwrap().is_group());
+    /// assert_eq!(dict.add_group(group), Err(DefinitionError::DuplicateGroup(5000)));
+    /// ```
+    pub fn add_group(&mut self, def: GroupDef) -> Result<(), DefinitionError> {
+        let count_field = self
+            .field_by_tag(def.count_tag)
+            .ok_or(DefinitionError::UnknownField(def.count_tag))?;
+        if !count_field.is_num_in_group() {
+            return Err(DefinitionError::NotNumInGroup(def.count_tag));
+        }
+        if self.starts_group(def.count_tag) {
+            return Err(DefinitionError::DuplicateGroup(def.count_tag));
+        }
+        let name: SmartString = count_field.name().into();
+        if self.component_by_name(&name).is_some() {
+            return Err(DefinitionError::DuplicateComponent(name.to_string()));
+        }
+        let mut tags = vec![def.delimiter_tag];
+        tags.extend_from_slice(&def.fields);
+        if tags.contains(&def.count_tag) {
+            return Err(DefinitionError::RepeatedTag(def.count_tag));
+        }
+        let items = self.custom_layout_items(&tags)?;
+        let group = LayoutItemData {
+            required: false,
+            kind: LayoutItemKindData::Group {
+                len_field_tag: def.count_tag,
+                items,
+            },
+        };
+        self.insert_custom_component(name, true, vec![group]);
+        Ok(())
+    }
+
+    /// Appends the component named `name` to the definition of the message
+    /// with `MsgType <35>` equal to `msg_type`, e.g. a component added with
+    /// [`Dictionary::add_component`].
+    pub fn add_component_to_message(
+        &mut self,
+        msg_type: &str,
+        name: &str,
+    ) -> Result<(), DefinitionError> {
+        if self.component_by_name(name).is_none() {
+            return Err(DefinitionError::UnknownComponent(name.to_string()));
+        }
+        let message = self
+            .messages_by_msgtype
+            .get_mut(msg_type)
+            .ok_or_else(|| DefinitionError::UnknownMessage(msg_type.to_string()))?;
+        message.layout_items.push(LayoutItemData {
+            required: false,
+            kind: LayoutItemKindData::Component { name: name.into() },
+        });
+        self.is_customized = true;
+        Ok(())
+    }
+
+    /// Like [`Dictionary::add_component_to_message`], but for the custom
+    /// repeating group that starts with `count_tag` (see
+    /// [`Dictionary::add_group`]).
+    pub fn add_group_to_message(
+        &mut self,
+        msg_type: &str,
+        count_tag: u32,
+    ) -> Result<(), DefinitionError> {
+        let name = self
+            .custom_group_name(count_tag)
+            .ok_or(DefinitionError::UnknownField(count_tag))?;
+        self.add_component_to_message(msg_type, &name)
+    }
+
+    fn insert_custom_component(
+        &mut self,
+        name: SmartString,
+        is_repeating: bool,
+        layout_items: Vec<LayoutItemData>,
+    ) {
+        self.insert_component(ComponentData {
+            id: 0,
+            component_type: FixmlComponentAttributes::Block {
+                is_repeating,
+                is_implicit: false,
+                is_optimized: false,
+            },
+            layout_items,
+            category_name: "".into(),
+            name,
+            abbr_name: None,
+        });
+        self.is_customized = true;
+    }
+
+    fn custom_layout_items(&self, tags: &[u32]) -> Result<Vec<LayoutItemData>, DefinitionError> {
+        let mut items = Vec::with_capacity(tags.len());
+        for (i, tag) in tags.iter().copied().enumerate() {
+            if tags[..i].contains(&tag) {
+                return Err(DefinitionError::RepeatedTag(tag));
+            }
+            if self.field_by_tag(tag).is_none() {
+                return Err(DefinitionError::UnknownField(tag));
+            }
+            let kind = match self.custom_group_name(tag) {
+                Some(name) => LayoutItemKindData::Component { name },
+                None => LayoutItemKindData::Field { tag },
+            };
+            items.push(LayoutItemData {
+                required: false,
+                kind,
+            });
+        }
+        Ok(items)
+    }
+
+    // Custom repeating groups are the only components which consist of a
+    // single group.
+    fn custom_group_name(&self, count_tag: u32) -> Option<SmartString> {
+        let name = self.field_by_tag(count_tag)?.name();
+        let component = self.components_by_name.get(name)?;
+        match &component.layout_items[..] {
+            [LayoutItemData {
+                kind: LayoutItemKindData::Group { len_field_tag, .. },
+                ..
+            }] if *len_field_tag == count_tag => Some(name.into()),
+            _ => None,
+        }
+    }
+
+    fn starts_group(&self, count_tag: u32) -> bool {
+        fn any_group(items: &[LayoutItemData], count_tag: u32) -> bool {
+            items.iter().any(|item| match &item.kind {
+                LayoutItemKindData::Group {
+                    len_field_tag,
+                    items,
+                } => *len_field_tag == count_tag || any_group(items, count_tag),
+                _ => false,
+            })
+        }
+
+        self.messages_by_msgtype
+            .values()
+            .any(|message| any_group(&message.layout_items, count_tag))
+            || self
+                .components_by_name
+                .values()
+                .any(|component| any_group(&component.layout_items, count_tag))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn field(tag: u32, name: &str, datatype: FixDatatype) -> FieldDef {
+        FieldDef {
+            tag: TagU32::new(tag).unwrap(),
+            name: name.to_string(),
+            datatype,
+        }
+    }
+
+    #[test]
+    fn duplicate_fields_are_refused() {
+        let mut dict = Dictionary::fix44();
+        assert_eq!(
+            dict.add_field(field(55, "Ticker", FixDatatype::String)),
+            Err(DefinitionError::DuplicateField(55))
+        );
+        assert_eq!(
+            dict.add_field(field(5000, "Symbol", FixDatatype::String)),
+            Err(DefinitionError::DuplicateField(5000))
+        );
+        assert!(!dict.version_info().is_customized);
+    }
+
+    #[test]
+    fn inconsistent_groups_are_refused() {
+        let mut dict = Dictionary::fix44();
+        dict.add_field(field(5000, "NoLimits", FixDatatype::NumInGroup))
+            .unwrap();
+        dict.add_field(field(5001, "LimitType", FixDatatype::String))
+            .unwrap();
+        let group = |count_tag, delimiter_tag, fields| GroupDef {
+            count_tag,
+            delimiter_tag,
+            fields,
+        };
+        // `NoMDEntries <268>` is a built-in group.
+        assert_eq!(
+            dict.add_group(group(268, 5001, vec![])),
+            Err(DefinitionError::DuplicateGroup(268))
+        );
+        assert_eq!(
+            dict.add_group(group(5001, 55, vec![])),
+            Err(DefinitionError::NotNumInGroup(5001))
+        );
+        assert_eq!(
+            dict.add_group(group(5000, 5001, vec![5001])),
+            Err(DefinitionError::RepeatedTag(5001))
+        );
+        assert_eq!(
+            dict.add_group(group(5000, 5001, vec![5999])),
+            Err(DefinitionError::UnknownField(5999))
+        );
+        assert!(dict.component_by_name("NoLimits").is_none());
+        assert_eq!(
+            dict.add_group_to_message("D", 5000),
+            Err(DefinitionError::UnknownField(5000))
+        );
+    }
+
+    #[test]
+    fn nested_custom_groups() {
+        let mut dict = Dictionary::fix44();
+        dict.add_field(field(5000, "NoLimits", FixDatatype::NumInGroup))
+            .unwrap();
+        dict.add_field(field(5001, "NoLimitLevels", FixDatatype::NumInGroup))
+            .unwrap();
+        dict.add_group(GroupDef {
+            count_tag: 5001,
+            delimiter_tag: 44,
+            fields: vec![],
+        })
+        .unwrap();
+        dict.add_group(GroupDef {
+            count_tag: 5000,
+            delimiter_tag: 55,
+            fields: vec![5001],
+        })
+        .unwrap();
+        dict.add_component(ComponentDef {
+            name: "Limits".to_string(),
+            fields: vec![1, 5000],
+        })
+        .unwrap();
+        dict.add_component_to_message("D", "Limits").unwrap();
+        assert_eq!(
+            dict.add_component_to_message("ZZZ", "Limits"),
+            Err(DefinitionError::UnknownMessage("ZZZ".to_string()))
+        );
+        let component = dict.component_by_name("Limits").unwrap();
+        let tags: Vec<String> = component.items().map(|item| item.tag_text()).collect();
+        assert_eq!(tags, vec!["Account", "NoLimits"]);
+        let message = dict.message_by_msgtype("D").unwrap();
+        let limits = message.layout().last().unwrap();
+        assert_eq!(limits.tag_text(), "Limits");
+    }
+}
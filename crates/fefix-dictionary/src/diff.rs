@@ -0,0 +1,206 @@
+use super::{Dictionary, Field, FixDatatype, LayoutItem, LayoutItemKind, Message};
+use std::collections::BTreeSet;
+
+/// The differences between two [`Dictionary`] values, as returned by
+/// [`Dictionary::diff`].
+///
+/// All lists are sorted by tag or message type, so the same two dictionaries
+/// always result in the same [`DictionaryDiff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DictionaryDiff {
+    /// Tags of the fields that only exist in the new dictionary.
+    pub added_fields: Vec<u32>,
+    /// Tags of the fields that only exist in the old dictionary.
+    pub removed_fields: Vec<u32>,
+    /// Fields that exist in both dictionaries, but with different definitions.
+    pub changed_fields: Vec<FieldDiff>,
+    /// Message types that only exist in the new dictionary.
+    pub added_messages: Vec<String>,
+    /// Message types that only exist in the old dictionary.
+    pub removed_messages: Vec<String>,
+    /// Messages that exist in both dictionaries, but with different sets of
+    /// required fields.
+    pub changed_messages: Vec<MessageDiff>,
+}
+
+impl DictionaryDiff {
+    /// Returns `true` if and only if no difference was found.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The differences between the definitions of a field with the same tag in
+/// two [`Dictionary`] values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FieldDiff {
+    /// The tag of the field.
+    pub tag: u32,
+    /// The old and new names of the field, if they're different.
+    pub name: Option<(String, String)>,
+    /// The old and new datatypes of the field, if they're different.
+    pub datatype: Option<(FixDatatype, FixDatatype)>,
+    /// Enum values that only exist in the new dictionary.
+    pub added_enums: Vec<String>,
+    /// Enum values that only exist in the old dictionary.
+    pub removed_enums: Vec<String>,
+}
+
+/// The differences between the definitions of a message with the same message
+/// type in two [`Dictionary`] values.
+///
+/// Only fields that are required at the top level of the message are taken
+/// into account, including those inside required components. Fields inside
+/// repeating groups are not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MessageDiff {
+    /// The message type, i.e. `MsgType <35>`.
+    pub msg_type: String,
+    /// Tags of the fields that are required only by the new dictionary.
+    pub newly_required: Vec<u32>,
+    /// Tags of the fields that are required only by the old dictionary.
+    pub no_longer_required: Vec<u32>,
+}
+
+pub(super) fn diff(old: &Dictionary, new: &Dictionary) -> DictionaryDiff {
+    let old_tags: BTreeSet<u32> = old.fields_by_tags.keys().copied().collect();
+    let new_tags: BTreeSet<u32> = new.fields_by_tags.keys().copied().collect();
+    let old_msg_types: BTreeSet<&str> =
+        old.messages_by_msgtype.keys().map(|s| s.as_str()).collect();
+    let new_msg_types: BTreeSet<&str> =
+        new.messages_by_msgtype.keys().map(|s| s.as_str()).collect();
+    DictionaryDiff {
+        added_fields: new_tags.difference(&old_tags).copied().collect(),
+        removed_fields: old_tags.difference(&new_tags).copied().collect(),
+        changed_fields: old_tags
+            .intersection(&new_tags)
+            .filter_map(|tag| {
+                let old_field = old.field_by_tag(*tag)?;
+                let new_field = new.field_by_tag(*tag)?;
+                field_diff(old_field, new_field)
+            })
+            .collect(),
+        added_messages: to_strings(new_msg_types.difference(&old_msg_types)),
+        removed_messages: to_strings(old_msg_types.difference(&new_msg_types)),
+        changed_messages: old_msg_types
+            .intersection(&new_msg_types)
+            .filter_map(|msg_type| {
+                let old_msg = old.message_by_msgtype(msg_type)?;
+                let new_msg = new.message_by_msgtype(msg_type)?;
+                message_diff(old_msg, new_msg)
+            })
+            .collect(),
+    }
+}
+
+fn field_diff(old: Field, new: Field) -> Option<FieldDiff> {
+    let old_enums = enum_values(old);
+    let new_enums = enum_values(new);
+    let diff = FieldDiff {
+        tag: old.tag().get(),
+        name: (old.name() != new.name()).then(|| (old.name().to_string(), new.name().to_string())),
+        datatype: (old.fix_datatype() != new.fix_datatype())
+            .then(|| (old.fix_datatype(), new.fix_datatype())),
+        added_enums: to_strings(new_enums.difference(&old_enums)),
+        removed_enums: to_strings(old_enums.difference(&new_enums)),
+    };
+    let is_unchanged = diff.name.is_none()
+        && diff.datatype.is_none()
+        && diff.added_enums.is_empty()
+        && diff.removed_enums.is_empty();
+    if is_unchanged {
+        None
+    } else {
+        Some(diff)
+    }
+}
+
+fn message_diff(old: Message, new: Message) -> Option<MessageDiff> {
+    let mut old_required = BTreeSet::new();
+    collect_required_tags(old.layout(), &mut old_required);
+    let mut new_required = BTreeSet::new();
+    collect_required_tags(new.layout(), &mut new_required);
+    if old_required == new_required {
+        return None;
+    }
+    Some(MessageDiff {
+        msg_type: old.msg_type().to_string(),
+        newly_required: new_required.difference(&old_required).copied().collect(),
+        no_longer_required: old_required.difference(&new_required).copied().collect(),
+    })
+}
+
+fn collect_required_tags<'a, I>(items: I, tags: &mut BTreeSet<u32>)
+where
+    I: Iterator<Item = LayoutItem<'a>>,
+{
+    for item in items.filter(LayoutItem::required) {
+        match item.kind() {
+            LayoutItemKind::Field(field) | LayoutItemKind::Group(field, _) => {
+                tags.insert(field.tag().get());
+            }
+            LayoutItemKind::Component(component) => {
+                collect_required_tags(component.items(), tags);
+            }
+        }
+    }
+}
+
+fn enum_values<'a>(field: Field<'a>) -> BTreeSet<&'a str> {
+    field
+        .1
+        .value_restrictions
+        .iter()
+        .flatten()
+        .map(|field_enum| field_enum.value.as_str())
+        .collect()
+}
+
+fn to_strings<'a, I>(strings: I) -> Vec<String>
+where
+    I: Iterator<Item = &'a &'a str>,
+{
+    strings.map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_dictionary_has_no_differences() {
+        let dict = Dictionary::fix44();
+        assert!(dict.diff(&dict).is_empty());
+    }
+
+    #[cfg(feature = "fix42")]
+    #[test]
+    fn fix42_to_fix44() {
+        let fix42 = Dictionary::fix42();
+        let fix44 = Dictionary::fix44();
+        let diff = fix42.diff(&fix44);
+        // `TradeCaptureReport <AE>` was introduced by FIX 4.3.
+        assert!(diff.added_messages.contains(&"AE".to_string()));
+        assert!(diff.removed_messages.is_empty());
+        assert!(diff.added_fields.contains(&453));
+        assert!(diff.added_fields.windows(2).all(|w| w[0] < w[1]));
+        // `SecurityType <167>` got many new enum values.
+        let security_type = diff.changed_fields.iter().find(|f| f.tag == 167).unwrap();
+        assert!(security_type.added_enums.contains(&"EUCORP".to_string()));
+        // `HandlInst <21>` is optional since FIX 4.3.
+        let new_order_single = diff
+            .changed_messages
+            .iter()
+            .find(|m| m.msg_type == "D")
+            .unwrap();
+        assert!(new_order_single.no_longer_required.contains(&21));
+        // Reversing the diff swaps additions and removals.
+        let reversed = fix44.diff(&fix42);
+        assert_eq!(reversed.removed_fields, diff.added_fields);
+        assert_eq!(reversed.removed_messages, diff.added_messages);
+        assert_eq!(diff, fix42.diff(&fix44));
+    }
+}
@@ -1,12 +1,16 @@
 //! Access to FIX Dictionary reference and message specifications.
 
+mod custom;
+mod diff;
 mod quickfix;
 
+pub use custom::{ComponentDef, DefinitionError, FieldDef, GroupDef};
 pub use datatype::FixDatatype;
+pub use diff::{DictionaryDiff, FieldDiff, MessageDiff};
 use fnv::FnvHashMap;
 use quickfix::{ParseDictionaryError, QuickFixReader};
 use smartstring::alias::String as SmartString;
-use std::{fmt, sync::Arc};
+use std::{fmt, sync::Arc, sync::OnceLock};
 
 /// Type alias for FIX tags: 32-bit unsigned integers, strictly positive.
 pub type TagU32 = std::num::NonZeroU32;
@@ -54,6 +58,304 @@ pub enum FieldLocation {
 /// A mapping from FIX version strings to [`Dictionary`] values.
 pub type Dictionaries = FnvHashMap<String, Arc<Dictionary>>;
 
+/// A FIX version for which a built-in [`Dictionary`] is available. Only
+/// versions enabled via feature flags are present.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AppVersion {
+    /// FIX 4.0.
+    #[cfg(feature = "fix40")]
+    Fix40,
+    /// FIX 4.1.
+    #[cfg(feature = "fix41")]
+    Fix41,
+    /// FIX 4.2.
+    #[cfg(feature = "fix42")]
+    Fix42,
+    /// FIX 4.3.
+    #[cfg(feature = "fix43")]
+    Fix43,
+    /// FIX 4.4.
+    Fix44,
+    /// FIX 5.0.
+    #[cfg(feature = "fix50")]
+    Fix50,
+    /// FIX 5.0 SP1.
+    #[cfg(feature = "fix50sp1")]
+    Fix50Sp1,
+    /// FIX 5.0 SP2.
+    #[cfg(feature = "fix50sp2")]
+    Fix50Sp2,
+    /// FIXT 1.1.
+    #[cfg(feature = "fixt11")]
+    Fixt11,
+}
+
+impl AppVersion {
+    /// Returns the version string of `self`, as returned by
+    /// [`Dictionary::version`].
+    ///
+    /// ```
+    /// use fefix_dictionary::AppVersion;
+    ///
+    /// assert_eq!(AppVersion::Fix44.version(), "FIX.4.4");
+    /// ```
+    pub fn version(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "fix40")]
+            Self::Fix40 => "FIX.4.0",
+            #[cfg(feature = "fix41")]
+            Self::Fix41 => "FIX.4.1",
+            #[cfg(feature = "fix42")]
+            Self::Fix42 => "FIX.4.2",
+            #[cfg(feature = "fix43")]
+            Self::Fix43 => "FIX.4.3",
+            Self::Fix44 => "FIX.4.4",
+            #[cfg(feature = "fix50")]
+            Self::Fix50 => "FIX.5.0",
+            #[cfg(feature = "fix50sp1")]
+            Self::Fix50Sp1 => "FIX.5.0-SP1",
+            #[cfg(feature = "fix50sp2")]
+            Self::Fix50Sp2 => "FIX.5.0-SP2",
+            #[cfg(feature = "fixt11")]
+            Self::Fixt11 => "FIXT.1.1",
+        }
+    }
+
+    /// Returns the [`AppVersion`] with the given version string, if any. This
+    /// is the inverse of [`AppVersion::version`].
+    ///
+    /// ```
+    /// use fefix_dictionary::AppVersion;
+    ///
+    /// assert_eq!(AppVersion::from_version("FIX.4.4"), Some(AppVersion::Fix44));
+    /// assert_eq!(AppVersion::from_version("FIX.9.9"), None);
+    /// ```
+    pub fn from_version(version: &str) -> Option<Self> {
+        match version {
+            #[cfg(feature = "fix40")]
+            "FIX.4.0" => Some(Self::Fix40),
+            #[cfg(feature = "fix41")]
+            "FIX.4.1" => Some(Self::Fix41),
+            #[cfg(feature = "fix42")]
+            "FIX.4.2" => Some(Self::Fix42),
+            #[cfg(feature = "fix43")]
+            "FIX.4.3" => Some(Self::Fix43),
+            "FIX.4.4" => Some(Self::Fix44),
+            #[cfg(feature = "fix50")]
+            "FIX.5.0" => Some(Self::Fix50),
+            #[cfg(feature = "fix50sp1")]
+            "FIX.5.0-SP1" => Some(Self::Fix50Sp1),
+            #[cfg(feature = "fix50sp2")]
+            "FIX.5.0-SP2" => Some(Self::Fix50Sp2),
+            #[cfg(feature = "fixt11")]
+            "FIXT.1.1" => Some(Self::Fixt11),
+            _ => None,
+        }
+    }
+
+    /// Returns the `ApplVerID <1128>` code of `self`, e.g. for
+    /// `DefaultApplVerID <1137>`, as per the standard mapping:
+    ///
+    /// |Code|Version    |
+    /// |----|-----------|
+    /// |`0` |FIX.2.7    |
+    /// |`1` |FIX.3.0    |
+    /// |`2` |FIX.4.0    |
+    /// |`3` |FIX.4.1    |
+    /// |`4` |FIX.4.2    |
+    /// |`5` |FIX.4.3    |
+    /// |`6` |FIX.4.4    |
+    /// |`7` |FIX.5.0    |
+    /// |`8` |FIX.5.0-SP1|
+    /// |`9` |FIX.5.0-SP2|
+    /// |`10`|FIX Latest |
+    ///
+    /// FIXT 1.1 is a session layer version and it has no code.
+    ///
+    /// ```
+    /// use fefix_dictionary::AppVersion;
+    ///
+    /// assert_eq!(AppVersion::Fix44.appl_ver_id(), Some("6"));
+    /// ```
+    pub fn appl_ver_id(&self) -> Option<&'static str> {
+        match self {
+            #[cfg(feature = "fix40")]
+            Self::Fix40 => Some("2"),
+            #[cfg(feature = "fix41")]
+            Self::Fix41 => Some("3"),
+            #[cfg(feature = "fix42")]
+            Self::Fix42 => Some("4"),
+            #[cfg(feature = "fix43")]
+            Self::Fix43 => Some("5"),
+            Self::Fix44 => Some("6"),
+            #[cfg(feature = "fix50")]
+            Self::Fix50 => Some("7"),
+            #[cfg(feature = "fix50sp1")]
+            Self::Fix50Sp1 => Some("8"),
+            #[cfg(feature = "fix50sp2")]
+            Self::Fix50Sp2 => Some("9"),
+            #[cfg(feature = "fixt11")]
+            Self::Fixt11 => None,
+        }
+    }
+
+    /// Returns the [`AppVersion`] with the given `ApplVerID <1128>` code, if
+    /// any. This is the inverse of [`AppVersion::appl_ver_id`]. Custom codes
+    /// can be registered with [`DictionaryRegistry::insert_appl_ver_id`].
+    ///
+    /// ```
+    /// use fefix_dictionary::AppVersion;
+    ///
+    /// assert_eq!(AppVersion::from_appl_ver_id("6"), Some(AppVersion::Fix44));
+    /// // FIX.2.7 has no built-in dictionary.
+    /// assert_eq!(AppVersion::from_appl_ver_id("0"), None);
+    /// ```
+    pub fn from_appl_ver_id(appl_ver_id: &str) -> Option<Self> {
+        match appl_ver_id {
+            #[cfg(feature = "fix40")]
+            "2" => Some(Self::Fix40),
+            #[cfg(feature = "fix41")]
+            "3" => Some(Self::Fix41),
+            #[cfg(feature = "fix42")]
+            "4" => Some(Self::Fix42),
+            #[cfg(feature = "fix43")]
+            "5" => Some(Self::Fix43),
+            "6" => Some(Self::Fix44),
+            #[cfg(feature = "fix50")]
+            "7" => Some(Self::Fix50),
+            #[cfg(feature = "fix50sp1")]
+            "8" => Some(Self::Fix50Sp1),
+            #[cfg(feature = "fix50sp2")]
+            "9" => Some(Self::Fix50Sp2),
+            _ => None,
+        }
+    }
+}
+
+/// Information about the FIX version of a [`Dictionary`], as returned by
+/// [`Dictionary::version_info`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct VersionInfo {
+    /// The [`AppVersion`] which the [`Dictionary`] is based on, if it's known
+    /// and enabled.
+    pub app_version: Option<AppVersion>,
+    /// `true` unless the [`Dictionary`] is one of the built-in ones, e.g.
+    /// [`Dictionary::fix44`]. Customized dictionaries may add, remove, or
+    /// change fields and messages with respect to `app_version`.
+    pub is_customized: bool,
+}
+
+impl Default for VersionInfo {
+    /// Returns the [`VersionInfo`] of an unknown, customized [`Dictionary`].
+    fn default() -> Self {
+        Self {
+            app_version: None,
+            is_customized: true,
+        }
+    }
+}
+
+/// A mapping from `BeginString <8>` values to [`Dictionary`] values, e.g. for
+/// acceptors which serve several FIX versions on the same port.
+///
+/// FIX 5.0 and later versions all use `FIXT.1.1` as `BeginString <8>`, and
+/// the application version is given by `ApplVerID <1128>` instead. A
+/// [`DictionaryRegistry`] maps `ApplVerID <1128>` codes to [`Dictionary`]
+/// values too, including custom codes.
+///
+/// # Examples
+///
+/// ```
+/// use fefix_dictionary::{AppVersion, Dictionary, DictionaryRegistry};
+///
+/// let mut registry = DictionaryRegistry::new();
+/// registry.insert_app_version(AppVersion::Fix44);
+/// assert_eq!(registry.get(b"FIX.4.4").unwrap().version(), "FIX.4.4");
+/// assert_eq!(registry.get_by_appl_ver_id(b"6").unwrap().version(), "FIX.4.4");
+/// assert!(registry.get(b"FIX.4.2").is_none());
+///
+/// // A venue-specific application version.
+/// registry.insert_appl_ver_id("EXCH1", Dictionary::precompiled(AppVersion::Fix44));
+/// assert!(registry.get_by_appl_ver_id(b"EXCH1").is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryRegistry {
+    dictionaries: Dictionaries,
+    dictionaries_by_appl_ver_id: Dictionaries,
+}
+
+impl DictionaryRegistry {
+    /// Creates an empty [`DictionaryRegistry`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Associates `dict` with `begin_string`, and returns the [`Dictionary`]
+    /// that was previously associated with it, if any.
+    pub fn insert<S>(&mut self, begin_string: S, dict: Arc<Dictionary>) -> Option<Arc<Dictionary>>
+    where
+        S: Into<String>,
+    {
+        self.dictionaries.insert(begin_string.into(), dict)
+    }
+
+    /// Associates the precompiled [`Dictionary`] of `version` (see
+    /// [`Dictionary::precompiled`]) with its own version string, and with its
+    /// `ApplVerID <1128>` code (see [`AppVersion::appl_ver_id`]) if any.
+    pub fn insert_app_version(&mut self, version: AppVersion) -> Option<Arc<Dictionary>> {
+        let dict = Dictionary::precompiled(version);
+        if let Some(appl_ver_id) = version.appl_ver_id() {
+            self.insert_appl_ver_id(appl_ver_id, dict.clone());
+        }
+        self.insert(version.version(), dict)
+    }
+
+    /// Associates `dict` with the `ApplVerID <1128>` code `appl_ver_id`, which
+    /// may be a standard code or a custom one, and returns the [`Dictionary`]
+    /// that was previously associated with it, if any.
+    pub fn insert_appl_ver_id<S>(
+        &mut self,
+        appl_ver_id: S,
+        dict: Arc<Dictionary>,
+    ) -> Option<Arc<Dictionary>>
+    where
+        S: Into<String>,
+    {
+        self.dictionaries_by_appl_ver_id
+            .insert(appl_ver_id.into(), dict)
+    }
+
+    /// Returns the [`Dictionary`] associated with the `ApplVerID <1128>` code
+    /// `appl_ver_id`, if any.
+    pub fn get_by_appl_ver_id(&self, appl_ver_id: &[u8]) -> Option<&Arc<Dictionary>> {
+        let appl_ver_id = std::str::from_utf8(appl_ver_id).ok()?;
+        self.dictionaries_by_appl_ver_id.get(appl_ver_id)
+    }
+
+    /// Returns an [`Iterator`] over all `ApplVerID <1128>` codes in `self`,
+    /// together with their [`Dictionary`]. The ordering of items is not
+    /// specified.
+    pub fn appl_ver_ids(&self) -> impl Iterator<Item = (&str, &Arc<Dictionary>)> {
+        self.dictionaries_by_appl_ver_id
+            .iter()
+            .map(|(appl_ver_id, dict)| (appl_ver_id.as_str(), dict))
+    }
+
+    /// Returns the [`Dictionary`] associated with `begin_string`, if any.
+    pub fn get(&self, begin_string: &[u8]) -> Option<&Arc<Dictionary>> {
+        let begin_string = std::str::from_utf8(begin_string).ok()?;
+        self.dictionaries.get(begin_string)
+    }
+
+    /// Returns an [`Iterator`] over all `BeginString <8>` values in `self`. The
+    /// ordering of items is not specified.
+    pub fn begin_strings(&self) -> impl Iterator<Item = &str> {
+        self.dictionaries.keys().map(String::as_str)
+    }
+}
+
 /// Specifies business semantics for application-level entities within the FIX
 /// Protocol.
 ///
@@ -73,6 +375,7 @@ pub type Dictionaries = FnvHashMap<String, Arc<Dictionary>>;
 #[derive(Debug, Clone)]
 pub struct Dictionary {
     version: String,
+    is_customized: bool,
 
     abbreviation_definitions: FnvHashMap<SmartString, AbbreviationData>,
 
@@ -132,6 +435,7 @@ impl Dictionary {
         Dictionary {
             header: Vec::new(), // FIXME
             version: version.to_string(),
+            is_customized: true,
             abbreviation_definitions: FnvHashMap::default(),
             data_types_by_name: FnvHashMap::default(),
             fields_by_tags: FnvHashMap::default(),
@@ -158,18 +462,48 @@ impl Dictionary {
     /// use fefix_dictionary::Dictionary;
     ///
     /// let dict = Dictionary::fix44();
-    /// assert_eq!(dict.get_version(), "FIX.4.4");
+    /// assert_eq!(dict.version(), "FIX.4.4");
     /// ```
     pub fn version(&self) -> &str {
         self.version.as_str()
     }
 
+    /// Returns the [`AppVersion`] of `self`, if its version string matches
+    /// any. Mind that customized dictionaries may have a standard version
+    /// string; see [`Dictionary::version_info`].
+    pub fn app_version(&self) -> Option<AppVersion> {
+        AppVersion::from_version(self.version())
+    }
+
+    /// Returns the [`AppVersion`] of `self`, together with whether `self` is
+    /// a customized [`Dictionary`] (i.e. not a built-in one).
+    ///
+    /// ```
+    /// use fefix_dictionary::{AppVersion, Dictionary};
+    ///
+    /// let info = Dictionary::fix44().version_info();
+    /// assert_eq!(info.app_version, Some(AppVersion::Fix44));
+    /// assert!(!info.is_customized);
+    /// ```
+    pub fn version_info(&self) -> VersionInfo {
+        VersionInfo {
+            app_version: self.app_version(),
+            is_customized: self.is_customized,
+        }
+    }
+
+    fn builtin(spec: &str) -> Self {
+        let mut dict = Dictionary::from_quickfix_spec(spec).unwrap();
+        dict.is_customized = false;
+        dict
+    }
+
     /// Creates a new [`Dictionary`] for FIX 4.0.
     #[cfg(feature = "fix40")]
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix40")))]
     pub fn fix40() -> Self {
         let spec = include_str!("resources/quickfix/FIX-4.0.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 4.1.
@@ -177,7 +511,7 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix41")))]
     pub fn fix41() -> Self {
         let spec = include_str!("resources/quickfix/FIX-4.1.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 4.2.
@@ -185,7 +519,7 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix42")))]
     pub fn fix42() -> Self {
         let spec = include_str!("resources/quickfix/FIX-4.2.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 4.3.
@@ -193,13 +527,13 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix43")))]
     pub fn fix43() -> Self {
         let spec = include_str!("resources/quickfix/FIX-4.3.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 4.4.
     pub fn fix44() -> Self {
         let spec = include_str!("resources/quickfix/FIX-4.4.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0.
@@ -207,7 +541,7 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50")))]
     pub fn fix50() -> Self {
         let spec = include_str!("resources/quickfix/FIX-5.0.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0 SP1.
@@ -215,7 +549,7 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50sp1")))]
     pub fn fix50sp1() -> Self {
         let spec = include_str!("resources/quickfix/FIX-5.0-SP1.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIX 5.0 SP2.
@@ -223,7 +557,7 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fix50sp1")))]
     pub fn fix50sp2() -> Self {
         let spec = include_str!("resources/quickfix/FIX-5.0-SP2.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
     }
 
     /// Creates a new [`Dictionary`] for FIXT 1.1.
@@ -231,7 +565,79 @@ impl Dictionary {
     #[cfg_attr(doc_cfg, doc(cfg(feature = "fixt11")))]
     pub fn fixt11() -> Self {
         let spec = include_str!("resources/quickfix/FIXT-1.1.xml");
-        Dictionary::from_quickfix_spec(spec).unwrap()
+        Dictionary::builtin(spec)
+    }
+
+    /// Returns a process-wide, shared [`Dictionary`] for `version`. The
+    /// dictionary is built the first time it's requested and is reused by all
+    /// subsequent calls, which only need to clone an [`Arc`].
+    ///
+    /// Prefer this over e.g. [`Dictionary::fix44`] when creating many
+    /// decoders and encoders.
+    ///
+    /// ```
+    /// use fefix_dictionary::{AppVersion, Dictionary};
+    /// use std::sync::Arc;
+    ///
+    /// let dict1 = Dictionary::precompiled(AppVersion::Fix44);
+    /// let dict2 = Dictionary::precompiled(AppVersion::Fix44);
+    /// assert_eq!(dict1.version(), "FIX.4.4");
+    /// assert!(Arc::ptr_eq(&dict1, &dict2));
+    /// ```
+    pub fn precompiled(version: AppVersion) -> Arc<Dictionary> {
+        fn cached(
+            cell: &'static OnceLock<Arc<Dictionary>>,
+            f: fn() -> Dictionary,
+        ) -> Arc<Dictionary> {
+            cell.get_or_init(|| Arc::new(f())).clone()
+        }
+
+        match version {
+            #[cfg(feature = "fix40")]
+            AppVersion::Fix40 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix40)
+            }
+            #[cfg(feature = "fix41")]
+            AppVersion::Fix41 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix41)
+            }
+            #[cfg(feature = "fix42")]
+            AppVersion::Fix42 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix42)
+            }
+            #[cfg(feature = "fix43")]
+            AppVersion::Fix43 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix43)
+            }
+            AppVersion::Fix44 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix44)
+            }
+            #[cfg(feature = "fix50")]
+            AppVersion::Fix50 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix50)
+            }
+            #[cfg(feature = "fix50sp1")]
+            AppVersion::Fix50Sp1 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix50sp1)
+            }
+            #[cfg(feature = "fix50sp2")]
+            AppVersion::Fix50Sp2 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fix50sp2)
+            }
+            #[cfg(feature = "fixt11")]
+            AppVersion::Fixt11 => {
+                static DICT: OnceLock<Arc<Dictionary>> = OnceLock::new();
+                cached(&DICT, Self::fixt11)
+            }
+        }
     }
 
     /// Returns a [`Vec`] of FIX [`Dictionary`]'s for the most common FIX
@@ -411,34 +817,51 @@ impl Dictionary {
             .map(|data| Component(self, data))
             .collect()
     }
+
+    /// Lists the differences between `self` and `other`, where `self` is the
+    /// old dictionary and `other` is the new one. This is useful to audit
+    /// what needs to change when upgrading a counterparty to a different FIX
+    /// version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix_dictionary::Dictionary;
+    ///
+    /// let dict = Dictionary::fix44();
+    /// assert!(dict.diff(&dict).is_empty());
+    /// ```
+    pub fn diff(&self, other: &Dictionary) -> DictionaryDiff {
+        diff::diff(self, other)
+    }
 }
 
 /// Builder utilities
 impl Dictionary {
-    fn add_field(&mut self, field: FieldData) {
+    fn insert_field(&mut self, field: FieldData) {
         self.field_tags_by_name
             .insert(field.name.clone(), field.tag);
         self.fields_by_tags.insert(field.tag, field);
     }
 
-    fn add_message(&mut self, message: MessageData) {
+    fn insert_message(&mut self, message: MessageData) {
         self.message_msgtypes_by_name
             .insert(message.name.clone(), message.msg_type.clone());
         self.messages_by_msgtype
             .insert(message.msg_type.clone(), message);
     }
 
-    fn add_component(&mut self, component: ComponentData) {
+    fn insert_component(&mut self, component: ComponentData) {
         self.components_by_name
             .insert(component.name.clone(), component);
     }
 
-    fn add_datatype(&mut self, datatype: DatatypeData) {
+    fn insert_datatype(&mut self, datatype: DatatypeData) {
         self.data_types_by_name
             .insert(datatype.datatype.name().into(), datatype);
     }
 
-    fn add_category(&mut self, category: CategoryData) {
+    fn insert_category(&mut self, category: CategoryData) {
         self.categories_by_name
             .insert(category.name.clone().into(), category);
     }
@@ -786,6 +1209,7 @@ mod datatype {
                 "UTCTIMEONLY" => FixDatatype::UtcTimeOnly,
                 "UTCTIMESTAMP" => FixDatatype::UtcTimestamp,
                 "SEQNUM" => FixDatatype::SeqNum,
+                "TAGNUM" => FixDatatype::TagNum,
                 "TIME" => FixDatatype::UtcTimestamp,
                 "XMLDATA" => FixDatatype::XmlData,
                 _ => {
@@ -1073,8 +1497,10 @@ impl<'a> Field<'a> {
             s.chars().nth(i).map(|c| c.is_ascii_uppercase()) == Some(true)
         }
 
+        // `Length` fields of `data` fields (e.g. `EncodedTextLen <354>`) also
+        // end with "Len", but they don't start repeating groups.
         self.fix_datatype().base_type() == FixDatatype::NumInGroup
-            || self.name().ends_with("Len")
+            || (self.fix_datatype() != FixDatatype::Length && self.name().ends_with("Len"))
             || (self.name().starts_with("No") && nth_char_is_uppercase(self.name(), 2))
     }
 
@@ -1085,7 +1511,7 @@ impl<'a> Field<'a> {
 
     /// Returns the name of `self`. Field names are unique across each FIX
     /// [`Dictionary`].
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> &'a str {
         self.1.name.as_str()
     }
 
@@ -1352,6 +1778,65 @@ mod test {
         }));
     }
 
+    #[test]
+    fn precompiled_dictionary_matches_version() {
+        let versions = [
+            #[cfg(feature = "fix42")]
+            AppVersion::Fix42,
+            AppVersion::Fix44,
+            #[cfg(feature = "fixt11")]
+            AppVersion::Fixt11,
+        ];
+        for version in versions {
+            let dict = Dictionary::precompiled(version);
+            assert_eq!(dict.version(), version.version());
+            assert_eq!(dict.app_version(), Some(version));
+            assert!(Arc::ptr_eq(&dict, &Dictionary::precompiled(version)));
+        }
+    }
+
+    #[test]
+    fn appl_ver_ids_round_trip() {
+        let versions = [
+            #[cfg(feature = "fix42")]
+            AppVersion::Fix42,
+            AppVersion::Fix44,
+            #[cfg(feature = "fix50sp2")]
+            AppVersion::Fix50Sp2,
+        ];
+        for version in versions {
+            let appl_ver_id = version.appl_ver_id().unwrap();
+            assert_eq!(AppVersion::from_appl_ver_id(appl_ver_id), Some(version));
+        }
+        #[cfg(feature = "fix50sp2")]
+        assert_eq!(AppVersion::Fix50Sp2.appl_ver_id(), Some("9"));
+        for appl_ver_id in ["0", "1", "10", "", "06"] {
+            assert_eq!(AppVersion::from_appl_ver_id(appl_ver_id), None);
+        }
+    }
+
+    #[test]
+    fn registry_resolves_appl_ver_ids() {
+        let mut registry = DictionaryRegistry::new();
+        registry.insert_app_version(AppVersion::Fix44);
+        let custom = Arc::new(Dictionary::fix44());
+        assert!(registry.insert_appl_ver_id("6", custom.clone()).is_some());
+        assert!(Arc::ptr_eq(
+            registry.get_by_appl_ver_id(b"6").unwrap(),
+            &custom
+        ));
+        assert!(registry.get_by_appl_ver_id(b"9").is_none());
+        assert_eq!(registry.appl_ver_ids().count(), 1);
+    }
+
+    #[test]
+    fn quickfix_spec_is_customized() {
+        let spec = include_str!("resources/quickfix/FIX-4.4.xml");
+        let info = Dictionary::from_quickfix_spec(spec).unwrap().version_info();
+        assert_eq!(info.app_version, Some(AppVersion::Fix44));
+        assert!(info.is_customized);
+    }
+
     #[test]
     fn all_datatypes_are_used_at_least_once() {
         for dict in Dictionary::common_dictionaries().iter() {
@@ -1404,6 +1889,28 @@ mod test {
         assert!(field_167.enums().unwrap().any(|e| e.value() == "EUCORP"));
     }
 
+    #[test]
+    fn fix44_length_fields_are_not_num_in_group() {
+        let dict = Dictionary::fix44();
+        assert!(dict.field_by_tag(453).unwrap().is_num_in_group());
+        assert!(!dict.field_by_tag(354).unwrap().is_num_in_group());
+    }
+
+    #[test]
+    fn fix44_component_references_keep_component_definitions() {
+        let dict = Dictionary::fix44();
+        let md_req_grp = dict.component_by_name("MDReqGrp").unwrap();
+        let items: Vec<_> = md_req_grp.items().collect();
+        assert_eq!(items.len(), 1);
+        match items[0].kind() {
+            LayoutItemKind::Group(field, items) => {
+                assert_eq!(field.tag().get(), 267);
+                assert_eq!(items.len(), 1);
+            }
+            _ => panic!(),
+        }
+    }
+
     const INVALID_QUICKFIX_SPECS: &[&str] = &[
         include_str!("test_data/quickfix_specs/empty_file.xml"),
         include_str!("test_data/quickfix_specs/missing_components.xml"),
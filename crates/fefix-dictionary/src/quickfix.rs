@@ -118,7 +118,7 @@ fn import_field(builder: &mut Dictionary, node: roxmltree::Node) -> ParseResult<
         base_category_id: None,
         description: None,
     };
-    builder.add_field(field);
+    builder.insert_field(field);
     Ok(())
 }
 
@@ -151,7 +151,7 @@ fn import_message(dict: &mut Dictionary, node: roxmltree::Node) -> ParseResult<(
         elaboration: None,
         description: String::new(),
     };
-    dict.add_message(message);
+    dict.insert_message(message);
     Ok(())
 }
 
@@ -175,7 +175,7 @@ fn import_component(dict: &mut Dictionary, node: roxmltree::Node, name: &str) ->
         name: name.into(),
         abbr_name: None,
     };
-    dict.add_component(component);
+    dict.insert_component(component);
     Ok(())
 }
 
@@ -197,7 +197,7 @@ fn import_datatype(dict: &mut Dictionary, node: roxmltree::Node) -> SmartString
             description: String::new(),
             examples: Vec::new(),
         };
-        dict.add_datatype(dt);
+        dict.insert_datatype(dt);
     }
     name.into()
 }
@@ -238,8 +238,11 @@ fn import_layout_item(dict: &mut Dictionary, node: roxmltree::Node) -> ParseResu
             LayoutItemKindData::Field { tag: field_tag }
         }
         "component" => {
-            // Components may *not* be already present.
-            import_component(dict, node, name)?;
+            // Components may *not* be already present. References must not
+            // overwrite their definition, though, as they have no contents.
+            if dict.component_by_name(name).is_none() {
+                import_component(dict, node, name)?;
+            }
             LayoutItemKindData::Component { name: name.into() }
         }
         "group" => {
@@ -266,7 +269,7 @@ fn import_category(dict: &mut Dictionary, node: roxmltree::Node) -> ParseResult<
     let name = node.attribute("msgcat").ok_or(ParseError::InvalidFormat)?;
 
     if dict.category_by_name(name).is_none() {
-        dict.add_category(CategoryData {
+        dict.insert_category(CategoryData {
             name: name.to_string(),
             fixml_filename: String::new(),
         });
@@ -280,6 +283,7 @@ type ParseResult<T> = Result<T, ParseError>;
 
 /// The error type that can arise when decoding a QuickFIX Dictionary.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub enum ParseDictionaryError {
     InvalidFormat,
     InvalidData(String),
@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use fefix::prelude::*;
-use fefix::tagvalue::Decoder;
+use fefix::tagvalue::{Decoder, Encoder};
 use fefix::GetConfig;
 
 const FIX_MESSAGE: &[u8] = b"8=FIX.4.4|9=122|35=D|34=215|49=CLIENT12|52=20100225-19:41:57.316|56=B|1=Marcel|11=13346|21=1|40=2|44=5|54=1|59=0|60=20100225-19:39:52.020|10=072|";
@@ -9,6 +9,54 @@ fn decode_fix_message(fix_decoder: &mut Decoder, msg: &[u8]) {
     fix_decoder.decode(msg).expect("Invalid FIX message");
 }
 
+/// A ~2KB `MarketDataIncrementalRefresh <X>` message.
+fn large_fix_message() -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    encoder.config_mut().separator = b'|';
+    let mut buffer = Vec::new();
+    let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"X");
+    msg.set(fix44::MSG_SEQ_NUM, 215);
+    msg.set(fix44::SENDER_COMP_ID, "CLIENT12");
+    msg.set(fix44::TARGET_COMP_ID, "B");
+    msg.set(fix44::SENDING_TIME, "20100225-19:41:57.316");
+    msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+        for i in 0..40u32 {
+            group.entry(|entry| {
+                entry.set(fix44::MD_UPDATE_ACTION, fix44::MdUpdateAction::New);
+                entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+                entry.set(fix44::SYMBOL, "EUR/USD");
+                entry.set(fix44::MD_ENTRY_PX, 1.37215f32);
+                entry.set(fix44::MD_ENTRY_SIZE, 1000 + i);
+            });
+        }
+    });
+    msg.done().0.to_vec()
+}
+
+/// A `NewOrderSingle <D>` message with 100 fields, mostly user-defined.
+fn wide_fix_message() -> Vec<u8> {
+    let mut encoder = Encoder::new();
+    encoder.config_mut().separator = b'|';
+    let mut buffer = Vec::new();
+    let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+    msg.set(fix44::MSG_SEQ_NUM, 215);
+    msg.set(fix44::SENDER_COMP_ID, "CLIENT12");
+    msg.set(fix44::TARGET_COMP_ID, "B");
+    msg.set(fix44::SENDING_TIME, "20100225-19:41:57.316");
+    for tag in 5000..5094u32 {
+        msg.set(tag, tag);
+    }
+    msg.done().0.to_vec()
+}
+
+/// Decodes `msg` and looks up 20 of its fields.
+fn decode_and_lookup_fields(fix_decoder: &mut Decoder, msg: &[u8]) {
+    let msg = fix_decoder.decode(msg).expect("Invalid FIX message");
+    for tag in (5000..5094u32).step_by(5).take(20) {
+        black_box(msg.get_raw(tag).expect("Missing field"));
+    }
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let fix_dictionary = Dictionary::fix44();
     let fix_decoder = &mut Decoder::new(fix_dictionary);
@@ -17,6 +65,25 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("FIX tag-value decoding", |b| {
         b.iter(|| decode_fix_message(black_box(fix_decoder), black_box(FIX_MESSAGE)))
     });
+    let large_fix_message = &large_fix_message()[..];
+    c.bench_function("FIX tag-value decoding (2KB)", |b| {
+        b.iter(|| decode_fix_message(black_box(fix_decoder), black_box(large_fix_message)))
+    });
+    c.bench_function("FIX tag-value header decoding (2KB)", |b| {
+        b.iter(|| {
+            fix_decoder
+                .decode_header(black_box(large_fix_message))
+                .expect("Invalid FIX message")
+        })
+    });
+    let wide_fix_message = &wide_fix_message()[..];
+    c.bench_function("FIX tag-value 20 lookups (100 fields)", |b| {
+        b.iter(|| decode_and_lookup_fields(black_box(fix_decoder), black_box(wide_fix_message)))
+    });
+    fix_decoder.config_mut().should_decode_associative = false;
+    c.bench_function("FIX tag-value 20 lookups (100 fields, lazy index)", |b| {
+        b.iter(|| decode_and_lookup_fields(black_box(fix_decoder), black_box(wide_fix_message)))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,55 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fefix::prelude::*;
+use fefix::tagvalue::{CompiledMessage, Config, Encoder};
+
+/// The fields of an `ExecutionReport <8>` acknowledging a new order.
+const TAGS: &[u32] = &[49, 56, 34, 52, 37, 11, 17, 150, 39, 55, 54, 151, 14, 6];
+const VALUES: &[&[u8]] = &[
+    b"BROKER",
+    b"CLIENT12",
+    b"215",
+    b"20100225-19:41:57.316",
+    b"ORDER-1",
+    b"13346",
+    b"EXEC-1",
+    b"0",
+    b"0",
+    b"EUR/USD",
+    b"1",
+    b"1000000",
+    b"0",
+    b"0",
+];
+
+fn encode_order_ack(encoder: &mut Encoder, buffer: &mut Vec<u8>) {
+    buffer.clear();
+    let mut msg = encoder.start_message(b"FIX.4.4", buffer, b"8");
+    for (tag, value) in TAGS.iter().zip(VALUES) {
+        msg.set(*tag, *value);
+    }
+    msg.done();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut config = Config::default();
+    config.separator = b'|';
+    let mut buffer = Vec::new();
+
+    let encoder = &mut Encoder::new();
+    *encoder.config_mut() = config.clone();
+    c.bench_function("FIX tag-value encoding (order ack)", |b| {
+        b.iter(|| encode_order_ack(black_box(encoder), black_box(&mut buffer)))
+    });
+
+    let compiled =
+        CompiledMessage::new(&Dictionary::fix44(), &config, b"FIX.4.4", b"8", TAGS).unwrap();
+    c.bench_function("FIX tag-value compiled encoding (order ack)", |b| {
+        b.iter(|| {
+            buffer.clear();
+            compiled.emit(black_box(&mut buffer), black_box(VALUES));
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+//! Run with `cargo +nightly fuzz run tagvalue_decode` from `crates/fefix`.
+
+#![no_main]
+
+use fefix::dict::AppVersion;
+use fefix::prelude::*;
+use fefix::tagvalue::Decoder;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut decoder = Decoder::new(Dictionary::precompiled(AppVersion::Fix44));
+    decoder.config_mut().verify_checksum = false;
+    if let Ok(message) = decoder.decode(data) {
+        for _ in message.fields() {}
+        message.for_each_with_context(|_, _, _| {});
+    }
+    // The same bytes, but with a human-readable separator.
+    decoder.config_mut().separator = b'|';
+    let _ = decoder.decode(data);
+});
@@ -0,0 +1,138 @@
+use crate::{Buffer, FieldType};
+
+const ERR_LENGTH: &str = "Invalid length for code.";
+const ERR_CHARS: &str = "Invalid character in code.";
+
+macro_rules! define_code {
+    (
+        $(#[$meta:meta])*
+        $name:ident, $len:literal, $is_valid_char:expr, $example:literal
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Validation is only structural, i.e. [`FieldType::deserialize`] checks
+        /// length and characters, but not that the code is actually assigned.
+        /// [`FieldType::deserialize_lossy`] only checks length.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        #[doc = concat!("use fefix::field_types::", stringify!($name), ";")]
+        /// use fefix::FieldType;
+        ///
+        #[doc = concat!("let code = ", stringify!($name), "::deserialize(", stringify!($example), ").unwrap();")]
+        #[doc = concat!("assert_eq!(code.as_bytes(), ", stringify!($example), ");")]
+        #[doc = concat!("assert!(", stringify!($name), "::deserialize(b\"\").is_err());")]
+        /// ```
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            /// Creates a new code from its raw bytes, if they're valid.
+            pub fn new(code: [u8; $len]) -> Option<Self> {
+                let is_valid_char: fn(&u8) -> bool = $is_valid_char;
+                if code.iter().all(is_valid_char) {
+                    Some(Self(code))
+                } else {
+                    None
+                }
+            }
+
+            /// Returns the raw bytes of `self`.
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            /// Returns `self` as a string slice.
+            pub fn as_str(&self) -> &str {
+                // Lossy deserialization doesn't check characters, so this
+                // might not be valid UTF-8.
+                std::str::from_utf8(&self.0).unwrap_or_default()
+            }
+        }
+
+        impl<'a> FieldType<'a> for $name {
+            type Error = &'static str;
+            type SerializeSettings = ();
+
+            fn serialize_with<B>(&self, buffer: &mut B, _settings: ()) -> usize
+            where
+                B: Buffer,
+            {
+                buffer.extend_from_slice(&self.0);
+                $len
+            }
+
+            fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+                let code = Self::deserialize_lossy(data)?;
+                Self::new(code.0).ok_or(ERR_CHARS)
+            }
+
+            fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+                data.try_into().map(Self).map_err(|_| ERR_LENGTH)
+            }
+        }
+    };
+}
+
+define_code!(
+    /// An ISO 3166-1 alpha-2 country code, i.e. two uppercase letters (e.g.
+    /// `US`).
+    Country,
+    2,
+    u8::is_ascii_uppercase,
+    b"US"
+);
+
+define_code!(
+    /// An ISO 4217 alphabetic currency code, i.e. three uppercase letters
+    /// (e.g. `EUR`).
+    Currency,
+    3,
+    u8::is_ascii_uppercase,
+    b"EUR"
+);
+
+define_code!(
+    /// An ISO 10383 *Market Identifier Code* (MIC), i.e. four uppercase
+    /// letters or digits (e.g. `XNYS`).
+    Exchange,
+    4,
+    |c| c.is_ascii_uppercase() || c.is_ascii_digit(),
+    b"XNYS"
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lowercase_currency_is_invalid() {
+        assert!(Currency::deserialize(b"eur").is_err());
+        assert!(Currency::deserialize_lossy(b"eur").is_ok());
+        assert_eq!(Currency::new(*b"USD").unwrap().as_str(), "USD");
+    }
+
+    #[test]
+    fn wrong_length_is_invalid() {
+        assert!(Country::deserialize(b"USA").is_err());
+        assert!(Country::deserialize_lossy(b"U").is_err());
+        assert!(Exchange::deserialize(b"XNY").is_err());
+    }
+
+    #[test]
+    fn mic_can_contain_digits() {
+        assert!(Exchange::deserialize(b"XLON").is_ok());
+        assert!(Exchange::deserialize(b"360T").is_ok());
+        assert!(Exchange::deserialize(b"XL-N").is_err());
+        assert!(Currency::deserialize(b"US1").is_err());
+    }
+
+    #[test]
+    fn serialization_roundtrip() {
+        let code = Exchange::new(*b"XNAS").unwrap();
+        let mut buffer = Vec::new();
+        assert_eq!(code.serialize(&mut buffer), 4);
+        assert_eq!(Exchange::deserialize(&buffer[..]), Ok(code));
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{Buffer, BufferWriter, FieldType};
+use std::fmt::{Display, Write};
+
+/// The number of digits after the decimal point when serializing [`Price`],
+/// [`PriceOffset`], [`Qty`], [`Amt`], and [`Percentage`]; see
+/// [`FieldType::SerializeSettings`]. [`None`] (the default) keeps the compact
+/// representation of the inner value.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Scale(pub Option<usize>);
+
+macro_rules! define_decimal {
+    (
+        $(#[$meta:meta])*
+        $name:ident
+    ) => {
+        $(#[$meta])*
+        ///
+        /// The inner value can be any decimal [`FieldType`], e.g.
+        /// [`f64`] (the default) or [`struct@rust_decimal::Decimal`]. Parsing
+        /// is delegated to it, while serialization supports [`Scale`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        #[doc = concat!("use fefix::field_types::{", stringify!($name), ", Scale};")]
+        /// use fefix::FieldType;
+        ///
+        #[doc = concat!("let value = ", stringify!($name), "::<f64>::deserialize(b\"1.5\").unwrap();")]
+        /// assert_eq!(value.0, 1.5);
+        ///
+        /// let mut buffer = Vec::new();
+        /// value.serialize_with(&mut buffer, Scale(Some(3)));
+        /// assert_eq!(&buffer[..], b"1.500");
+        /// ```
+        #[derive(Debug, Copy, Clone, Default, PartialEq, PartialOrd)]
+        pub struct $name<T = f64>(pub T);
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                Self(value)
+            }
+        }
+
+        impl<'a, T> FieldType<'a> for $name<T>
+        where
+            T: FieldType<'a> + Display,
+        {
+            type Error = T::Error;
+            type SerializeSettings = Scale;
+
+            fn serialize_with<B>(&self, buffer: &mut B, settings: Scale) -> usize
+            where
+                B: Buffer,
+            {
+                match settings.0 {
+                    Some(scale) => {
+                        let initial_len = buffer.len();
+                        write!(BufferWriter(buffer), "{:.*}", scale, self.0).unwrap();
+                        buffer.len() - initial_len
+                    }
+                    None => self.0.serialize(buffer),
+                }
+            }
+
+            fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+                T::deserialize(data).map(Self)
+            }
+
+            fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+                T::deserialize_lossy(data).map(Self)
+            }
+        }
+    };
+}
+
+define_decimal!(
+    /// A value of FIX datatype `Price`, e.g. `Price <44>`.
+    Price
+);
+
+define_decimal!(
+    /// A value of FIX datatype `PriceOffset`, i.e. the difference between two
+    /// prices, e.g. `PegOffsetValue <211>`.
+    PriceOffset
+);
+
+define_decimal!(
+    /// A value of FIX datatype `Qty`, e.g. `OrderQty <38>`.
+    Qty
+);
+
+define_decimal!(
+    /// A value of FIX datatype `Amt`, i.e. a price times a quantity, e.g.
+    /// `GrossTradeAmt <381>`.
+    Amt
+);
+
+define_decimal!(
+    /// A value of FIX datatype `Percentage`, e.g. `ParticipationRate <849>`.
+    Percentage
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_scale_is_compact() {
+        assert_eq!(Qty(100f64).to_bytes(), b"100");
+        assert_eq!(Price(0.25f32).to_bytes(), b"0.25");
+    }
+
+    #[test]
+    fn scale_rounds_and_pads() {
+        let mut buffer = Vec::new();
+        Price(1.23456f64).serialize_with(&mut buffer, Scale(Some(2)));
+        assert_eq!(&buffer[..], b"1.23");
+        buffer.clear();
+        Amt(7f64).serialize_with(&mut buffer, Scale(Some(0)));
+        assert_eq!(&buffer[..], b"7");
+    }
+
+    #[test]
+    fn invalid_decimal_is_error() {
+        assert!(Price::<f64>::deserialize(b"1,5").is_err());
+        assert!(Percentage::<f64>::deserialize(b"").is_err());
+    }
+
+    #[cfg(feature = "utils-rust-decimal")]
+    #[test]
+    fn rust_decimal_with_scale() {
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        let price = Price::<Decimal>::deserialize(b"101.5").unwrap();
+        assert_eq!(price.0, Decimal::from_str("101.5").unwrap());
+        let mut buffer = Vec::new();
+        price.serialize_with(&mut buffer, Scale(Some(4)));
+        assert_eq!(&buffer[..], b"101.5000");
+    }
+}
@@ -0,0 +1,194 @@
+use crate::FieldMap;
+use std::borrow::Cow;
+
+const MESSAGE_ENCODING: u32 = 347;
+const ENCODED_TEXT_LEN: u32 = 354;
+const ENCODED_TEXT: u32 = 355;
+
+const UTF_8: &str = "UTF-8";
+
+/// The reason why an [`EncodedText`] can't be read or decoded.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum EncodedTextError {
+    /// The `data` field with the encoded text is missing.
+    #[error("Missing encoded text field.")]
+    Missing,
+    /// The `Length` field is missing or it doesn't match the encoded text.
+    #[error("Missing or wrong length of encoded text.")]
+    Length,
+    /// The charset given by `MessageEncoding <347>` is not supported.
+    #[error("Unsupported `MessageEncoding <347>`: {0}")]
+    UnsupportedEncoding(String),
+    /// The encoded text is not valid according to its charset.
+    #[error("Invalid encoded text.")]
+    Invalid,
+}
+
+/// Non-ASCII text in a `data` field, e.g. `EncodedText <355>`, together with
+/// the charset declared by `MessageEncoding <347>`.
+///
+/// FerrumFIX only decodes UTF-8 text by itself. Other charsets (e.g.
+/// `ISO-2022-JP`, `Shift_JIS`) can be decoded with
+/// [`EncodedText::decode_with`] and a third-party crate such as
+/// [`encoding_rs`](https://docs.rs/encoding_rs).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::field_types::EncodedText;
+/// use fefix::tagvalue::Decoder;
+/// use fefix::prelude::*;
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+/// let msg = "8=FIX.4.4|9=43|35=B|347=UTF-8|148=x|354=10|355=東京|€|10=000|";
+/// let msg = decoder.decode(msg.as_bytes()).unwrap();
+///
+/// let encoded_text = EncodedText::from_message(&msg).unwrap();
+/// assert_eq!(encoded_text.message_encoding, Some(b"UTF-8" as &[u8]));
+/// assert_eq!(encoded_text.decode().unwrap(), "東京|€");
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EncodedText<'a> {
+    /// The value of `MessageEncoding <347>`, if present.
+    pub message_encoding: Option<&'a [u8]>,
+    /// The raw bytes of the encoded text.
+    pub text: &'a [u8],
+}
+
+impl<'a> EncodedText<'a> {
+    /// Reads `EncodedText <355>` and `EncodedTextLen <354>` from `msg`.
+    pub fn from_message<M>(msg: &'a M) -> Result<Self, EncodedTextError>
+    where
+        M: FieldMap<u32>,
+    {
+        Self::from_fields(msg, ENCODED_TEXT_LEN, ENCODED_TEXT)
+    }
+
+    /// Reads any pair of `Length` and `data` fields from `msg`, e.g.
+    /// `EncodedSubjectLen <356>` and `EncodedSubject <357>`. The value of
+    /// `length_tag` must be the length of the value of `text_tag`.
+    pub fn from_fields<M>(
+        msg: &'a M,
+        length_tag: u32,
+        text_tag: u32,
+    ) -> Result<Self, EncodedTextError>
+    where
+        M: FieldMap<u32>,
+    {
+        let text = msg.get_raw(text_tag).ok_or(EncodedTextError::Missing)?;
+        let len = msg
+            .get::<usize>(length_tag)
+            .map_err(|_| EncodedTextError::Length)?;
+        if len != text.len() {
+            return Err(EncodedTextError::Length);
+        }
+        Ok(Self {
+            message_encoding: msg.get_raw(MESSAGE_ENCODING),
+            text,
+        })
+    }
+
+    /// Decodes `self` as UTF-8, which is also assumed when
+    /// `MessageEncoding <347>` is missing. Any other charset results in
+    /// [`EncodedTextError::UnsupportedEncoding`].
+    pub fn decode(&self) -> Result<Cow<'a, str>, EncodedTextError> {
+        self.decode_with(|_, _| None)
+    }
+
+    /// Like [`EncodedText::decode`], but `decode` is called for all charsets
+    /// other than UTF-8, with the value of `MessageEncoding <347>` and the raw
+    /// bytes of `self`. `decode` should return [`None`] for unsupported
+    /// charsets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::{EncodedText, EncodedTextError};
+    ///
+    /// let encoded_text = EncodedText {
+    ///     message_encoding: Some(b"ISO-2022-JP"),
+    ///     text: b"\x1b$BEl5~\x1b(B",
+    /// };
+    /// let decode = |charset: &str, text: &[u8]| {
+    ///     // e.g. `encoding_rs::Encoding::for_label(charset.as_bytes())`.
+    ///     (charset == "ISO-2022-JP" && text == b"\x1b$BEl5~\x1b(B").then(|| "東京".to_string())
+    /// };
+    /// assert_eq!(encoded_text.decode_with(decode).unwrap(), "東京");
+    /// assert_eq!(
+    ///     encoded_text.decode(),
+    ///     Err(EncodedTextError::UnsupportedEncoding("ISO-2022-JP".to_string()))
+    /// );
+    /// ```
+    pub fn decode_with<F>(&self, decode: F) -> Result<Cow<'a, str>, EncodedTextError>
+    where
+        F: FnOnce(&str, &'a [u8]) -> Option<String>,
+    {
+        let charset = match self.message_encoding {
+            Some(charset) => std::str::from_utf8(charset).map_err(|_| EncodedTextError::Invalid)?,
+            None => UTF_8,
+        };
+        if charset.eq_ignore_ascii_case(UTF_8) {
+            std::str::from_utf8(self.text)
+                .map(Cow::Borrowed)
+                .map_err(|_| EncodedTextError::Invalid)
+        } else {
+            decode(charset, self.text)
+                .map(Cow::Owned)
+                .ok_or_else(|| EncodedTextError::UnsupportedEncoding(charset.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Decoder;
+    use crate::{Dictionary, GetConfig};
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    #[test]
+    fn missing_message_encoding_is_utf8() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=27|35=B|148=x|354=5|355=h|llo|10=000|")
+            .unwrap();
+        let encoded_text = EncodedText::from_message(&msg).unwrap();
+        assert_eq!(encoded_text.message_encoding, None);
+        assert_eq!(encoded_text.decode(), Ok(Cow::Borrowed("h|llo")));
+    }
+
+    #[test]
+    fn missing_or_wrong_length() {
+        let mut decoder = decoder();
+        decoder.config_mut().should_check_data_lengths = false;
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=19|35=B|148=x|355=foo|10=000|")
+            .unwrap();
+        assert_eq!(
+            EncodedText::from_message(&msg),
+            Err(EncodedTextError::Length)
+        );
+        assert_eq!(
+            EncodedText::from_fields(&msg, 356, 357),
+            Err(EncodedTextError::Missing)
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_text() {
+        let encoded_text = EncodedText {
+            message_encoding: Some(b"utf-8"),
+            text: b"\xff\xfe",
+        };
+        assert_eq!(encoded_text.decode(), Err(EncodedTextError::Invalid));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::dict::FixDatatype;
+
+/// The reason why a field value doesn't respect the constraints of its
+/// integer datatype, as returned by [`check_int_subtype`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum IntSubtypeError {
+    /// The field value is not a valid integer.
+    #[error("Invalid integer digits.")]
+    NotAnInteger,
+    /// The datatype requires strictly positive values (e.g. `SeqNum`), but the
+    /// field value is zero or negative.
+    #[error("`{}` values must be positive.", .0.name())]
+    NotPositive(FixDatatype),
+    /// The datatype requires non-negative values (e.g. `Length`), but the field
+    /// value is negative.
+    #[error("`{}` values can't be negative.", .0.name())]
+    Negative(FixDatatype),
+    /// The datatype forbids leading zeros (i.e. `TagNum`), but the field
+    /// value has some. See [`check_leading_zeros`].
+    #[error("`{}` values can't have leading zeros.", .0.name())]
+    LeadingZeros(FixDatatype),
+}
+
+/// Checks the raw contents of a field against the range and sign constraints of
+/// its integer `datatype`:
+///
+/// | FIX datatype            | Constraint           |
+/// |-------------------------|----------------------|
+/// | `int`                   | Any integer.         |
+/// | `Length`, `NumInGroup`  | Non-negative.        |
+/// | `SeqNum`, `TagNum`      | Strictly positive.   |
+///
+/// Non-integer datatypes are always accepted.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::dict::FixDatatype;
+/// use fefix::field_types::{check_int_subtype, IntSubtypeError};
+///
+/// assert!(check_int_subtype(FixDatatype::SeqNum, b"42").is_ok());
+/// assert_eq!(
+///     check_int_subtype(FixDatatype::SeqNum, b"0"),
+///     Err(IntSubtypeError::NotPositive(FixDatatype::SeqNum))
+/// );
+/// assert_eq!(
+///     check_int_subtype(FixDatatype::NumInGroup, b"-1"),
+///     Err(IntSubtypeError::Negative(FixDatatype::NumInGroup))
+/// );
+/// assert!(check_int_subtype(FixDatatype::Int, b"-1").is_ok());
+/// ```
+pub fn check_int_subtype(datatype: FixDatatype, data: &[u8]) -> Result<(), IntSubtypeError> {
+    let must_be_positive = match datatype {
+        FixDatatype::Int => None,
+        FixDatatype::Length | FixDatatype::NumInGroup => Some(false),
+        FixDatatype::SeqNum | FixDatatype::TagNum => Some(true),
+        _ => return Ok(()),
+    };
+    let (is_negative, digits) = match data.split_first() {
+        Some((b'-', digits)) => (true, digits),
+        _ => (false, data),
+    };
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(IntSubtypeError::NotAnInteger);
+    }
+    let is_zero = digits.iter().all(|digit| *digit == b'0');
+    match must_be_positive {
+        Some(true) if is_negative || is_zero => Err(IntSubtypeError::NotPositive(datatype)),
+        Some(false) if is_negative && !is_zero => Err(IntSubtypeError::Negative(datatype)),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the number of leading zeros of the integer `data`, after its sign
+/// if any, which can be removed without changing its value, e.g. 3 for
+/// `00042` and 2 for `-007`. The last digit is never counted, so `0` has
+/// none.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::field_types::leading_zeros;
+///
+/// assert_eq!(leading_zeros(b"00042"), 3);
+/// assert_eq!(leading_zeros(b"-007"), 2);
+/// assert_eq!(leading_zeros(b"000"), 2);
+/// assert_eq!(leading_zeros(b"42"), 0);
+/// ```
+pub fn leading_zeros(data: &[u8]) -> usize {
+    let digits = data.strip_prefix(b"-").unwrap_or(data);
+    let zeros = digits.iter().take_while(|digit| **digit == b'0').count();
+    zeros.min(digits.len().saturating_sub(1))
+}
+
+/// Checks that the raw contents of a field don't have leading zeros if its
+/// `datatype` forbids them. All integer datatypes allow leading zeros, e.g.
+/// `00042` is just 42, except for `TagNum`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::dict::FixDatatype;
+/// use fefix::field_types::{check_leading_zeros, IntSubtypeError};
+///
+/// assert!(check_leading_zeros(FixDatatype::SeqNum, b"0042").is_ok());
+/// assert_eq!(
+///     check_leading_zeros(FixDatatype::TagNum, b"055"),
+///     Err(IntSubtypeError::LeadingZeros(FixDatatype::TagNum))
+/// );
+/// ```
+pub fn check_leading_zeros(datatype: FixDatatype, data: &[u8]) -> Result<(), IntSubtypeError> {
+    if datatype == FixDatatype::TagNum && leading_zeros(data) > 0 {
+        Err(IntSubtypeError::LeadingZeros(datatype))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns `true` if `datatype` is `int` or one of its subtypes checked by
+/// [`check_int_subtype`].
+pub(crate) fn is_int_datatype(datatype: FixDatatype) -> bool {
+    matches!(
+        datatype,
+        FixDatatype::Int
+            | FixDatatype::Length
+            | FixDatatype::NumInGroup
+            | FixDatatype::SeqNum
+            | FixDatatype::TagNum
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zero_is_valid_length_and_num_in_group() {
+        assert!(check_int_subtype(FixDatatype::Length, b"0").is_ok());
+        assert!(check_int_subtype(FixDatatype::NumInGroup, b"0").is_ok());
+        assert!(check_int_subtype(FixDatatype::NumInGroup, b"-0").is_ok());
+    }
+
+    #[test]
+    fn zero_tag_num_is_invalid() {
+        assert_eq!(
+            check_int_subtype(FixDatatype::TagNum, b"000"),
+            Err(IntSubtypeError::NotPositive(FixDatatype::TagNum))
+        );
+    }
+
+    #[test]
+    fn non_integers_are_invalid() {
+        for data in [b"" as &[u8], b"-", b"1.5", b"+3", b"12a"] {
+            assert_eq!(
+                check_int_subtype(FixDatatype::Int, data),
+                Err(IntSubtypeError::NotAnInteger)
+            );
+        }
+    }
+
+    #[test]
+    fn non_integer_datatypes_are_ignored() {
+        assert!(check_int_subtype(FixDatatype::String, b"-1").is_ok());
+        assert!(check_int_subtype(FixDatatype::Price, b"foo").is_ok());
+    }
+}
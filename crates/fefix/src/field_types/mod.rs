@@ -8,11 +8,13 @@
 //! | `SeqNum`                   | [`u64`].                                                                           |
 //! | `TagNum`                   | [`TagU32`](crate::TagU32).                                                         |
 //! | `DayOfMonth`               | [`u32`].                                                                           |
-//! | `float`, `Price`, etc.     | [`f32`], [`f64`], [`struct@rust_decimal::Decimal`], [`struct@decimal::d128`]. |
+//! | `float`                    | [`f32`], [`f64`], [`struct@rust_decimal::Decimal`], [`struct@decimal::d128`]. |
+//! | `Price`, `PriceOffset`     | [`Price`], [`PriceOffset`] over any `float` implementor.                           |
+//! | `Qty`, `Amt`, `Percentage` | [`Qty`], [`Amt`], [`Percentage`] over any `float` implementor.                     |
 //! | `Boolean`                  | [`bool`].                                                                          |
 //! | `char`                     | [`u8`] [^1].                                                                      |
 //! | `String`                   | [`Vec<u8>`], `&[u8]`.[^1]                                                          |
-//! | `data`                     | [`Vec<u8>`], `&[u8]` (also [`String`], [`str`] for UTF-8 content).                 |
+//! | `data`                     | [`Vec<u8>`], `&[u8]` (also [`String`], [`str`], `Cow<str>` for UTF-8 content).     |
 //! | `MultipleCharValue`        | [`MultipleChars`] [^1].                                                            |
 //! | `MultipleValueString`      | [`MultipleStrings`] [^1].                                                          |
 //! | `Country`                  | [`Country`].                                                                       |
@@ -21,14 +23,21 @@
 //! | `month-year`               | [`MonthYear`].                                                                     |
 //! | `UTCTimeOnly`              | [`Time`], [`chrono::NaiveTime`].                                                                          |
 //! | `UTCDateOnly`              | [`Date`], [`chrono::NaiveDate`].                                                                          |
-//! | `UTCTimestamp`             | [`Timestamp`], [`chrono::NaiveDateTime`].                                                                     |
+//! | `UTCTimestamp`             | [`Timestamp`], [`chrono::NaiveDateTime`], [`chrono::DateTime<chrono::Utc>`].                                  |
 //! | `TZTimestamp`              | [`TzTimestamp`], [`chrono::DateTime<chrono::FixedOffset>`].                                                                   |
 //! | `LocalMktDate`             | [`Date`], [`chrono::NaiveDate`].                                                                     |
-//! | `TZTimeOnly`               | [`TzTime`],  |
+//! | `TZTimeOnly`               | [`TzTime`].  |
 //!
 //! The above table provides some useful guidelines that work for the vast
 //! majority of use cases.
 //!
+//! Note that unsigned Rust integers don't capture all the constraints of the
+//! FIX integer subtypes, e.g. `SeqNum` must be positive but [`u64`] allows
+//! zero. [`check_int_subtype`] enforces those constraints;
+//! [`Decoder`](crate::tagvalue::Decoder) uses it to validate all integer fields
+//! according to their datatype in the dictionary. Leading zeros are allowed,
+//! e.g. `00042` is 42, except for `TagNum`; see [`check_leading_zeros`].
+//!
 //! # Quick tour of [`FieldType`]
 //!
 //! ```
@@ -52,12 +61,40 @@
 //! assert_eq!(&buffer[..], b"1337" as &[u8]);
 //! ```
 //!
+//! # String fields and UTF-8
+//!
+//! Most FIX fields are ASCII, but `EncodedText <355>` and other free-text
+//! fields might not be valid UTF-8. You can pick how to deal with
+//! non-UTF-8 contents simply by choosing the right type:
+//!
+//! | Policy     | How                                                   | Invalid UTF-8 |
+//! |------------|-------------------------------------------------------|---------------|
+//! | **Strict** | `&str`, [`String`], `Cow<str>` with `get`             | [`Err`]       |
+//! | **Lossy**  | `Cow<str>`, [`String`] with `get_lossy`               | `U+FFFD`      |
+//! | **Raw**    | `&[u8]` with `get`, or [`FieldMap::get_raw`](crate::FieldMap::get_raw) | Untouched     |
+//!
+//! ```
+//! use fefix::FieldType;
+//! use std::borrow::Cow;
+//!
+//! let bytes = b"caf\xE9";
+//! assert!(<&str>::deserialize(bytes).is_err());
+//! assert_eq!(Cow::<str>::deserialize_lossy(bytes).unwrap(), "caf\u{FFFD}");
+//! assert_eq!(<&[u8]>::deserialize(bytes).unwrap(), bytes);
+//! ```
+//!
+//! None of the above ever panics on invalid input.
+//!
 //! [^1]: With the exception of datatype `data`, FIX mandates a single-byte
 //! encoding (Latin alphabet No. 1 by default), while Rust strings are UTF-8,
 //! which is a multibyte encoding. These are *not* compatible. Watch out!
 
 mod checksum;
+mod codes;
 mod date;
+mod decimals;
+mod encoded_text;
+mod int_subtype;
 mod monthyear;
 mod multiple_chars;
 mod multiple_strings;
@@ -65,9 +102,11 @@ mod primitives;
 mod tagu32;
 mod time;
 mod timestamp;
+mod typed_value;
 mod tz;
 mod tz_time;
 mod tz_timestamp;
+mod utc_datetime;
 
 #[cfg(feature = "utils-chrono")]
 mod utils_chrono;
@@ -78,24 +117,23 @@ mod utils_rust_decimal;
 
 use crate::FieldType;
 pub use checksum::CheckSum;
+pub use codes::{Country, Currency, Exchange};
 pub use date::Date;
+pub use decimals::{Amt, Percentage, Price, PriceOffset, Qty, Scale};
+pub use encoded_text::{EncodedText, EncodedTextError};
+pub(crate) use int_subtype::is_int_datatype;
+pub use int_subtype::{check_int_subtype, check_leading_zeros, leading_zeros, IntSubtypeError};
 pub use monthyear::MonthYear;
 pub use multiple_chars::MultipleChars;
 pub use multiple_strings::MultipleStrings;
+use serde::{Deserialize, Serialize};
 pub use time::Time;
 pub use timestamp::Timestamp;
+pub use typed_value::TypedValue;
 pub use tz::Tz;
 pub use tz_time::TzTime;
 pub use tz_timestamp::TzTimestamp;
 
-/// Type alias for ISO 3166-1 alpha-2 strings (two-letter country codes).
-pub type Country = [u8; 2];
-/// Type alias for ISO 4217 alpha codes (three-letter currency codes).
-pub type Currency = [u8; 3];
-/// Type alias for four-letter *Market Identifier Codes* (MICs) as defined by
-/// ISO 10383.
-pub type Exchange = [u8; 4];
-
 pub(crate) const ERR_UTF8: &str = "Invalid byte sequence; expected UTF-8 valid bytes.";
 pub(crate) const ERR_INT_INVALID: &str = "Invalid integer digits.";
 pub(crate) const ERR_TIME: &str = "Invalid time.";
@@ -105,6 +143,65 @@ pub(crate) const ERR_DECIMAL: &str = "Invalid decimal number.";
 #[derive(Debug, Copy, Clone, Default)]
 pub struct ZeroPadding(pub usize);
 
+/// The number of fractional digits of `UTCTimestamp` values, e.g. of
+/// `SendingTime <52>`; see [`FieldType::SerializeSettings`].
+///
+/// Milliseconds by default.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TimestampPrecision {
+    /// `YYYYMMDD-HH:MM:SS`.
+    Seconds,
+    /// `YYYYMMDD-HH:MM:SS.sss`.
+    #[default]
+    Millis,
+    /// `YYYYMMDD-HH:MM:SS.ssssss`.
+    Micros,
+    /// `YYYYMMDD-HH:MM:SS.sssssssss`.
+    Nanos,
+}
+
+impl TimestampPrecision {
+    /// Returns the number of fractional digits of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::TimestampPrecision;
+    ///
+    /// assert_eq!(TimestampPrecision::Seconds.digits(), 0);
+    /// assert_eq!(TimestampPrecision::Micros.digits(), 6);
+    /// ```
+    pub fn digits(self) -> usize {
+        match self {
+            Self::Seconds => 0,
+            Self::Millis => 3,
+            Self::Micros => 6,
+            Self::Nanos => 9,
+        }
+    }
+
+    /// Returns the highest precision that counterparties can be expected to
+    /// accept with `begin_string`: seconds before FIX 4.2, milliseconds up
+    /// to FIX 4.4, and nanoseconds with FIXT 1.1 (i.e. FIX 5.0 and later).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::TimestampPrecision;
+    ///
+    /// assert_eq!(TimestampPrecision::for_begin_string(b"FIX.4.1"), TimestampPrecision::Seconds);
+    /// assert_eq!(TimestampPrecision::for_begin_string(b"FIX.4.4"), TimestampPrecision::Millis);
+    /// assert_eq!(TimestampPrecision::for_begin_string(b"FIXT.1.1"), TimestampPrecision::Nanos);
+    /// ```
+    pub fn for_begin_string(begin_string: &[u8]) -> Self {
+        match begin_string {
+            b"FIX.4.0" | b"FIX.4.1" => Self::Seconds,
+            b"FIXT.1.1" => Self::Nanos,
+            _ => Self::Millis,
+        }
+    }
+}
+
 /// Tries to [`FieldType::serialize`] an `item`, then to
 /// [`FieldType::deserialize`] it, and finally checks for equality with the
 /// initial data. [`FieldType::deserialize_lossy`] is then
@@ -125,6 +222,30 @@ mod test {
     use crate::FieldType;
     use quickcheck_macros::quickcheck;
 
+    #[test]
+    fn invalid_utf8_strict_and_lossy() {
+        use std::borrow::Cow;
+
+        let bytes = b"Hello \xF0\x28\x8C\x28";
+        assert!(<&str>::deserialize(bytes).is_err());
+        assert!(String::deserialize(bytes).is_err());
+        assert!(Cow::<str>::deserialize(bytes).is_err());
+        assert_eq!(
+            String::deserialize_lossy(bytes).unwrap(),
+            "Hello \u{FFFD}(\u{FFFD}("
+        );
+        assert!(matches!(
+            Cow::<str>::deserialize_lossy(b"Hello"),
+            Ok(Cow::Borrowed("Hello"))
+        ));
+        assert_eq!(<&[u8]>::deserialize(bytes).unwrap(), bytes);
+    }
+
+    #[quickcheck]
+    fn verify_serialization_behavior_string(s: String) -> bool {
+        super::test_utility_verify_serialization_behavior(s)
+    }
+
     #[test]
     fn serialize_bools() {
         let mut buffer = Vec::new();
@@ -1,5 +1,6 @@
 use super::{ZeroPadding, ERR_INT_INVALID, ERR_UTF8};
 use crate::{Buffer, BufferWriter, FieldType};
+use std::borrow::Cow;
 use std::fmt::Write;
 
 const ERR_BOOL_LENGTH: &str = "Invalid length; a boolean is Y or N (1 char).";
@@ -177,6 +178,56 @@ impl<'a> FieldType<'a> for &'a str {
     }
 }
 
+/// Strict UTF-8 decoding with [`FieldType::deserialize`], and replacement of
+/// invalid byte sequences with `U+FFFD` with [`FieldType::deserialize_lossy`].
+/// Valid UTF-8 is always borrowed.
+impl<'a> FieldType<'a> for Cow<'a, str> {
+    type Error = std::str::Utf8Error;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        self.as_ref().serialize_with(buffer, settings)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        std::str::from_utf8(data).map(Cow::Borrowed)
+    }
+
+    #[inline]
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(String::from_utf8_lossy(data))
+    }
+}
+
+/// Like [`Cow<str>`](std::borrow::Cow), but always allocates.
+impl<'a> FieldType<'a> for String {
+    type Error = std::str::Utf8Error;
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        self.as_str().serialize_with(buffer, settings)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        <&str>::deserialize(data).map(ToString::to_string)
+    }
+
+    #[inline]
+    fn deserialize_lossy(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(String::from_utf8_lossy(data).into_owned())
+    }
+}
+
 impl<'a> FieldType<'a> for &'a [u8] {
     type Error = ();
     type SerializeSettings = ();
@@ -196,6 +247,25 @@ impl<'a> FieldType<'a> for &'a [u8] {
     }
 }
 
+/// Like `&[u8]`, but always allocates.
+impl<'a> FieldType<'a> for Vec<u8> {
+    type Error = ();
+    type SerializeSettings = ();
+
+    #[inline]
+    fn serialize_with<B>(&self, buffer: &mut B, settings: ()) -> usize
+    where
+        B: Buffer,
+    {
+        self.as_slice().serialize_with(buffer, settings)
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Ok(data.to_vec())
+    }
+}
+
 impl<'a, const N: usize> FieldType<'a> for [u8; N] {
     type Error = ();
     type SerializeSettings = ();
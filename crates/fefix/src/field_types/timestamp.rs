@@ -24,6 +24,57 @@ impl Timestamp {
         Some(Self::new(date, time))
     }
 
+    /// Like [`Timestamp::parse`], but it tolerates the most common deviations
+    /// from the `UTCTimestamp` format, e.g. in `SendingTime <52>` values of
+    /// nonconforming counterparties. The result serializes back to the
+    /// standard format. The tolerated deviations are, exactly:
+    ///
+    /// - A space or a `T` instead of the `-` between date and time.
+    /// - Fractional seconds with any number of digits from 1 to 9, instead of
+    ///   exactly 3. They're truncated to millisecond precision. Like with
+    ///   [`Timestamp::parse`], fractional seconds may also be missing
+    ///   altogether.
+    /// - A trailing `Z`.
+    ///
+    /// Everything else is still rejected, e.g. missing seconds or invalid
+    /// dates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::Timestamp;
+    /// use fefix::FieldType;
+    ///
+    /// let timestamp = Timestamp::parse_lenient(b"20210101 10:00:00.5Z").unwrap();
+    /// assert_eq!(timestamp.to_string(), "20210101-10:00:00.500");
+    /// assert!(Timestamp::parse(b"20210101 10:00:00.5Z").is_none());
+    /// assert!(Timestamp::parse_lenient(b"20210101 10:00").is_none());
+    /// ```
+    pub fn parse_lenient(data: &[u8]) -> Option<Self> {
+        let data = data.strip_suffix(b"Z").unwrap_or(data);
+        if data.len() < 17 || !matches!(data[8], b'-' | b' ' | b'T') {
+            return None;
+        }
+        let date = Date::deserialize(&data[0..8]).ok()?;
+        let (time, fraction) = data[9..].split_at(8);
+        let milli = match fraction {
+            [] => 0,
+            [b'.', digits @ ..]
+                if (1..=9).contains(&digits.len()) && digits.iter().all(u8::is_ascii_digit) =>
+            {
+                digits
+                    .iter()
+                    .chain(b"00")
+                    .take(3)
+                    .fold(0, |milli, digit| milli * 10 + u32::from(digit - b'0'))
+            }
+            _ => return None,
+        };
+        let time = Time::deserialize(time).ok()?;
+        let time = Time::from_hmsm(time.hour(), time.minute(), time.second(), milli)?;
+        Some(Self::new(date, time))
+    }
+
     /// Returns the current UTC system time with millisecond precision.
     pub fn utc_now() -> Self {
         use chrono::{Datelike, Timelike};
@@ -106,4 +157,42 @@ mod test {
     fn verify_serialization_behavior(timestamp: Timestamp) -> bool {
         crate::field_types::test_utility_verify_serialization_behavior(timestamp)
     }
+
+    #[quickcheck]
+    fn lenient_parsing_accepts_standard_timestamps(timestamp: Timestamp) -> bool {
+        Timestamp::parse_lenient(timestamp.to_string().as_bytes()) == Some(timestamp)
+    }
+
+    #[test]
+    fn lenient_parsing_normalizes_common_deviations() {
+        for (data, expected) in [
+            ("20210101-10:00:00", "20210101-10:00:00.000"),
+            ("20210101 10:00:00", "20210101-10:00:00.000"),
+            ("20210101T10:00:00.123Z", "20210101-10:00:00.123"),
+            ("20210101-10:00:00.12", "20210101-10:00:00.120"),
+            ("20210101-10:00:00.123456789", "20210101-10:00:00.123"),
+            ("20210101-23:59:60Z", "20210101-23:59:60.000"),
+        ] {
+            let timestamp = Timestamp::parse_lenient(data.as_bytes()).unwrap();
+            assert_eq!(timestamp.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn lenient_parsing_rejects_unparseable_timestamps() {
+        for data in [
+            "",
+            "20210101",
+            "20210101-10:00",
+            "20210101/10:00:00",
+            "20211301-10:00:00",
+            "20210101-10:00:00.",
+            "20210101-10:00:00.1234567890",
+            "20210101-10:00:00.12x",
+            "20210101-10:00:00+01",
+            "20210101-10:00:00ZZ",
+        ] {
+            assert_eq!(Timestamp::parse_lenient(data.as_bytes()), None, "{}", data);
+        }
+    }
 }
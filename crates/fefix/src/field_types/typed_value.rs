@@ -0,0 +1,167 @@
+use crate::dict::FixDatatype;
+use crate::field_types::{Date, MonthYear, MultipleStrings, Time, Timestamp};
+use crate::FieldType;
+
+/// A field value, parsed according to its FIX datatype. See
+/// [`TypedValue::parse`] and
+/// [`Message::iter_typed`](crate::tagvalue::Message::iter_typed).
+///
+/// `Price`, `Qty`, `Amt`, and `PriceOffset` are exact decimals, so they're
+/// kept as their digits in [`TypedValue::Decimal`], e.g. for
+/// [`rust_decimal::Decimal::from_str`](https://docs.rs/rust_decimal). Other
+/// `float`-based datatypes are parsed as [`f64`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum TypedValue {
+    /// `char`.
+    Char(u8),
+    /// `Boolean`.
+    Bool(bool),
+    /// `int` and all of its subtypes, e.g. `SeqNum` and `NumInGroup`.
+    Int(i64),
+    /// `float` and `Percentage`.
+    Float(f64),
+    /// `Price`, `Qty`, `Amt`, and `PriceOffset`, as the decimal digits of the
+    /// value with an optional sign, e.g. `"-0.0125"`.
+    Decimal(String),
+    /// `String`, `Currency`, `Exchange`, `Country`, and `Language`.
+    Str(String),
+    /// `MultipleCharValue` and `MultipleStringValue`.
+    MultipleValues(Vec<String>),
+    /// `UTCDateOnly` and `LocalMktDate`.
+    Date(Date),
+    /// `UTCTimeOnly`.
+    Time(Time),
+    /// `UTCTimestamp`.
+    UtcTimestamp(Timestamp),
+    /// `month-year`.
+    MonthYear(MonthYear),
+    /// `data` and `XMLData`.
+    Data(Vec<u8>),
+    /// A value that is not valid for its datatype (e.g. non-UTF-8 strings),
+    /// or whose datatype is unknown.
+    Raw(Vec<u8>),
+}
+
+impl TypedValue {
+    /// Parses `data` as a value of `datatype`. Returns [`TypedValue::Raw`]
+    /// instead of an error if `data` is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::FixDatatype;
+    /// use fefix::field_types::TypedValue;
+    ///
+    /// assert_eq!(TypedValue::parse(FixDatatype::SeqNum, b"42"), TypedValue::Int(42));
+    /// assert_eq!(TypedValue::parse(FixDatatype::Price, b"1.50"), TypedValue::Decimal("1.50".to_string()));
+    /// assert_eq!(TypedValue::parse(FixDatatype::Float, b"1.5"), TypedValue::Float(1.5));
+    /// assert_eq!(TypedValue::parse(FixDatatype::Boolean, b"?"), TypedValue::Raw(b"?".to_vec()));
+    /// ```
+    pub fn parse(datatype: FixDatatype, data: &[u8]) -> Self {
+        Self::try_parse(datatype, data).unwrap_or_else(|| TypedValue::Raw(data.to_vec()))
+    }
+
+    fn try_parse(datatype: FixDatatype, data: &[u8]) -> Option<Self> {
+        Some(match datatype {
+            FixDatatype::Char => match data {
+                [byte] => TypedValue::Char(*byte),
+                _ => return None,
+            },
+            FixDatatype::Boolean => TypedValue::Bool(bool::deserialize(data).ok()?),
+            FixDatatype::Int
+            | FixDatatype::DayOfMonth
+            | FixDatatype::Length
+            | FixDatatype::NumInGroup
+            | FixDatatype::SeqNum
+            | FixDatatype::TagNum => TypedValue::Int(i64::deserialize(data).ok()?),
+            FixDatatype::Amt | FixDatatype::Price | FixDatatype::PriceOffset | FixDatatype::Qty => {
+                TypedValue::Decimal(parse_decimal(data)?)
+            }
+            FixDatatype::Float | FixDatatype::Percentage => {
+                TypedValue::Float(f64::deserialize(data).ok()?)
+            }
+            FixDatatype::String
+            | FixDatatype::Currency
+            | FixDatatype::Exchange
+            | FixDatatype::Country
+            | FixDatatype::Language => TypedValue::Str(String::deserialize(data).ok()?),
+            FixDatatype::MultipleCharValue | FixDatatype::MultipleStringValue => {
+                TypedValue::MultipleValues(
+                    MultipleStrings::new(data)
+                        .map(|value| String::deserialize(value).ok())
+                        .collect::<Option<_>>()?,
+                )
+            }
+            FixDatatype::UtcDateOnly | FixDatatype::LocalMktDate => {
+                TypedValue::Date(Date::deserialize(data).ok()?)
+            }
+            FixDatatype::UtcTimeOnly => TypedValue::Time(Time::deserialize(data).ok()?),
+            FixDatatype::UtcTimestamp => {
+                TypedValue::UtcTimestamp(Timestamp::deserialize(data).ok()?)
+            }
+            FixDatatype::MonthYear => TypedValue::MonthYear(MonthYear::deserialize(data).ok()?),
+            FixDatatype::Data | FixDatatype::XmlData => TypedValue::Data(data.to_vec()),
+            _ => return None,
+        })
+    }
+}
+
+// FIX decimals have an optional `-`, and digits with an optional `.`, but no
+// exponent.
+fn parse_decimal(data: &[u8]) -> Option<String> {
+    let digits = data.strip_prefix(b"-").unwrap_or(data);
+    let mut parts = digits.splitn(2, |byte| *byte == b'.');
+    let integer = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let is_valid = integer.len() + fraction.len() > 0
+        && integer.iter().chain(fraction).all(u8::is_ascii_digit);
+    // Only ASCII bytes are left, so this can't fail.
+    is_valid.then(|| String::from_utf8_lossy(data).into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn invalid_values_are_raw() {
+        let raw = |data: &[u8]| TypedValue::Raw(data.to_vec());
+        assert_eq!(TypedValue::parse(FixDatatype::Char, b"AB"), raw(b"AB"));
+        assert_eq!(TypedValue::parse(FixDatatype::Int, b"1.0"), raw(b"1.0"));
+        assert_eq!(TypedValue::parse(FixDatatype::Qty, b"x"), raw(b"x"));
+        assert_eq!(
+            TypedValue::parse(FixDatatype::String, b"caf\xE9"),
+            raw(b"caf\xE9")
+        );
+        assert_eq!(
+            TypedValue::parse(FixDatatype::UtcTimestamp, b"20210101"),
+            raw(b"20210101")
+        );
+    }
+
+    #[test]
+    fn decimals_keep_their_digits() {
+        let decimal = |data: &str| TypedValue::Decimal(data.to_string());
+        for data in ["0", "-1.50", ".5", "5.", "12345678901234567890.0000000001"] {
+            assert_eq!(
+                TypedValue::parse(FixDatatype::Price, data.as_bytes()),
+                decimal(data)
+            );
+        }
+        for data in ["", "-", ".", "1e5", "+1", "1.2.3", "NaN"] {
+            assert_eq!(
+                TypedValue::parse(FixDatatype::Qty, data.as_bytes()),
+                TypedValue::Raw(data.as_bytes().to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn multiple_values() {
+        assert_eq!(
+            TypedValue::parse(FixDatatype::MultipleCharValue, b"2 A F"),
+            TypedValue::MultipleValues(vec!["2".to_string(), "A".to_string(), "F".to_string()])
+        );
+    }
+}
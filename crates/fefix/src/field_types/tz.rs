@@ -60,7 +60,7 @@ impl Tz {
     pub fn to_chrono_offset(&self) -> chrono::FixedOffset {
         // unwrap(): we already verified that the offset is within bounds during
         // deserialization
-        chrono::FixedOffset::east_opt(self.offset().1.as_secs() as i32).unwrap()
+        chrono::FixedOffset::east_opt(self.offset_from_utc_in_seconds).unwrap()
     }
 
     /// Creates a [`Tz`] from a [`chrono::FixedOffset`].
@@ -114,34 +114,29 @@ impl<'a> FieldType<'a> for Tz {
         if data.is_empty() {
             return Err(ERR_INVALID);
         }
-        let sign: i32;
         match data[0] {
-            b'Z' => {
+            b'Z' if data.len() == 1 => {
                 return Ok(Self::UTC);
             }
-            b'+' => {
-                sign = 1;
-            }
-            b'-' => {
-                sign = -1;
-            }
+            b'+' | b'-' => {}
             _ => return Err(ERR_INVALID),
         }
-        match data.len() {
-            3 => {
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR) as i32,
-                })
-            }
+        let is_valid = match data.len() {
+            3 => data[1..].iter().all(u8::is_ascii_digit),
+            // Minutes go from 00 to 59.
             6 => {
-                let hour = ascii_digit_to_u32(data[1], 10) + ascii_digit_to_u32(data[2], 1);
-                let minute = ascii_digit_to_u32(data[4], 10) + ascii_digit_to_u32(data[5], 1);
-                Ok(Self {
-                    offset_from_utc_in_seconds: sign * (hour * HOUR + minute * MINUTE) as i32,
-                })
+                data[3] == b':'
+                    && data[4] <= b'5'
+                    && [data[1], data[2], data[4], data[5]]
+                        .iter()
+                        .all(u8::is_ascii_digit)
             }
-            _ => Err(ERR_INVALID),
+            _ => false,
+        };
+        if is_valid {
+            Self::deserialize_lossy(data)
+        } else {
+            Err(ERR_INVALID)
         }
     }
 
@@ -168,6 +163,15 @@ impl<'a> FieldType<'a> for Tz {
     }
 }
 
+/// Splits `data` into its local time and its optional timezone offset suffix,
+/// which starts at the first `Z`, `+`, or `-`.
+pub(super) fn split_tz(data: &[u8]) -> Result<(&[u8], Option<Tz>), &'static str> {
+    match data.iter().position(|b| matches!(b, b'Z' | b'+' | b'-')) {
+        Some(i) => Ok((&data[..i], Some(Tz::deserialize(&data[i..])?))),
+        None => Ok((data, None)),
+    }
+}
+
 const fn u32_digit_to_ascii(digit: u32) -> u8 {
     digit as u8 + b'0'
 }
@@ -205,4 +209,35 @@ mod test {
         assert_eq!(&tz.to_bytes()[..], "-01".as_bytes());
         assert_eq!(tz.offset_as_secs(), -3600);
     }
+
+    #[test]
+    fn invalid_offsets() {
+        for data in [
+            b"Z0" as &[u8],
+            b"+1",
+            b"+0100",
+            b"+01:60",
+            b"-0a",
+            b"+01-30",
+        ] {
+            Tz::deserialize(data).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn split_missing_offset() {
+        assert_eq!(split_tz(b"07:39"), Ok((b"07:39" as &[u8], None)));
+        assert_eq!(
+            split_tz(b"07:39-05:30"),
+            Ok((b"07:39" as &[u8], Some(Tz::deserialize(b"-05:30").unwrap())))
+        );
+    }
+
+    #[cfg(feature = "utils-chrono")]
+    #[test]
+    fn chrono_offset_keeps_sign() {
+        let tz = Tz::deserialize(b"-05").unwrap();
+        assert_eq!(tz.to_chrono_offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(Tz::from_chrono_offset(tz.to_chrono_offset()), tz);
+    }
 }
@@ -1,19 +1,33 @@
-use super::{Tz, ERR_TIME};
+use super::{tz::split_tz, Time, Tz, ERR_TIME};
 use crate::{Buffer, FieldType};
 
-/// Timezone-aware intra-day timestamp.
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// Timezone-aware intra-day timestamp, in `HH:MM[:SS][Z | [ + | - hh[:mm]]]`
+/// format.
+///
+/// The timezone offset is optional: when missing, the time is local and there
+/// is no way to know its UTC equivalent.
 ///
 /// # Examples
 ///
 /// ```
 /// use fefix::FieldType;
-/// use fefix::field_types::{Tz, TzTime};
+/// use fefix::field_types::{Time, Tz, TzTime};
 ///
 /// let tztime = TzTime::deserialize(b"07:39:20Z").unwrap();
 /// assert_eq!(tztime.hour(), 7);
 /// assert_eq!(tztime.minute(), 39);
 /// assert_eq!(tztime.second(), 20);
-/// assert_eq!(tztime.timezone(), Tz::UTC);
+/// assert_eq!(tztime.timezone(), Some(Tz::UTC));
+///
+/// let tztime = TzTime::deserialize(b"02:39-05").unwrap();
+/// assert_eq!(tztime.to_utc(), Time::from_hmsm(7, 39, 0, 0));
+/// assert_eq!(&tztime.to_bytes()[..], b"02:39-05");
+///
+/// let tztime = TzTime::deserialize(b"02:39").unwrap();
+/// assert_eq!(tztime.timezone(), None);
+/// assert_eq!(tztime.to_utc(), None);
 /// ```
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct TzTime {
@@ -21,7 +35,7 @@ pub struct TzTime {
     minute: u32,
     second: u32,
     second_is_explicit: bool,
-    tz: Tz,
+    tz: Option<Tz>,
 }
 
 impl TzTime {
@@ -52,10 +66,20 @@ impl TzTime {
         self.second
     }
 
-    /// Returns the timezone ([`Tz`]) of `self`.
-    pub fn timezone(&self) -> Tz {
+    /// Returns the timezone ([`Tz`]) of `self`, or `None` if `self` is in
+    /// unspecified local time.
+    pub fn timezone(&self) -> Option<Tz> {
         self.tz
     }
+
+    /// Converts `self` to UTC, wrapping around midnight if necessary. Returns
+    /// `None` if the timezone of `self` is unspecified.
+    pub fn to_utc(&self) -> Option<Time> {
+        let offset_in_minutes = self.tz?.offset_as_secs() / 60;
+        let minutes = (self.hour * 60 + self.minute) as i32 - offset_in_minutes;
+        let minutes = minutes.rem_euclid(MINUTES_PER_DAY) as u32;
+        Time::from_hmsm(minutes / 60, minutes % 60, self.second, 0)
+    }
 }
 
 impl<'a> FieldType<'a> for TzTime {
@@ -67,47 +91,61 @@ impl<'a> FieldType<'a> for TzTime {
         B: Buffer,
     {
         let bytes = &[
-            self.hour() as u8 / 10,
-            self.hour() as u8 % 10,
+            u32_digit_to_ascii(self.hour() / 10),
+            u32_digit_to_ascii(self.hour() % 10),
             b':',
-            self.minute() as u8 / 10,
-            self.minute() as u8 % 10,
+            u32_digit_to_ascii(self.minute() / 10),
+            u32_digit_to_ascii(self.minute() % 10),
             b':',
-            self.second() as u8 / 10,
-            self.second() as u8 % 10,
+            u32_digit_to_ascii(self.second() / 10),
+            u32_digit_to_ascii(self.second() % 10),
         ];
+        let bytes = if self.second_is_explicit {
+            &bytes[..]
+        } else {
+            &bytes[..5]
+        };
         buffer.extend_from_slice(bytes);
-        bytes.len() + self.timezone().serialize(buffer)
+        bytes.len() + self.tz.map_or(0, |tz| tz.serialize(buffer))
     }
 
     fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() < 6 || data[2] != b':' {
+        let (time, tz) = split_tz(data).map_err(|_| ERR_TIME)?;
+        let second_is_explicit = match time.len() {
+            5 => false,
+            8 if time[5] == b':' => true,
+            _ => return Err(ERR_TIME),
+        };
+        let digits_are_ok = time[2] == b':'
+            && time
+                .iter()
+                .enumerate()
+                .all(|(i, byte)| i % 3 == 2 || byte.is_ascii_digit());
+        if !digits_are_ok {
             return Err(ERR_TIME);
         }
-        let hour = ascii_digit_to_u32(data[0], 10) + ascii_digit_to_u32(data[1], 1);
-        let minute = ascii_digit_to_u32(data[3], 10) + ascii_digit_to_u32(data[4], 1);
-        match data[5] {
-            b':' => {
-                let second = ascii_digit_to_u32(data[6], 10) + ascii_digit_to_u32(data[7], 1);
-                Ok(TzTime {
-                    hour,
-                    minute,
-                    second,
-                    second_is_explicit: true,
-                    tz: Tz::deserialize(&data[8..]).map_err(|_| ERR_TIME)?,
-                })
-            }
-            _ => Ok(TzTime {
-                hour,
-                minute,
-                second: 0,
-                second_is_explicit: false,
-                tz: Tz::deserialize(&data[5..]).map_err(|_| ERR_TIME)?,
-            }),
-        }
+        let hour = ascii_digit_to_u32(time[0], 10) + ascii_digit_to_u32(time[1], 1);
+        let minute = ascii_digit_to_u32(time[3], 10) + ascii_digit_to_u32(time[4], 1);
+        let second = if second_is_explicit {
+            ascii_digit_to_u32(time[6], 10) + ascii_digit_to_u32(time[7], 1)
+        } else {
+            0
+        };
+        Time::from_hmsm(hour, minute, second, 0).ok_or(ERR_TIME)?;
+        Ok(TzTime {
+            hour,
+            minute,
+            second,
+            second_is_explicit,
+            tz,
+        })
     }
 }
 
+const fn u32_digit_to_ascii(digit: u32) -> u8 {
+    digit as u8 + b'0'
+}
+
 const fn ascii_digit_to_u32(digit: u8, multiplier: u32) -> u32 {
     (digit as u32).wrapping_sub(b'0' as u32) * multiplier
 }
@@ -148,6 +186,29 @@ mod test {
             assert_eq!(tz_time.hour(), test_case.hour);
             assert_eq!(tz_time.minute(), test_case.minute);
             assert_eq!(tz_time.second(), test_case.second);
+            assert_eq!(&tz_time.to_bytes()[..], test_case.bytes);
+        }
+    }
+
+    #[test]
+    fn utc_conversion_wraps_around_midnight() {
+        let tz_time = TzTime::deserialize(b"01:30:15+05:30").unwrap();
+        assert_eq!(tz_time.to_utc(), Time::from_hmsm(20, 0, 15, 0));
+        let tz_time = TzTime::deserialize(b"22:00-03").unwrap();
+        assert_eq!(tz_time.to_utc(), Time::from_hmsm(1, 0, 0, 0));
+    }
+
+    #[test]
+    fn invalid_times() {
+        for data in [
+            b"7:39Z" as &[u8],
+            b"24:00Z",
+            b"07:60",
+            b"07:39:2Z",
+            b"07-39Z",
+            b"07:39+",
+        ] {
+            TzTime::deserialize(data).unwrap_err();
         }
     }
 }
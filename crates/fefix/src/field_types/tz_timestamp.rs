@@ -1,23 +1,84 @@
-use super::{Timestamp, Tz};
+use super::{tz::split_tz, Date, Time, Timestamp, Tz};
 use crate::{Buffer, FieldType};
 
-/// A time and date combination representing local time with an offset from UTC.
+const ERR_INVALID: &str = "Invalid timestamp format";
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+/// A time and date combination representing local time with an offset from
+/// UTC, in `YYYYMMDD-HH:MM[:SS[.sss]][Z | [ + | - hh[:mm]]]` format.
+///
+/// The original precision and timezone offset are preserved, so that
+/// serialization gives back the same bytes. The timezone offset is optional:
+/// when missing, the timestamp is local and there is no way to know its UTC
+/// equivalent.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::FieldType;
+/// use fefix::field_types::{Tz, TzTimestamp};
+///
+/// let tz_timestamp = TzTimestamp::deserialize(b"20060901-02:39-05").unwrap();
+/// assert_eq!(tz_timestamp.timezone(), Some(Tz::deserialize(b"-05").unwrap()));
+/// assert_eq!(tz_timestamp.timestamp().time().hour(), 2);
+/// assert_eq!(&tz_timestamp.to_bytes()[..], b"20060901-02:39-05");
+///
+/// let utc = tz_timestamp.to_utc().unwrap();
+/// assert_eq!(&utc.to_bytes()[..], b"20060901-07:39:00.000");
+///
+/// let tz_timestamp = TzTimestamp::deserialize(b"20060901-02:39:20").unwrap();
+/// assert_eq!(tz_timestamp.timezone(), None);
+/// assert_eq!(tz_timestamp.to_utc(), None);
+/// ```
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TzTimestamp {
     timestamp: Timestamp,
-    tz: Tz,
+    // How many bytes of `Time::to_bytes` were originally present, i.e. the
+    // precision of `timestamp`.
+    time_len: usize,
+    tz: Option<Tz>,
 }
 
 impl TzTimestamp {
+    /// Combines a local `timestamp` and its optional timezone offset into a
+    /// [`TzTimestamp`] with millisecond precision.
+    pub fn new(timestamp: Timestamp, tz: Option<Tz>) -> Self {
+        Self {
+            timestamp,
+            time_len: 12,
+            tz,
+        }
+    }
+
     /// Returns the [`Timestamp`] (without timezone information) of `self`.
     pub fn timestamp(&self) -> Timestamp {
         self.timestamp.clone()
     }
 
-    /// Returns the [`Tz`] timezone information of `self`.
-    pub fn timezone(&self) -> Tz {
+    /// Returns the [`Tz`] timezone information of `self`, or `None` if
+    /// `self` is in unspecified local time.
+    pub fn timezone(&self) -> Option<Tz> {
         self.tz
     }
+
+    /// Normalizes `self` to a UTC [`Timestamp`]. Returns `None` if the
+    /// timezone of `self` is unspecified or if the resulting date is out of
+    /// bounds.
+    pub fn to_utc(&self) -> Option<Timestamp> {
+        let date = self.timestamp.date();
+        let time = self.timestamp.time();
+        let offset_in_minutes = i64::from(self.tz?.offset_as_secs() / 60);
+        // Offsets are whole minutes, so seconds (including leap seconds) never
+        // change.
+        let minutes = days_from_civil(date) * MINUTES_PER_DAY
+            + i64::from(time.hour() * 60 + time.minute())
+            - offset_in_minutes;
+        let date = civil_from_days(minutes.div_euclid(MINUTES_PER_DAY))?;
+        let minutes = minutes.rem_euclid(MINUTES_PER_DAY) as u32;
+        let time = Time::from_hmsm(minutes / 60, minutes % 60, time.second(), time.milli())?;
+        Some(Timestamp::new(date, time))
+    }
 }
 
 impl<'a> FieldType<'a> for TzTimestamp {
@@ -28,10 +89,105 @@ impl<'a> FieldType<'a> for TzTimestamp {
     where
         B: Buffer,
     {
-        self.timestamp().serialize(buffer)
+        let time = self.timestamp.time().to_bytes();
+        let len = self.timestamp.date().serialize(buffer) + b"-".serialize(buffer);
+        buffer.extend_from_slice(&time[..self.time_len]);
+        len + self.time_len + self.tz.map_or(0, |tz| tz.serialize(buffer))
+    }
+
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < 9 || data[8] != b'-' {
+            return Err(ERR_INVALID);
+        }
+        let date = Date::deserialize(&data[..8])?;
+        let (time, tz) = split_tz(&data[9..]).map_err(|_| ERR_INVALID)?;
+        let time_len = time.len();
+        let time = match time_len {
+            // Seconds are optional.
+            5 => {
+                let mut with_seconds = *b"HH:MM:00";
+                with_seconds[..5].copy_from_slice(time);
+                Time::deserialize(&with_seconds[..])?
+            }
+            _ => Time::deserialize(time)?,
+        };
+        Ok(Self {
+            timestamp: Timestamp::new(date, time),
+            time_len,
+            tz,
+        })
+    }
+}
+
+// Days since 1970-01-01 in the proleptic Gregorian calendar; see
+// <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_from_civil(date: Date) -> i64 {
+    let month = i64::from(date.month());
+    let year = i64::from(date.year()) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(date.day()) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> Option<Date> {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = (mp + 2) % 12 + 1;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    Date::new(u32::try_from(year).ok()?, month as u32, day as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip_preserves_precision_and_offset() {
+        for data in [
+            b"20060901-07:39Z" as &[u8],
+            b"20060901-02:39:00-05",
+            b"20060901-15:39:00.123+08",
+            b"20060901-13:09:59+05:30",
+            b"20060901-13:09:59",
+        ] {
+            let tz_timestamp = TzTimestamp::deserialize(data).unwrap();
+            assert_eq!(&tz_timestamp.to_bytes()[..], data);
+        }
+    }
+
+    #[test]
+    fn utc_conversion_crosses_month_and_year_boundaries() {
+        let tz_timestamp = TzTimestamp::deserialize(b"20210101-01:00:00.500+05:30").unwrap();
+        let utc = tz_timestamp.to_utc().unwrap();
+        assert_eq!(&utc.to_bytes()[..], b"20201231-19:30:00.500");
+        let tz_timestamp = TzTimestamp::deserialize(b"20240228-22:00:00-03").unwrap();
+        let utc = tz_timestamp.to_utc().unwrap();
+        assert_eq!(&utc.to_bytes()[..], b"20240229-01:00:00.000");
+        let tz_timestamp = TzTimestamp::deserialize(b"00000101-00:00Z").unwrap();
+        assert!(tz_timestamp.to_utc().is_some());
+        let tz_timestamp = TzTimestamp::deserialize(b"00000101-00:00+01").unwrap();
+        assert_eq!(tz_timestamp.to_utc(), None);
     }
 
-    fn deserialize(_data: &'a [u8]) -> Result<Self, Self::Error> {
-        unimplemented!()
+    #[test]
+    fn invalid_timestamps() {
+        for data in [
+            b"" as &[u8],
+            b"20060901",
+            b"20060901-",
+            b"20060901T07:39Z",
+            b"20060901-07:39:2Z",
+            b"20060901-07:39+5",
+        ] {
+            TzTimestamp::deserialize(data).unwrap_err();
+        }
     }
 }
@@ -0,0 +1,80 @@
+use super::{TimestampPrecision, ERR_TIME, ERR_UTF8};
+use crate::{Buffer, BufferWriter, FieldType};
+use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use std::fmt::Write;
+
+// Unlike the rest of the `chrono` integration, this doesn't depend on the
+// `utils-chrono` feature: `Session` stamps `SendingTime <52>` with it.
+impl<'a> FieldType<'a> for DateTime<Utc> {
+    type Error = &'static str;
+    type SerializeSettings = TimestampPrecision;
+
+    fn serialize_with<B>(&self, buffer: &mut B, precision: Self::SerializeSettings) -> usize
+    where
+        B: Buffer,
+    {
+        let naive = self.naive_utc();
+        write!(BufferWriter(buffer), "{}", naive.format("%Y%m%d-%H:%M:%S")).unwrap();
+        let len = "YYYYMMDD-HH:MM:SS".len();
+        let digits = precision.digits();
+        if digits == 0 {
+            return len;
+        }
+        // Leap seconds are represented by nanoseconds beyond one second.
+        let nanos = naive.nanosecond().min(999_999_999);
+        let fraction = nanos / 10u32.pow(9 - digits as u32);
+        write!(
+            BufferWriter(buffer),
+            ".{:0width$}",
+            fraction,
+            width = digits
+        )
+        .unwrap();
+        len + 1 + digits
+    }
+
+    #[inline]
+    fn deserialize(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let s = std::str::from_utf8(data).map_err(|_| ERR_UTF8)?;
+        let naive = NaiveDateTime::parse_from_str(s, "%Y%m%d-%H:%M:%S%.f").map_err(|_| ERR_TIME)?;
+        Ok(naive.and_utc())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn utc_timestamp_precision() {
+        let time = Utc
+            .with_ymd_and_hms(2021, 1, 1, 10, 0, 0)
+            .unwrap()
+            .with_nanosecond(717_000_000)
+            .unwrap();
+        let serialize = |precision| {
+            let mut buffer = Vec::new();
+            let len = time.serialize_with(&mut buffer, precision);
+            assert_eq!(len, buffer.len());
+            String::from_utf8(buffer).unwrap()
+        };
+        assert_eq!(serialize(TimestampPrecision::Seconds), "20210101-10:00:00");
+        assert_eq!(
+            serialize(TimestampPrecision::Millis),
+            "20210101-10:00:00.717"
+        );
+        assert_eq!(
+            serialize(TimestampPrecision::Micros),
+            "20210101-10:00:00.717000"
+        );
+        assert_eq!(
+            serialize(TimestampPrecision::Nanos),
+            "20210101-10:00:00.717000000"
+        );
+        for precision in [TimestampPrecision::Millis, TimestampPrecision::Nanos] {
+            let data = serialize(precision);
+            assert_eq!(DateTime::deserialize(data.as_bytes()), Ok(time));
+        }
+    }
+}
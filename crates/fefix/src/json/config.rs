@@ -36,12 +36,106 @@ pub struct Config {
     /// {"Header":{"...":"..."},"Body":{"...":"..."},"Trailer":{"...":"..."}}
     /// ```
     pub pretty_print: bool,
+    /// This setting indicates that all encoded messages should be wrapped in
+    /// an outer "envelope" object, which carries an `"Encoding"` identifier
+    /// (see [`Config::envelope_encoding`]) and the actual message under the
+    /// `"Message"` key.
+    ///
+    /// This is turned **off** by default.
+    ///
+    /// This setting has no effect when decoding messages: both bare and
+    /// enveloped messages are always accepted.
+    ///
+    /// # Output examples
+    ///
+    /// ```json
+    /// {
+    ///     "Encoding": "FIX-JSON",
+    ///     "Message": {
+    ///         "Header": {
+    ///             "...": "..."
+    ///         },
+    ///         "Body": {
+    ///             "...": "..."
+    ///         },
+    ///         "Trailer": {
+    ///             "...": "..."
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub envelope: bool,
+    /// The value of the `"Encoding"` identifier within enveloped messages.
+    /// Encoders write this value when [`Config::envelope`] is on, and decoders
+    /// reject enveloped messages with any other identifier.
+    ///
+    /// This is `"FIX-JSON"` by default. You may want to change it when
+    /// interoperating with counterparties that use a nonstandard identifier.
+    pub envelope_encoding: String,
+    /// This setting indicates that `XmlData <213>` should be written as a
+    /// nested JSON object rather than as a string, on a best-effort basis.
+    /// Malformed XML is still written as a string. See
+    /// [`Encoder::encode_tagvalue`](super::Encoder::encode_tagvalue) for the
+    /// mapping.
+    ///
+    /// This is turned **off** by default.
+    ///
+    /// This setting has no effect when decoding messages: nested objects under
+    /// `XmlData` are always accepted, and serialized back to XML.
+    ///
+    /// # Output examples
+    ///
+    /// `<Quote Px="1.5"><Leg>A</Leg><Leg>B</Leg></Quote>` becomes:
+    ///
+    /// ```json
+    /// {
+    ///     "XmlData": {
+    ///         "Quote": {
+    ///             "@Px": "1.5",
+    ///             "Leg": ["A", "B"]
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub nested_xml_data: bool,
+    /// This setting indicates that the fields of components, e.g.
+    /// `Instrument` or `Parties`, should be written within a nested object
+    /// under the name of the component, as defined by the
+    /// [`Dictionary`](crate::Dictionary). Components without any fields in
+    /// the message are omitted altogether. `StandardHeader` and
+    /// `StandardTrailer` are always written as `"Header"` and `"Trailer"`.
+    ///
+    /// This is turned **off** by default, i.e. all fields of a section or
+    /// group entry are written side by side.
+    ///
+    /// This setting has no effect when decoding messages: both nested and
+    /// flat components are always accepted.
+    ///
+    /// # Output examples
+    ///
+    /// ```json
+    /// {
+    ///     "Body": {
+    ///         "ClOrdID": "foo",
+    ///         "Instrument": {
+    ///             "Symbol": "EUR/USD",
+    ///             "SecurityID": "..."
+    ///         },
+    ///         "Side": "1"
+    ///     }
+    /// }
+    /// ```
+    pub nested_components: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             pretty_print: false,
+            envelope: false,
+            envelope_encoding: "FIX-JSON".to_string(),
+            nested_xml_data: false,
+            nested_components: false,
         }
     }
 }
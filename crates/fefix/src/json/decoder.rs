@@ -1,8 +1,13 @@
+use super::xml_data::XmlJson;
 use super::{Config, DecodeError};
-use crate::dict::{FieldLocation, IsFieldDefinition};
-use crate::{FieldMap, FieldType, FieldValueError, GetConfig, RepeatingGroup};
+use crate::dict::{FieldLocation, IsFieldDefinition, LayoutItem, LayoutItemKind};
+use crate::tagvalue::{self, EncoderHandle};
+use crate::{
+    Buffer, FieldMap, FieldType, FieldValueError, GetConfig, RepeatingGroup, SetField, TagU32,
+};
 use fefix_dictionary::Dictionary;
-use serde::{Deserialize, Serialize};
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -12,6 +17,7 @@ use std::sync::Arc;
 pub struct Message<'a> {
     internal: &'a MessageInternal<'a>,
     group_map: Option<&'a Fields<'a>>,
+    dict: &'a Dictionary,
 }
 
 impl<'a> Message<'a> {
@@ -22,6 +28,80 @@ impl<'a> Message<'a> {
         }
     }
 
+    /// Returns `self` as a [`serde_json::Value`], in the same
+    /// (non-enveloped) form as accepted by [`Decoder::decode`]. For group
+    /// entries, i.e. [`MessageGroup::get`](crate::RepeatingGroup::get), only
+    /// the fields of the entry are returned. See also [`Decoder::decode_value`].
+    pub fn to_json_value(&self) -> serde_json::Value {
+        match self.group_map {
+            Some(context) => fields_to_json_value(context),
+            None => serde_json::json!({
+                "Header": fields_to_json_value(&self.internal.std_header),
+                "Body": fields_to_json_value(&self.internal.body),
+                "Trailer": fields_to_json_value(&self.internal.std_trailer),
+            }),
+        }
+    }
+
+    fn field_definition(&self, tag: u32) -> Option<FieldDefinition<'a>> {
+        let field = self.dict.field_by_tag(tag)?;
+        let name = field.name();
+        let location = [
+            (&self.internal.std_header, FieldLocation::Header),
+            (&self.internal.std_trailer, FieldLocation::Trailer),
+        ]
+        .iter()
+        .find(|(fields, _)| fields.contains_key(name))
+        .map_or(FieldLocation::Body, |(_, location)| *location);
+        Some(FieldDefinition {
+            name,
+            tag: field.tag(),
+            location,
+        })
+    }
+
+    // Fields under numeric keys, e.g. `"5001"`, which may not be part of the
+    // dictionary at all.
+    fn numeric_field(&self, tag: u32) -> Option<&'a FieldOrGroup<'a>> {
+        let key = ToString::to_string(&tag);
+        if let Some(context) = self.group_map {
+            context.get(key.as_str())
+        } else {
+            [
+                &self.internal.std_header,
+                &self.internal.body,
+                &self.internal.std_trailer,
+            ]
+            .into_iter()
+            .find_map(|fields| fields.get(key.as_str()))
+        }
+    }
+
+    fn message_group(
+        &self,
+        field_or_group: &'a FieldOrGroup<'a>,
+    ) -> Result<MessageGroup<'a>, FieldValueError<<usize as FieldType<'a>>::Error>> {
+        let entries = match field_or_group {
+            FieldOrGroup::Group(entries) => &entries[..],
+            // An empty repeating group may be written as its `NumInGroup`
+            // field alone, rather than as an empty array.
+            FieldOrGroup::Field(num_in_group) => {
+                if <usize as FieldType>::deserialize(num_in_group.as_bytes())? == 0 {
+                    &[]
+                } else {
+                    return Err(FieldValueError::Missing);
+                }
+            }
+        };
+        Ok(MessageGroup {
+            message: Message {
+                group_map: None,
+                ..*self
+            },
+            entries,
+        })
+    }
+
     fn field_map<F>(&self, field: &F) -> &'a Fields<'a>
     where
         F: IsFieldDefinition,
@@ -36,6 +116,221 @@ impl<'a> Message<'a> {
             }
         }
     }
+
+    /// Encodes `self` in the tag-value encoding with `encoder`, appending the
+    /// resulting FIX message at the end of `buffer`. Fields are written in the
+    /// order of the definitions in `dict` and arrays of objects become
+    /// repeating groups, so that tagvalue→JSON→tagvalue round-trips preserve
+    /// group contents and order. `BodyLength <9>` and `CheckSum <10>` are
+    /// computed anew.
+    ///
+    /// Fields under numeric keys (e.g. `"5001"`) are written at the end of
+    /// their section or group entry, in ascending tag order. Other fields
+    /// that are not part of the message definition are ignored. Returns
+    /// [`DecodeError::InvalidMsgType`] if `BeginString <8>` or `MsgType <35>`
+    /// are missing or unknown to `dict`, and [`DecodeError::InvalidData`] if
+    /// a field and a repeating group are confused for each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::json::Decoder;
+    /// use fefix::tagvalue::Encoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let mut decoder = Decoder::new(dict.clone());
+    /// let json = br#"{
+    ///     "Header": { "BeginString": "FIX.4.4", "MsgType": "W" },
+    ///     "Body": {
+    ///         "Symbol": "EUR/USD",
+    ///         "NoMDEntries": [
+    ///             { "MDEntryType": "0", "MDEntryPx": "1.5" },
+    ///             { "MDEntryType": "1", "MDEntryPx": "1.75" }
+    ///         ]
+    ///     },
+    ///     "Trailer": {}
+    /// }"#;
+    /// let msg = decoder.decode(json).unwrap();
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let data = msg.encode_tagvalue(&dict, &mut encoder, &mut buffer).unwrap();
+    /// let data = std::str::from_utf8(data).unwrap();
+    /// assert!(data.contains("|35=W|55=EUR/USD|268=2|269=0|270=1.5|269=1|270=1.75|10="));
+    /// ```
+    pub fn encode_tagvalue<'b, B>(
+        &self,
+        dict: &Dictionary,
+        encoder: &'b mut tagvalue::Encoder,
+        buffer: &'b mut B,
+    ) -> Result<&'b [u8], DecodeError>
+    where
+        B: Buffer,
+    {
+        let header = &self.internal.std_header;
+        let begin_string = match header.get("BeginString") {
+            Some(FieldOrGroup::Field(value)) => value,
+            _ => return Err(DecodeError::InvalidMsgType),
+        };
+        let message_def = match header.get("MsgType") {
+            Some(FieldOrGroup::Field(msg_type)) => dict.message_by_msgtype(msg_type),
+            _ => None,
+        }
+        .ok_or(DecodeError::InvalidMsgType)?;
+        let std_header = dict
+            .component_by_name("StandardHeader")
+            .ok_or(DecodeError::InvalidMsgType)?;
+        let std_trailer = dict
+            .component_by_name("StandardTrailer")
+            .ok_or(DecodeError::InvalidMsgType)?;
+        let mut msg = encoder.start_message(
+            begin_string.as_bytes(),
+            buffer,
+            message_def.msg_type().as_bytes(),
+        );
+        set_tagvalue_fields(&mut msg, header, std_header.items())?;
+        set_tagvalue_fields(&mut msg, &self.internal.body, message_def.layout())?;
+        set_tagvalue_fields(&mut msg, &self.internal.std_trailer, std_trailer.items())?;
+        Ok(msg.done().0)
+    }
+}
+
+fn fields_to_json_value(fields: &Fields) -> serde_json::Value {
+    let object = fields
+        .iter()
+        .map(|(key, field_or_group)| {
+            let value = match field_or_group {
+                FieldOrGroup::Field(value) => serde_json::Value::from(value.as_ref()),
+                FieldOrGroup::Group(entries) => entries.iter().map(fields_to_json_value).collect(),
+            };
+            (String::from(key.as_ref()), value)
+        })
+        .collect();
+    serde_json::Value::Object(object)
+}
+
+// `BeginString <8>`, `BodyLength <9>`, `CheckSum <10>`, and `MsgType <35>` are
+// written by the tag-value encoder itself.
+const TAGVALUE_ENCODER_TAGS: &[u32] = &[8, 9, 10, 35];
+
+// Fields with numeric keys, e.g. `"5001"`, sorted by tag.
+type NumericFields<'f, 'a> = Vec<(TagU32, &'f FieldOrGroup<'a>)>;
+
+/// Writes `fields` in the order of `items`. Fields can be given either by
+/// name or by numeric tag, but not both. Numeric keys which are not part of
+/// `items`, e.g. custom fields, are written last in order of tag.
+fn set_tagvalue_fields<'d, B, I>(
+    msg: &mut EncoderHandle<B>,
+    fields: &Fields,
+    items: I,
+) -> Result<(), DecodeError>
+where
+    B: Buffer,
+    I: IntoIterator<Item = LayoutItem<'d>>,
+{
+    let mut numeric_fields: NumericFields = fields
+        .iter()
+        .filter_map(|(key, value)| Some((numeric_tag(key)?, value)))
+        .collect();
+    numeric_fields.sort_unstable_by_key(|(tag, _)| *tag);
+    let mut written = Vec::new();
+    set_layout_fields(msg, fields, &numeric_fields, items, &mut written)?;
+    for (tag, value) in numeric_fields {
+        match value {
+            _ if TAGVALUE_ENCODER_TAGS.contains(&tag.get()) || written.contains(&tag) => {}
+            FieldOrGroup::Field(value) => msg.set(tag.get(), value.as_bytes()),
+            // There's no way of telling the layout of group entries.
+            FieldOrGroup::Group(_) => return Err(DecodeError::InvalidData),
+        }
+    }
+    Ok(())
+}
+
+// Looks up the field with `name` and `tag` in `fields`, and adds its tag to
+// `written` if it was given by numeric tag.
+fn layout_field<'f, 'a>(
+    fields: &'f Fields<'a>,
+    numeric_fields: &NumericFields<'f, 'a>,
+    name: &str,
+    tag: TagU32,
+    written: &mut Vec<TagU32>,
+) -> Result<Option<&'f FieldOrGroup<'a>>, DecodeError> {
+    let by_tag = numeric_fields
+        .iter()
+        .find(|(numeric_tag, _)| *numeric_tag == tag)
+        .map(|(_, value)| *value);
+    match (fields.get(name), by_tag) {
+        (Some(_), Some(_)) => Err(DecodeError::DuplicateField(tag.get())),
+        (Some(value), None) => Ok(Some(value)),
+        (None, Some(value)) => {
+            written.push(tag);
+            Ok(Some(value))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+fn set_layout_fields<'d, 'f, 'a, B, I>(
+    msg: &mut EncoderHandle<B>,
+    fields: &'f Fields<'a>,
+    numeric_fields: &NumericFields<'f, 'a>,
+    items: I,
+    written: &mut Vec<TagU32>,
+) -> Result<(), DecodeError>
+where
+    B: Buffer,
+    I: IntoIterator<Item = LayoutItem<'d>>,
+{
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(field) => {
+                let tag = field.tag().get();
+                match layout_field(fields, numeric_fields, field.name(), field.tag(), written)? {
+                    _ if TAGVALUE_ENCODER_TAGS.contains(&tag) => {}
+                    Some(FieldOrGroup::Field(value)) => msg.set(tag, value.as_bytes()),
+                    Some(FieldOrGroup::Group(_)) => return Err(DecodeError::InvalidData),
+                    None => {}
+                }
+            }
+            LayoutItemKind::Component(component) => {
+                set_layout_fields(msg, fields, numeric_fields, component.items(), written)?;
+            }
+            LayoutItemKind::Group(field, items) => {
+                let entries =
+                    match layout_field(fields, numeric_fields, field.name(), field.tag(), written)?
+                    {
+                        Some(FieldOrGroup::Group(entries)) => &entries[..],
+                        Some(FieldOrGroup::Field(num_in_group)) if num_in_group == "0" => &[],
+                        Some(FieldOrGroup::Field(_)) => return Err(DecodeError::InvalidData),
+                        None => continue,
+                    };
+                let mut result = Ok(());
+                msg.set_group(field.tag().get(), |group| {
+                    for entry_fields in entries {
+                        group.entry(|entry| {
+                            if result.is_ok() {
+                                result =
+                                    set_tagvalue_fields(entry, entry_fields, items.iter().cloned());
+                            }
+                        });
+                    }
+                });
+                result?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the tag of `key` if it's numeric, e.g. `"5001"`.
+fn numeric_tag(key: &str) -> Option<TagU32> {
+    if key.bytes().all(|byte| byte.is_ascii_digit()) {
+        key.parse().ok().and_then(TagU32::new)
+    } else {
+        None
+    }
 }
 
 impl<'a, F> FieldMap<&F> for Message<'a>
@@ -51,32 +346,75 @@ where
         self.field_map(field)
             .get(field.name())
             .ok_or(FieldValueError::Missing)
-            .and_then(|field_or_group| {
-                if let FieldOrGroup::Group(ref entries) = field_or_group {
-                    Ok(MessageGroup {
-                        message: Message {
-                            internal: self.internal,
-                            group_map: None,
-                        },
-                        entries,
-                    })
-                } else {
-                    Err(FieldValueError::Missing)
-                }
-            })
+            .and_then(|field_or_group| self.message_group(field_or_group))
     }
 
     fn get_raw(&self, field: &F) -> Option<&[u8]> {
         self.field_map(field)
             .get(field.name())
-            .and_then(|field_or_group| {
-                if let FieldOrGroup::Field(value) = field_or_group {
-                    let s: &str = value.borrow();
-                    Some(s.as_bytes())
-                } else {
-                    None
-                }
-            })
+            .and_then(field_value)
+    }
+}
+
+/// Fields are looked up by their name in the [`Dictionary`] of the
+/// [`Decoder`], first within `"Header"`, then `"Body"` and `"Trailer"`, and
+/// then by their numeric tag, e.g. `"5001"`. The latter also works for tags
+/// which are unknown to the [`Dictionary`]. This allows code that is agnostic
+/// to the encoding of FIX messages, e.g.
+/// [`session::Session`](crate::session::Session), to read JSON messages as
+/// well.
+impl<'a> FieldMap<u32> for Message<'a> {
+    type Group = MessageGroup<'a>;
+
+    fn group(
+        &self,
+        tag: u32,
+    ) -> Result<Self::Group, FieldValueError<<usize as FieldType<'_>>::Error>> {
+        let result = match self.field_definition(tag) {
+            Some(field) => FieldMap::group(self, &field),
+            None => Err(FieldValueError::Missing),
+        };
+        result.or_else(|err| match self.numeric_field(tag) {
+            Some(field_or_group) => self.message_group(field_or_group),
+            None => Err(err),
+        })
+    }
+
+    fn get_raw(&self, tag: u32) -> Option<&[u8]> {
+        self.field_definition(tag)
+            .and_then(|field| FieldMap::get_raw(self, &field))
+            .or_else(|| self.numeric_field(tag).and_then(field_value))
+    }
+}
+
+fn field_value<'a>(field_or_group: &'a FieldOrGroup) -> Option<&'a [u8]> {
+    if let FieldOrGroup::Field(value) = field_or_group {
+        let s: &str = value.borrow();
+        Some(s.as_bytes())
+    } else {
+        None
+    }
+}
+
+/// A [`Field`](crate::dict::Field) of a [`Message`], in whichever section of
+/// the message it's found.
+struct FieldDefinition<'a> {
+    name: &'a str,
+    tag: TagU32,
+    location: FieldLocation,
+}
+
+impl<'a> IsFieldDefinition for FieldDefinition<'a> {
+    fn tag(&self) -> TagU32 {
+        self.tag
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn location(&self) -> FieldLocation {
+        self.location
     }
 }
 
@@ -96,8 +434,8 @@ impl<'a> RepeatingGroup for MessageGroup<'a> {
 
     fn get(&self, i: usize) -> Option<Self::Entry> {
         self.entries.get(i).map(|context| Message {
-            internal: self.message.internal,
             group_map: Some(context),
+            ..self.message
         })
     }
 }
@@ -118,6 +456,7 @@ impl<'a> Iterator for MessageFieldsIter<'a> {
 /// A codec for the JSON encoding type.
 #[derive(Debug, Clone)]
 pub struct Decoder {
+    dict: Arc<Dictionary>,
     dictionaries: HashMap<String, Arc<Dictionary>>,
     message_builder: MessageInternal<'static>,
     config: Config,
@@ -126,42 +465,133 @@ pub struct Decoder {
 impl Decoder {
     /// Creates a new JSON [`Decoder`]. `dict` serves as a reference for data type inference
     /// of incoming messages' fields. Configuration options are initialized via [`Default`].
-    pub fn new(dict: Dictionary) -> Self {
+    pub fn new<D>(dict: D) -> Self
+    where
+        D: Into<Arc<Dictionary>>,
+    {
+        let dict = dict.into();
         let mut dictionaries = HashMap::new();
-        dictionaries.insert(dict.version().to_string(), Arc::new(dict));
+        dictionaries.insert(dict.version().to_string(), dict.clone());
         Self {
+            dict,
             dictionaries,
             message_builder: MessageInternal::default(),
             config: Config::default(),
         }
     }
 
+    /// Decodes `data` into a [`Message`]. Both bare and enveloped messages
+    /// are accepted (see [`Config::envelope`]); the `"Encoding"` identifier of
+    /// enveloped messages must match [`Config::envelope_encoding`].
+    ///
+    /// Keys must be either field names known to the [`Dictionary`] or numeric
+    /// tags, e.g. `"5001"`. The latter are kept as they are, so that fields
+    /// ahead of the [`Dictionary`] are preserved. Components may be nested
+    /// objects under their names, as written with [`Config::nested_components`],
+    /// or flat. Any other key results in [`DecodeError::UnknownField`].
     pub fn decode<'a>(&'a mut self, data: &'a [u8]) -> Result<Message<'a>, DecodeError> {
         let mut deserilizer = serde_json::Deserializer::from_slice(data);
-        let msg = self.message_builder();
-        MessageInternal::deserialize_in_place(&mut deserilizer, msg).map_err(|err| {
-            if err.is_syntax() || err.is_eof() || err.is_io() {
-                DecodeError::Syntax
-            } else {
-                DecodeError::Schema
-            }
-        })?;
+        let envelope_encoding = self.config.envelope_encoding.clone();
+        let msg = Self::message_builder(&mut self.message_builder);
+        let seed = MessageSeed {
+            message: &mut *msg,
+            envelope_encoding: Some(&envelope_encoding),
+        };
+        seed.deserialize(&mut deserilizer)
+            .and_then(|()| deserilizer.end())
+            .map_err(decode_error)?;
+        Self::message(&self.dict, msg)
+    }
+
+    /// Like [`Decoder::decode`], but for a JSON `value` which was already
+    /// parsed, e.g. the body of an HTTP request. Strings are borrowed from
+    /// `value` rather than copied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::json::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let value = serde_json::json!({
+    ///     "Header": { "BeginString": "FIX.4.4", "MsgType": "0" },
+    ///     "Body": { "TestReqID": "foo" },
+    ///     "Trailer": {},
+    /// });
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// let msg = decoder.decode_value(&value).unwrap();
+    /// assert_eq!(msg.get_raw(fix44::TEST_REQ_ID), Some(b"foo" as &[u8]));
+    /// assert_eq!(msg.to_json_value(), value);
+    /// ```
+    pub fn decode_value<'a>(
+        &'a mut self,
+        value: &'a serde_json::Value,
+    ) -> Result<Message<'a>, DecodeError> {
+        let envelope_encoding = self.config.envelope_encoding.clone();
+        let msg = Self::message_builder(&mut self.message_builder);
+        let seed = MessageSeed {
+            message: &mut *msg,
+            envelope_encoding: Some(&envelope_encoding),
+        };
+        seed.deserialize(value).map_err(decode_error)?;
+        Self::message(&self.dict, msg)
+    }
+
+    fn message<'a>(
+        dict: &'a Dictionary,
+        msg: &'a MessageInternal<'a>,
+    ) -> Result<Message<'a>, DecodeError> {
+        for fields in [&msg.std_header, &msg.body, &msg.std_trailer] {
+            check_field_names(dict, fields)?;
+        }
+        if let Some(name) = msg
+            .components
+            .iter()
+            .find(|name| dict.component_by_name(name).is_none())
+        {
+            return Err(DecodeError::UnknownField(String::from(name.as_ref())));
+        }
         Ok(Message {
             internal: msg,
             group_map: None,
+            dict,
         })
     }
 
-    fn message_builder<'a>(&'a mut self) -> &'a mut MessageInternal<'a> {
-        self.message_builder.clear();
+    fn message_builder<'a>(
+        message_builder: &'a mut MessageInternal<'static>,
+    ) -> &'a mut MessageInternal<'a> {
+        message_builder.clear();
         unsafe {
             std::mem::transmute::<&'a mut MessageInternal<'static>, &'a mut MessageInternal<'a>>(
-                &mut self.message_builder,
+                message_builder,
             )
         }
     }
 }
 
+fn decode_error(err: serde_json::Error) -> DecodeError {
+    if err.is_syntax() || err.is_eof() || err.is_io() {
+        DecodeError::Syntax
+    } else {
+        DecodeError::Schema
+    }
+}
+
+fn check_field_names(dict: &Dictionary, fields: &Fields) -> Result<(), DecodeError> {
+    for (key, value) in fields {
+        if dict.field_by_name(key).is_none() && numeric_tag(key).is_none() {
+            return Err(DecodeError::UnknownField(String::from(key.as_ref())));
+        }
+        if let FieldOrGroup::Group(entries) = value {
+            for entry in entries {
+                check_field_names(dict, entry)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 impl GetConfig for Decoder {
     type Config = Config;
 
@@ -192,6 +622,292 @@ struct MessageInternal<'a> {
     body: Fields<'a>,
     #[serde(borrow, rename = "Trailer")]
     std_trailer: Fields<'a>,
+    // The names of nested components (see `Config::nested_components`),
+    // whose fields are flattened into the enclosing section or group entry.
+    #[serde(skip)]
+    components: Vec<Cow<'a, str>>,
+}
+
+/// Deserializes a [`MessageInternal`] in place, so that its allocations can be
+/// reused. When `envelope_encoding` is [`Some`], the enveloped form is accepted
+/// as well.
+struct MessageSeed<'a, 'b> {
+    message: &'b mut MessageInternal<'a>,
+    envelope_encoding: Option<&'b str>,
+}
+
+#[derive(Deserialize)]
+enum MessageKey {
+    Header,
+    Body,
+    Trailer,
+    Encoding,
+    Message,
+    #[serde(other)]
+    Other,
+}
+
+/// Deserializes the fields of a section or group entry into `fields`.
+/// Nested components are flattened, and their names are added to
+/// `components`.
+struct FieldsSeed<'a, 'b> {
+    fields: &'b mut Fields<'a>,
+    components: &'b mut Vec<Cow<'a, str>>,
+}
+
+impl<'a, 'b> DeserializeSeed<'a> for FieldsSeed<'a, 'b> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        self.fields.clear();
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// Deserializes the value of a key within [`FieldsSeed`], which is either a
+/// field, a repeating group, or a nested component.
+struct FieldValueSeed<'a, 'b> {
+    fields: &'b mut Fields<'a>,
+    components: &'b mut Vec<Cow<'a, str>>,
+}
+
+enum FieldValue<'a> {
+    FieldOrGroup(FieldOrGroup<'a>),
+    // The fields of the component were added to the enclosing fields.
+    Component,
+}
+
+impl<'a, 'b> DeserializeSeed<'a> for FieldValueSeed<'a, 'b> {
+    type Value = FieldValue<'a>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<FieldValue<'a>, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'a, 'b> Visitor<'a> for FieldValueSeed<'a, 'b> {
+    type Value = FieldValue<'a>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a FIX field, repeating group, or component")
+    }
+
+    fn visit_borrowed_str<E>(self, value: &'a str) -> Result<FieldValue<'a>, E> {
+        Ok(FieldValue::FieldOrGroup(FieldOrGroup::Field(
+            Cow::Borrowed(value),
+        )))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<FieldValue<'a>, E> {
+        Ok(FieldValue::FieldOrGroup(FieldOrGroup::Field(Cow::Owned(
+            value.to_string(),
+        ))))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<FieldValue<'a>, E> {
+        Ok(FieldValue::FieldOrGroup(FieldOrGroup::Field(Cow::Owned(
+            value,
+        ))))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<FieldValue<'a>, A::Error>
+    where
+        A: SeqAccess<'a>,
+    {
+        let mut entries = Vec::new();
+        loop {
+            let mut entry = Fields::new();
+            let seed = FieldsSeed {
+                fields: &mut entry,
+                components: &mut *self.components,
+            };
+            if seq.next_element_seed(seed)?.is_none() {
+                break;
+            }
+            entries.push(entry);
+        }
+        Ok(FieldValue::FieldOrGroup(FieldOrGroup::Group(entries)))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<FieldValue<'a>, A::Error>
+    where
+        A: MapAccess<'a>,
+    {
+        let seed = FieldsSeed {
+            fields: self.fields,
+            components: self.components,
+        };
+        seed.visit_map(map)?;
+        Ok(FieldValue::Component)
+    }
+}
+
+/// The value of `XmlData <213>`, which may be nested JSON (see
+/// [`Config::nested_xml_data`]).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum XmlDataValue<'a> {
+    #[serde(borrow)]
+    FieldOrGroup(FieldOrGroup<'a>),
+    Xml(XmlJson),
+}
+
+impl<'a, 'b> Visitor<'a> for FieldsSeed<'a, 'b> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("FIX fields")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'a>,
+    {
+        use serde::de::Error;
+
+        let mut xml_data_len = None;
+        while let Some(key) = map.next_key::<Cow<'a, str>>()? {
+            let value = if key == "XmlData" || key == "213" {
+                match map.next_value()? {
+                    XmlDataValue::FieldOrGroup(value) => value,
+                    XmlDataValue::Xml(xml_json) => {
+                        let xml = xml_json
+                            .to_xml()
+                            .ok_or_else(|| A::Error::custom("invalid nested `XmlData`"))?;
+                        xml_data_len = Some(xml.len());
+                        FieldOrGroup::Field(Cow::Owned(xml))
+                    }
+                }
+            } else {
+                let seed = FieldValueSeed {
+                    fields: &mut *self.fields,
+                    components: &mut *self.components,
+                };
+                match map.next_value_seed(seed)? {
+                    FieldValue::FieldOrGroup(value) => value,
+                    FieldValue::Component => {
+                        self.components.push(key);
+                        continue;
+                    }
+                }
+            };
+            self.fields.insert(key, value);
+        }
+        // `XmlDataLen <212>` must match the XML, not the JSON.
+        if let Some(len) = xml_data_len {
+            let key = if self.fields.contains_key("212") {
+                "212"
+            } else {
+                "XmlDataLen"
+            };
+            self.fields.insert(
+                Cow::Borrowed(key),
+                FieldOrGroup::Field(Cow::Owned(ToString::to_string(&len))),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<'a, 'b> DeserializeSeed<'a> for MessageSeed<'a, 'b> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'b> Visitor<'a> for MessageSeed<'a, 'b> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a FIX JSON message")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'a>,
+    {
+        use serde::de::Error;
+
+        let mut has_header = false;
+        let mut has_body = false;
+        let mut has_trailer = false;
+        let mut encoding: Option<Cow<str>> = None;
+        let mut has_message = false;
+        while let Some(key) = map.next_key()? {
+            match key {
+                MessageKey::Header => {
+                    map.next_value_seed(FieldsSeed {
+                        fields: &mut self.message.std_header,
+                        components: &mut self.message.components,
+                    })?;
+                    has_header = true;
+                }
+                MessageKey::Body => {
+                    map.next_value_seed(FieldsSeed {
+                        fields: &mut self.message.body,
+                        components: &mut self.message.components,
+                    })?;
+                    has_body = true;
+                }
+                MessageKey::Trailer => {
+                    map.next_value_seed(FieldsSeed {
+                        fields: &mut self.message.std_trailer,
+                        components: &mut self.message.components,
+                    })?;
+                    has_trailer = true;
+                }
+                MessageKey::Encoding if self.envelope_encoding.is_some() => {
+                    encoding = Some(map.next_value()?);
+                }
+                MessageKey::Message if self.envelope_encoding.is_some() => {
+                    map.next_value_seed(MessageSeed {
+                        message: &mut *self.message,
+                        envelope_encoding: None,
+                    })?;
+                    has_message = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        match (encoding, has_message) {
+            (None, false) => {
+                if !has_header {
+                    Err(A::Error::missing_field("Header"))
+                } else if !has_body {
+                    Err(A::Error::missing_field("Body"))
+                } else if !has_trailer {
+                    Err(A::Error::missing_field("Trailer"))
+                } else {
+                    Ok(())
+                }
+            }
+            (Some(encoding), true) => {
+                if Some(encoding.as_ref()) == self.envelope_encoding {
+                    Ok(())
+                } else {
+                    Err(A::Error::invalid_value(
+                        serde::de::Unexpected::Str(&encoding),
+                        &self.envelope_encoding.unwrap_or_default(),
+                    ))
+                }
+            }
+            (None, true) => Err(A::Error::missing_field("Encoding")),
+            (Some(_), false) => Err(A::Error::missing_field("Message")),
+        }
+    }
 }
 
 impl<'a> std::ops::Drop for MessageInternal<'a> {
@@ -205,15 +921,18 @@ impl<'a> MessageInternal<'a> {
         self.std_header.clear();
         self.body.clear();
         self.std_trailer.clear();
+        self.components.clear();
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::definitions::fix44;
 
     const MESSAGE_SIMPLE: &str = include_str!("test_data/message_simple.json");
     const MESSAGE_WITHOUT_HEADER: &str = include_str!("test_data/message_without_header.json");
+    const MESSAGE_ENVELOPED: &str = include_str!("test_data/message_enveloped.json");
 
     fn encoder_fix44() -> Decoder {
         Decoder::new(Dictionary::fix44())
@@ -236,6 +955,96 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn empty_groups() {
+        let mut decoder = encoder_fix44();
+        let json = MESSAGE_SIMPLE.replace(
+            r#""MDReqID": "789","#,
+            r#""MDReqID": "789", "NoPartyIDs": [], "NoLegs": "0","#,
+        );
+        let msg = decoder.decode(json.as_bytes()).unwrap();
+        assert_eq!(msg.group(fix44::NO_PARTY_I_DS).unwrap().len(), 0);
+        assert_eq!(msg.group(fix44::NO_LEGS).unwrap().len(), 0);
+        assert_eq!(msg.group(fix44::NO_MD_ENTRIES).unwrap().len(), 2);
+        assert!(msg.group(fix44::NO_ROUTING_I_DS).is_err());
+    }
+
+    #[test]
+    fn num_in_group_without_entries_is_invalid_data() {
+        let dict = Dictionary::fix44();
+        let mut decoder = encoder_fix44();
+        let json =
+            MESSAGE_SIMPLE.replace(r#""NoMDEntries": ["#, r#""NoMDEntries": "2", "9999": ["#);
+        let msg = decoder.decode(json.as_bytes()).unwrap();
+        assert!(msg.group(fix44::NO_MD_ENTRIES).is_err());
+        let mut encoder = tagvalue::Encoder::new();
+        let mut buffer = Vec::new();
+        let result = msg.encode_tagvalue(&dict, &mut encoder, &mut buffer);
+        assert!(matches!(result, Err(DecodeError::InvalidData)));
+    }
+
+    #[test]
+    fn numeric_keys() {
+        let mut decoder = encoder_fix44();
+        let json = MESSAGE_SIMPLE.replace(
+            r#""MDReqID": "789","#,
+            r#""MDReqID": "789", "9001": "foo", "262": "bar","#,
+        );
+        let msg = decoder.decode(json.as_bytes()).unwrap();
+        assert_eq!(msg.get_raw(9001), Some(b"foo" as &[u8]));
+        assert_eq!(msg.get_raw(262), Some(b"789" as &[u8]));
+        let dict = Dictionary::fix44();
+        let mut encoder = tagvalue::Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        assert!(matches!(
+            msg.encode_tagvalue(&dict, &mut encoder, &mut buffer),
+            Err(DecodeError::DuplicateField(262))
+        ));
+
+        // Known numeric keys are written at their place in the layout.
+        let json = MESSAGE_SIMPLE
+            .replace(r#""MDReqID": "789","#, r#""9001": "foo", "262": "789","#)
+            .replace(
+                r#""MDEntryPx": "1.75","#,
+                r#""270": "1.75", "9002": "bar","#,
+            );
+        let msg = decoder.decode(json.as_bytes()).unwrap();
+        let mut buffer = Vec::new();
+        let data = msg
+            .encode_tagvalue(&dict, &mut encoder, &mut buffer)
+            .unwrap();
+        let data = std::str::from_utf8(data).unwrap();
+        assert_eq!(data.matches("|262=").count(), 1);
+        assert!(data.contains("|262=789|48=ESU6|22=8|268=2|"));
+        assert!(data.contains("|269=1|270=1.75|271=25|273=21:14:38.688|9002=bar|9001=foo|10="));
+
+        let json = MESSAGE_SIMPLE.replace(r#""MDReqID": "789","#, r#""Foo": "bar","#);
+        match decoder.decode(json.as_bytes()) {
+            Err(DecodeError::UnknownField(name)) => assert_eq!(name, "Foo"),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn json_value_round_trip() {
+        let value: serde_json::Value = serde_json::from_str(MESSAGE_SIMPLE).unwrap();
+        let mut decoder = encoder_fix44();
+        let msg = decoder.decode_value(&value).unwrap();
+        assert_eq!(msg.get_raw(fix44::SECURITY_ID), Some(&b"ESU6"[..]));
+        assert_eq!(msg.to_json_value(), value);
+        let entry = msg.group(fix44::NO_MD_ENTRIES).unwrap().get(1).unwrap();
+        assert_eq!(entry.to_json_value(), value["Body"]["NoMDEntries"][1]);
+
+        let enveloped: serde_json::Value = serde_json::from_str(MESSAGE_ENVELOPED).unwrap();
+        assert!(decoder.decode_value(&enveloped).is_ok());
+        let value = serde_json::json!({ "Header": {}, "Body": [] });
+        assert!(matches!(
+            decoder.decode_value(&value),
+            Err(DecodeError::Schema)
+        ));
+    }
+
     #[test]
     fn invalid_json() {
         let mut encoder = encoder_fix44();
@@ -245,4 +1054,41 @@ mod test {
             _ => panic!(),
         };
     }
+
+    #[test]
+    fn enveloped_message() {
+        let mut decoder = encoder_fix44();
+        let msg = decoder.decode(MESSAGE_ENVELOPED.as_bytes()).unwrap();
+        assert_eq!(msg.get_raw(fix44::SECURITY_ID), Some(&b"ESU6"[..]));
+        assert_eq!(msg.get_raw(fix44::MSG_TYPE), Some(&b"W"[..]));
+    }
+
+    #[test]
+    fn enveloped_message_with_custom_encoding() {
+        let mut decoder = encoder_fix44();
+        decoder.config_mut().envelope_encoding = "X-CUSTOM".to_string();
+        let json = MESSAGE_ENVELOPED.replace("FIX-JSON", "X-CUSTOM");
+        assert!(decoder.decode(json.as_bytes()).is_ok());
+    }
+
+    #[test]
+    fn enveloped_message_with_wrong_encoding() {
+        let mut decoder = encoder_fix44();
+        decoder.config_mut().envelope_encoding = "X-CUSTOM".to_string();
+        let result = decoder.decode(MESSAGE_ENVELOPED.as_bytes());
+        match result {
+            Err(DecodeError::Schema) => (),
+            _ => panic!(),
+        };
+    }
+
+    #[test]
+    fn envelope_without_message() {
+        let mut decoder = encoder_fix44();
+        let result = decoder.decode(br#"{"Encoding":"FIX-JSON"}"#);
+        match result {
+            Err(DecodeError::Schema) => (),
+            _ => panic!(),
+        };
+    }
 }
@@ -1,11 +1,21 @@
-use crate::dict::IsFieldDefinition;
-use crate::FieldType;
+use super::xml_data::{XmlJson, XML_DATA};
+use super::{Config, EncodeError};
+use crate::dict::{IsFieldDefinition, LayoutItem, LayoutItemKind};
+use crate::tagvalue;
+use crate::{Dictionary, FieldMap, FieldType, GetConfig, RepeatingGroup, TagU32};
+
+// `BodyLength <9>` and `CheckSum <10>` are specific to the tag-value encoding.
+const TAGVALUE_ONLY_TAGS: &[u32] = &[9, 10];
 
 /// A codec for the JSON encoding type.
 #[derive(Debug, Clone)]
 pub struct Encoder {
     buffer: Vec<u8>,
     has_message: bool,
+    // Whether or not a comma must be written before the next key or array
+    // item.
+    needs_comma: bool,
+    config: Config,
 }
 
 impl Encoder {
@@ -13,14 +23,278 @@ impl Encoder {
         Self {
             buffer: Vec::new(),
             has_message: false,
+            needs_comma: false,
+            config: Config::default(),
         }
     }
 
     pub fn start_message(&mut self) -> encoder_states::Initial {
         self.buffer.clear();
         self.has_message = true;
+        self.needs_comma = false;
         encoder_states::Initial { encoder: self }
     }
+
+    /// Encodes the [`tagvalue::Message`] `msg` according to the definitions of
+    /// `dict`. Fields are written in the
+    /// order of the dictionary, and repeating groups become arrays of objects
+    /// under the name of their `NumInGroup` field, e.g. `"NoMDEntries": [{
+    /// ... }, { ... }]`. Empty repeating groups become empty arrays.
+    ///
+    /// `BeginString <8>` is written as part of `"Header"`, but `BodyLength <9>`
+    /// and `CheckSum <10>` are not, as they're meaningless in JSON. Fields
+    /// with tags unknown to `dict` are written at the end of `"Body"` under
+    /// their numeric tag, e.g. `"5001": "..."`, so that they survive
+    /// JSON→tagvalue round-trips (see
+    /// [`Message::encode_tagvalue`](super::Message::encode_tagvalue)). Other
+    /// fields that are not part of the message definition are not written.
+    /// Returns [`EncodeError::Dictionary`] if `MsgType <35>` is missing or
+    /// unknown to `dict`, and [`EncodeError::InvalidUtf8`] if any value is not
+    /// valid UTF-8, e.g. Latin-1 text.
+    ///
+    /// With [`Config::nested_xml_data`], well-formed XML in `XmlData <213>` is
+    /// written as a nested object: elements become objects, with attributes
+    /// under `"@name"` keys and text under `"#text"`, or just strings if they
+    /// have neither attributes nor child elements. Repeated child elements
+    /// become arrays. Comments, processing instructions, and the order of
+    /// mixed content are not preserved.
+    ///
+    /// With [`Config::nested_components`], the fields of components are
+    /// written within a nested object under the name of the component, e.g.
+    /// `"Instrument": { "Symbol": "..." }`, unless none of them are present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::json::Encoder;
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let mut decoder = Decoder::new(dict.clone());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let msg = b"8=FIX.4.4|9=36|35=W|55=EUR/USD|268=1|269=0|270=1.5|10=000|";
+    /// let msg = decoder.decode(&msg[..]).unwrap();
+    ///
+    /// let mut encoder = Encoder::new();
+    /// let json = encoder.encode_tagvalue(&msg, &dict).unwrap();
+    /// assert!(json.contains(r#""NoMDEntries":[{"MDEntryType":"0","MDEntryPx":"1.5"}]"#));
+    /// ```
+    pub fn encode_tagvalue<T>(
+        &mut self,
+        msg: &tagvalue::Message<T>,
+        dict: &Dictionary,
+    ) -> Result<&str, EncodeError>
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        // JSON strings must be valid UTF-8, and decoded messages are external
+        // input.
+        if let Some((tag, _)) = msg
+            .fields()
+            .find(|(_, value)| std::str::from_utf8(value).is_err())
+        {
+            return Err(EncodeError::InvalidUtf8 { tag: tag.get() });
+        }
+        let msg_type = msg
+            .get_raw(35)
+            .and_then(|msg_type| std::str::from_utf8(msg_type).ok())
+            .ok_or(EncodeError::Dictionary)?;
+        let message_def = dict
+            .message_by_msgtype(msg_type)
+            .ok_or(EncodeError::Dictionary)?;
+        let std_header = dict
+            .component_by_name("StandardHeader")
+            .ok_or(EncodeError::Dictionary)?;
+        let std_trailer = dict
+            .component_by_name("StandardTrailer")
+            .ok_or(EncodeError::Dictionary)?;
+        let header = self.start_message().with_header();
+        header.encoder.write_layout(msg, std_header.items())?;
+        let body = header.with_body();
+        body.encoder.write_layout(msg, message_def.layout())?;
+        body.encoder.write_unknown_fields(msg, dict);
+        let trailer = body.with_trailer();
+        trailer.encoder.write_layout(msg, std_trailer.items())?;
+        Ok(trailer.done())
+    }
+
+    fn write_layout<'d, M, I>(&mut self, msg: &M, items: I) -> Result<(), EncodeError>
+    where
+        M: FieldMap<u32>,
+        I: IntoIterator<Item = LayoutItem<'d>>,
+    {
+        for item in items {
+            match item.kind() {
+                LayoutItemKind::Field(field) => {
+                    let tag = field.tag().get();
+                    if TAGVALUE_ONLY_TAGS.contains(&tag) {
+                        continue;
+                    }
+                    if let Some(value) = msg.get_raw(tag) {
+                        self.write_field(tag, field.name(), value);
+                    }
+                }
+                LayoutItemKind::Component(component) if self.config.nested_components => {
+                    let (len, needs_comma) = (self.buffer.len(), self.needs_comma);
+                    self.write_key(component.name());
+                    self.open(b'{');
+                    let content_start = self.buffer.len();
+                    self.write_layout(msg, component.items())?;
+                    if self.buffer.len() == content_start {
+                        // Absent components don't leave empty objects behind.
+                        self.buffer.truncate(len);
+                        self.needs_comma = needs_comma;
+                    } else {
+                        self.close(b'}');
+                    }
+                }
+                LayoutItemKind::Component(component) => {
+                    self.write_layout(msg, component.items())?;
+                }
+                LayoutItemKind::Group(field, items) => {
+                    let group = msg
+                        .group_opt(field.tag().get())
+                        .map_err(|_| EncodeError::Dictionary)?;
+                    if let Some(group) = group {
+                        self.write_key(field.name());
+                        self.open(b'[');
+                        for i in 0..group.len() {
+                            let entry = group.get(i).ok_or(EncodeError::Dictionary)?;
+                            self.open(b'{');
+                            self.write_layout(&entry, items.iter().cloned())?;
+                            self.close(b'}');
+                        }
+                        self.close(b']');
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_unknown_fields<T>(&mut self, msg: &tagvalue::Message<T>, dict: &Dictionary)
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        // `tagvalue::Decoder` only ever puts known fields in repeating groups,
+        // so unknown ones are all at the top level.
+        let mut tags: Vec<TagU32> = Vec::new();
+        for (tag, _) in msg.fields() {
+            if dict.field_by_tag(tag.get()).is_some() || tags.contains(&tag) {
+                continue;
+            }
+            tags.push(tag);
+            if let Some(value) = msg.get_raw(tag.get()) {
+                self.write_key(&ToString::to_string(&tag));
+                self.write_value(value);
+            }
+        }
+    }
+
+    fn write_field(&mut self, tag: u32, name: &str, value: &[u8]) {
+        self.write_key(name);
+        let xml_json = if tag == XML_DATA && self.config.nested_xml_data {
+            std::str::from_utf8(value).ok().and_then(XmlJson::from_xml)
+        } else {
+            None
+        };
+        match xml_json {
+            Some(xml_json) => self.write_xml_json(&xml_json),
+            None => self.write_value(value),
+        }
+    }
+
+    fn write_xml_json(&mut self, value: &XmlJson) {
+        match value {
+            XmlJson::Text(text) => self.write_array_item(text.as_bytes()),
+            XmlJson::Object(entries) => {
+                self.open(b'{');
+                for (key, value) in entries {
+                    // XML names may be non-ASCII, so they must be escaped
+                    // rather than written by `write_key`.
+                    self.write_array_item(key.as_bytes());
+                    self.buffer.push(b':');
+                    self.needs_comma = false;
+                    self.write_xml_json(value);
+                }
+                self.close(b'}');
+            }
+            XmlJson::Array(values) => {
+                self.open(b'[');
+                for value in values {
+                    self.write_xml_json(value);
+                }
+                self.close(b']');
+            }
+        }
+    }
+
+    /// Like [`Encoder::write_value`], but for values without a key, which
+    /// would otherwise take care of the comma.
+    fn write_array_item(&mut self, value: &[u8]) {
+        if self.needs_comma {
+            self.buffer.push(b',');
+        }
+        self.write_value(value);
+    }
+
+    fn write_key(&mut self, key: &str) {
+        debug_assert!(key.is_ascii());
+        if self.needs_comma {
+            self.buffer.push(b',');
+        }
+        self.buffer.push(b'"');
+        self.buffer.extend_from_slice(key.as_bytes());
+        self.buffer.extend_from_slice(b"\":");
+        // The value comes right after.
+        self.needs_comma = false;
+    }
+
+    fn write_value(&mut self, value: &[u8]) {
+        self.buffer.push(b'"');
+        for byte in value.iter().copied() {
+            match byte {
+                b'"' => self.buffer.extend_from_slice(b"\\\""),
+                b'\\' => self.buffer.extend_from_slice(b"\\\\"),
+                0x00..=0x1f => {
+                    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+                    self.buffer.extend_from_slice(b"\\u00");
+                    self.buffer.push(HEX_DIGITS[usize::from(byte >> 4)]);
+                    self.buffer.push(HEX_DIGITS[usize::from(byte & 0xf)]);
+                }
+                _ => self.buffer.push(byte),
+            }
+        }
+        self.buffer.push(b'"');
+        self.needs_comma = true;
+    }
+
+    fn open(&mut self, bracket: u8) {
+        if self.needs_comma {
+            self.buffer.push(b',');
+        }
+        self.buffer.push(bracket);
+        self.needs_comma = false;
+    }
+
+    fn close(&mut self, bracket: u8) {
+        self.buffer.push(bracket);
+        self.needs_comma = true;
+    }
+}
+
+impl GetConfig for Encoder {
+    type Config = Config;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut Self::Config {
+        &mut self.config
+    }
 }
 
 /// Typestates for the JSON [`Encoder`].
@@ -36,12 +310,18 @@ pub mod encoder_states {
 
     impl<'a> Initial<'a> {
         pub fn with_header(self) -> StdHeader<'a> {
-            self.encoder
-                .buffer
-                .extend_from_slice(br#"{"StandardHeader":{"#);
-            StdHeader {
-                encoder: self.encoder,
+            let encoder = self.encoder;
+            if encoder.config.envelope {
+                encoder.buffer.extend_from_slice(br#"{"Encoding":"#);
+                // The identifier is user-provided and must be escaped. Writing
+                // to a `Vec<u8>` is infallible.
+                let _ =
+                    serde_json::to_writer(&mut encoder.buffer, &encoder.config.envelope_encoding);
+                encoder.buffer.extend_from_slice(br#","Message":"#);
             }
+            encoder.buffer.extend_from_slice(br#"{"Header":{"#);
+            encoder.needs_comma = false;
+            StdHeader { encoder }
         }
     }
 
@@ -57,27 +337,51 @@ pub mod encoder_states {
             T: FieldType<'a>,
             F: IsFieldDefinition,
         {
-            debug_assert!(field.name().is_ascii());
-            let encoder = self.encoder_mut();
-            encoder.buffer.extend_from_slice(br#"""#);
-            field.name().as_bytes().serialize(&mut encoder.buffer);
-            encoder.buffer.extend_from_slice(br#"":""#);
-            value.serialize(&mut encoder.buffer);
-            encoder.buffer.extend_from_slice(br#"""#);
+            set_field(self.encoder_mut(), field, value);
+            self
+        }
+
+        /// Adds a repeating group to the current message.
+        fn set_group<F, G>(mut self, field: &F, f: G) -> Self
+        where
+            F: IsFieldDefinition,
+            G: FnOnce(&mut Group),
+        {
+            set_group(self.encoder_mut(), field, f);
             self
         }
     }
 
+    fn set_field<'a, T, F>(encoder: &mut Encoder, field: &F, value: T)
+    where
+        T: FieldType<'a>,
+        F: IsFieldDefinition,
+    {
+        encoder.write_field(field.tag().get(), field.name(), &value.to_bytes());
+    }
+
+    fn set_group<F, G>(encoder: &mut Encoder, field: &F, f: G)
+    where
+        F: IsFieldDefinition,
+        G: FnOnce(&mut Group),
+    {
+        encoder.write_key(field.name());
+        encoder.open(b'[');
+        f(&mut Group { encoder });
+        encoder.close(b']');
+    }
+
     /// Typestate produced by [`Initial::with_header`].
     #[derive(Debug)]
     #[must_use]
     pub struct StdHeader<'a> {
-        encoder: &'a mut Encoder,
+        pub(in super::super) encoder: &'a mut Encoder,
     }
 
     impl<'a> StdHeader<'a> {
         pub fn with_body(self) -> Body<'a> {
             self.encoder.buffer.extend_from_slice(br#"},"Body":{"#);
+            self.encoder.needs_comma = false;
             Body {
                 encoder: self.encoder,
             }
@@ -90,6 +394,17 @@ pub mod encoder_states {
         {
             EncoderStateAtTopLevel::set(self, field, value)
         }
+
+        /// Adds a repeating group, rendered as an array of objects under the
+        /// name of `field`, i.e. its `NumInGroup` field. Entries are added via
+        /// [`Group::entry`] within `f`.
+        pub fn set_group<F, G>(self, field: &F, f: G) -> Self
+        where
+            F: IsFieldDefinition,
+            G: FnOnce(&mut Group),
+        {
+            EncoderStateAtTopLevel::set_group(self, field, f)
+        }
     }
 
     impl<'a> EncoderStateAtTopLevel for StdHeader<'a> {
@@ -102,14 +417,13 @@ pub mod encoder_states {
     #[derive(Debug)]
     #[must_use]
     pub struct Body<'a> {
-        encoder: &'a mut Encoder,
+        pub(in super::super) encoder: &'a mut Encoder,
     }
 
     impl<'a> Body<'a> {
         pub fn with_trailer(self) -> StdTrailer<'a> {
-            self.encoder
-                .buffer
-                .extend_from_slice(br#"},"StandardTrailer":{"#);
+            self.encoder.buffer.extend_from_slice(br#"},"Trailer":{"#);
+            self.encoder.needs_comma = false;
             StdTrailer {
                 encoder: self.encoder,
             }
@@ -122,6 +436,41 @@ pub mod encoder_states {
         {
             EncoderStateAtTopLevel::set(self, field, value)
         }
+
+        /// Like [`StdHeader::set_group`].
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use fefix::definitions::fix44;
+        /// use fefix::json::Encoder;
+        ///
+        /// let mut encoder = Encoder::new();
+        /// let json = encoder
+        ///     .start_message()
+        ///     .with_header()
+        ///     .with_body()
+        ///     .set(fix44::MD_REQ_ID, "foo")
+        ///     .set_group(fix44::NO_MD_ENTRIES, |group| {
+        ///         group.entry(|entry| {
+        ///             entry.set(fix44::MD_ENTRY_TYPE, "0");
+        ///             entry.set(fix44::MD_ENTRY_PX, "1.5");
+        ///         });
+        ///     })
+        ///     .with_trailer()
+        ///     .done();
+        /// assert_eq!(
+        ///     json,
+        ///     r#"{"Header":{},"Body":{"MDReqID":"foo","NoMDEntries":[{"MDEntryType":"0","MDEntryPx":"1.5"}]},"Trailer":{}}"#
+        /// );
+        /// ```
+        pub fn set_group<F, G>(self, field: &F, f: G) -> Self
+        where
+            F: IsFieldDefinition,
+            G: FnOnce(&mut Group),
+        {
+            EncoderStateAtTopLevel::set_group(self, field, f)
+        }
     }
 
     impl<'a> EncoderStateAtTopLevel for Body<'a> {
@@ -134,12 +483,15 @@ pub mod encoder_states {
     #[derive(Debug)]
     #[must_use]
     pub struct StdTrailer<'a> {
-        encoder: &'a mut Encoder,
+        pub(in super::super) encoder: &'a mut Encoder,
     }
 
     impl<'a> StdTrailer<'a> {
         pub fn done(self) -> &'a str {
             self.encoder.buffer.extend_from_slice(b"}}");
+            if self.encoder.config.envelope {
+                self.encoder.buffer.push(b'}');
+            }
             std::str::from_utf8(&self.encoder.buffer[..]).unwrap()
         }
 
@@ -157,11 +509,426 @@ pub mod encoder_states {
             self.encoder
         }
     }
+
+    /// A repeating group which is being encoded, as created by
+    /// [`Body::set_group`].
+    #[derive(Debug)]
+    pub struct Group<'a> {
+        encoder: &'a mut Encoder,
+    }
+
+    impl<'a> Group<'a> {
+        /// Adds a new entry to the repeating group. Fields are written via
+        /// `f`, starting with the delimiter field.
+        pub fn entry<F>(&mut self, f: F)
+        where
+            F: FnOnce(&mut GroupEntry),
+        {
+            self.encoder.open(b'{');
+            f(&mut GroupEntry {
+                encoder: self.encoder,
+            });
+            self.encoder.close(b'}');
+        }
+    }
+
+    /// An entry of a [`Group`].
+    #[derive(Debug)]
+    pub struct GroupEntry<'a> {
+        encoder: &'a mut Encoder,
+    }
+
+    impl<'a> GroupEntry<'a> {
+        /// Adds a `field` with a `value` to this entry.
+        pub fn set<'v, T, F>(&mut self, field: &F, value: T) -> &mut Self
+        where
+            T: FieldType<'v>,
+            F: IsFieldDefinition,
+        {
+            set_field(self.encoder, field, value);
+            self
+        }
+
+        /// Adds a nested repeating group to this entry.
+        pub fn set_group<F, G>(&mut self, field: &F, f: G) -> &mut Self
+        where
+            F: IsFieldDefinition,
+            G: FnOnce(&mut Group),
+        {
+            set_group(self.encoder, field, f);
+            self
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::definitions::fix44;
+    use crate::json::{DecodeError, Decoder};
+    use crate::tagvalue;
+    use crate::SetField;
+
+    fn market_data_request(num_related_sym: usize) -> Vec<u8> {
+        let mut encoder = tagvalue::Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"V");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 2);
+        msg.set(fix44::MD_REQ_ID, "foo");
+        msg.set(fix44::SUBSCRIPTION_REQUEST_TYPE, "1");
+        msg.set(fix44::MARKET_DEPTH, 0);
+        msg.set_group(fix44::NO_MD_ENTRY_TYPES, |group| {
+            group.entry(|entry| entry.set(fix44::MD_ENTRY_TYPE, "0"));
+            group.entry(|entry| entry.set(fix44::MD_ENTRY_TYPE, "1"));
+        });
+        msg.set_group(fix44::NO_RELATED_SYM, |group| {
+            for i in 0..num_related_sym {
+                group.entry(|entry| {
+                    entry.set(fix44::SYMBOL, format!("SYM{}", i).as_str());
+                    entry.set_group(fix44::NO_SECURITY_ALT_ID, |group| {
+                        group.entry(|entry| {
+                            entry.set(fix44::SECURITY_ALT_ID, "ID1");
+                            entry.set(fix44::SECURITY_ALT_ID_SOURCE, "8");
+                        });
+                        group.entry(|entry| {
+                            entry.set(fix44::SECURITY_ALT_ID, "ID2");
+                            entry.set(fix44::SECURITY_ALT_ID_SOURCE, "4");
+                        });
+                    });
+                });
+            }
+        });
+        msg.done().0.to_vec()
+    }
+
+    fn tagvalue_to_json_to_tagvalue(
+        dict: &Dictionary,
+        data: &[u8],
+    ) -> (serde_json::Value, Vec<u8>) {
+        let mut tagvalue_decoder = tagvalue::Decoder::new(dict.clone());
+        tagvalue_decoder.config_mut().separator = b'|';
+        let msg = tagvalue_decoder.decode(data).unwrap();
+        let mut json_encoder = Encoder::new();
+        let json = json_encoder.encode_tagvalue(&msg, &dict).unwrap();
+        let value = serde_json::from_str(json).unwrap();
+        let mut json_decoder = Decoder::new(dict.clone());
+        let msg = json_decoder.decode(json.as_bytes()).unwrap();
+        let mut tagvalue_encoder = tagvalue::Encoder::new();
+        tagvalue_encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        msg.encode_tagvalue(dict, &mut tagvalue_encoder, &mut buffer)
+            .unwrap();
+        (value, buffer)
+    }
+
+    #[test]
+    fn nested_groups_round_trip() {
+        let data = market_data_request(2);
+        let (json, round_trip) = tagvalue_to_json_to_tagvalue(&Dictionary::fix44(), &data);
+        assert_eq!(
+            json["Body"]["NoRelatedSym"][1],
+            serde_json::json!({
+                "Symbol": "SYM1",
+                "NoSecurityAltID": [
+                    { "SecurityAltID": "ID1", "SecurityAltIDSource": "8" },
+                    { "SecurityAltID": "ID2", "SecurityAltIDSource": "4" },
+                ],
+            })
+        );
+        assert_eq!(json["Header"]["BeginString"], "FIX.4.4");
+        assert!(json["Header"].get("BodyLength").is_none());
+        assert!(json["Trailer"].get("CheckSum").is_none());
+        assert_eq!(
+            String::from_utf8_lossy(&round_trip),
+            String::from_utf8_lossy(&data)
+        );
+    }
+
+    #[test]
+    fn custom_group_round_trip() {
+        use crate::dict::{FieldDef, FixDatatype, GroupDef};
+        use crate::TagU32;
+
+        let mut dict = Dictionary::fix44();
+        for (tag, name, datatype) in [
+            (5000, "NoLimits", FixDatatype::NumInGroup),
+            (5001, "LimitType", FixDatatype::String),
+            (5002, "LimitAmt", FixDatatype::Amt),
+        ] {
+            let tag = TagU32::new(tag).unwrap();
+            let name = name.to_string();
+            dict.add_field(FieldDef {
+                tag,
+                name,
+                datatype,
+            })
+            .unwrap();
+        }
+        dict.add_group(GroupDef {
+            count_tag: 5000,
+            delimiter_tag: 5001,
+            fields: vec![5002],
+        })
+        .unwrap();
+        dict.add_group_to_message("D", 5000).unwrap();
+
+        let mut encoder = tagvalue::Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 2);
+        msg.set(fix44::CL_ORD_ID, "foo");
+        msg.set_group(5000, |group| {
+            group.entry(|entry| {
+                entry.set(5001, "A");
+                entry.set(5002, "10");
+            });
+            group.entry(|entry| {
+                entry.set(5001, "B");
+                entry.set(5002, "20");
+            });
+        });
+        let data = msg.done().0.to_vec();
+
+        let mut decoder = tagvalue::Decoder::new(dict.clone());
+        decoder.config_mut().separator = b'|';
+        let msg = decoder.decode(&data[..]).unwrap();
+        let group = msg.group(5000).unwrap();
+        assert_eq!(group.len(), 2);
+        assert_eq!(group.get(1).unwrap().get_raw(5002), Some(b"20" as &[u8]));
+
+        let (json, round_trip) = tagvalue_to_json_to_tagvalue(&dict, &data);
+        assert_eq!(
+            json["Body"]["NoLimits"],
+            serde_json::json!([
+                { "LimitType": "A", "LimitAmt": "10" },
+                { "LimitType": "B", "LimitAmt": "20" },
+            ])
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&round_trip),
+            String::from_utf8_lossy(&data)
+        );
+    }
+
+    #[test]
+    fn non_utf8_values_are_refused() {
+        let dict = Dictionary::fix44();
+        let mut tagvalue_decoder = tagvalue::Decoder::new(dict.clone());
+        tagvalue_decoder.config_mut().separator = b'|';
+        tagvalue_decoder.config_mut().verify_checksum = false;
+        // Latin-1 rather than UTF-8.
+        let data = b"8=FIX.4.4|9=29|35=0|49=A|56=B|34=1|112=caf\xe9|10=000|";
+        let msg = tagvalue_decoder.decode(&data[..]).unwrap();
+        let mut json_encoder = Encoder::new();
+        assert!(matches!(
+            json_encoder.encode_tagvalue(&msg, &dict),
+            Err(EncodeError::InvalidUtf8 { tag: 112 })
+        ));
+    }
+
+    #[test]
+    fn nested_components_round_trip() {
+        let dict = Dictionary::fix44();
+        let mut tagvalue_decoder = tagvalue::Decoder::new(dict.clone());
+        tagvalue_decoder.config_mut().separator = b'|';
+        tagvalue_decoder.config_mut().verify_checksum = false;
+        let data = b"8=FIX.4.4|9=107|35=D|49=A|56=B|34=1|52=20210101-10:00:00|11=foo|55=EUR/USD|48=X|22=8|54=1|60=20210101-10:00:00|38=100|40=1|10=000|";
+        let msg = tagvalue_decoder.decode(&data[..]).unwrap();
+        let mut json_encoder = Encoder::new();
+        let flat = json_encoder
+            .encode_tagvalue(&msg, &dict)
+            .unwrap()
+            .to_string();
+        json_encoder.config_mut().nested_components = true;
+        let nested = json_encoder
+            .encode_tagvalue(&msg, &dict)
+            .unwrap()
+            .to_string();
+        let value: serde_json::Value = serde_json::from_str(&nested).unwrap();
+        assert_eq!(
+            value["Body"]["Instrument"],
+            serde_json::json!({ "Symbol": "EUR/USD", "SecurityID": "X", "SecurityIDSource": "8" })
+        );
+        assert_eq!(value["Body"]["OrderQtyData"]["OrderQty"], "100");
+        assert_eq!(value["Body"]["ClOrdID"], "foo");
+        assert!(value["Body"].get("Parties").is_none());
+        assert!(value["Body"].get("Symbol").is_none());
+
+        let mut json_decoder = Decoder::new(dict.clone());
+        let mut tagvalue_encoder = tagvalue::Encoder::new();
+        tagvalue_encoder.config_mut().separator = b'|';
+        let mut round_trips = Vec::new();
+        for json in [&flat, &nested] {
+            let msg = json_decoder.decode(json.as_bytes()).unwrap();
+            assert_eq!(msg.get_raw(fix44::SECURITY_ID), Some(b"X" as &[u8]));
+            let mut buffer = Vec::new();
+            msg.encode_tagvalue(&dict, &mut tagvalue_encoder, &mut buffer)
+                .unwrap();
+            round_trips.push(buffer);
+        }
+        assert_eq!(round_trips[0], round_trips[1]);
+
+        let json = nested.replace("\"Instrument\"", "\"Foo\"");
+        assert!(matches!(
+            json_decoder.decode(json.as_bytes()),
+            Err(DecodeError::UnknownField(name)) if name == "Foo"
+        ));
+    }
+
+    #[test]
+    fn nested_components_within_groups() {
+        let dict = Dictionary::fix44();
+        let mut tagvalue_decoder = tagvalue::Decoder::new(dict.clone());
+        tagvalue_decoder.config_mut().separator = b'|';
+        tagvalue_decoder.config_mut().verify_checksum = false;
+        let data = b"8=FIX.4.4|9=63|35=V|262=x|263=0|264=0|267=1|269=0|146=2|55=EUR/USD|55=USD/JPY|10=000|";
+        let msg = tagvalue_decoder.decode(&data[..]).unwrap();
+        let mut json_encoder = Encoder::new();
+        json_encoder.config_mut().nested_components = true;
+        let json = json_encoder
+            .encode_tagvalue(&msg, &dict)
+            .unwrap()
+            .to_string();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        // FIX 4.4 wraps repeating groups in components of their own.
+        assert_eq!(
+            value["Body"]["InstrmtMDReqGrp"]["NoRelatedSym"],
+            serde_json::json!([
+                { "Instrument": { "Symbol": "EUR/USD" } },
+                { "Instrument": { "Symbol": "USD/JPY" } },
+            ])
+        );
+
+        let mut json_decoder = Decoder::new(dict);
+        let msg = json_decoder.decode(json.as_bytes()).unwrap();
+        let group = msg.group(fix44::NO_RELATED_SYM).unwrap();
+        let entry = group.get(1).unwrap();
+        assert_eq!(entry.get_raw(fix44::SYMBOL), Some(b"USD/JPY" as &[u8]));
+    }
+
+    #[test]
+    fn nested_xml_data_round_trip() {
+        let dict = Dictionary::fix44();
+        for (xml, is_nested) in [
+            (
+                r#"<Quote Px="1.5"><Leg>A &amp; B</Leg><Leg/></Quote>"#,
+                true,
+            ),
+            ("<Quote><Leg></Quote>", false),
+        ] {
+            let mut encoder = tagvalue::Encoder::new();
+            encoder.config_mut().separator = b'|';
+            let mut buffer = Vec::new();
+            let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+            msg.set(fix44::SENDER_COMP_ID, "A");
+            msg.set(fix44::TARGET_COMP_ID, "B");
+            msg.set(fix44::XML_DATA_LEN, xml.len());
+            msg.set(fix44::XML_DATA, xml);
+            let data = msg.done().0.to_vec();
+
+            let mut tagvalue_decoder = tagvalue::Decoder::new(dict.clone());
+            tagvalue_decoder.config_mut().separator = b'|';
+            let msg = tagvalue_decoder.decode(&data[..]).unwrap();
+            let mut json_encoder = Encoder::new();
+            json_encoder.config_mut().nested_xml_data = true;
+            let json = json_encoder.encode_tagvalue(&msg, &dict).unwrap();
+            let value: serde_json::Value = serde_json::from_str(json).unwrap();
+            if is_nested {
+                assert_eq!(
+                    value["Header"]["XmlData"],
+                    serde_json::json!({ "Quote": { "@Px": "1.5", "Leg": ["A & B", ""] } })
+                );
+            } else {
+                assert_eq!(value["Header"]["XmlData"], xml);
+            }
+
+            let mut json_decoder = Decoder::new(dict.clone());
+            let msg = json_decoder.decode(json.as_bytes()).unwrap();
+            assert_eq!(msg.get_raw(fix44::XML_DATA), Some(xml.as_bytes()));
+            let mut buffer = Vec::new();
+            let mut tagvalue_encoder = tagvalue::Encoder::new();
+            tagvalue_encoder.config_mut().separator = b'|';
+            let round_trip = msg
+                .encode_tagvalue(&dict, &mut tagvalue_encoder, &mut buffer)
+                .unwrap();
+            assert_eq!(round_trip, &data[..]);
+        }
+    }
+
+    #[test]
+    fn nested_xml_data_length_is_recomputed() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        let json = br#"{
+            "Header": { "XmlDataLen": "1", "XmlData": { "a": { "b": "c" } } },
+            "Body": {},
+            "Trailer": {}
+        }"#;
+        let msg = decoder.decode(json).unwrap();
+        assert_eq!(
+            msg.get_raw(fix44::XML_DATA),
+            Some(b"<a><b>c</b></a>" as &[u8])
+        );
+        assert_eq!(msg.get_raw(fix44::XML_DATA_LEN), Some(b"15" as &[u8]));
+        let json =
+            br#"{ "Header": { "XmlData": { "a": "", "b": "" } }, "Body": {}, "Trailer": {} }"#;
+        assert!(matches!(decoder.decode(json), Err(DecodeError::Schema)));
+    }
+
+    #[test]
+    fn unknown_fields_round_trip() {
+        let mut encoder = tagvalue::Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 2);
+        msg.set(fix44::CL_ORD_ID, "foo");
+        msg.set(9001, "bar");
+        msg.set(9002, "baz");
+        let data = msg.done().0.to_vec();
+
+        let (json, round_trip) = tagvalue_to_json_to_tagvalue(&Dictionary::fix44(), &data);
+        assert_eq!(json["Body"]["9001"], "bar");
+        assert_eq!(json["Body"]["9002"], "baz");
+        assert_eq!(
+            String::from_utf8_lossy(&round_trip),
+            String::from_utf8_lossy(&data)
+        );
+    }
+
+    #[test]
+    fn empty_group_round_trip() {
+        let data = market_data_request(0);
+        let (json, round_trip) = tagvalue_to_json_to_tagvalue(&Dictionary::fix44(), &data);
+        assert_eq!(json["Body"]["NoRelatedSym"], serde_json::json!([]));
+        assert_eq!(
+            String::from_utf8_lossy(&round_trip),
+            String::from_utf8_lossy(&data)
+        );
+    }
+
+    #[test]
+    fn field_values_are_escaped() {
+        let mut encoder = Encoder::new();
+        let message = encoder
+            .start_message()
+            .with_header()
+            .set(fix44::SENDER_COMP_ID, "\"A\"\\")
+            .with_body()
+            .set(fix44::TEXT, "foo\nbar")
+            .with_trailer()
+            .done();
+        let json = serde_json::from_str::<serde_json::Value>(message).unwrap();
+        assert_eq!(json["Header"]["SenderCompID"], "\"A\"\\");
+        assert_eq!(json["Body"]["Text"], "foo\nbar");
+    }
 
     #[test]
     fn empty_message_is_valid_json() {
@@ -175,4 +942,20 @@ mod test {
         let json = serde_json::from_str::<serde_json::Value>(message);
         assert!(json.is_ok());
     }
+
+    #[test]
+    fn enveloped_empty_message_is_valid_json() {
+        let mut encoder = Encoder::new();
+        encoder.config_mut().envelope = true;
+        encoder.config_mut().envelope_encoding = "X-\"QUOTED\"".to_string();
+        let message = encoder
+            .start_message()
+            .with_header()
+            .with_body()
+            .with_trailer()
+            .done();
+        let json = serde_json::from_str::<serde_json::Value>(message).unwrap();
+        assert_eq!(json["Encoding"], "X-\"QUOTED\"");
+        assert!(json["Message"].is_object());
+    }
 }
@@ -3,6 +3,7 @@
 mod config;
 mod decoder;
 mod encoder;
+mod xml_data;
 
 pub use config::Config;
 pub use decoder::{Decoder, FieldOrGroup, Message, MessageFieldsIter, MessageGroup};
@@ -12,16 +13,25 @@ pub use encoder::Encoder;
 
 /// The type returned in the event of an error when encoding a FIX JSON message.
 #[derive(Copy, Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum EncodeError {
     /// The type returned in case there is an inconsistency between
     /// `BeginString`, `MsgType`, fields presence and other encoding rules as
     /// establised by the dictionary.
     #[error("Inconsistency between the FIX message and encoding rules as established by the dictionary.")]
     Dictionary,
+    /// The value of the field with this tag is not valid UTF-8, so it can't
+    /// be written as a JSON string.
+    #[error("The value of field {tag} is not valid UTF-8.")]
+    InvalidUtf8 {
+        /// The tag of the field.
+        tag: u32,
+    },
 }
 
 /// The type returned in the event of an error when decoding a FIX JSON message.
-#[derive(Copy, Clone, Debug, thiserror::Error)]
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum DecodeError {
     /// Bad JSON syntax.
     #[error("Bad JSON syntax.")]
@@ -35,4 +45,12 @@ pub enum DecodeError {
     /// The data does not conform to the specified message type.
     #[error("The data does not conform to the specified message type.")]
     InvalidData,
+    /// A key is neither the name of a field in the dictionary nor a numeric
+    /// tag.
+    #[error("Unknown field name `{0}`.")]
+    UnknownField(String),
+    /// A field is given both by name and by numeric tag, e.g. `"MDReqID"` and
+    /// `"262"`, in the same section or group entry.
+    #[error("Field {0} is given both by name and by numeric tag.")]
+    DuplicateField(u32),
 }
@@ -0,0 +1,336 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+/// `XmlData <213>`.
+pub const XML_DATA: u32 = 213;
+
+/// The contents of `XmlData <213>` as a JSON value. See
+/// [`Config::nested_xml_data`](super::Config::nested_xml_data).
+///
+/// Elements become objects, with their attributes under `"@name"` keys and
+/// their text under `"#text"`, or strings if they have neither attributes nor
+/// child elements. Repeated child elements become arrays. The whole document
+/// is an object with the root element as its only key, e.g.
+/// `<a x="1"><b>foo</b><b/></a>` becomes
+/// `{"a": {"@x": "1", "b": ["foo", ""]}}`. Comments, processing instructions,
+/// and the order of mixed content are lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlJson {
+    Text(String),
+    // Keys in order.
+    Object(Vec<(String, XmlJson)>),
+    Array(Vec<XmlJson>),
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl XmlJson {
+    /// Converts the XML document `xml` to JSON, if well-formed.
+    pub fn from_xml(xml: &str) -> Option<Self> {
+        let root = parse(xml)?;
+        Some(Self::Object(vec![(
+            root.name.clone(),
+            Self::from_element(root),
+        )]))
+    }
+
+    /// Converts `self` back to an XML document, or returns [`None`] if it
+    /// doesn't have the shape described in [`XmlJson`].
+    pub fn to_xml(&self) -> Option<String> {
+        let mut xml = String::new();
+        match self {
+            Self::Object(entries) if entries.len() == 1 => {
+                write_element(&mut xml, &entries[0].0, &entries[0].1)?;
+                Some(xml)
+            }
+            _ => None,
+        }
+    }
+
+    fn from_element(element: Element) -> Self {
+        if element.attributes.is_empty() && element.children.is_empty() {
+            return Self::Text(element.text);
+        }
+        let mut entries: Vec<(String, XmlJson)> = element
+            .attributes
+            .into_iter()
+            .map(|(name, value)| (format!("@{}", name), Self::Text(value)))
+            .collect();
+        // Whitespace between child elements is just indentation.
+        let text = if element.children.is_empty() {
+            element.text.as_str()
+        } else {
+            element.text.trim()
+        };
+        if !text.is_empty() {
+            entries.push(("#text".to_string(), Self::Text(text.to_string())));
+        }
+        for child in element.children {
+            let name = child.name.clone();
+            let value = Self::from_element(child);
+            match entries.iter_mut().find(|(key, _)| *key == name) {
+                Some((_, Self::Array(values))) => values.push(value),
+                Some((_, previous)) => {
+                    let first = std::mem::replace(previous, Self::Array(Vec::new()));
+                    *previous = Self::Array(vec![first, value]);
+                }
+                None => entries.push((name, value)),
+            }
+        }
+        Self::Object(entries)
+    }
+}
+
+fn parse(xml: &str) -> Option<Element> {
+    let mut reader = Reader::from_str(xml);
+    let mut buffer = Vec::new();
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    loop {
+        match reader.read_event(&mut buffer).ok()? {
+            Event::Start(start) => stack.push(element(&start)?),
+            Event::Empty(start) => close(&mut stack, &mut root, element(&start)?)?,
+            Event::End(_) => {
+                let element = stack.pop()?;
+                close(&mut stack, &mut root, element)?;
+            }
+            Event::Text(text) => {
+                let text = text.unescaped().ok()?;
+                push_text(&mut stack, std::str::from_utf8(&text).ok()?)?;
+            }
+            Event::CData(data) => push_text(&mut stack, std::str::from_utf8(&data).ok()?)?,
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+    if stack.is_empty() {
+        root
+    } else {
+        None
+    }
+}
+
+fn element(start: &BytesStart) -> Option<Element> {
+    let mut element = Element {
+        name: String::from_utf8(start.name().to_vec()).ok()?,
+        ..Element::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute.ok()?;
+        let value = attribute.unescaped_value().ok()?;
+        element.attributes.push((
+            String::from_utf8(attribute.key.to_vec()).ok()?,
+            String::from_utf8(value.into_owned()).ok()?,
+        ));
+    }
+    Some(element)
+}
+
+fn close(stack: &mut [Element], root: &mut Option<Element>, element: Element) -> Option<()> {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(element),
+        // There must be exactly one root element.
+        None if root.is_none() => *root = Some(element),
+        None => return None,
+    }
+    Some(())
+}
+
+fn push_text(stack: &mut [Element], text: &str) -> Option<()> {
+    match stack.last_mut() {
+        Some(element) => element.text.push_str(text),
+        None if text.trim().is_empty() => {}
+        None => return None,
+    }
+    Some(())
+}
+
+fn write_element(xml: &mut String, name: &str, value: &XmlJson) -> Option<()> {
+    let is_valid_name = !name.is_empty()
+        && !name.starts_with(|c: char| c == '@' || c == '#' || c.is_ascii_digit())
+        && !name.contains(|c: char| c.is_whitespace() || "<>&\"'=/".contains(c));
+    if !is_valid_name {
+        return None;
+    }
+    xml.push('<');
+    xml.push_str(name);
+    let entries = match value {
+        XmlJson::Text(text) if text.is_empty() => {
+            xml.push_str("/>");
+            return Some(());
+        }
+        XmlJson::Text(text) => {
+            xml.push('>');
+            escape(xml, text);
+            return close_tag(xml, name);
+        }
+        XmlJson::Object(entries) => entries,
+        XmlJson::Array(_) => return None,
+    };
+    for (key, value) in entries {
+        if let Some(attribute) = key.strip_prefix('@') {
+            match value {
+                XmlJson::Text(value) => {
+                    xml.push(' ');
+                    xml.push_str(attribute);
+                    xml.push_str("=\"");
+                    escape(xml, value);
+                    xml.push('"');
+                }
+                _ => return None,
+            }
+        }
+    }
+    let mut content = String::new();
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            (key, _) if key.starts_with('@') => {}
+            ("#text", XmlJson::Text(text)) => escape(&mut content, text),
+            (_, XmlJson::Array(values)) => {
+                for value in values {
+                    write_element(&mut content, key, value)?;
+                }
+            }
+            (key, value) => write_element(&mut content, key, value)?,
+        }
+    }
+    if content.is_empty() {
+        xml.push_str("/>");
+        Some(())
+    } else {
+        xml.push('>');
+        xml.push_str(&content);
+        close_tag(xml, name)
+    }
+}
+
+fn close_tag(xml: &mut String, name: &str) -> Option<()> {
+    xml.push_str("</");
+    xml.push_str(name);
+    xml.push('>');
+    Some(())
+}
+
+fn escape(xml: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => xml.push_str("&amp;"),
+            '<' => xml.push_str("&lt;"),
+            '>' => xml.push_str("&gt;"),
+            '"' => xml.push_str("&quot;"),
+            '\'' => xml.push_str("&apos;"),
+            c => xml.push(c),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for XmlJson {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(XmlJsonVisitor)
+    }
+}
+
+struct XmlJsonVisitor;
+
+impl<'de> Visitor<'de> for XmlJsonVisitor {
+    type Value = XmlJson;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("XML data as JSON")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value))
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value.to_string()))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value.to_string()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value.to_string()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(value.to_string()))
+    }
+
+    fn visit_unit<E>(self) -> Result<XmlJson, E> {
+        Ok(XmlJson::Text(String::new()))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<XmlJson, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(XmlJson::Array(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<XmlJson, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(XmlJson::Object(entries))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn xml_json_round_trip() {
+        let xml = r#"<a x="1 &amp; 2"><b>foo</b><c y="z"/><b/><d>bar<e/></d></a>"#;
+        let json = XmlJson::from_xml(xml).unwrap();
+        let expected: XmlJson = serde_json::from_str(
+            r##"{"a": {"@x": "1 & 2", "b": ["foo", ""], "c": {"@y": "z"}, "d": {"#text": "bar", "e": ""}}}"##,
+        )
+        .unwrap();
+        assert_eq!(json, expected);
+        assert_eq!(
+            json.to_xml().unwrap(),
+            r#"<a x="1 &amp; 2"><b>foo</b><b/><c y="z"/><d>bar<e/></d></a>"#
+        );
+    }
+
+    #[test]
+    fn malformed_xml() {
+        for xml in [
+            "", "foo", "<a>", "<a></b>", "<a/><b/>", "<a/>foo", "<a x=1/>",
+        ] {
+            assert_eq!(XmlJson::from_xml(xml), None, "{}", xml);
+        }
+        let json: XmlJson = serde_json::from_str(r#"{"a": "", "b": ""}"#).unwrap();
+        assert_eq!(json.to_xml(), None);
+        let json: XmlJson = serde_json::from_str(r#"{"a b": ""}"#).unwrap();
+        assert_eq!(json.to_xml(), None);
+    }
+}
@@ -41,6 +41,14 @@
 //!
 //! Logging of [`tagvalue::Message`]s.
 //!
+//! ### `utils-tracing`
+//!
+//! Structured [`tracing`](https://docs.rs/tracing) events for message
+//! decoding and encoding (within `decode` and `encode` spans), `CheckSum <10>`
+//! failures, and session-level events (e.g. sequence gaps, logon, logout, and
+//! termination). Without this feature, no spans or events are emitted and
+//! there's no runtime cost at all.
+//!
 //! ### `utils-bytes`, `utils-tokio`
 //!
 //! FIX decoders and encoders that integrate nicely with the Tokio ecosystem.
@@ -49,6 +57,11 @@
 //!
 //! Decode and encode FIX messages with JSON.
 //!
+//! ### `sofh`
+//!
+//! Decode SOFH-framed messages with mixed encodings, e.g. FIX tag-value and
+//! SBE. See [`fesofh`](https://docs.rs/fesofh) for SOFH framing alone.
+//!
 //! ### `codegen`
 //!
 //! This feature it intended to be used within Cargo's `[build-dependencies]`, like this:
@@ -117,6 +130,16 @@ pub mod tagvalue;
 #[cfg(feature = "json-encoding")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "json-encoding")))]
 pub mod json;
+#[cfg(feature = "sofh")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "sofh")))]
+pub mod sofh;
+#[cfg(feature = "test-alloc")]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "test-alloc")))]
+pub mod test_alloc;
+
+#[cfg(all(test, feature = "test-alloc"))]
+#[global_allocator]
+static ALLOCATOR: test_alloc::CountingAllocator = test_alloc::CountingAllocator;
 
 pub use buffer::{Buffer, BufferWriter};
 #[cfg(feature = "codegen")]
@@ -266,6 +289,7 @@ pub trait SetField<F> {
 
 /// Either a field that is missing or has an invalid value.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum FieldValueError<E> {
     /// No such field was found.
     #[error("Missing field tag")]
@@ -0,0 +1,204 @@
+use super::OutboundMessage;
+use crate::{FieldMap, SetField};
+
+const MSG_SEQ_NUM: u32 = 34;
+const MSG_TYPE: u32 = 35;
+const REF_SEQ_NUM: u32 = 45;
+const TEXT: u32 = 58;
+const REF_MSG_TYPE: u32 = 372;
+const BUSINESS_REJECT_REF_ID: u32 = 379;
+const BUSINESS_REJECT_REASON: u32 = 380;
+
+/// Fields which identify application messages, in order of preference for
+/// `BusinessRejectRefID <379>`: `ClOrdID <11>`, `QuoteID <117>`,
+/// `QuoteReqID <131>`, `MDReqID <262>`, `SecurityReqID <320>`,
+/// `TradSesReqID <335>`, `TradeRequestID <568>`, and `PosReqID <710>`.
+const REF_ID_TAGS: &[u32] = &[11, 117, 131, 262, 320, 335, 568, 710];
+
+/// The `BusinessRejectReason <380>` of a `BusinessMessageReject <j>`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum BusinessRejectReason {
+    Other,
+    UnknownId,
+    UnknownSecurity,
+    UnsupportedMessageType,
+    ApplicationNotAvailable,
+    ConditionallyRequiredFieldMissing,
+    NotAuthorized,
+    DeliverToFirmNotAvailable,
+    InvalidPriceIncrement,
+}
+
+impl BusinessRejectReason {
+    /// Returns the value of `BusinessRejectReason <380>` for `self`.
+    pub fn code(self) -> u32 {
+        match self {
+            Self::Other => 0,
+            Self::UnknownId => 1,
+            Self::UnknownSecurity => 2,
+            Self::UnsupportedMessageType => 3,
+            Self::ApplicationNotAvailable => 4,
+            Self::ConditionallyRequiredFieldMissing => 5,
+            Self::NotAuthorized => 6,
+            Self::DeliverToFirmNotAvailable => 7,
+            Self::InvalidPriceIncrement => 18,
+        }
+    }
+
+    /// Returns the [`BusinessRejectReason`] with `code` as its value of
+    /// `BusinessRejectReason <380>`, if known.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => Self::Other,
+            1 => Self::UnknownId,
+            2 => Self::UnknownSecurity,
+            3 => Self::UnsupportedMessageType,
+            4 => Self::ApplicationNotAvailable,
+            5 => Self::ConditionallyRequiredFieldMissing,
+            6 => Self::NotAuthorized,
+            7 => Self::DeliverToFirmNotAvailable,
+            18 => Self::InvalidPriceIncrement,
+            _ => return None,
+        })
+    }
+}
+
+/// Returns a `BusinessMessageReject <j>` in response to the application
+/// message `inbound`, e.g. to be sent with
+/// [`Session::send`](super::Session::send). `RefSeqNum <45>`,
+/// `RefMsgType <372>`, and `BusinessRejectRefID <379>` (e.g. from
+/// `ClOrdID <11>`) are taken from `inbound`.
+///
+/// `BusinessMessageReject <j>` is for valid messages which the application
+/// can't process. It's not to be confused with the session-level
+/// `Reject <3>`, which [`Session`](super::Session) sends by itself for
+/// messages that violate the FIX session protocol.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{business_reject_for, BusinessRejectReason};
+/// use fefix::tagvalue::Decoder;
+/// use fefix::prelude::*;
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+/// let msg = decoder.decode(b"8=FIX.4.4|9=28|35=D|49=A|56=B|34=42|11=foo|10=000|").unwrap();
+///
+/// let reject = business_reject_for(&msg, BusinessRejectReason::NotAuthorized, Some("Nope"));
+/// assert_eq!(reject.msg_type(), b"j");
+/// let fields: Vec<_> = reject.fields().map(|(tag, value)| (tag.get(), value)).collect();
+/// assert_eq!(
+///     fields,
+///     vec![
+///         (45, b"42" as &[u8]),
+///         (372, b"D"),
+///         (379, b"foo"),
+///         (380, b"6"),
+///         (58, b"Nope"),
+///     ]
+/// );
+/// ```
+pub fn business_reject_for<M>(
+    inbound: &M,
+    reason: BusinessRejectReason,
+    text: Option<&str>,
+) -> OutboundMessage
+where
+    M: FieldMap<u32>,
+{
+    let mut reject = OutboundMessage::new(b"j");
+    if let Some(msg_seq_num) = inbound.get_raw(MSG_SEQ_NUM) {
+        reject.set(REF_SEQ_NUM, msg_seq_num);
+    }
+    reject.set(REF_MSG_TYPE, inbound.get_raw(MSG_TYPE).unwrap_or_default());
+    if let Some(ref_id) = REF_ID_TAGS.iter().find_map(|tag| inbound.get_raw(*tag)) {
+        reject.set(BUSINESS_REJECT_REF_ID, ref_id);
+    }
+    reject.set(BUSINESS_REJECT_REASON, reason.code());
+    if let Some(text) = text {
+        reject.set(TEXT, text);
+    }
+    reject
+}
+
+/// The contents of an inbound `BusinessMessageReject <j>`, which
+/// [`Session::process_incoming`](super::Session::process_incoming) reports as
+/// [`Incoming::App`](super::Incoming::App).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BusinessReject {
+    /// `RefSeqNum <45>`, i.e. the `MsgSeqNum <34>` of the rejected message.
+    pub ref_seq_num: Option<u64>,
+    /// `RefMsgType <372>`, i.e. the `MsgType <35>` of the rejected message.
+    pub ref_msg_type: Vec<u8>,
+    /// `BusinessRejectRefID <379>`, e.g. the `ClOrdID <11>` of the rejected
+    /// message.
+    pub ref_id: Option<Vec<u8>>,
+    /// `BusinessRejectReason <380>`; see [`BusinessRejectReason::from_code`].
+    pub reason: u32,
+    /// `Text <58>`.
+    pub text: Option<String>,
+}
+
+impl BusinessReject {
+    /// Reads a [`BusinessReject`] from `msg`. Returns [`None`] if `msg` is not
+    /// a `BusinessMessageReject <j>`, or if it lacks either `RefMsgType <372>`
+    /// or `BusinessRejectReason <380>`.
+    pub fn from_message<M>(msg: &M) -> Option<Self>
+    where
+        M: FieldMap<u32>,
+    {
+        if msg.get_raw(MSG_TYPE) != Some(b"j") {
+            return None;
+        }
+        Some(Self {
+            ref_seq_num: msg.get(REF_SEQ_NUM).ok(),
+            ref_msg_type: msg.get_raw(REF_MSG_TYPE)?.to_vec(),
+            ref_id: msg.get_raw(BUSINESS_REJECT_REF_ID).map(<[u8]>::to_vec),
+            reason: msg.get(BUSINESS_REJECT_REASON).ok()?,
+            text: msg
+                .get_raw(TEXT)
+                .map(|text| String::from_utf8_lossy(text).into_owned()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{Decoder, Encoder};
+    use crate::{Dictionary, GetConfig};
+
+    #[test]
+    fn business_reject_round_trip() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let inbound = decoder
+            .decode(b"8=FIX.4.4|9=28|35=V|49=A|56=B|34=7|262=md1|10=000|")
+            .unwrap();
+        assert_eq!(BusinessReject::from_message(&inbound), None);
+        let reject =
+            business_reject_for(&inbound, BusinessRejectReason::UnsupportedMessageType, None);
+
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, reject.msg_type());
+        msg.set_fields(reject.fields());
+        let data = msg.done().0.to_vec();
+        let msg = decoder.decode(&data[..]).unwrap();
+        let reject = BusinessReject::from_message(&msg).unwrap();
+        assert_eq!(reject.ref_seq_num, Some(7));
+        assert_eq!(reject.ref_msg_type, b"V");
+        assert_eq!(reject.ref_id.as_deref(), Some(b"md1" as &[u8]));
+        assert_eq!(
+            BusinessRejectReason::from_code(reject.reason),
+            Some(BusinessRejectReason::UnsupportedMessageType)
+        );
+        assert_eq!(reject.text, None);
+    }
+}
@@ -0,0 +1,288 @@
+use super::OutboundMessage;
+use crate::FieldMap;
+
+const MSG_TYPE: u32 = 35;
+
+/// When a [`ConditionalRule`] applies, based on the other fields of a
+/// message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Condition {
+    /// The field with `tag` is present.
+    Present(u32),
+    /// The field with `tag` has one of `values`.
+    OneOf {
+        /// The tag of the field.
+        tag: u32,
+        /// All values of the field for which the rule applies.
+        values: Vec<Vec<u8>>,
+    },
+}
+
+impl Condition {
+    /// Returns the tag of the field which `self` looks at.
+    pub fn tag(&self) -> u32 {
+        match self {
+            Self::Present(tag) => *tag,
+            Self::OneOf { tag, .. } => *tag,
+        }
+    }
+
+    fn holds<'a>(&self, get_raw: &impl Fn(u32) -> Option<&'a [u8]>) -> bool {
+        match self {
+            Self::Present(tag) => get_raw(*tag).is_some(),
+            Self::OneOf { tag, values } => match get_raw(*tag) {
+                Some(value) => values.iter().any(|v| v == value),
+                None => false,
+            },
+        }
+    }
+}
+
+/// A field which is "required when X" by the FIX specification or the
+/// rules of engagement of a counterparty, e.g. `StopPx <99>` when
+/// `OrdType <40>` is `Stop`. See [`ConditionalRules`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConditionalRule {
+    /// The `MsgType <35>` values to which the rule applies, or all messages
+    /// if empty.
+    pub msg_types: Vec<Vec<u8>>,
+    /// When the rule applies.
+    pub condition: Condition,
+    /// The fields of which at least one must then be present, e.g.
+    /// `ExpireTime <126>` and `ExpireDate <432>` for `TimeInForce <59>`
+    /// `GoodTillDate`.
+    pub required: Vec<u32>,
+}
+
+impl ConditionalRule {
+    /// Creates a new [`ConditionalRule`] requiring `required` in all messages
+    /// in which `condition` holds.
+    pub fn new(condition: Condition, required: u32) -> Self {
+        Self {
+            msg_types: Vec::new(),
+            condition,
+            required: vec![required],
+        }
+    }
+
+    /// Restricts `self` to messages with any of `msg_types`.
+    pub fn for_msg_types<'a, I>(mut self, msg_types: I) -> Self
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        self.msg_types = msg_types.into_iter().map(|m| m.to_vec()).collect();
+        self
+    }
+
+    /// Allows `alternative` to be present instead of any other required
+    /// field.
+    pub fn or(mut self, alternative: u32) -> Self {
+        self.required.push(alternative);
+        self
+    }
+
+    fn applies_to(&self, msg_type: &[u8]) -> bool {
+        self.msg_types.is_empty() || self.msg_types.iter().any(|m| m == msg_type)
+    }
+}
+
+/// The type returned in the event of a missing conditionally required field,
+/// e.g. to be answered with a `BusinessMessageReject <j>` with
+/// [`BusinessRejectReason::ConditionallyRequiredFieldMissing`](super::BusinessRejectReason::ConditionallyRequiredFieldMissing).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Field {tag} is required because of field {condition_tag}.")]
+pub struct ConditionalFieldMissing {
+    /// The tag of the missing field, i.e. the first of
+    /// [`ConditionalRule::required`].
+    pub tag: u32,
+    /// The tag of the field on which the requirement depends, i.e. that of
+    /// [`ConditionalRule::condition`].
+    pub condition_tag: u32,
+}
+
+/// A set of [`ConditionalRule`]s, which catches messages that would be
+/// rejected by the counterparty before they are sent.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{
+///     Condition, ConditionalFieldMissing, ConditionalRule, ConditionalRules, OutboundMessage,
+/// };
+/// use fefix::prelude::*;
+///
+/// let mut rules = ConditionalRules::standard();
+/// // Our counterparty wants `Account <1>` for all orders of customers.
+/// rules.add(
+///     ConditionalRule::new(Condition::OneOf { tag: 204, values: vec![b"0".to_vec()] }, 1)
+///         .for_msg_types([b"D" as &[u8]]),
+/// );
+///
+/// let mut order = OutboundMessage::new(b"D");
+/// order.set(11, "foo");
+/// order.set(40, "3");
+/// assert_eq!(
+///     rules.check_outbound(&order),
+///     Err(ConditionalFieldMissing { tag: 99, condition_tag: 40 })
+/// );
+/// order.set(99, "1.5");
+/// order.set(204, 0);
+/// assert_eq!(
+///     rules.check_outbound(&order),
+///     Err(ConditionalFieldMissing { tag: 1, condition_tag: 204 })
+/// );
+/// order.set(1, "ACC");
+/// assert!(rules.check_outbound(&order).is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionalRules {
+    rules: Vec<ConditionalRule>,
+}
+
+impl ConditionalRules {
+    /// Creates a new [`ConditionalRules`] without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`ConditionalRules`] with the conditionally required
+    /// fields of the FIX specification for order entry:
+    ///
+    /// - `Price <44>` for limit `OrdType <40>`s in `NewOrderSingle <D>` and
+    ///   `OrderCancelReplaceRequest <G>`, and `StopPx <99>` for stop
+    ///   `OrdType <40>`s.
+    /// - `ExpireTime <126>` or `ExpireDate <432>` for `TimeInForce <59>`
+    ///   `GoodTillDate`, in the same messages.
+    /// - `SettlDate <64>` for `SettlType <63>` `Future`, in the same messages.
+    /// - `LastQty <32>` and `LastPx <31>` for `ExecType <150>` `Trade` in
+    ///   `ExecutionReport <8>`.
+    /// - `SecurityIDSource <22>` for `SecurityID <48>`, in all messages.
+    pub fn standard() -> Self {
+        const ORDERS: &[&[u8]] = &[b"D", b"G"];
+        let one_of = |tag, values: &[&[u8]]| Condition::OneOf {
+            tag,
+            values: values.iter().map(|v| v.to_vec()).collect(),
+        };
+        let mut rules = Self::new();
+        for rule in [
+            ConditionalRule::new(one_of(40, &[b"2", b"4", b"7", b"B"]), 44),
+            ConditionalRule::new(one_of(40, &[b"3", b"4"]), 99),
+            ConditionalRule::new(one_of(59, &[b"6"]), 126).or(432),
+            ConditionalRule::new(one_of(63, &[b"6"]), 64),
+        ] {
+            rules.add(rule.for_msg_types(ORDERS.iter().copied()));
+        }
+        for required in [32, 31] {
+            rules.add(
+                ConditionalRule::new(one_of(150, &[b"F"]), required).for_msg_types([b"8" as &[u8]]),
+            );
+        }
+        rules.add(ConditionalRule::new(Condition::Present(48), 22));
+        rules
+    }
+
+    /// Adds `rule` to `self`.
+    pub fn add(&mut self, rule: ConditionalRule) {
+        self.rules.push(rule);
+    }
+
+    /// Returns an [`Iterator`] over all rules of `self`, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &ConditionalRule> {
+        self.rules.iter()
+    }
+
+    /// Checks all rules of `self` against `msg`, e.g. an inbound
+    /// [`Message`](crate::tagvalue::Message), and returns the first
+    /// violation, if any. Only top-level fields are considered.
+    pub fn check<M>(&self, msg: &M) -> Result<(), ConditionalFieldMissing>
+    where
+        M: FieldMap<u32>,
+    {
+        let msg_type = msg.get_raw(MSG_TYPE).unwrap_or_default();
+        self.check_with(msg_type, |tag| msg.get_raw(tag))
+    }
+
+    /// Like [`ConditionalRules::check`], but for an [`OutboundMessage`].
+    /// Fields of repeating groups are considered as well.
+    pub fn check_outbound(&self, msg: &OutboundMessage) -> Result<(), ConditionalFieldMissing> {
+        self.check_with(msg.msg_type(), |tag| {
+            msg.fields()
+                .find(|(t, _)| t.get() == tag)
+                .map(|(_, value)| value)
+        })
+    }
+
+    fn check_with<'a>(
+        &self,
+        msg_type: &[u8],
+        get_raw: impl Fn(u32) -> Option<&'a [u8]>,
+    ) -> Result<(), ConditionalFieldMissing> {
+        let violation = self.rules.iter().find(|rule| {
+            rule.applies_to(msg_type)
+                && rule.condition.holds(&get_raw)
+                && rule.required.iter().all(|tag| get_raw(*tag).is_none())
+        });
+        match violation {
+            Some(rule) => Err(ConditionalFieldMissing {
+                tag: rule.required.first().copied().unwrap_or_default(),
+                condition_tag: rule.condition.tag(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Decoder;
+    use crate::{Dictionary, GetConfig, SetField};
+
+    #[test]
+    fn standard_order_entry_rules() {
+        let rules = ConditionalRules::standard();
+        let mut order = OutboundMessage::new(b"D");
+        order.set(40, "1");
+        order.set(59, "6");
+        assert_eq!(
+            rules.check_outbound(&order),
+            Err(ConditionalFieldMissing {
+                tag: 126,
+                condition_tag: 59
+            })
+        );
+        order.set(432, "20210101");
+        assert_eq!(rules.check_outbound(&order), Ok(()));
+
+        // Other messages are unaffected.
+        let mut report = OutboundMessage::new(b"8");
+        report.set(40, "2");
+        report.set(150, "F");
+        report.set(32, 100);
+        assert_eq!(
+            rules.check_outbound(&report),
+            Err(ConditionalFieldMissing {
+                tag: 31,
+                condition_tag: 150
+            })
+        );
+    }
+
+    #[test]
+    fn inbound_messages_are_checked() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=30|35=G|49=A|56=B|34=1|40=4|44=2|10=000|")
+            .unwrap();
+        assert_eq!(
+            ConditionalRules::standard().check(&msg),
+            Err(ConditionalFieldMissing {
+                tag: 99,
+                condition_tag: 40
+            })
+        );
+        assert_eq!(ConditionalRules::new().check(&msg), Ok(()));
+    }
+}
@@ -1,4 +1,6 @@
-use super::{Environment, MsgSeqNumCounter, SeqNumbers};
+use super::{Environment, FlushPolicy, MsgSeqNumCounter, SeqNumbers};
+use crate::field_types::TimestampPrecision;
+use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use std::num::NonZeroU64;
 use std::time::Duration;
@@ -24,6 +26,22 @@ pub trait Configure: Clone + Default {
         Duration::from_secs(3)
     }
 
+    /// Asks the FIX connector to refuse inbound messages without
+    /// `SendingTime <52>` with a `Reject <3>` message. If turned off, such
+    /// messages are accepted and stamped with the local receive time instead.
+    /// `true` by default.
+    fn require_sending_time(&self) -> bool {
+        true
+    }
+
+    /// Returns how far the `SendingTime <52>` of inbound messages may be from
+    /// the local clock, in either direction. Messages outside of this window
+    /// are refused with a `Reject <3>` and a `Logout <5>` message. [`None`] by
+    /// default, i.e. `SendingTime <52>` is not checked.
+    fn max_clock_skew(&self) -> Option<Duration> {
+        None
+    }
+
     fn begin_string(&self) -> &[u8] {
         b"FIX.4.4"
     }
@@ -36,6 +54,41 @@ pub trait Configure: Clone + Default {
         b"TARGET_COMP"
     }
 
+    /// Returns our `SenderSubID <50>`, if any. It's set on all outbound
+    /// messages, and inbound messages with a different `TargetSubID <57>` are
+    /// refused. [`None`] by default.
+    fn sender_sub_id(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Returns the counterparty's `SenderSubID <50>`, if any, i.e. our
+    /// `TargetSubID <57>`. See [`Configure::sender_sub_id`]. [`None`] by
+    /// default.
+    fn target_sub_id(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Like [`Configure::sender_sub_id`], but for `SenderLocationID <142>`
+    /// and `TargetLocationID <143>`. [`None`] by default.
+    fn sender_location_id(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Like [`Configure::target_sub_id`], but for `SenderLocationID <142>`
+    /// and `TargetLocationID <143>`. [`None`] by default.
+    fn target_location_id(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Asks the FIX connector to refuse inbound messages which lack any of
+    /// the sub IDs and location IDs that are configured (see
+    /// [`Configure::sender_sub_id`] and friends). Otherwise, only those that
+    /// are present must match. IDs which aren't configured are never checked.
+    /// `false` by default.
+    fn require_sub_ids(&self) -> bool {
+        false
+    }
+
     fn environment(&self) -> Environment {
         Environment::Production { allow_test: true }
     }
@@ -43,25 +96,212 @@ pub trait Configure: Clone + Default {
     fn heartbeat(&self) -> Duration {
         Duration::from_secs(30)
     }
+
+    /// Returns how long to wait for the counterparty's `Logout <5>` message
+    /// after sending ours, before closing the connection anyway. Ten seconds
+    /// by default.
+    fn logout_timeout(&self) -> Duration {
+        Duration::from_secs(10)
+    }
+
+    /// Tells whether the FIX connector initiates FIX sessions with
+    /// [`Session::logon`](super::Session::logon), rather than waiting for the
+    /// counterparty's `Logon <A>` message. Initiators refuse `Logon <A>`
+    /// messages that arrive before theirs was sent. `false` by default.
+    fn is_initiator(&self) -> bool {
+        false
+    }
+
+    /// Asks the FIX connector to set `ResetSeqNumFlag <141>` on its
+    /// `Logon <A>` message and to wind both sequence numbers back to 1 first.
+    /// Acceptors do so as soon as the counterparty's `Logon <A>` is received,
+    /// whether or not it has `ResetSeqNumFlag <141>` set. `false` by default.
+    fn reset_on_logon(&self) -> bool {
+        false
+    }
+
+    /// Asks the FIX connector to recover from sequence gaps at logon by means
+    /// of `NextExpectedMsgSeqNum <789>` (FIX 4.4+), rather than with
+    /// `ResendRequest <2>` messages. Our `Logon <A>` then declares the next
+    /// inbound `MsgSeqNum <34>`, and if the counterparty's does too, the
+    /// messages it's missing are reported by
+    /// [`Session::process_incoming`](super::Session::process_incoming) as
+    /// [`Incoming::ResendRequest`](super::Incoming::ResendRequest). Counterparties
+    /// whose `Logon <A>` lacks `NextExpectedMsgSeqNum <789>` are handled as
+    /// usual. `false` by default.
+    fn next_expected_msg_seq_num(&self) -> bool {
+        false
+    }
+
+    /// Asks the FIX connector to set `LastMsgSeqNumProcessed <369>` on all
+    /// outbound messages, once at least one inbound message was processed.
+    /// `false` by default.
+    fn last_msg_seq_num_processed(&self) -> bool {
+        false
+    }
+
+    /// Returns the `DefaultApplVerID <1137>` of our `Logon <A>`, if any, i.e.
+    /// the `ApplVerID <1128>` code of the application messages of FIXT 1.1
+    /// sessions, e.g. `9` for FIX 5.0 SP2. See
+    /// [`AppVersion::appl_ver_id`](crate::dict::AppVersion::appl_ver_id).
+    /// [`None`] by default.
+    fn default_appl_ver_id(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Returns how often [`Session::store`](super::Session::store) flushes
+    /// the [`MessageStore`](super::MessageStore). See [`FlushPolicy`] for the
+    /// durability trade-offs. [`FlushPolicy::EveryMessage`] by default.
+    fn persistence_flush_policy(&self) -> FlushPolicy {
+        FlushPolicy::EveryMessage
+    }
+
+    /// Returns the precision of `SendingTime <52>`, as written by
+    /// [`Session`](super::Session), and all other timestamps generated by the
+    /// FIX connector. It follows
+    /// [`Configure::begin_string`] by default; see
+    /// [`TimestampPrecision::for_begin_string`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::TimestampPrecision;
+    /// use fefix::session::{Config, Session};
+    ///
+    /// let mut config = Config::default();
+    /// config.sending_time_precision = Some(TimestampPrecision::Micros);
+    ///
+    /// let mut session = Session::new(config);
+    /// session.heartbeat();
+    /// let heartbeat = session.next_outgoing().unwrap();
+    /// let (_, sending_time) = heartbeat
+    ///     .header_fields()
+    ///     .find(|(tag, _)| tag.get() == 52)
+    ///     .unwrap();
+    /// assert_eq!(sending_time.len(), "YYYYMMDD-HH:MM:SS.ssssss".len());
+    /// ```
+    fn sending_time_precision(&self) -> TimestampPrecision {
+        TimestampPrecision::for_begin_string(self.begin_string())
+    }
+
+    /// Checks the configuration options for contradictions that would make
+    /// any FIX session fail, e.g. identical comp. IDs.
+    /// [`Session::try_new`](super::Session::try_new) calls this.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.sender_comp_id().is_empty() || self.target_comp_id().is_empty() {
+            return Err(ConfigError::EmptyCompId);
+        }
+        if self.sender_comp_id() == self.target_comp_id() {
+            return Err(ConfigError::SameCompIds);
+        }
+        if !BEGIN_STRINGS.contains(&self.begin_string()) {
+            return Err(ConfigError::UnknownBeginString);
+        }
+        // `TestRequest <1>` messages are sent after heartbeat intervals
+        // without inbound messages, so there must be some.
+        if self.heartbeat().is_zero() {
+            return Err(ConfigError::ZeroHeartbeat);
+        }
+        // `HeartBtInt <108>` is in seconds.
+        if self.heartbeat().subsec_nanos() != 0 {
+            return Err(ConfigError::FractionalHeartbeat);
+        }
+        Ok(())
+    }
+}
+
+const BEGIN_STRINGS: &[&[u8]] = &[
+    b"FIX.4.0",
+    b"FIX.4.1",
+    b"FIX.4.2",
+    b"FIX.4.3",
+    b"FIX.4.4",
+    b"FIXT.1.1",
+];
+
+/// The type returned by [`Configure::validate`] for contradictory
+/// configuration options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// Either `SenderCompID <49>` or `TargetCompID <56>` is empty.
+    #[error("`SenderCompID <49>` and `TargetCompID <56>` must not be empty.")]
+    EmptyCompId,
+    /// `SenderCompID <49>` and `TargetCompID <56>` are the same.
+    #[error("`SenderCompID <49>` and `TargetCompID <56>` must be different.")]
+    SameCompIds,
+    /// `BeginString <8>` is not a known FIX version.
+    #[error("`BeginString <8>` is not a known FIX version.")]
+    UnknownBeginString,
+    /// The heartbeat interval is zero, which leaves no time for the
+    /// counterparty to answer `TestRequest <1>` messages.
+    #[error("The heartbeat interval must not be zero.")]
+    ZeroHeartbeat,
+    /// The heartbeat interval has sub-second precision, which
+    /// `HeartBtInt <108>` can't express.
+    #[error("The heartbeat interval must be a whole number of seconds.")]
+    FractionalHeartbeat,
 }
 
 /// The canonical implementor of [`Configure`]. Every setting can be changed.
 /// Most fields simply mirror the methods of the [`Configure`] trait.
-#[derive(Debug, Clone)]
+///
+/// [`Config`] can be (de)serialized with [`serde`], e.g. to load session
+/// definitions from a configuration file. Missing fields take their default
+/// values.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Config, ConfigError, Configure};
+///
+/// let config: Config = serde_json::from_str(r#"{
+///     "begin_string": "FIX.4.2",
+///     "sender_comp_id": "BUYSIDE",
+///     "target_comp_id": "SELLSIDE",
+///     "heartbeat": { "secs": 60, "nanos": 0 },
+///     "reset_on_logon": true
+/// }"#).unwrap();
+/// assert_eq!(config.validate(), Ok(()));
+///
+/// let mut config = config.clone();
+/// config.target_comp_id = "BUYSIDE".to_string();
+/// assert_eq!(config.validate(), Err(ConfigError::SameCompIds));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 #[allow(missing_docs)]
 pub struct Config {
+    #[serde(skip)]
     phantom: PhantomData<()>,
 
     pub verify_test_indicator: bool,
     pub max_allowed_latency: Duration,
+    pub require_sending_time: bool,
+    pub max_clock_skew: Option<Duration>,
     pub begin_string: String,
     pub environment: Environment,
     pub heartbeat: Duration,
+    pub logout_timeout: Duration,
+    pub is_initiator: bool,
+    pub reset_on_logon: bool,
+    pub next_expected_msg_seq_num: bool,
+    pub last_msg_seq_num_processed: bool,
+    pub persistence_flush_policy: FlushPolicy,
+    pub default_appl_ver_id: Option<String>,
+    /// Follows [`Config::begin_string`] when [`None`]; see
+    /// [`Configure::sending_time_precision`].
+    pub sending_time_precision: Option<TimestampPrecision>,
     pub seq_numbers: SeqNumbers,
     pub msg_seq_num_inbound: MsgSeqNumCounter,
     pub msg_seq_num_outbound: MsgSeqNumCounter,
     pub sender_comp_id: String,
     pub target_comp_id: String,
+    pub sender_sub_id: Option<String>,
+    pub target_sub_id: Option<String>,
+    pub sender_location_id: Option<String>,
+    pub target_location_id: Option<String>,
+    pub require_sub_ids: bool,
 }
 
 impl Configure for Config {
@@ -73,6 +313,14 @@ impl Configure for Config {
         self.max_allowed_latency
     }
 
+    fn require_sending_time(&self) -> bool {
+        self.require_sending_time
+    }
+
+    fn max_clock_skew(&self) -> Option<Duration> {
+        self.max_clock_skew
+    }
+
     fn sender_comp_id(&self) -> &[u8] {
         self.sender_comp_id.as_bytes()
     }
@@ -81,6 +329,26 @@ impl Configure for Config {
         self.target_comp_id.as_bytes()
     }
 
+    fn sender_sub_id(&self) -> Option<&[u8]> {
+        self.sender_sub_id.as_deref().map(str::as_bytes)
+    }
+
+    fn target_sub_id(&self) -> Option<&[u8]> {
+        self.target_sub_id.as_deref().map(str::as_bytes)
+    }
+
+    fn sender_location_id(&self) -> Option<&[u8]> {
+        self.sender_location_id.as_deref().map(str::as_bytes)
+    }
+
+    fn target_location_id(&self) -> Option<&[u8]> {
+        self.target_location_id.as_deref().map(str::as_bytes)
+    }
+
+    fn require_sub_ids(&self) -> bool {
+        self.require_sub_ids
+    }
+
     fn begin_string(&self) -> &[u8] {
         self.begin_string.as_bytes()
     }
@@ -92,6 +360,39 @@ impl Configure for Config {
     fn heartbeat(&self) -> Duration {
         self.heartbeat
     }
+
+    fn logout_timeout(&self) -> Duration {
+        self.logout_timeout
+    }
+
+    fn is_initiator(&self) -> bool {
+        self.is_initiator
+    }
+
+    fn reset_on_logon(&self) -> bool {
+        self.reset_on_logon
+    }
+
+    fn next_expected_msg_seq_num(&self) -> bool {
+        self.next_expected_msg_seq_num
+    }
+
+    fn last_msg_seq_num_processed(&self) -> bool {
+        self.last_msg_seq_num_processed
+    }
+
+    fn persistence_flush_policy(&self) -> FlushPolicy {
+        self.persistence_flush_policy
+    }
+
+    fn default_appl_ver_id(&self) -> Option<&[u8]> {
+        self.default_appl_ver_id.as_ref().map(|s| s.as_bytes())
+    }
+
+    fn sending_time_precision(&self) -> TimestampPrecision {
+        self.sending_time_precision
+            .unwrap_or_else(|| TimestampPrecision::for_begin_string(self.begin_string()))
+    }
 }
 
 impl Default for Config {
@@ -100,14 +401,29 @@ impl Default for Config {
             phantom: PhantomData::default(),
             verify_test_indicator: true,
             max_allowed_latency: Duration::from_secs(3),
+            require_sending_time: true,
+            max_clock_skew: None,
             begin_string: "FIX.4.4".to_string(),
             environment: Environment::Production { allow_test: true },
             heartbeat: Duration::from_secs(30),
+            logout_timeout: Duration::from_secs(10),
+            is_initiator: false,
+            reset_on_logon: false,
+            next_expected_msg_seq_num: false,
+            last_msg_seq_num_processed: false,
+            persistence_flush_policy: FlushPolicy::EveryMessage,
+            default_appl_ver_id: None,
+            sending_time_precision: None,
             seq_numbers: SeqNumbers::new(NonZeroU64::new(1).unwrap(), NonZeroU64::new(1).unwrap()),
             msg_seq_num_inbound: MsgSeqNumCounter::START,
             msg_seq_num_outbound: MsgSeqNumCounter::START,
             sender_comp_id: "SENDER_COMP".to_string(),
             target_comp_id: "TARGET_COMP".to_string(),
+            sender_sub_id: None,
+            target_sub_id: None,
+            sender_location_id: None,
+            target_location_id: None,
+            require_sub_ids: false,
         }
     }
 }
@@ -133,6 +449,97 @@ mod test {
             config.verify_test_indicator(),
             ConfigDefault.verify_test_indicator()
         );
+        assert_eq!(config.logout_timeout(), ConfigDefault.logout_timeout());
+        assert_eq!(
+            config.require_sending_time(),
+            ConfigDefault.require_sending_time()
+        );
+        assert_eq!(config.max_clock_skew(), ConfigDefault.max_clock_skew());
+        assert_eq!(config.is_initiator(), ConfigDefault.is_initiator());
+        assert_eq!(config.reset_on_logon(), ConfigDefault.reset_on_logon());
+        assert_eq!(
+            config.next_expected_msg_seq_num(),
+            ConfigDefault.next_expected_msg_seq_num()
+        );
+        assert_eq!(
+            config.last_msg_seq_num_processed(),
+            ConfigDefault.last_msg_seq_num_processed()
+        );
+        assert_eq!(config.sender_sub_id(), ConfigDefault.sender_sub_id());
+        assert_eq!(
+            config.target_location_id(),
+            ConfigDefault.target_location_id()
+        );
+        assert_eq!(config.require_sub_ids(), ConfigDefault.require_sub_ids());
+        assert_eq!(
+            config.persistence_flush_policy(),
+            ConfigDefault.persistence_flush_policy()
+        );
+        assert_eq!(
+            config.default_appl_ver_id(),
+            ConfigDefault.default_appl_ver_id()
+        );
+        assert_eq!(
+            config.sending_time_precision(),
+            ConfigDefault.sending_time_precision()
+        );
+    }
+
+    #[test]
+    fn sending_time_precision_follows_begin_string() {
+        let mut config = Config::default();
+        config.begin_string = "FIX.4.2".to_string();
+        assert_eq!(config.sending_time_precision(), TimestampPrecision::Millis);
+        config.begin_string = "FIXT.1.1".to_string();
+        assert_eq!(config.sending_time_precision(), TimestampPrecision::Nanos);
+        config.sending_time_precision = Some(TimestampPrecision::Seconds);
+        assert_eq!(config.sending_time_precision(), TimestampPrecision::Seconds);
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn contradictory_configs_are_invalid() {
+        let invalid = |f: fn(&mut Config)| {
+            let mut config = Config::default();
+            f(&mut config);
+            config.validate().unwrap_err()
+        };
+        assert_eq!(
+            invalid(|c| c.sender_comp_id.clear()),
+            ConfigError::EmptyCompId
+        );
+        assert_eq!(
+            invalid(|c| c.target_comp_id = c.sender_comp_id.clone()),
+            ConfigError::SameCompIds
+        );
+        assert_eq!(
+            invalid(|c| c.begin_string = "FIX.5.0".to_string()),
+            ConfigError::UnknownBeginString
+        );
+        assert_eq!(
+            invalid(|c| c.heartbeat = Duration::ZERO),
+            ConfigError::ZeroHeartbeat
+        );
+        assert_eq!(
+            invalid(|c| c.heartbeat = Duration::from_millis(1500)),
+            ConfigError::FractionalHeartbeat
+        );
+    }
+
+    #[test]
+    fn config_round_trips_through_serde() {
+        let mut config = Config::default();
+        config.environment = Environment::Testing;
+        config.heartbeat = Duration::from_secs(5);
+        let json = serde_json::to_string(&config).unwrap();
+        let config: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(config.environment(), Environment::Testing);
+        assert_eq!(config.heartbeat(), Duration::from_secs(5));
+        assert_eq!(config.seq_numbers.next_inbound(), 1);
     }
 
     #[quickcheck]
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 /// An indicator for the kind of environment relative to a FIX Connection.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum Environment {
     /// Test messages will be ignored or refused under this environment setting.
@@ -69,7 +69,8 @@ where
                 Delay::new(now - self.last_reset + self.heartbeat_soft_tolerance).fuse();
             let mut timer_logout =
                 Delay::new(now - self.last_reset + self.heartbeat_hard_tolerance).fuse();
-            let mut read_result = self.input.read(buf).fuse();
+            // Reads can be partial, so we keep filling where we left off.
+            let mut read_result = self.input.read(&mut buf[buf_filled_len..]).fuse();
 
             select! {
                 read_result = read_result => {
@@ -85,7 +86,7 @@ where
 
                             let result = self.decoder.try_parse();
                             buf_filled_len = 0;
-                            buf = &mut self.decoder.fillable()[buf_filled_len..];
+                            buf = self.decoder.fillable();
 
                             match result {
                                 Ok(Some(())) => {
@@ -111,6 +112,11 @@ where
                     return Some(LlEvent::TestRequest);
                 },
                 () = timer_logout => {
+                    trace_event!(
+                        info,
+                        heartbeat_secs = self.heartbeat.as_secs(),
+                        "Counterparty stopped responding, logging out"
+                    );
                     self.is_alive = false;
                     return Some(LlEvent::Logout);
                 }
@@ -122,6 +128,23 @@ where
     pub fn ping_heartbeat(&mut self) {
         self.last_reset = Instant::now();
     }
+
+    /// Brings `self` back to its initial state for a new connection over
+    /// `input`, and returns the previous input. This allows to reuse `self`
+    /// after [`LlEvent::BadMessage`], [`LlEvent::Logout`], or I/O errors.
+    ///
+    /// The decoder is reset with
+    /// [`DecoderStreaming::reset`](crate::tagvalue::DecoderStreaming::reset),
+    /// all `Heartbeat <0>` -associated timers restart from now, and
+    /// [`LlEventLoop::next_event`] produces events again. The heartbeat
+    /// interval and tolerances are preserved.
+    pub fn reset(&mut self, input: I) -> I {
+        self.decoder.reset();
+        self.last_reset = Instant::now();
+        self.last_heartbeat = Instant::now();
+        self.is_alive = true;
+        std::mem::replace(&mut self.input, input)
+    }
 }
 
 /// A low level event produced by a [`LlEventLoop`].
@@ -0,0 +1,140 @@
+use super::{SessionStatus, Termination};
+use std::fmt::Debug;
+use std::ops::Range;
+
+/// A state transition or a significant event of a
+/// [`Session`](super::Session), as given to the handler of
+/// [`Session::set_event_handler`](super::Session::set_event_handler).
+///
+/// Outbound messages are "sent" as soon as they're queued for
+/// [`Session::next_outgoing`](super::Session::next_outgoing), and inbound
+/// messages are "received" by
+/// [`Session::process_incoming`](super::Session::process_incoming). Events
+/// carry no timestamps: handlers are called synchronously, so they can read
+/// the clock themselves if needed.
+///
+/// New variants may be added in future releases, so matches must have a
+/// wildcard arm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SessionEvent {
+    /// The [`SessionStatus`] changed.
+    StatusChanged {
+        /// The previous status.
+        from: SessionStatus,
+        /// The current status.
+        to: SessionStatus,
+    },
+    /// A `Logon <A>` message was sent.
+    LogonSent {
+        /// The `MsgSeqNum <34>` of the `Logon <A>`.
+        msg_seq_num: u64,
+        /// Whether the `Logon <A>` has `ResetSeqNumFlag <141>` set to `Y`.
+        reset_seq_num: bool,
+    },
+    /// The counterparty's `Logon <A>` message was received.
+    LogonReceived {
+        /// The `MsgSeqNum <34>` of the `Logon <A>`.
+        msg_seq_num: u64,
+        /// Whether the `Logon <A>` has `ResetSeqNumFlag <141>` set to `Y`.
+        reset_seq_num: bool,
+    },
+    /// A `Heartbeat <0>` message was sent, e.g. in response to a
+    /// `TestRequest <1>`.
+    HeartbeatSent {
+        /// The `MsgSeqNum <34>` of the `Heartbeat <0>`.
+        msg_seq_num: u64,
+    },
+    /// A `TestRequest <1>` message was sent.
+    TestRequestSent {
+        /// The `MsgSeqNum <34>` of the `TestRequest <1>`.
+        msg_seq_num: u64,
+    },
+    /// An inbound message has a higher `MsgSeqNum <34>` than expected.
+    SeqGap {
+        /// The expected `MsgSeqNum <34>`.
+        expected: u64,
+        /// The actual `MsgSeqNum <34>` of the message.
+        actual: u64,
+    },
+    /// A `ResendRequest <2>` message was sent for all inbound messages from
+    /// `begin_seq_no` onwards.
+    ResendRequestSent {
+        /// The `MsgSeqNum <34>` of the `ResendRequest <2>`.
+        msg_seq_num: u64,
+        /// The `BeginSeqNo <7>` of the `ResendRequest <2>`.
+        begin_seq_no: u64,
+    },
+    /// The counterparty asked for the outbound messages within `range`, either
+    /// with a `ResendRequest <2>` or with the `NextExpectedMsgSeqNum <789>` of
+    /// its `Logon <A>`.
+    ResendRequestReceived {
+        /// The `MsgSeqNum <34>` of the inbound message.
+        msg_seq_num: u64,
+        /// The outbound messages to send again.
+        range: Range<u64>,
+    },
+    /// The sequence gap which caused the last [`SessionEvent::SeqGap`] was
+    /// filled, i.e. the counterparty completed its resend.
+    GapFilled {
+        /// The `MsgSeqNum <34>` of the message which filled the gap.
+        msg_seq_num: u64,
+    },
+    /// A `SequenceReset <4>` message was sent by
+    /// [`Session::gap_fill`](super::Session::gap_fill).
+    SequenceResetSent {
+        /// The `MsgSeqNum <34>` of the `SequenceReset <4>`.
+        msg_seq_num: u64,
+        /// The `NewSeqNo <36>` of the `SequenceReset <4>`.
+        new_seq_no: u64,
+    },
+    /// A `SequenceReset <4>` message was received.
+    SequenceResetReceived {
+        /// The `NewSeqNo <36>` of the `SequenceReset <4>`.
+        new_seq_no: u64,
+        /// Whether the `SequenceReset <4>` has `GapFillFlag <123>` set to
+        /// `Y`.
+        gap_fill: bool,
+    },
+    /// A `Reject <3>` message was sent.
+    RejectSent {
+        /// The `MsgSeqNum <34>` of the `Reject <3>`.
+        msg_seq_num: u64,
+        /// The `MsgSeqNum <34>` of the rejected message.
+        ref_seq_num: u64,
+        /// The `SessionRejectReason <373>` of the `Reject <3>`.
+        reason: u32,
+    },
+    /// A `Logout <5>` message was sent.
+    LogoutSent {
+        /// The `MsgSeqNum <34>` of the `Logout <5>`.
+        msg_seq_num: u64,
+    },
+    /// The counterparty's `Logout <5>` message was received.
+    LogoutReceived {
+        /// The `MsgSeqNum <34>` of the `Logout <5>`.
+        msg_seq_num: u64,
+    },
+    /// The session is over, right after [`SessionEvent::StatusChanged`] to
+    /// [`SessionStatus::Terminated`]. The transport can be closed as soon as
+    /// all outgoing messages are sent.
+    Terminated(Termination),
+}
+
+pub(super) type HandleEvent = dyn FnMut(SessionEvent) + Send;
+
+// Handlers can't be cloned, so clones of a `Session` start without one.
+#[derive(Default)]
+pub(super) struct EventHandler(pub(super) Option<Box<HandleEvent>>);
+
+impl Clone for EventHandler {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl Debug for EventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EventHandler")
+    }
+}
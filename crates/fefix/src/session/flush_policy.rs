@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+/// How often [`Session::store`](super::Session::store) durably flushes the
+/// [`MessageStore`](super::MessageStore), i.e. calls
+/// [`MessageStore::flush`](super::MessageStore::flush). See
+/// [`Configure::persistence_flush_policy`](super::Configure::persistence_flush_policy).
+///
+/// Flushing is usually the bottleneck of high-throughput sessions with
+/// persistent stores, as it means one `fsync` or equivalent. Policies other
+/// than [`FlushPolicy::EveryMessage`] trade durability for throughput: on a
+/// crash, the outbound messages and sequence numbers stored since the last
+/// flush may be lost. The session then restarts with sequence numbers that are
+/// too low: the counterparty refuses our outbound `MsgSeqNum <34>` with a
+/// `Logout <5>` until it's fixed by hand, and inbound messages are processed
+/// twice after a `ResendRequest <2>`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum FlushPolicy {
+    /// Flushes after each message. Nothing is ever lost, at the cost of one
+    /// flush per message. This is the default.
+    #[default]
+    EveryMessage,
+    /// Flushes after every `n`-th message. Up to `n - 1` messages may be lost.
+    EveryN(NonZeroU64),
+    /// Flushes as soon as a message is stored at least the given interval
+    /// after the last flush. All messages stored within the last interval may
+    /// be lost, and so may the last messages of a burst, until the next
+    /// message is stored: a timer should also call
+    /// [`Session::flush_store`](super::Session::flush_store) at the given
+    /// interval, just like it calls
+    /// [`Session::heartbeat`](super::Session::heartbeat).
+    OnInterval(Duration),
+}
+
+impl FlushPolicy {
+    /// Returns `true` if `self` calls for a flush once `unflushed` messages
+    /// were stored since the last flush, which was `since_last_flush` ago.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::FlushPolicy;
+    /// use std::num::NonZeroU64;
+    /// use std::time::Duration;
+    ///
+    /// let policy = FlushPolicy::EveryN(NonZeroU64::new(3).unwrap());
+    /// assert!(!policy.is_due(2, Duration::ZERO));
+    /// assert!(policy.is_due(3, Duration::ZERO));
+    /// assert!(FlushPolicy::EveryMessage.is_due(1, Duration::ZERO));
+    ///
+    /// let policy = FlushPolicy::OnInterval(Duration::from_millis(10));
+    /// assert!(!policy.is_due(1000, Duration::from_millis(9)));
+    /// assert!(policy.is_due(1, Duration::from_millis(10)));
+    /// assert!(!policy.is_due(0, Duration::from_millis(10)));
+    /// ```
+    pub fn is_due(&self, unflushed: u64, since_last_flush: Duration) -> bool {
+        match self {
+            Self::EveryMessage => unflushed >= 1,
+            Self::EveryN(n) => unflushed >= n.get(),
+            Self::OnInterval(interval) => unflushed >= 1 && since_last_flush >= *interval,
+        }
+    }
+}
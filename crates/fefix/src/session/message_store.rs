@@ -0,0 +1,46 @@
+use super::SeqNumbers;
+use std::collections::BTreeMap;
+use std::io;
+
+/// A persistent log of outbound FIX messages, indexed by `MsgSeqNum <34>`,
+/// from which messages are retrieved in response to `ResendRequest <2>`
+/// messages.
+///
+/// [`BTreeMap<u64, Vec<u8>>`] is the simplest implementor, e.g. for tests and
+/// non-persistent sessions.
+pub trait MessageStore {
+    /// Stores the raw byte contents of the outbound message with
+    /// `msg_seq_num`, replacing any previous message with the same
+    /// `MsgSeqNum <34>`.
+    fn insert(&mut self, msg_seq_num: u64, message: &[u8]);
+
+    /// Returns the raw byte contents of the outbound message with
+    /// `msg_seq_num`, if stored.
+    fn get(&self, msg_seq_num: u64) -> Option<&[u8]>;
+
+    /// Stores the current sequence numbers of the session, so that they can
+    /// be restored with
+    /// [`Session::seq_numbers_mut`](super::Session::seq_numbers_mut) after a
+    /// restart. Nothing is stored by default.
+    fn set_seq_numbers(&mut self, seq_numbers: SeqNumbers) {
+        let _ = seq_numbers;
+    }
+
+    /// Makes all messages and sequence numbers stored so far durable, e.g. by
+    /// means of [`File::sync_data`](std::fs::File::sync_data). Non-persistent
+    /// stores have nothing to do, which is the default. See
+    /// [`FlushPolicy`](super::FlushPolicy).
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl MessageStore for BTreeMap<u64, Vec<u8>> {
+    fn insert(&mut self, msg_seq_num: u64, message: &[u8]) {
+        BTreeMap::insert(self, msg_seq_num, message.to_vec());
+    }
+
+    fn get(&self, msg_seq_num: u64) -> Option<&[u8]> {
+        BTreeMap::get(self, &msg_seq_num).map(Vec::as_slice)
+    }
+}
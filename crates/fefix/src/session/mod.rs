@@ -6,24 +6,43 @@
 //! state machine and transitions between initiator and acceptor.
 
 pub mod backends;
+mod business_reject;
+mod conditional;
 mod config;
 //mod connection; FIXME
 mod environment;
 mod errs;
 mod event_loop;
+mod events;
+mod flush_policy;
 mod heartbeat_rule;
+mod message_store;
+mod resend;
 mod resend_request_range;
+mod routing;
 mod seq_numbers;
+mod state_machine;
 
 use crate::tagvalue::Message;
 use crate::{FieldType, SetField};
-pub use config::{Config, Configure};
+pub use business_reject::{business_reject_for, BusinessReject, BusinessRejectReason};
+pub use conditional::{Condition, ConditionalFieldMissing, ConditionalRule, ConditionalRules};
+pub use config::{Config, ConfigError, Configure};
 // pub use connection::*; FIXME
 pub use environment::Environment;
 pub use event_loop::*;
+pub use events::SessionEvent;
+pub use flush_policy::FlushPolicy;
 pub use heartbeat_rule::HeartbeatRule;
+pub use message_store::MessageStore;
+pub use resend::{resend_messages, Resend};
 pub use resend_request_range::ResendRequestRange;
+pub use routing::{reply_routing_for, verify_comp_ids, CompIdError, RoutingFields};
 pub use seq_numbers::{SeqNumberError, SeqNumbers};
+use serde::{Deserialize, Serialize};
+pub use state_machine::{
+    GroupEntry, Incoming, OutboundMessage, Session, SessionError, SessionStatus, Termination,
+};
 use std::ops::Range;
 
 /// The owner of a [`FixConnection`]. It can react to events, store incoming
@@ -91,7 +110,7 @@ pub struct State {
     next_outbound: u64,
 }
 
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct MsgSeqNumCounter(u64);
 
 impl MsgSeqNumCounter {
@@ -0,0 +1,280 @@
+use super::MessageStore;
+use crate::tagvalue::{DecodeError, Decoder, Encoder};
+use crate::{FieldMap, SetField};
+use std::ops::Range;
+
+const BEGIN_STRING: u32 = 8;
+const BODY_LENGTH: u32 = 9;
+const CHECK_SUM: u32 = 10;
+const MSG_SEQ_NUM: u32 = 34;
+const MSG_TYPE: u32 = 35;
+const POSS_DUP_FLAG: u32 = 43;
+const SENDING_TIME: u32 = 52;
+const ORIG_SENDING_TIME: u32 = 122;
+
+/// Administrative messages which must never be resent, as per FIX Session
+/// Layer specifications: `Heartbeat <0>`, `TestRequest <1>`,
+/// `ResendRequest <2>`, `SequenceReset <4>`, `Logout <5>`, and `Logon <A>`.
+const GAP_FILLED_MSG_TYPES: &[&[u8]] = &[b"0", b"1", b"2", b"4", b"5", b"A"];
+
+/// What to send in place of a stored outbound message in response to a
+/// `ResendRequest <2>`, as returned by [`resend_messages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resend {
+    /// The raw byte contents of an application message, ready to be resent.
+    Message(Vec<u8>),
+    /// A range of administrative or missing messages, which must be skipped
+    /// with a `SequenceReset <4>` message with `GapFillFlag <123>` set to `Y`.
+    GapFill {
+        /// The `MsgSeqNum <34>` of the `SequenceReset <4>` message, i.e. of
+        /// the first skipped message.
+        msg_seq_num: u64,
+        /// The `NewSeqNo <36>` of the `SequenceReset <4>` message, i.e. the
+        /// `MsgSeqNum <34>` after the last skipped message.
+        new_seq_no: u64,
+    },
+}
+
+/// Retrieves the outbound messages within `range` from `store` and prepares
+/// them for resending in response to a `ResendRequest <2>`. `range.end` should
+/// be the next outbound `MsgSeqNum <34>` when `EndSeqNo <16>` is `0`.
+///
+/// Application messages are decoded with `decoder` and re-encoded with
+/// `encoder`, rewriting only their header as follows:
+///
+/// - `PossDupFlag <43>` is set to `Y`, right after `MsgSeqNum <34>`.
+/// - `SendingTime <52>` is set to `sending_time`.
+/// - `OrigSendingTime <122>` is set to the original `SendingTime <52>`, right
+///   after it. Messages that were already resent keep their original
+///   `OrigSendingTime <122>`.
+///
+/// `BodyLength <9>` and `CheckSum <10>` are computed anew, and all other
+/// fields are left untouched. Consecutive administrative and missing messages
+/// are merged into a single [`Resend::GapFill`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{resend_messages, Resend};
+/// use fefix::tagvalue::{Decoder, Encoder};
+/// use fefix::prelude::*;
+/// use std::collections::BTreeMap;
+///
+/// let mut store = BTreeMap::new();
+/// let logon = b"8=FIX.4.4|9=31|35=A|34=1|52=20210101-10:00:00|10=000|";
+/// store.insert(1, logon.to_vec());
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+/// let mut encoder = Encoder::new();
+/// encoder.config_mut().separator = b'|';
+///
+/// let resends =
+///     resend_messages(&store, 1..3, &mut decoder, &mut encoder, b"20210101-10:05:00").unwrap();
+/// assert_eq!(resends, vec![Resend::GapFill { msg_seq_num: 1, new_seq_no: 3 }]);
+/// ```
+pub fn resend_messages<S>(
+    store: &S,
+    range: Range<u64>,
+    decoder: &mut Decoder,
+    encoder: &mut Encoder,
+    sending_time: &[u8],
+) -> Result<Vec<Resend>, DecodeError>
+where
+    S: MessageStore + ?Sized,
+{
+    let mut resends = Vec::new();
+    for msg_seq_num in range {
+        let msg = match store.get(msg_seq_num) {
+            Some(data) => decoder.decode(data)?,
+            None => {
+                push_gap_fill(&mut resends, msg_seq_num);
+                continue;
+            }
+        };
+        let begin_string = msg
+            .get_raw(BEGIN_STRING)
+            .ok_or(DecodeError::FieldPresence)?;
+        let msg_type = msg.get_raw(MSG_TYPE).ok_or(DecodeError::FieldPresence)?;
+        if GAP_FILLED_MSG_TYPES.contains(&msg_type) {
+            push_gap_fill(&mut resends, msg_seq_num);
+            continue;
+        }
+        let orig_sending_time = msg
+            .get_raw(ORIG_SENDING_TIME)
+            .or_else(|| msg.get_raw(SENDING_TIME));
+        let mut buffer = Vec::new();
+        let mut resent = encoder.start_message(begin_string, &mut buffer, msg_type);
+        for (tag, value) in msg.fields() {
+            match tag.get() {
+                BEGIN_STRING | BODY_LENGTH | CHECK_SUM | MSG_TYPE | POSS_DUP_FLAG
+                | ORIG_SENDING_TIME => {}
+                MSG_SEQ_NUM => {
+                    resent.set(MSG_SEQ_NUM, value);
+                    resent.set(POSS_DUP_FLAG, true);
+                }
+                SENDING_TIME => {
+                    resent.set(SENDING_TIME, sending_time);
+                    if let Some(orig_sending_time) = orig_sending_time {
+                        resent.set(ORIG_SENDING_TIME, orig_sending_time);
+                    }
+                }
+                tag => resent.set(tag, value),
+            }
+        }
+        resent.done();
+        resends.push(Resend::Message(buffer));
+    }
+    Ok(resends)
+}
+
+fn push_gap_fill(resends: &mut Vec<Resend>, msg_seq_num: u64) {
+    match resends.last_mut() {
+        Some(Resend::GapFill { new_seq_no, .. }) if *new_seq_no == msg_seq_num => {
+            *new_seq_no = msg_seq_num + 1;
+        }
+        _ => resends.push(Resend::GapFill {
+            msg_seq_num,
+            new_seq_no: msg_seq_num + 1,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::{Dictionary, GetConfig};
+    use std::collections::BTreeMap;
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder
+    }
+
+    fn encoder() -> Encoder {
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        encoder
+    }
+
+    fn store() -> BTreeMap<u64, Vec<u8>> {
+        let mut store = BTreeMap::new();
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"A");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 1);
+        msg.set(fix44::SENDING_TIME, "20210101-10:00:00");
+        store.insert(1, msg.done().0.to_vec());
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"8");
+        msg.set(fix44::SENDER_COMP_ID, "A");
+        msg.set(fix44::TARGET_COMP_ID, "B");
+        msg.set(fix44::MSG_SEQ_NUM, 2);
+        msg.set(fix44::SENDING_TIME, "20210101-10:00:01.500");
+        msg.set(fix44::ORDER_ID, "123");
+        msg.set(fix44::EXEC_ID, "456");
+        msg.set(fix44::EXEC_TYPE, "0");
+        msg.set(fix44::ORD_STATUS, "0");
+        msg.set(fix44::SIDE, "1");
+        msg.set(fix44::LEAVES_QTY, 100);
+        msg.set(fix44::CUM_QTY, 0);
+        msg.set(fix44::AVG_PX, 0);
+        store.insert(2, msg.done().0.to_vec());
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.set(fix44::MSG_SEQ_NUM, 3);
+        store.insert(3, msg.done().0.to_vec());
+        store
+    }
+
+    #[test]
+    fn resent_execution_report_has_original_and_new_sending_times() {
+        let store = store();
+        let mut decoder = decoder();
+        let resends = resend_messages(
+            &store,
+            1..6,
+            &mut decoder,
+            &mut encoder(),
+            b"20210101-10:05:00.000",
+        )
+        .unwrap();
+        assert_eq!(resends.len(), 3);
+        assert_eq!(
+            resends[0],
+            Resend::GapFill {
+                msg_seq_num: 1,
+                new_seq_no: 2
+            }
+        );
+        assert_eq!(
+            resends[2],
+            Resend::GapFill {
+                msg_seq_num: 3,
+                new_seq_no: 6
+            }
+        );
+        let data = match &resends[1] {
+            Resend::Message(data) => data,
+            resend => panic!("{:?}", resend),
+        };
+        let resent = decoder.decode(&data[..]).unwrap();
+        assert_eq!(resent.get(fix44::POSS_DUP_FLAG), Ok(true));
+        assert_eq!(resent.get_raw(52), Some(b"20210101-10:05:00.000" as &[u8]));
+        assert_eq!(resent.get_raw(122), Some(b"20210101-10:00:01.500" as &[u8]));
+        let resent_fields: Vec<_> = resent
+            .fields()
+            .map(|(tag, value)| (tag.get(), value.to_vec()))
+            .filter(|(tag, _)| ![9, 10, 43, 52, 122].contains(tag))
+            .collect();
+        let original = decoder.decode(&store[&2][..]).unwrap();
+        let original_fields: Vec<_> = original
+            .fields()
+            .map(|(tag, value)| (tag.get(), value.to_vec()))
+            .filter(|(tag, _)| ![9, 10, 52].contains(tag))
+            .collect();
+        assert_eq!(resent_fields, original_fields);
+    }
+
+    #[test]
+    fn resending_twice_keeps_orig_sending_time() {
+        let mut decoder = decoder();
+        let mut encoder = encoder();
+        let mut store = store();
+        let resends = resend_messages(
+            &store,
+            2..3,
+            &mut decoder,
+            &mut encoder,
+            b"20210101-10:05:00",
+        )
+        .unwrap();
+        match &resends[..] {
+            [Resend::Message(data)] => MessageStore::insert(&mut store, 2, data),
+            resends => panic!("{:?}", resends),
+        }
+        let resends = resend_messages(
+            &store,
+            2..3,
+            &mut decoder,
+            &mut encoder,
+            b"20210101-10:10:00",
+        )
+        .unwrap();
+        let data = match &resends[..] {
+            [Resend::Message(data)] => data,
+            resends => panic!("{:?}", resends),
+        };
+        let resent = decoder.decode(&data[..]).unwrap();
+        assert_eq!(resent.get_all(43).count(), 1);
+        assert_eq!(
+            resent.get_all(122).collect::<Vec<_>>(),
+            vec![b"20210101-10:00:01.500" as &[u8]]
+        );
+        assert_eq!(resent.get_raw(52), Some(b"20210101-10:10:00" as &[u8]));
+    }
+}
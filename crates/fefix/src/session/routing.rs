@@ -0,0 +1,333 @@
+use super::Configure;
+use crate::{FieldMap, SetField};
+
+const SENDER_COMP_ID: u32 = 49;
+const SENDER_SUB_ID: u32 = 50;
+const TARGET_COMP_ID: u32 = 56;
+const TARGET_SUB_ID: u32 = 57;
+const SENDER_LOCATION_ID: u32 = 142;
+const TARGET_LOCATION_ID: u32 = 143;
+const ON_BEHALF_OF_COMP_ID: u32 = 115;
+const ON_BEHALF_OF_SUB_ID: u32 = 116;
+const ON_BEHALF_OF_LOCATION_ID: u32 = 144;
+const DELIVER_TO_COMP_ID: u32 = 128;
+const DELIVER_TO_SUB_ID: u32 = 129;
+const DELIVER_TO_LOCATION_ID: u32 = 145;
+
+/// Third-party routing fields, as used by hub-and-spoke FIX deployments
+/// where messages travel through one or more intermediaries.
+///
+/// - `OnBehalfOf*` fields identify the firm which originated the message
+///   when it's sent by an intermediary.
+/// - `DeliverTo*` fields identify the firm which the message is ultimately
+///   meant for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct RoutingFields {
+    pub on_behalf_of_comp_id: Option<Vec<u8>>,
+    pub on_behalf_of_sub_id: Option<Vec<u8>>,
+    pub on_behalf_of_location_id: Option<Vec<u8>>,
+    pub deliver_to_comp_id: Option<Vec<u8>>,
+    pub deliver_to_sub_id: Option<Vec<u8>>,
+    pub deliver_to_location_id: Option<Vec<u8>>,
+}
+
+impl RoutingFields {
+    /// Reads all routing fields from the header of `msg`.
+    pub fn from_message<M>(msg: &M) -> Self
+    where
+        M: FieldMap<u32>,
+    {
+        let get = |tag| msg.get_raw(tag).map(<[u8]>::to_vec);
+        Self {
+            on_behalf_of_comp_id: get(ON_BEHALF_OF_COMP_ID),
+            on_behalf_of_sub_id: get(ON_BEHALF_OF_SUB_ID),
+            on_behalf_of_location_id: get(ON_BEHALF_OF_LOCATION_ID),
+            deliver_to_comp_id: get(DELIVER_TO_COMP_ID),
+            deliver_to_sub_id: get(DELIVER_TO_SUB_ID),
+            deliver_to_location_id: get(DELIVER_TO_LOCATION_ID),
+        }
+    }
+
+    /// Returns `true` if and only if no routing field is set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Returns the routing fields for a reply to a message with `self`
+    /// routing, i.e. with `OnBehalfOf*` and `DeliverTo*` fields swapped.
+    pub fn reversed(&self) -> Self {
+        Self {
+            on_behalf_of_comp_id: self.deliver_to_comp_id.clone(),
+            on_behalf_of_sub_id: self.deliver_to_sub_id.clone(),
+            on_behalf_of_location_id: self.deliver_to_location_id.clone(),
+            deliver_to_comp_id: self.on_behalf_of_comp_id.clone(),
+            deliver_to_sub_id: self.on_behalf_of_sub_id.clone(),
+            deliver_to_location_id: self.on_behalf_of_location_id.clone(),
+        }
+    }
+
+    /// Writes all routing fields that are set to `msg`. This should be done
+    /// right after `SenderCompID <49>` and `TargetCompID <56>`.
+    pub fn set_on<M>(&self, msg: &mut M)
+    where
+        M: SetField<u32>,
+    {
+        let fields = [
+            (ON_BEHALF_OF_COMP_ID, &self.on_behalf_of_comp_id),
+            (DELIVER_TO_COMP_ID, &self.deliver_to_comp_id),
+            (ON_BEHALF_OF_SUB_ID, &self.on_behalf_of_sub_id),
+            (DELIVER_TO_SUB_ID, &self.deliver_to_sub_id),
+            (ON_BEHALF_OF_LOCATION_ID, &self.on_behalf_of_location_id),
+            (DELIVER_TO_LOCATION_ID, &self.deliver_to_location_id),
+        ];
+        for (tag, value) in fields {
+            if let Some(value) = value {
+                msg.set(tag, value.as_slice());
+            }
+        }
+    }
+}
+
+/// Returns the [`RoutingFields`] of a reply to `inbound`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::reply_routing_for;
+/// use fefix::tagvalue::Decoder;
+/// use fefix::prelude::*;
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// let msg = b"8=FIX.4.4|9=37|35=0|49=HUB|56=B|34=2|115=A|116=DESK|10=015|";
+/// let msg = decoder.decode(msg).unwrap();
+///
+/// let routing = reply_routing_for(&msg);
+/// assert_eq!(routing.deliver_to_comp_id.as_deref(), Some(b"A" as &[u8]));
+/// assert_eq!(routing.deliver_to_sub_id.as_deref(), Some(b"DESK" as &[u8]));
+/// assert_eq!(routing.on_behalf_of_comp_id, None);
+/// ```
+pub fn reply_routing_for<M>(inbound: &M) -> RoutingFields
+where
+    M: FieldMap<u32>,
+{
+    RoutingFields::from_message(inbound).reversed()
+}
+
+/// The reason why the comp. IDs of an inbound message don't match the
+/// session, as returned by [`verify_comp_ids`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum CompIdError {
+    /// `SenderCompID <49>` is missing or it's not the counterparty's comp. ID.
+    #[error("Invalid `SenderCompID <49>`.")]
+    SenderCompId,
+    /// `TargetCompID <56>` is missing or it's not our comp. ID.
+    #[error("Invalid `TargetCompID <56>`.")]
+    TargetCompId,
+    /// The routing fields would send the message back to where it came from,
+    /// e.g. `DeliverToCompID <128>` is the same as `SenderCompID <49>`.
+    #[error("Third-party routing loop.")]
+    RoutingLoop,
+    /// `SenderSubID <50>` is not the counterparty's sub ID, or it's missing
+    /// and [`Configure::require_sub_ids`] is on.
+    #[error("Invalid `SenderSubID <50>`.")]
+    SenderSubId,
+    /// Like [`CompIdError::SenderSubId`], but for `TargetSubID <57>`.
+    #[error("Invalid `TargetSubID <57>`.")]
+    TargetSubId,
+    /// Like [`CompIdError::SenderSubId`], but for `SenderLocationID <142>`.
+    #[error("Invalid `SenderLocationID <142>`.")]
+    SenderLocationId,
+    /// Like [`CompIdError::SenderSubId`], but for `TargetLocationID <143>`.
+    #[error("Invalid `TargetLocationID <143>`.")]
+    TargetLocationId,
+}
+
+/// Checks `SenderCompID <49>` and `TargetCompID <56>` of `inbound` against
+/// the comp. IDs of the session, taking [`RoutingFields`] into account:
+/// neither `OnBehalfOfCompID <115>` nor `DeliverToCompID <128>` may point back
+/// to the counterparty on the other side of the message.
+///
+/// Sub IDs and location IDs (e.g. `SenderSubID <50>`) are only checked if
+/// the session has them; see [`Configure::sender_sub_id`].
+pub fn verify_comp_ids<C, M>(config: &C, inbound: &M) -> Result<(), CompIdError>
+where
+    C: Configure,
+    M: FieldMap<u32>,
+{
+    let sender = inbound.get_raw(SENDER_COMP_ID);
+    let target = inbound.get_raw(TARGET_COMP_ID);
+    if sender != Some(config.target_comp_id()) {
+        return Err(CompIdError::SenderCompId);
+    }
+    if target != Some(config.sender_comp_id()) {
+        return Err(CompIdError::TargetCompId);
+    }
+    if inbound.get_raw(DELIVER_TO_COMP_ID) == sender
+        || inbound.get_raw(ON_BEHALF_OF_COMP_ID) == target
+    {
+        return Err(CompIdError::RoutingLoop);
+    }
+    let identity = [
+        (
+            SENDER_SUB_ID,
+            config.target_sub_id(),
+            CompIdError::SenderSubId,
+        ),
+        (
+            TARGET_SUB_ID,
+            config.sender_sub_id(),
+            CompIdError::TargetSubId,
+        ),
+        (
+            SENDER_LOCATION_ID,
+            config.target_location_id(),
+            CompIdError::SenderLocationId,
+        ),
+        (
+            TARGET_LOCATION_ID,
+            config.sender_location_id(),
+            CompIdError::TargetLocationId,
+        ),
+    ];
+    for (tag, expected, err) in identity {
+        let Some(expected) = expected else {
+            continue;
+        };
+        match inbound.get_raw(tag) {
+            Some(actual) if actual == expected => {}
+            None if !config.require_sub_ids() => {}
+            _ => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::Config;
+    use crate::tagvalue::{Decoder, Encoder};
+    use crate::{Dictionary, GetConfig};
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.sender_comp_id = "HUB".to_string();
+        config.target_comp_id = "A".to_string();
+        config
+    }
+
+    #[test]
+    fn no_routing_fields() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=22|35=0|49=A|56=HUB|34=2|10=000|")
+            .unwrap();
+        assert!(RoutingFields::from_message(&msg).is_empty());
+        assert!(reply_routing_for(&msg).is_empty());
+        assert_eq!(verify_comp_ids(&config(), &msg), Ok(()));
+    }
+
+    #[test]
+    fn reversed_twice_is_identity() {
+        let routing = RoutingFields {
+            on_behalf_of_comp_id: Some(b"A".to_vec()),
+            deliver_to_comp_id: Some(b"B".to_vec()),
+            deliver_to_location_id: Some(b"UK".to_vec()),
+            ..Default::default()
+        };
+        assert_ne!(routing.reversed(), routing);
+        assert_eq!(routing.reversed().reversed(), routing);
+    }
+
+    #[test]
+    fn reply_routing_is_written_after_comp_ids() {
+        let mut decoder = decoder();
+        let inbound = decoder
+            .decode(b"8=FIX.4.4|9=28|35=0|49=A|56=HUB|34=2|128=B|10=000|")
+            .unwrap();
+        let routing = reply_routing_for(&inbound);
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.set(SENDER_COMP_ID, "HUB");
+        msg.set(TARGET_COMP_ID, "A");
+        routing.set_on(&mut msg);
+        let (msg, _) = msg.done();
+        assert!(msg.starts_with(b"8=FIX.4.4|9=00000023|35=0|49=HUB|56=A|115=B|"));
+    }
+
+    #[test]
+    fn wrong_comp_ids_are_detected() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=22|35=0|49=B|56=HUB|34=2|10=000|")
+            .unwrap();
+        assert_eq!(
+            verify_comp_ids(&config(), &msg),
+            Err(CompIdError::SenderCompId)
+        );
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=20|35=0|49=A|56=C|34=2|10=000|")
+            .unwrap();
+        assert_eq!(
+            verify_comp_ids(&config(), &msg),
+            Err(CompIdError::TargetCompId)
+        );
+    }
+
+    #[test]
+    fn sub_ids_are_checked_only_if_configured() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=35|35=0|49=A|56=HUB|34=2|50=DESK|57=X|10=000|")
+            .unwrap();
+        assert_eq!(verify_comp_ids(&config(), &msg), Ok(()));
+        let mut config = config();
+        config.target_sub_id = Some("DESK".to_string());
+        config.sender_location_id = Some("LDN".to_string());
+        assert_eq!(verify_comp_ids(&config, &msg), Ok(()));
+        config.require_sub_ids = true;
+        assert_eq!(
+            verify_comp_ids(&config, &msg),
+            Err(CompIdError::TargetLocationId)
+        );
+        config.sender_location_id = None;
+        config.sender_sub_id = Some("Y".to_string());
+        assert_eq!(
+            verify_comp_ids(&config, &msg),
+            Err(CompIdError::TargetSubId)
+        );
+        config.sender_sub_id = Some("X".to_string());
+        assert_eq!(verify_comp_ids(&config, &msg), Ok(()));
+    }
+
+    #[test]
+    fn routing_loops_are_detected() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=28|35=0|49=A|56=HUB|34=2|128=A|10=000|")
+            .unwrap();
+        assert_eq!(
+            verify_comp_ids(&config(), &msg),
+            Err(CompIdError::RoutingLoop)
+        );
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=30|35=0|49=A|56=HUB|34=2|115=HUB|10=000|")
+            .unwrap();
+        assert_eq!(
+            verify_comp_ids(&config(), &msg),
+            Err(CompIdError::RoutingLoop)
+        );
+    }
+}
@@ -1,8 +1,9 @@
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::num::NonZeroU64;
 
 /// A tracker for seq. numbers inside a FIX session.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SeqNumbers {
     pub next_inbound: u64,
     pub next_outbound: u64,
@@ -27,6 +28,12 @@ impl SeqNumbers {
         self.next_outbound
     }
 
+    /// Winds both seq. numbers back to 1, as at the start of a new FIX session
+    /// (e.g. after `ResetSeqNumFlag <141>`).
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
     pub fn incr_inbound(&mut self) {
         self.next_inbound += 1;
     }
@@ -38,8 +45,25 @@ impl SeqNumbers {
     pub fn validate_inbound(&self, inbound: u64) -> Result<(), SeqNumberError> {
         match inbound.cmp(&self.next_inbound) {
             Ordering::Equal => Ok(()),
-            Ordering::Less => Err(SeqNumberError::TooLow),
-            Ordering::Greater => Err(SeqNumberError::Recover),
+            Ordering::Less => {
+                trace_event!(
+                    error,
+                    expected = self.next_inbound,
+                    actual = inbound,
+                    "`MsgSeqNum <34>` too low"
+                );
+                Err(SeqNumberError::TooLow)
+            }
+            Ordering::Greater => {
+                trace_event!(
+                    warn,
+                    expected = self.next_inbound,
+                    actual = inbound,
+                    gap = inbound - self.next_inbound,
+                    "Sequence gap detected"
+                );
+                Err(SeqNumberError::Recover)
+            }
         }
     }
 }
@@ -56,6 +80,7 @@ impl Default for SeqNumbers {
 }
 
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum SeqNumberError {
     Recover,
     TooLow,
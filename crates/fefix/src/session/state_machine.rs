@@ -0,0 +1,2379 @@
+use super::events::EventHandler;
+use super::{
+    errs, verify_comp_ids, CompIdError, ConfigError, Configure, MessageStore, SeqNumberError,
+    SeqNumbers, SessionEvent,
+};
+use crate::dict::FieldLocation;
+use crate::tagvalue;
+use crate::{FieldMap, FieldType, SetField, TagU32};
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Range;
+use std::time::{Duration, Instant, SystemTime};
+
+const BEGIN_SEQ_NO: u32 = 7;
+const BEGIN_STRING: u32 = 8;
+const BODY_LENGTH: u32 = 9;
+const CHECK_SUM: u32 = 10;
+const END_SEQ_NO: u32 = 16;
+const MSG_SEQ_NUM: u32 = 34;
+const MSG_TYPE: u32 = 35;
+const NEW_SEQ_NO: u32 = 36;
+const POSS_DUP_FLAG: u32 = 43;
+const REF_SEQ_NUM: u32 = 45;
+const SENDER_COMP_ID: u32 = 49;
+const SENDER_SUB_ID: u32 = 50;
+const SENDING_TIME: u32 = 52;
+const TARGET_COMP_ID: u32 = 56;
+const TARGET_SUB_ID: u32 = 57;
+const TEXT: u32 = 58;
+const ENCRYPT_METHOD: u32 = 98;
+const HEART_BT_INT: u32 = 108;
+const TEST_REQ_ID: u32 = 112;
+const ORIG_SENDING_TIME: u32 = 122;
+const GAP_FILL_FLAG: u32 = 123;
+const RESET_SEQ_NUM_FLAG: u32 = 141;
+const SENDER_LOCATION_ID: u32 = 142;
+const TARGET_LOCATION_ID: u32 = 143;
+const LAST_MSG_SEQ_NUM_PROCESSED: u32 = 369;
+const REF_TAG_ID: u32 = 371;
+const REF_MSG_TYPE: u32 = 372;
+const SESSION_REJECT_REASON: u32 = 373;
+const NEXT_EXPECTED_MSG_SEQ_NUM: u32 = 789;
+const DEFAULT_APPL_VER_ID: u32 = 1137;
+
+// `SessionRejectReason <373>` for missing mandatory fields.
+const REQUIRED_TAG_MISSING: u32 = 1;
+// `SessionRejectReason <373>` for out-of-range field values.
+const VALUE_IS_INCORRECT: u32 = 5;
+// `SessionRejectReason <373>` for wrong comp. IDs.
+const COMP_ID_PROBLEM: u32 = 9;
+// `SessionRejectReason <373>` for `SendingTime <52>` beyond the clock skew.
+const SENDING_TIME_ACCURACY_PROBLEM: u32 = 10;
+
+/// An outbound FIX message which is not tied to any encoding, as returned by
+/// [`Session::next_outgoing`].
+///
+/// Fields are kept in order, and [`SetField`] appends them to the body.
+/// Repeating groups can be added and later modified with
+/// [`OutboundMessage::set_group`] and [`OutboundMessage::append_group_entry`].
+/// `BeginString <8>` (see [`Configure::begin_string`]) is left to the
+/// transport layer, as it depends on the encoding.
+///
+/// # Examples
+///
+/// Encoding an [`OutboundMessage`] in the tag-value format:
+///
+/// ```
+/// use fefix::session::OutboundMessage;
+/// use fefix::tagvalue::Encoder;
+/// use fefix::prelude::*;
+///
+/// let mut outbound = OutboundMessage::new(b"0");
+/// outbound.set(112, "foo");
+///
+/// let mut encoder = Encoder::new();
+/// encoder.config_mut().separator = b'|';
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, outbound.msg_type());
+/// msg.set_fields(outbound.fields());
+/// assert!(msg.done().0.starts_with(b"8=FIX.4.4|9=00000013|35=0|112=foo|"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct OutboundMessage {
+    msg_type: Vec<u8>,
+    fields: Vec<(TagU32, Vec<u8>)>,
+    body_start: usize,
+    groups: Vec<OutboundGroup>,
+}
+
+// A repeating group added via `OutboundMessage::set_group` or
+// `OutboundMessage::append_group_entry`.
+#[derive(Debug, Clone)]
+struct OutboundGroup {
+    num_in_group_tag: TagU32,
+    // The index of the `NumInGroup` field, relative to the start of the body.
+    start: usize,
+    // The number of fields of each entry.
+    entry_lens: Vec<usize>,
+}
+
+impl OutboundGroup {
+    // The number of fields of the whole group, `NumInGroup` included.
+    fn len(&self) -> usize {
+        1 + self.entry_lens.iter().sum::<usize>()
+    }
+}
+
+impl OutboundMessage {
+    /// Creates a new [`OutboundMessage`] of type `msg_type`, without any
+    /// fields.
+    pub fn new(msg_type: &[u8]) -> Self {
+        Self {
+            msg_type: msg_type.to_vec(),
+            fields: Vec::new(),
+            body_start: 0,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Creates a new [`OutboundMessage`] with the same `MsgType <35>` and
+    /// fields as `msg`, e.g. to modify a decoded message before forwarding
+    /// it. `BeginString <8>`, `BodyLength <9>`, and `CheckSum <10>` are left
+    /// out, as they're written by [`Encoder`](crate::tagvalue::Encoder);
+    /// all other fields are kept, in order. Header fields become
+    /// [`OutboundMessage::header_fields`], so [`Session::send`] replaces them.
+    ///
+    /// Repeating groups of the body can be replaced in place with
+    /// [`OutboundMessage::set_group`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::OutboundMessage;
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=46|35=X|49=A|56=B|34=12|268=2|279=0|279=1|58=foo|10=205|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// let msg = OutboundMessage::from_message(&decoder.decode(DATA).unwrap());
+    ///
+    /// assert_eq!(msg.msg_type(), b"X");
+    /// assert_eq!(msg.msg_seq_num(), Some(12));
+    /// assert_eq!(msg.group_entries(268).unwrap().len(), 2);
+    /// assert_eq!(msg.body_fields().last().unwrap().1, b"foo");
+    /// ```
+    pub fn from_message<T>(msg: &tagvalue::Message<T>) -> Self
+    where
+        T: AsRef<[u8]> + Clone,
+    {
+        let mut outbound = Self::new(msg.get_raw(MSG_TYPE).unwrap_or_default());
+        // The index of the last top-level field, and whether it's followed by
+        // the repeating group in `groups.last()`.
+        let mut last_top_level = 0;
+        let mut in_group = false;
+        msg.for_each_with_context(|context, tag, value| {
+            let body_index = outbound.fields.len().saturating_sub(outbound.body_start);
+            match (context.location, context.groups.first()) {
+                (FieldLocation::Header, _) => {
+                    if ![BEGIN_STRING, BODY_LENGTH, MSG_TYPE].contains(&tag.get()) {
+                        outbound.fields.push((tag, value.to_vec()));
+                        outbound.body_start = outbound.fields.len();
+                    }
+                    return;
+                }
+                (FieldLocation::Trailer, _) if tag.get() == CHECK_SUM => return,
+                (FieldLocation::Body, Some(entry)) => {
+                    if !in_group {
+                        outbound.groups.push(OutboundGroup {
+                            num_in_group_tag: entry.num_in_group_tag,
+                            start: last_top_level,
+                            entry_lens: Vec::new(),
+                        });
+                        in_group = true;
+                    }
+                    if let Some(group) = outbound.groups.last_mut() {
+                        if group.entry_lens.len() <= entry.entry_index {
+                            group.entry_lens.push(0);
+                        }
+                        group.entry_lens[entry.entry_index] += 1;
+                    }
+                }
+                _ => {
+                    last_top_level = body_index;
+                    in_group = false;
+                }
+            }
+            outbound.fields.push((tag, value.to_vec()));
+        });
+        outbound
+    }
+
+    /// Returns the `MsgType <35>` of `self`.
+    pub fn msg_type(&self) -> &[u8] {
+        &self.msg_type
+    }
+
+    /// Returns the `MsgSeqNum <34>` of `self`, as assigned by [`Session`].
+    pub fn msg_seq_num(&self) -> Option<u64> {
+        self.header_fields()
+            .find(|(tag, _)| tag.get() == MSG_SEQ_NUM)
+            .and_then(|(_, value)| u64::deserialize(value).ok())
+    }
+
+    /// Returns an [`Iterator`] over all fields in `self`, in order. This
+    /// doesn't include `MsgType <35>`.
+    pub fn fields(&self) -> impl Iterator<Item = (TagU32, &[u8])> {
+        self.fields
+            .iter()
+            .map(|(tag, value)| (*tag, value.as_slice()))
+    }
+
+    /// Like [`OutboundMessage::fields`], but only for the header fields
+    /// written by [`Session`], e.g. `SenderCompID <49>`, `TargetCompID <56>`,
+    /// `MsgSeqNum <34>`, and `SendingTime <52>`.
+    pub fn header_fields(&self) -> impl Iterator<Item = (TagU32, &[u8])> {
+        self.fields().take(self.body_start)
+    }
+
+    /// Like [`OutboundMessage::fields`], but only for the fields after
+    /// [`OutboundMessage::header_fields`].
+    pub fn body_fields(&self) -> impl Iterator<Item = (TagU32, &[u8])> {
+        self.fields().skip(self.body_start)
+    }
+
+    /// Sets the repeating group with `num_in_group_tag` (e.g.
+    /// `NoMDEntries <268>`) to `entries`, in order, with a consistent
+    /// `NumInGroup` field. If `self` already has such a group, it's replaced
+    /// where it is; otherwise, it's appended to the body.
+    ///
+    /// [`OutboundMessage`] doesn't know the layout of repeating groups, so
+    /// only groups added via [`OutboundMessage::set_group`] and
+    /// [`OutboundMessage::append_group_entry`] or decoded by
+    /// [`OutboundMessage::from_message`] can be replaced, as well as
+    /// top-level `NumInGroup` fields of empty groups.
+    ///
+    /// # Examples
+    ///
+    /// Filtering the entries of a repeating group before forwarding a
+    /// message:
+    ///
+    /// ```
+    /// use fefix::session::{GroupEntry, OutboundMessage};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut msg = OutboundMessage::new(b"W");
+    /// msg.set(55, "EUR/USD");
+    /// for (entry_type, px) in [("0", "1.5"), ("1", "1.75"), ("2", "1.6")] {
+    ///     let mut entry = GroupEntry::new();
+    ///     entry.set(269, entry_type);
+    ///     entry.set(270, px);
+    ///     msg.append_group_entry(268, entry);
+    /// }
+    /// msg.set(58, "foo");
+    ///
+    /// let entries = msg.group_entries(268).unwrap();
+    /// let bids_and_offers = entries
+    ///     .into_iter()
+    ///     .filter(|entry| entry.fields().next().unwrap().1 != b"2")
+    ///     .collect();
+    /// msg.set_group(268, bids_and_offers);
+    ///
+    /// let fields: Vec<_> = msg.fields().map(|(tag, value)| (tag.get(), value)).collect();
+    /// assert_eq!(
+    ///     fields,
+    ///     vec![
+    ///         (55, b"EUR/USD" as &[u8]),
+    ///         (268, b"2"),
+    ///         (269, b"0"),
+    ///         (270, b"1.5"),
+    ///         (269, b"1"),
+    ///         (270, b"1.75"),
+    ///         (58, b"foo"),
+    ///     ]
+    /// );
+    /// ```
+    pub fn set_group(&mut self, num_in_group_tag: u32, entries: Vec<GroupEntry>) {
+        let tag = match TagU32::new(num_in_group_tag) {
+            Some(tag) => tag,
+            None => return,
+        };
+        let entry_lens = entries.iter().map(|entry| entry.fields.len()).collect();
+        let mut fields = vec![(tag, entries.len().to_bytes())];
+        fields.extend(entries.into_iter().flat_map(|entry| entry.fields));
+        match self
+            .group_index(tag)
+            .or_else(|| self.empty_group_index(tag))
+        {
+            Some(i) => {
+                let start = self.body_start + self.groups[i].start;
+                let old_len = self.groups[i].len();
+                let new_len = fields.len();
+                self.fields.splice(start..start + old_len, fields);
+                self.groups[i].entry_lens = entry_lens;
+                self.shift_groups_after(i, old_len, new_len);
+            }
+            None => {
+                self.groups.push(OutboundGroup {
+                    num_in_group_tag: tag,
+                    start: self.fields.len() - self.body_start,
+                    entry_lens,
+                });
+                self.fields.extend(fields);
+            }
+        }
+    }
+
+    /// Appends `entry` to the repeating group with `num_in_group_tag` and
+    /// increments its `NumInGroup` field. The group is created at the end of
+    /// the body if `self` doesn't have it yet; see
+    /// [`OutboundMessage::set_group`].
+    pub fn append_group_entry(&mut self, num_in_group_tag: u32, entry: GroupEntry) {
+        let i = match TagU32::new(num_in_group_tag).and_then(|tag| self.group_index(tag)) {
+            Some(i) => i,
+            None => return self.set_group(num_in_group_tag, vec![entry]),
+        };
+        let group = &mut self.groups[i];
+        let start = self.body_start + group.start;
+        let end = start + group.len();
+        let entry_len = entry.fields.len();
+        group.entry_lens.push(entry_len);
+        self.fields[start].1 = group.entry_lens.len().to_bytes();
+        self.fields.splice(end..end, entry.fields);
+        self.shift_groups_after(i, 0, entry_len);
+    }
+
+    /// Returns a copy of all entries of the repeating group with
+    /// `num_in_group_tag`, if added via [`OutboundMessage::set_group`] or
+    /// [`OutboundMessage::append_group_entry`].
+    pub fn group_entries(&self, num_in_group_tag: u32) -> Option<Vec<GroupEntry>> {
+        let group = &self.groups[self.group_index(TagU32::new(num_in_group_tag)?)?];
+        let mut i = self.body_start + group.start + 1;
+        let entries = group
+            .entry_lens
+            .iter()
+            .map(|len| {
+                i += len;
+                GroupEntry {
+                    fields: self.fields[i - len..i].to_vec(),
+                }
+            })
+            .collect();
+        Some(entries)
+    }
+
+    fn group_index(&self, num_in_group_tag: TagU32) -> Option<usize> {
+        self.groups
+            .iter()
+            .position(|group| group.num_in_group_tag == num_in_group_tag)
+    }
+
+    // Turns a top-level body field with `num_in_group_tag`, e.g. the
+    // `NumInGroup` field of an empty group, into a group without entries.
+    fn empty_group_index(&mut self, num_in_group_tag: TagU32) -> Option<usize> {
+        let mut i = 0;
+        while let Some((tag, _)) = self.fields.get(self.body_start + i) {
+            if let Some(group) = self.groups.iter().find(|group| group.start == i) {
+                i += group.len();
+            } else if *tag == num_in_group_tag {
+                self.groups.push(OutboundGroup {
+                    num_in_group_tag,
+                    start: i,
+                    entry_lens: Vec::new(),
+                });
+                return Some(self.groups.len() - 1);
+            } else {
+                i += 1;
+            }
+        }
+        None
+    }
+
+    // Group `i` went from `old_len` to `new_len` fields, so all groups after
+    // it moved.
+    fn shift_groups_after(&mut self, i: usize, old_len: usize, new_len: usize) {
+        let start = self.groups[i].start;
+        for group in self.groups.iter_mut().filter(|group| group.start > start) {
+            group.start = group.start - old_len + new_len;
+        }
+    }
+}
+
+// Groups are mere bookkeeping and don't affect equality.
+impl PartialEq for OutboundMessage {
+    fn eq(&self, other: &Self) -> bool {
+        self.msg_type == other.msg_type
+            && self.fields == other.fields
+            && self.body_start == other.body_start
+    }
+}
+
+impl Eq for OutboundMessage {}
+
+impl SetField<u32> for OutboundMessage {
+    fn set_with<'s, V>(&'s mut self, tag: u32, value: V, settings: V::SerializeSettings)
+    where
+        V: FieldType<'s>,
+    {
+        push_field(&mut self.fields, tag, value, settings);
+    }
+}
+
+/// An entry of a repeating group within an [`OutboundMessage`]; see
+/// [`OutboundMessage::set_group`].
+///
+/// Fields are kept in order and [`SetField`] appends them, so the delimiter
+/// field of the repeating group must be set first. Nested repeating groups
+/// are written field by field, starting with their `NumInGroup` field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GroupEntry {
+    fields: Vec<(TagU32, Vec<u8>)>,
+}
+
+impl GroupEntry {
+    /// Creates a new [`GroupEntry`] without any fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an [`Iterator`] over all fields in `self`, in order, starting
+    /// with the delimiter field.
+    pub fn fields(&self) -> impl Iterator<Item = (TagU32, &[u8])> {
+        self.fields
+            .iter()
+            .map(|(tag, value)| (*tag, value.as_slice()))
+    }
+}
+
+impl SetField<u32> for GroupEntry {
+    fn set_with<'s, V>(&'s mut self, tag: u32, value: V, settings: V::SerializeSettings)
+    where
+        V: FieldType<'s>,
+    {
+        push_field(&mut self.fields, tag, value, settings);
+    }
+}
+
+fn push_field<'s, V>(
+    fields: &mut Vec<(TagU32, Vec<u8>)>,
+    tag: u32,
+    value: V,
+    settings: V::SerializeSettings,
+) where
+    V: FieldType<'s>,
+{
+    // Tag 0 is not a valid FIX tag, so there's nothing to encode.
+    if let Some(tag) = TagU32::new(tag) {
+        let mut buffer = Vec::new();
+        value.serialize_with(&mut buffer, settings);
+        fields.push((tag, buffer));
+    }
+}
+
+/// The state of a [`Session`] with regards to `Logon <A>` and `Logout <5>`
+/// messages.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SessionStatus {
+    /// No `Logon <A>` message was sent nor received yet.
+    AwaitingLogon,
+    /// A `Logon <A>` message was sent, and the counterparty's is yet to be
+    /// received.
+    LogonSent,
+    /// Both counterparties are logged on.
+    Active,
+    /// A `Logout <5>` message was sent, and the counterparty's is yet to be
+    /// received.
+    LogoutSent,
+    /// The FIX session is over. The transport can be closed as soon as all
+    /// outgoing messages are sent.
+    Terminated,
+}
+
+/// How a [`Session`] reached [`SessionStatus::Terminated`], as returned by
+/// [`Session::termination`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// Both counterparties sent a `Logout <5>` message.
+    Clean,
+    /// The counterparty didn't confirm our `Logout <5>` on time (see
+    /// [`Session::logout_timed_out`]), or it violated the FIX session
+    /// protocol.
+    Forced,
+}
+
+/// The outcome of [`Session::process_incoming`] for a valid inbound message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming {
+    /// An application message, which should be processed by the application.
+    /// This includes `BusinessMessageReject <j>` (see
+    /// [`BusinessReject`](super::BusinessReject)), whereas session-level
+    /// `Reject <3>` messages are [`Incoming::Admin`].
+    App,
+    /// An administrative message, which was fully processed by the
+    /// [`Session`].
+    Admin,
+    /// A `ResendRequest <2>` for the outbound messages within the range, e.g.
+    /// to be answered with [`resend_messages`](super::resend_messages). The
+    /// range only covers messages which were actually sent: an `EndSeqNo <16>`
+    /// beyond them is capped, and a `BeginSeqNo <7>` beyond them is rejected
+    /// with [`SessionError::MissingField`].
+    ///
+    /// With [`Configure::next_expected_msg_seq_num`], this is also the outcome
+    /// of a `Logon <A>` whose `NextExpectedMsgSeqNum <789>` shows that the
+    /// counterparty is missing some outbound messages. The `Logon <A>` itself
+    /// is processed as usual.
+    ResendRequest(Range<u64>),
+    /// A message with `PossDupFlag <43>` which was already received. It should
+    /// be ignored.
+    PossDup,
+    /// A message after a sequence gap. A `ResendRequest <2>` is sent and the
+    /// message should be ignored, as the counterparty will send it again.
+    ///
+    /// No `ResendRequest <2>` is sent for a `Logon <A>` with
+    /// `NextExpectedMsgSeqNum <789>` (see
+    /// [`Configure::next_expected_msg_seq_num`]), as ours tells the
+    /// counterparty what to send again.
+    SeqGap {
+        /// The expected `MsgSeqNum <34>`.
+        expected: u64,
+        /// The actual `MsgSeqNum <34>` of the message.
+        actual: u64,
+    },
+    /// A `Logout <5>` message. The [`Session`] is now
+    /// [`SessionStatus::Terminated`].
+    Logout,
+}
+
+/// The type returned by [`Session::process_incoming`] for inbound messages
+/// that violate the FIX session protocol.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum SessionError {
+    /// A mandatory field is missing or it has an invalid value. A `Reject <3>`
+    /// message is sent for a missing `SendingTime <52>` (see
+    /// [`Configure::require_sending_time`]) and for a `ResendRequest <2>`
+    /// with an out-of-range `BeginSeqNo <7>`.
+    #[error("Missing or invalid field with tag {0}.")]
+    MissingField(u32),
+    /// Wrong comp. IDs; see [`verify_comp_ids`]. A `Reject <3>` and a
+    /// `Logout <5>` message are sent.
+    #[error(transparent)]
+    CompId(#[from] CompIdError),
+    /// `MsgSeqNum <34>` is lower than expected, and it's not a possible
+    /// duplicate. A `Logout <5>` message is sent.
+    #[error("`MsgSeqNum <34>` is {actual}, but {expected} was expected.")]
+    MsgSeqNumTooLow {
+        /// The expected `MsgSeqNum <34>`.
+        expected: u64,
+        /// The actual `MsgSeqNum <34>` of the message.
+        actual: u64,
+    },
+    /// The first message of the FIX session is not a `Logon <A>`.
+    #[error("The first message is not a `Logon <A>`.")]
+    NotLoggedOn,
+    /// A `Logon <A>` was received before ours was sent, but we're the
+    /// initiator; see [`Configure::is_initiator`].
+    #[error("Unexpected `Logon <A>` before ours was sent.")]
+    UnexpectedLogon,
+    /// `SendingTime <52>` is too far from the local clock; see
+    /// [`Configure::max_clock_skew`]. A `Reject <3>` and a `Logout <5>`
+    /// message are sent.
+    #[error("`SendingTime <52>` is beyond the maximum clock skew.")]
+    SendingTimeAccuracy,
+    /// `NextExpectedMsgSeqNum <789>` is higher than the next outbound
+    /// `MsgSeqNum <34>`, i.e. the counterparty received messages which we
+    /// don't know of. A `Logout <5>` message is sent. See
+    /// [`Configure::next_expected_msg_seq_num`].
+    #[error("`NextExpectedMsgSeqNum <789>` is {actual}, but the next `MsgSeqNum <34>` is {next_outbound}.")]
+    NextExpectedMsgSeqNumTooHigh {
+        /// The next outbound `MsgSeqNum <34>`.
+        next_outbound: u64,
+        /// The value of `NextExpectedMsgSeqNum <789>`.
+        actual: u64,
+    },
+}
+
+/// A FIX session which is decoupled from the encoding of FIX messages and
+/// from the transport layer.
+///
+/// [`Session`] reads inbound messages via [`FieldMap`], so that both
+/// [`tagvalue::Message`](crate::tagvalue::Message) and
+/// [`json::Message`](crate::json::Message) (e.g. for FIX-over-WebSocket) can be
+/// processed by [`Session::process_incoming`]. It takes care of sequence
+/// numbers, comp. IDs, `Logon <A>`, `Heartbeat <0>`, `TestRequest <1>`,
+/// `ResendRequest <2>`, `SequenceReset <4>`, and `Logout <5>` messages. Outbound
+/// messages are returned by [`Session::next_outgoing`] as
+/// [`OutboundMessage`]s, which the caller encodes and sends.
+///
+/// [`Session`] has no notion of time: timers, e.g.
+/// [`LlEventLoop`](super::LlEventLoop), should call [`Session::heartbeat`] and
+/// [`Session::test_request`]. The system clock is still read directly, with
+/// no way to inject another one, for:
+///
+/// - the `SendingTime <52>` of outbound messages;
+/// - the [`Configure::max_clock_skew`] check of inbound messages;
+/// - the time-based [`Configure::persistence_flush_policy`] of
+///   [`Session::store`];
+/// - [`Session::receive_time`], when inbound messages lack
+///   `SendingTime <52>`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::session::{Config, Session, SessionStatus};
+///
+/// let mut session = Session::new(Config::default());
+/// session.logon();
+/// let logon = session.next_outgoing().unwrap();
+/// assert_eq!(logon.msg_type(), b"A");
+/// assert_eq!(logon.msg_seq_num(), Some(1));
+/// assert_eq!(session.status(), SessionStatus::LogonSent);
+/// assert_eq!(session.next_outgoing(), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Session<C> {
+    config: C,
+    seq_numbers: SeqNumbers,
+    status: SessionStatus,
+    outbox: VecDeque<OutboundMessage>,
+    // The `MsgSeqNum <34>` which triggered the outstanding `ResendRequest <2>`,
+    // if any. There's no need for more until the gap is filled.
+    resend_requested_until: Option<u64>,
+    termination: Option<Termination>,
+    receive_time: Option<SystemTime>,
+    // The number of messages given to `Session::store` since the last flush.
+    unflushed: u64,
+    last_flush: Instant,
+    event_handler: EventHandler,
+}
+
+impl<C> Session<C>
+where
+    C: Configure,
+{
+    /// Creates a new [`Session`] with `config`, which starts with both
+    /// sequence numbers set to 1.
+    pub fn new(config: C) -> Self {
+        Self {
+            config,
+            seq_numbers: SeqNumbers::default(),
+            status: SessionStatus::AwaitingLogon,
+            outbox: VecDeque::new(),
+            resend_requested_until: None,
+            termination: None,
+            receive_time: None,
+            unflushed: 0,
+            last_flush: Instant::now(),
+            event_handler: EventHandler::default(),
+        }
+    }
+
+    /// Like [`Session::new`], but refuses `config` if it's contradictory; see
+    /// [`Configure::validate`].
+    pub fn try_new(config: C) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(Self::new(config))
+    }
+
+    /// Returns an immutable reference to the configuration of `self`.
+    pub fn config(&self) -> &C {
+        &self.config
+    }
+
+    /// Returns the current [`SessionStatus`] of `self`.
+    pub fn status(&self) -> SessionStatus {
+        self.status
+    }
+
+    /// Returns how `self` was terminated, if it's
+    /// [`SessionStatus::Terminated`].
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    /// Returns the local time at which the last inbound message was
+    /// processed, if it had no `SendingTime <52>`. This only happens when
+    /// [`Configure::require_sending_time`] is turned off; the receive time can
+    /// then stand in for the missing `SendingTime <52>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::{Config, Session};
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut config = Config::default();
+    /// config.sender_comp_id = "A".to_string();
+    /// config.target_comp_id = "B".to_string();
+    /// config.require_sending_time = false;
+    /// let mut session = Session::new(config);
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let logon = decoder.decode(b"8=FIX.4.4|9=32|35=A|49=B|56=A|34=1|98=0|108=30|10=000|").unwrap();
+    /// assert!(session.process_incoming(&logon).is_ok());
+    /// assert!(session.receive_time().is_some());
+    /// ```
+    pub fn receive_time(&self) -> Option<SystemTime> {
+        self.receive_time
+    }
+
+    /// Returns the sequence numbers of `self`.
+    pub fn seq_numbers(&self) -> SeqNumbers {
+        self.seq_numbers
+    }
+
+    /// Returns a mutable reference to the sequence numbers of `self`, e.g. to
+    /// restore them from persistent storage.
+    pub fn seq_numbers_mut(&mut self) -> &mut SeqNumbers {
+        &mut self.seq_numbers
+    }
+
+    /// Calls `handler` with every [`SessionEvent`] of `self` from now on,
+    /// replacing any previous handler. `handler` is called synchronously at
+    /// the point of transition, so events are always in order; e.g. the
+    /// events of an inbound message all come before
+    /// [`Session::process_incoming`] returns. Clones of `self` start without
+    /// an event handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::{Config, Session, SessionEvent, SessionStatus};
+    /// use std::sync::mpsc;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut session = Session::new(Config::default());
+    /// session.set_event_handler(move |event| sender.send(event).unwrap());
+    /// session.logon();
+    /// assert_eq!(
+    ///     receiver.try_iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         SessionEvent::LogonSent {
+    ///             msg_seq_num: 1,
+    ///             reset_seq_num: false,
+    ///         },
+    ///         SessionEvent::StatusChanged {
+    ///             from: SessionStatus::AwaitingLogon,
+    ///             to: SessionStatus::LogonSent,
+    ///         },
+    ///     ]
+    /// );
+    /// ```
+    pub fn set_event_handler<F>(&mut self, handler: F)
+    where
+        F: FnMut(SessionEvent) + Send + 'static,
+    {
+        self.event_handler.0 = Some(Box::new(handler));
+    }
+
+    /// Brings `self` back to [`SessionStatus::AwaitingLogon`], e.g. to reuse
+    /// it after a disconnect. The outbox (i.e. all messages not yet returned by
+    /// [`Session::next_outgoing`]), the outstanding `ResendRequest <2>`, the
+    /// [`Termination`], and the [`Session::receive_time`] are all cleared, and
+    /// so is the count of messages stored since the last flush, as if
+    /// [`Session::flush_store`] had just been called: call it first if
+    /// needed.
+    ///
+    /// Both sequence numbers are reset if [`Configure::reset_on_logon`] is
+    /// turned on. Otherwise they're kept, so that the next logon continues the
+    /// same FIX session. The configuration and the event handler (see
+    /// [`Session::set_event_handler`]) are kept as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::{Config, Session, SessionStatus};
+    ///
+    /// let mut session = Session::new(Config::default());
+    /// session.logon();
+    /// session.logout(None);
+    /// session.logout_timed_out();
+    /// assert_eq!(session.status(), SessionStatus::Terminated);
+    ///
+    /// session.reset();
+    /// assert_eq!(session.status(), SessionStatus::AwaitingLogon);
+    /// assert_eq!(session.termination(), None);
+    /// assert_eq!(session.next_outgoing(), None);
+    /// assert_eq!(session.seq_numbers().next_outbound(), 3);
+    /// ```
+    pub fn reset(&mut self) {
+        if self.config.reset_on_logon() {
+            self.seq_numbers.reset();
+        }
+        self.outbox.clear();
+        self.resend_requested_until = None;
+        self.termination = None;
+        self.receive_time = None;
+        self.unflushed = 0;
+        self.last_flush = Instant::now();
+        self.set_status(SessionStatus::AwaitingLogon);
+    }
+
+    /// Sends a `Logon <A>` message to the counterparty, i.e. as the
+    /// initiator. Both sequence numbers are reset first if
+    /// [`Configure::reset_on_logon`] is turned on.
+    pub fn logon(&mut self) {
+        let reset_seq_num = self.config.reset_on_logon();
+        if reset_seq_num {
+            self.seq_numbers.reset();
+        }
+        self.queue_logon(reset_seq_num);
+        self.set_status(SessionStatus::LogonSent);
+    }
+
+    /// Sends a `Logout <5>` message with an optional `Text <58>` to the
+    /// counterparty. Messages that were queued before, e.g. with
+    /// [`Session::send`], are still returned by [`Session::next_outgoing`]
+    /// first, and inbound messages are processed until the counterparty
+    /// confirms with its own `Logout <5>`.
+    ///
+    /// The caller should wait at most [`Configure::logout_timeout`] for the
+    /// confirmation, and then call [`Session::logout_timed_out`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::{Config, OutboundMessage, Session, SessionStatus, Termination};
+    ///
+    /// let mut session = Session::new(Config::default());
+    /// session.logon();
+    /// session.send(OutboundMessage::new(b"D"));
+    /// session.logout(None);
+    /// assert_eq!(session.next_outgoing().unwrap().msg_type(), b"A");
+    /// assert_eq!(session.next_outgoing().unwrap().msg_type(), b"D");
+    /// assert_eq!(session.next_outgoing().unwrap().msg_type(), b"5");
+    /// assert_eq!(session.status(), SessionStatus::LogoutSent);
+    ///
+    /// // No confirmation within `Configure::logout_timeout`.
+    /// session.logout_timed_out();
+    /// assert_eq!(session.status(), SessionStatus::Terminated);
+    /// assert_eq!(session.termination(), Some(Termination::Forced));
+    /// ```
+    pub fn logout(&mut self, text: Option<&str>) {
+        self.queue_logout(text);
+        self.set_status(SessionStatus::LogoutSent);
+    }
+
+    /// Forcibly terminates `self` if it's still waiting for the counterparty's
+    /// `Logout <5>` message, i.e. once [`Configure::logout_timeout`] has
+    /// elapsed after [`Session::logout`]. It has no effect otherwise.
+    pub fn logout_timed_out(&mut self) {
+        if self.status == SessionStatus::LogoutSent {
+            self.set_terminated(Termination::Forced);
+        }
+    }
+
+    /// Sends a `Heartbeat <0>` message to the counterparty.
+    pub fn heartbeat(&mut self) {
+        let msg_seq_num = self.queue(OutboundMessage::new(b"0"));
+        self.emit(SessionEvent::HeartbeatSent { msg_seq_num });
+    }
+
+    /// Sends a `TestRequest <1>` message with `test_req_id` to the
+    /// counterparty.
+    pub fn test_request(&mut self, test_req_id: &[u8]) {
+        let mut test_request = OutboundMessage::new(b"1");
+        test_request.set(TEST_REQ_ID, test_req_id);
+        let msg_seq_num = self.queue(test_request);
+        self.emit(SessionEvent::TestRequestSent { msg_seq_num });
+    }
+
+    /// Sends `msg`, e.g. an application message. `SenderCompID <49>`,
+    /// `TargetCompID <56>`, `MsgSeqNum <34>`, and `SendingTime <52>` (with
+    /// [`Configure::sending_time_precision`]) are written by `self`, before
+    /// all [`OutboundMessage::body_fields`] of `msg`; its
+    /// [`OutboundMessage::header_fields`], if any, are replaced.
+    pub fn send(&mut self, msg: OutboundMessage) {
+        self.queue(msg);
+    }
+
+    /// Sends `msg` again in response to a `ResendRequest <2>`, with its
+    /// original `msg_seq_num` and `PossDupFlag <43>` set to `Y`.
+    /// `OrigSendingTime <122>` is set to the `SendingTime <52>` of `msg` if
+    /// it was returned by [`Session::next_outgoing`], or to the new
+    /// `SendingTime <52>` otherwise. Unlike
+    /// [`Session::send`], the outbound sequence number is left untouched, so
+    /// that the next new message still gets the next `MsgSeqNum <34>`.
+    ///
+    /// Application messages retrieved with
+    /// [`resend_messages`](super::resend_messages) already carry their
+    /// original `MsgSeqNum <34>` and can be sent as they are instead.
+    ///
+    /// `msg` is ignored if `msg_seq_num` was never sent, i.e. if it's zero
+    /// or not lower than [`SeqNumbers::next_outbound`].
+    pub fn replay(&mut self, msg_seq_num: u64, msg: OutboundMessage) {
+        if !self.was_sent(msg_seq_num) {
+            trace_event!(
+                warn,
+                msg_seq_num,
+                "Not replaying a message which was never sent"
+            );
+            return;
+        }
+        let stamped = self.stamp(msg, msg_seq_num, true);
+        self.outbox.push_back(stamped);
+    }
+
+    /// Skips the outbound messages within `range` in response to a
+    /// `ResendRequest <2>`, e.g. administrative messages (see
+    /// [`Resend::GapFill`](super::Resend::GapFill)). A `SequenceReset <4>`
+    /// message with `GapFillFlag <123>` is sent with `MsgSeqNum <34>` set to
+    /// `range.start` and `NewSeqNo <36>` set to `range.end`; see
+    /// [`Session::replay`], which also ignores its out-of-range sequence
+    /// numbers.
+    pub fn gap_fill(&mut self, range: Range<u64>) {
+        if !self.was_sent(range.start) {
+            trace_event!(
+                warn,
+                msg_seq_num = range.start,
+                "Not gap-filling messages which were never sent"
+            );
+            return;
+        }
+        let mut gap_fill = OutboundMessage::new(b"4");
+        gap_fill.set(GAP_FILL_FLAG, true);
+        gap_fill.set(NEW_SEQ_NO, range.end);
+        self.replay(range.start, gap_fill);
+        self.emit(SessionEvent::SequenceResetSent {
+            msg_seq_num: range.start,
+            new_seq_no: range.end,
+        });
+    }
+
+    /// Returns the next outbound message to send, if any. Outbound messages
+    /// are returned in the same order they were queued, which is the order of
+    /// `MsgSeqNum <34>` except for replayed messages.
+    pub fn next_outgoing(&mut self) -> Option<OutboundMessage> {
+        self.outbox.pop_front()
+    }
+
+    /// Stores `data`, i.e. the encoded form of the outbound message with
+    /// `msg_seq_num` just returned by [`Session::next_outgoing`], in `store`
+    /// along with the current sequence numbers of `self`. `store` is then
+    /// flushed as per [`Configure::persistence_flush_policy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::session::{Config, FlushPolicy, MessageStore, Session};
+    /// use std::collections::BTreeMap;
+    /// use std::num::NonZeroU64;
+    ///
+    /// #[derive(Default)]
+    /// struct Store {
+    ///     messages: BTreeMap<u64, Vec<u8>>,
+    ///     flushes: usize,
+    /// }
+    ///
+    /// impl MessageStore for Store {
+    ///     fn insert(&mut self, msg_seq_num: u64, message: &[u8]) {
+    ///         self.messages.insert(msg_seq_num, message.to_vec());
+    ///     }
+    ///
+    ///     fn get(&self, msg_seq_num: u64) -> Option<&[u8]> {
+    ///         self.messages.get(&msg_seq_num).map(Vec::as_slice)
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> std::io::Result<()> {
+    ///         self.flushes += 1;
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut config = Config::default();
+    /// config.persistence_flush_policy = FlushPolicy::EveryN(NonZeroU64::new(2).unwrap());
+    /// let mut session = Session::new(config);
+    /// let mut store = Store::default();
+    /// session.logon();
+    /// for _ in 0..3 {
+    ///     session.heartbeat();
+    /// }
+    /// while let Some(msg) = session.next_outgoing() {
+    ///     let data = b"..."; // Encoded `msg`.
+    ///     session.store(&mut store, msg.msg_seq_num().unwrap(), data).unwrap();
+    /// }
+    /// assert_eq!(store.messages.len(), 4);
+    /// assert_eq!(store.flushes, 2);
+    /// ```
+    pub fn store<S>(&mut self, store: &mut S, msg_seq_num: u64, data: &[u8]) -> io::Result<()>
+    where
+        S: MessageStore + ?Sized,
+    {
+        store.insert(msg_seq_num, data);
+        store.set_seq_numbers(self.seq_numbers);
+        self.unflushed += 1;
+        let since_last_flush = self.last_flush.elapsed();
+        if self
+            .config
+            .persistence_flush_policy()
+            .is_due(self.unflushed, since_last_flush)
+        {
+            self.flush_store(store)?;
+        }
+        Ok(())
+    }
+
+    /// Stores the current sequence numbers of `self` in `store`, e.g. after
+    /// processing inbound messages, and flushes it regardless of
+    /// [`Configure::persistence_flush_policy`]. Timers should call this for
+    /// [`FlushPolicy::OnInterval`](super::FlushPolicy::OnInterval), and so
+    /// should graceful shutdowns.
+    pub fn flush_store<S>(&mut self, store: &mut S) -> io::Result<()>
+    where
+        S: MessageStore + ?Sized,
+    {
+        store.set_seq_numbers(self.seq_numbers);
+        store.flush()?;
+        self.unflushed = 0;
+        self.last_flush = Instant::now();
+        Ok(())
+    }
+
+    /// Processes the inbound message `msg`, regardless of its encoding. Any
+    /// reply, e.g. a `Heartbeat <0>` in response to a `TestRequest <1>`, is
+    /// made available via [`Session::next_outgoing`].
+    pub fn process_incoming<M>(&mut self, msg: &M) -> Result<Incoming, SessionError>
+    where
+        M: FieldMap<u32>,
+    {
+        let msg_type = msg
+            .get_raw(MSG_TYPE)
+            .ok_or(SessionError::MissingField(MSG_TYPE))?;
+        let msg_seq_num = match msg.get::<u64>(MSG_SEQ_NUM) {
+            Ok(msg_seq_num) => msg_seq_num,
+            Err(_) => {
+                self.terminate(Some("Missing or invalid MsgSeqNum <34>"));
+                return Err(SessionError::MissingField(MSG_SEQ_NUM));
+            }
+        };
+        if let Err(err) = verify_comp_ids(&self.config, msg) {
+            let mut reject = OutboundMessage::new(b"3");
+            reject.set(REF_SEQ_NUM, msg_seq_num);
+            reject.set(SESSION_REJECT_REASON, COMP_ID_PROBLEM);
+            self.queue_reject(reject, msg_seq_num, COMP_ID_PROBLEM);
+            self.terminate(Some(&err.to_string()));
+            return Err(err.into());
+        }
+        let is_logon = msg_type == b"A";
+        let is_logged_on = !matches!(
+            self.status,
+            SessionStatus::AwaitingLogon | SessionStatus::LogonSent
+        );
+        if !is_logon && !is_logged_on {
+            self.set_terminated(Termination::Forced);
+            return Err(SessionError::NotLoggedOn);
+        }
+        let is_accepting = is_logon && self.status == SessionStatus::AwaitingLogon;
+        if is_accepting && self.config.is_initiator() {
+            self.set_terminated(Termination::Forced);
+            return Err(SessionError::UnexpectedLogon);
+        }
+        let reset_seq_num = is_logon && matches!(msg.get(RESET_SEQ_NUM_FLAG), Ok(true));
+        if reset_seq_num || (is_accepting && self.config.reset_on_logon()) {
+            if self.status == SessionStatus::AwaitingLogon {
+                self.seq_numbers.reset();
+            } else {
+                self.seq_numbers.next_inbound = 1;
+            }
+            self.resend_requested_until = None;
+        }
+        // The counterparty takes part in `NextExpectedMsgSeqNum <789>` recovery
+        // only if its `Logon <A>` says so.
+        let next_expected = match is_logon && self.config.next_expected_msg_seq_num() {
+            true => msg.get::<u64>(NEXT_EXPECTED_MSG_SEQ_NUM).ok(),
+            false => None,
+        };
+        // `SequenceReset <4>` in Reset mode ignores `MsgSeqNum <34>`.
+        if msg_type == b"4" && !matches!(msg.get(GAP_FILL_FLAG), Ok(true)) {
+            self.skip_to(msg, false)?;
+            return Ok(Incoming::Admin);
+        }
+        match self.seq_numbers.validate_inbound(msg_seq_num) {
+            Ok(()) => {
+                self.seq_numbers.incr_inbound();
+                if matches!(self.resend_requested_until, Some(end) if msg_seq_num >= end) {
+                    self.resend_requested_until = None;
+                    self.emit(SessionEvent::GapFilled { msg_seq_num });
+                }
+            }
+            Err(SeqNumberError::TooLow) if matches!(msg.get(POSS_DUP_FLAG), Ok(true)) => {
+                return Ok(Incoming::PossDup);
+            }
+            Err(SeqNumberError::TooLow) => {
+                let expected = self.seq_numbers.next_inbound();
+                self.terminate(Some(&errs::msg_seq_num(expected)));
+                return Err(SessionError::MsgSeqNumTooLow {
+                    expected,
+                    actual: msg_seq_num,
+                });
+            }
+            Err(_) => {
+                let expected = self.seq_numbers.next_inbound();
+                self.emit(SessionEvent::SeqGap {
+                    expected,
+                    actual: msg_seq_num,
+                });
+                if let Some(next_expected) = next_expected {
+                    let missing = self.missing_outbound(next_expected)?;
+                    self.on_logon(msg_seq_num, reset_seq_num);
+                    // The counterparty sends all messages from `expected`
+                    // again, as per our `NextExpectedMsgSeqNum <789>`.
+                    self.resend_requested_until = Some(msg_seq_num);
+                    if !missing.is_empty() {
+                        self.emit(SessionEvent::ResendRequestReceived {
+                            msg_seq_num,
+                            range: missing.clone(),
+                        });
+                        return Ok(Incoming::ResendRequest(missing));
+                    }
+                    return Ok(Incoming::SeqGap {
+                        expected,
+                        actual: msg_seq_num,
+                    });
+                }
+                if is_logon {
+                    self.on_logon(msg_seq_num, reset_seq_num);
+                }
+                if !matches!(self.resend_requested_until, Some(end) if expected <= end) {
+                    let mut resend_request = OutboundMessage::new(b"2");
+                    resend_request.set(BEGIN_SEQ_NO, expected);
+                    resend_request.set(END_SEQ_NO, 0);
+                    let resend_request_seq_num = self.queue(resend_request);
+                    self.emit(SessionEvent::ResendRequestSent {
+                        msg_seq_num: resend_request_seq_num,
+                        begin_seq_no: expected,
+                    });
+                    self.resend_requested_until = Some(msg_seq_num);
+                }
+                return Ok(Incoming::SeqGap {
+                    expected,
+                    actual: msg_seq_num,
+                });
+            }
+        }
+        self.receive_time = None;
+        if msg.get_raw(SENDING_TIME).is_none() {
+            if self.config.require_sending_time() {
+                let mut reject = OutboundMessage::new(b"3");
+                reject.set(REF_SEQ_NUM, msg_seq_num);
+                reject.set(REF_TAG_ID, SENDING_TIME);
+                reject.set(REF_MSG_TYPE, msg_type);
+                reject.set(SESSION_REJECT_REASON, REQUIRED_TAG_MISSING);
+                self.queue_reject(reject, msg_seq_num, REQUIRED_TAG_MISSING);
+                return Err(SessionError::MissingField(SENDING_TIME));
+            }
+            self.receive_time = Some(SystemTime::now());
+        }
+        if let (Some(max_clock_skew), Some(sending_time)) =
+            (self.config.max_clock_skew(), msg.get_raw(SENDING_TIME))
+        {
+            if !is_within_clock_skew(sending_time, max_clock_skew) {
+                let mut reject = OutboundMessage::new(b"3");
+                reject.set(REF_SEQ_NUM, msg_seq_num);
+                reject.set(REF_TAG_ID, SENDING_TIME);
+                reject.set(REF_MSG_TYPE, msg_type);
+                reject.set(SESSION_REJECT_REASON, SENDING_TIME_ACCURACY_PROBLEM);
+                self.queue_reject(reject, msg_seq_num, SENDING_TIME_ACCURACY_PROBLEM);
+                self.terminate(Some("SendingTime accuracy problem"));
+                return Err(SessionError::SendingTimeAccuracy);
+            }
+        }
+        match msg_type {
+            b"A" => {
+                let missing = match next_expected {
+                    Some(next_expected) => self.missing_outbound(next_expected)?,
+                    None => 0..0,
+                };
+                self.on_logon(msg_seq_num, reset_seq_num);
+                if missing.is_empty() {
+                    Ok(Incoming::Admin)
+                } else {
+                    self.emit(SessionEvent::ResendRequestReceived {
+                        msg_seq_num,
+                        range: missing.clone(),
+                    });
+                    Ok(Incoming::ResendRequest(missing))
+                }
+            }
+            b"1" => {
+                let mut heartbeat = OutboundMessage::new(b"0");
+                if let Some(test_req_id) = msg.get_raw(TEST_REQ_ID) {
+                    heartbeat.set(TEST_REQ_ID, test_req_id);
+                }
+                let heartbeat_seq_num = self.queue(heartbeat);
+                self.emit(SessionEvent::HeartbeatSent {
+                    msg_seq_num: heartbeat_seq_num,
+                });
+                Ok(Incoming::Admin)
+            }
+            b"2" => {
+                let begin_seq_no = msg
+                    .get::<u64>(BEGIN_SEQ_NO)
+                    .map_err(|_| SessionError::MissingField(BEGIN_SEQ_NO))?;
+                let next_outbound = self.seq_numbers.next_outbound();
+                let end = match msg.get::<u64>(END_SEQ_NO) {
+                    // Infinity, i.e. all messages sent so far.
+                    Ok(0) => next_outbound,
+                    Ok(end_seq_no) => end_seq_no.saturating_add(1).min(next_outbound),
+                    Err(_) => return Err(SessionError::MissingField(END_SEQ_NO)),
+                };
+                if !self.was_sent(begin_seq_no) || begin_seq_no >= end {
+                    let mut reject = OutboundMessage::new(b"3");
+                    reject.set(REF_SEQ_NUM, msg_seq_num);
+                    reject.set(REF_TAG_ID, BEGIN_SEQ_NO);
+                    reject.set(REF_MSG_TYPE, msg_type);
+                    reject.set(SESSION_REJECT_REASON, VALUE_IS_INCORRECT);
+                    self.queue_reject(reject, msg_seq_num, VALUE_IS_INCORRECT);
+                    return Err(SessionError::MissingField(BEGIN_SEQ_NO));
+                }
+                self.emit(SessionEvent::ResendRequestReceived {
+                    msg_seq_num,
+                    range: begin_seq_no..end,
+                });
+                Ok(Incoming::ResendRequest(begin_seq_no..end))
+            }
+            b"4" => {
+                self.skip_to(msg, true)?;
+                Ok(Incoming::Admin)
+            }
+            b"5" => {
+                trace_event!(info, msg_seq_num, "Logout received");
+                self.emit(SessionEvent::LogoutReceived { msg_seq_num });
+                if self.status != SessionStatus::LogoutSent {
+                    self.queue_logout(None);
+                }
+                self.set_terminated(Termination::Clean);
+                Ok(Incoming::Logout)
+            }
+            b"0" | b"3" => Ok(Incoming::Admin),
+            _ => Ok(Incoming::App),
+        }
+    }
+
+    fn on_logon(&mut self, msg_seq_num: u64, reset_seq_num: bool) {
+        trace_event!(info, msg_seq_num, reset_seq_num, "Logon received");
+        self.emit(SessionEvent::LogonReceived {
+            msg_seq_num,
+            reset_seq_num,
+        });
+        if self.status == SessionStatus::AwaitingLogon {
+            self.queue_logon(reset_seq_num || self.config.reset_on_logon());
+        }
+        self.set_status(SessionStatus::Active);
+    }
+
+    fn queue_logon(&mut self, reset_seq_num: bool) {
+        let mut logon = OutboundMessage::new(b"A");
+        logon.set(ENCRYPT_METHOD, 0);
+        logon.set(HEART_BT_INT, self.config.heartbeat().as_secs());
+        if reset_seq_num {
+            logon.set(RESET_SEQ_NUM_FLAG, true);
+        }
+        if self.config.next_expected_msg_seq_num() {
+            logon.set(NEXT_EXPECTED_MSG_SEQ_NUM, self.seq_numbers.next_inbound());
+        }
+        if let Some(default_appl_ver_id) = self.config.default_appl_ver_id() {
+            logon.set(DEFAULT_APPL_VER_ID, default_appl_ver_id);
+        }
+        let msg_seq_num = self.queue(logon);
+        trace_event!(info, msg_seq_num, reset_seq_num, "Logon sent");
+        self.emit(SessionEvent::LogonSent {
+            msg_seq_num,
+            reset_seq_num,
+        });
+    }
+
+    fn queue_logout(&mut self, text: Option<&str>) {
+        let mut logout = OutboundMessage::new(b"5");
+        if let Some(text) = text {
+            logout.set(TEXT, text);
+        }
+        let msg_seq_num = self.queue(logout);
+        trace_event!(info, msg_seq_num, "Logout sent");
+        self.emit(SessionEvent::LogoutSent { msg_seq_num });
+    }
+
+    fn queue_reject(&mut self, reject: OutboundMessage, ref_seq_num: u64, reason: u32) {
+        let msg_seq_num = self.queue(reject);
+        self.emit(SessionEvent::RejectSent {
+            msg_seq_num,
+            ref_seq_num,
+            reason,
+        });
+    }
+
+    // Whether `msg_seq_num` belongs to an outbound message which was already
+    // queued.
+    fn was_sent(&self, msg_seq_num: u64) -> bool {
+        msg_seq_num > 0 && msg_seq_num < self.seq_numbers.next_outbound()
+    }
+
+    // Returns the outbound messages which the counterparty is missing
+    // according to `NextExpectedMsgSeqNum <789>`. They must be sent before
+    // our `Logon <A>`, if any, is queued.
+    fn missing_outbound(&mut self, next_expected: u64) -> Result<Range<u64>, SessionError> {
+        let next_outbound = self.seq_numbers.next_outbound();
+        if next_expected > next_outbound {
+            self.terminate(Some(&format!(
+                "NextExpectedMsgSeqNum <789> too high, expecting at most {}",
+                next_outbound
+            )));
+            return Err(SessionError::NextExpectedMsgSeqNumTooHigh {
+                next_outbound,
+                actual: next_expected,
+            });
+        }
+        Ok(next_expected..next_outbound)
+    }
+
+    // Applies the `NewSeqNo <36>` of a `SequenceReset <4>` message. Sequence
+    // numbers can't go backwards.
+    fn skip_to<M>(&mut self, msg: &M, gap_fill: bool) -> Result<(), SessionError>
+    where
+        M: FieldMap<u32>,
+    {
+        let new_seq_no = msg
+            .get::<u64>(NEW_SEQ_NO)
+            .map_err(|_| SessionError::MissingField(NEW_SEQ_NO))?;
+        self.emit(SessionEvent::SequenceResetReceived {
+            new_seq_no,
+            gap_fill,
+        });
+        if new_seq_no > self.seq_numbers.next_inbound() {
+            self.seq_numbers.next_inbound = new_seq_no;
+        }
+        Ok(())
+    }
+
+    fn terminate(&mut self, text: Option<&str>) {
+        self.queue_logout(text);
+        self.set_terminated(Termination::Forced);
+    }
+
+    fn set_status(&mut self, status: SessionStatus) {
+        let from = std::mem::replace(&mut self.status, status);
+        if from != status {
+            self.emit(SessionEvent::StatusChanged { from, to: status });
+        }
+    }
+
+    fn set_terminated(&mut self, termination: Termination) {
+        let was_terminated = self.status == SessionStatus::Terminated;
+        self.set_status(SessionStatus::Terminated);
+        self.termination = Some(termination);
+        if !was_terminated {
+            trace_event!(info, termination = ?termination, "FIX session terminated");
+            self.emit(SessionEvent::Terminated(termination));
+        }
+    }
+
+    fn emit(&mut self, event: SessionEvent) {
+        if let Some(handler) = self.event_handler.0.as_mut() {
+            handler(event);
+        }
+    }
+
+    // Returns the `MsgSeqNum <34>` of `msg`.
+    fn queue(&mut self, msg: OutboundMessage) -> u64 {
+        let msg_seq_num = self.seq_numbers.next_outbound();
+        let stamped = self.stamp(msg, msg_seq_num, false);
+        self.seq_numbers.incr_outbound();
+        self.outbox.push_back(stamped);
+        msg_seq_num
+    }
+
+    fn stamp(&self, msg: OutboundMessage, msg_seq_num: u64, poss_dup: bool) -> OutboundMessage {
+        let mut stamped = OutboundMessage::new(&msg.msg_type);
+        stamped.set(SENDER_COMP_ID, self.config.sender_comp_id());
+        stamped.set(TARGET_COMP_ID, self.config.target_comp_id());
+        let identity = [
+            (SENDER_SUB_ID, self.config.sender_sub_id()),
+            (SENDER_LOCATION_ID, self.config.sender_location_id()),
+            (TARGET_SUB_ID, self.config.target_sub_id()),
+            (TARGET_LOCATION_ID, self.config.target_location_id()),
+        ];
+        for (tag, value) in identity {
+            if let Some(value) = value {
+                stamped.set(tag, value);
+            }
+        }
+        stamped.set(MSG_SEQ_NUM, msg_seq_num);
+        if poss_dup {
+            stamped.set(POSS_DUP_FLAG, true);
+        }
+        stamped.set_with(
+            SENDING_TIME,
+            chrono::Utc::now(),
+            self.config.sending_time_precision(),
+        );
+        if poss_dup {
+            let orig_sending_time = msg
+                .header_fields()
+                .find(|(tag, _)| tag.get() == ORIG_SENDING_TIME)
+                .or_else(|| {
+                    msg.header_fields()
+                        .find(|(tag, _)| tag.get() == SENDING_TIME)
+                })
+                .or_else(|| stamped.fields().find(|(tag, _)| tag.get() == SENDING_TIME))
+                .map(|(_, value)| value.to_vec());
+            if let Some(orig_sending_time) = orig_sending_time {
+                stamped.set(ORIG_SENDING_TIME, &orig_sending_time[..]);
+            }
+        }
+        let next_inbound = self.seq_numbers.next_inbound();
+        if self.config.last_msg_seq_num_processed() && next_inbound > 1 {
+            stamped.set(LAST_MSG_SEQ_NUM_PROCESSED, next_inbound - 1);
+        }
+        stamped.body_start = stamped.fields.len();
+        stamped
+            .fields
+            .extend(msg.fields.into_iter().skip(msg.body_start));
+        stamped.groups = msg.groups;
+        stamped
+    }
+}
+
+// Whether `sending_time` is at most `max_clock_skew` away from the local clock.
+// Unparsable timestamps never are.
+fn is_within_clock_skew(sending_time: &[u8], max_clock_skew: Duration) -> bool {
+    let Ok(sending_time) = chrono::DateTime::<chrono::Utc>::deserialize(sending_time) else {
+        return false;
+    };
+    let skew = (chrono::Utc::now() - sending_time).abs();
+    // Skews which `chrono` can't represent are practically unlimited.
+    chrono::Duration::from_std(max_clock_skew).map_or(true, |max| skew <= max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dict::AppVersion;
+    use crate::field_types::TimestampPrecision;
+    use crate::session::{resend_messages, Config, FlushPolicy, Resend};
+    use crate::tagvalue::{Decoder, Encoder};
+    use crate::{Dictionary, GetConfig, RepeatingGroup};
+    use std::collections::BTreeMap;
+    use std::num::NonZeroU64;
+
+    fn config(sender_comp_id: &str, target_comp_id: &str) -> Config {
+        let mut config = Config::default();
+        config.sender_comp_id = sender_comp_id.to_string();
+        config.target_comp_id = target_comp_id.to_string();
+        config
+    }
+
+    fn initiator_and_acceptor() -> (Session<Config>, Session<Config>) {
+        let mut initiator = config("A", "B");
+        initiator.is_initiator = true;
+        (Session::new(initiator), Session::new(config("B", "A")))
+    }
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder
+    }
+
+    fn encoder() -> Encoder {
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        encoder
+    }
+
+    fn encode_tagvalue(begin_string: &[u8], outbound: &OutboundMessage) -> Vec<u8> {
+        encode_tagvalue_with(begin_string, outbound, |_| true)
+    }
+
+    /// Like [`encode_tagvalue`], but only with the fields for which `keep`
+    /// returns `true`.
+    fn encode_tagvalue_with(
+        begin_string: &[u8],
+        outbound: &OutboundMessage,
+        keep: impl Fn(TagU32) -> bool,
+    ) -> Vec<u8> {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(begin_string, &mut buffer, outbound.msg_type());
+        msg.set_fields(outbound.fields().filter(|(tag, _)| keep(*tag)));
+        msg.done().0.to_vec()
+    }
+
+    /// Delivers all outgoing messages of `from` to `to` in the tag-value
+    /// format, and returns the outcomes.
+    fn transmit(
+        from: &mut Session<Config>,
+        to: &mut Session<Config>,
+    ) -> Vec<Result<Incoming, SessionError>> {
+        transmit_with(from, to, |_| true)
+    }
+
+    /// Like [`transmit`], but only with the fields for which `keep` returns
+    /// `true`.
+    fn transmit_with(
+        from: &mut Session<Config>,
+        to: &mut Session<Config>,
+        keep: impl Fn(TagU32) -> bool,
+    ) -> Vec<Result<Incoming, SessionError>> {
+        let mut decoder = decoder();
+        let mut outcomes = Vec::new();
+        while let Some(outbound) = from.next_outgoing() {
+            let data = encode_tagvalue_with(from.config().begin_string(), &outbound, &keep);
+            let msg = decoder.decode(&data[..]).unwrap();
+            outcomes.push(to.process_incoming(&msg));
+        }
+        outcomes
+    }
+
+    fn logged_on() -> (Session<Config>, Session<Config>) {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        initiator.logon();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::Admin)]
+        );
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::Admin)]
+        );
+        (initiator, acceptor)
+    }
+
+    #[test]
+    fn logon_handshake() {
+        let (initiator, acceptor) = logged_on();
+        assert_eq!(initiator.status(), SessionStatus::Active);
+        assert_eq!(acceptor.status(), SessionStatus::Active);
+        assert_eq!(initiator.seq_numbers().next_inbound(), 2);
+        assert_eq!(initiator.seq_numbers().next_outbound(), 2);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 2);
+        assert_eq!(acceptor.seq_numbers().next_outbound(), 2);
+    }
+
+    #[test]
+    fn reset_on_logon() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.heartbeat();
+        transmit(&mut initiator, &mut acceptor);
+        let mut config = config("A", "B");
+        config.reset_on_logon = true;
+        let mut initiator = Session::try_new(config).unwrap();
+        *initiator.seq_numbers_mut() = acceptor.seq_numbers();
+        initiator.logon();
+        let logon = initiator.next_outgoing().unwrap();
+        assert_eq!(logon.msg_seq_num(), Some(1));
+        assert!(logon
+            .body_fields()
+            .any(|(tag, value)| tag.get() == RESET_SEQ_NUM_FLAG && value == b"Y"));
+    }
+
+    #[test]
+    fn acceptors_reset_on_logon() {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        acceptor.config.reset_on_logon = true;
+        *acceptor.seq_numbers_mut() =
+            SeqNumbers::new(NonZeroU64::new(4).unwrap(), NonZeroU64::new(7).unwrap());
+        initiator.logon();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::Admin)]
+        );
+        let logon = acceptor.next_outgoing().unwrap();
+        assert_eq!(logon.msg_seq_num(), Some(1));
+        assert!(logon
+            .body_fields()
+            .any(|(tag, value)| tag.get() == RESET_SEQ_NUM_FLAG && value == b"Y"));
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 2);
+    }
+
+    #[test]
+    fn initiators_refuse_unexpected_logons() {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        acceptor.logon();
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Err(SessionError::UnexpectedLogon)]
+        );
+        assert_eq!(initiator.status(), SessionStatus::Terminated);
+    }
+
+    #[test]
+    fn sending_time_beyond_clock_skew_is_rejected() {
+        let (mut initiator, mut acceptor) = logged_on();
+        acceptor.config.max_clock_skew = Some(Duration::from_secs(60));
+        initiator.heartbeat();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::Admin)]
+        );
+
+        initiator.heartbeat();
+        let heartbeat = initiator.next_outgoing().unwrap();
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, heartbeat.msg_type());
+        msg.set_fields(heartbeat.fields().map(|(tag, value)| match tag.get() {
+            SENDING_TIME => (tag, &b"20100318-03:21:11.364"[..]),
+            _ => (tag, value),
+        }));
+        let data = msg.done().0.to_vec();
+        let mut decoder = decoder();
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(
+            acceptor.process_incoming(&msg),
+            Err(SessionError::SendingTimeAccuracy)
+        );
+        let reject = acceptor.next_outgoing().unwrap();
+        assert_eq!(reject.msg_type(), b"3");
+        assert!(reject
+            .body_fields()
+            .any(|(tag, value)| tag.get() == SESSION_REJECT_REASON && value == b"10"));
+        assert_eq!(acceptor.next_outgoing().unwrap().msg_type(), b"5");
+        assert_eq!(acceptor.status(), SessionStatus::Terminated);
+    }
+
+    #[test]
+    fn logon_declares_default_appl_ver_id() {
+        let mut config = config("A", "B");
+        config.begin_string = "FIXT.1.1".to_string();
+        config.default_appl_ver_id = AppVersion::Fix44.appl_ver_id().map(str::to_string);
+        let mut initiator = Session::try_new(config).unwrap();
+        initiator.logon();
+        let logon = initiator.next_outgoing().unwrap();
+        assert!(logon
+            .body_fields()
+            .any(|(tag, value)| tag.get() == DEFAULT_APPL_VER_ID && value == b"6"));
+    }
+
+    #[test]
+    fn contradictory_config_is_refused() {
+        assert!(matches!(
+            Session::try_new(config("A", "A")),
+            Err(ConfigError::SameCompIds)
+        ));
+    }
+
+    #[test]
+    fn first_message_must_be_logon() {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        initiator.heartbeat();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Err(SessionError::NotLoggedOn)]
+        );
+        assert_eq!(acceptor.status(), SessionStatus::Terminated);
+    }
+
+    #[test]
+    fn test_request_is_answered_with_heartbeat() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.test_request(b"foo");
+        transmit(&mut initiator, &mut acceptor);
+        let heartbeat = acceptor.next_outgoing().unwrap();
+        assert_eq!(heartbeat.msg_type(), b"0");
+        assert_eq!(
+            heartbeat.body_fields().collect::<Vec<_>>(),
+            vec![(TagU32::new(TEST_REQ_ID).unwrap(), &b"foo"[..])]
+        );
+    }
+
+    #[test]
+    fn seq_gap_triggers_a_single_resend_request() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.heartbeat();
+        initiator.next_outgoing();
+        initiator.heartbeat();
+        initiator.heartbeat();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![
+                Ok(Incoming::SeqGap {
+                    expected: 2,
+                    actual: 3
+                }),
+                Ok(Incoming::SeqGap {
+                    expected: 2,
+                    actual: 4
+                })
+            ]
+        );
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::ResendRequest(2..5))]
+        );
+    }
+
+    #[test]
+    fn events_are_reported_in_order() {
+        let (mut initiator, mut acceptor) = logged_on();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        acceptor.set_event_handler(move |event| sender.send(event).unwrap());
+        initiator.heartbeat();
+        initiator.next_outgoing();
+        initiator.test_request(b"foo");
+        transmit(&mut initiator, &mut acceptor);
+        transmit(&mut acceptor, &mut initiator);
+        initiator.replay(2, OutboundMessage::new(b"0"));
+        initiator.replay(3, OutboundMessage::new(b"0"));
+        initiator.logout(None);
+        transmit(&mut initiator, &mut acceptor);
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                SessionEvent::SeqGap {
+                    expected: 2,
+                    actual: 3
+                },
+                SessionEvent::ResendRequestSent {
+                    msg_seq_num: 2,
+                    begin_seq_no: 2
+                },
+                SessionEvent::GapFilled { msg_seq_num: 3 },
+                SessionEvent::LogoutReceived { msg_seq_num: 4 },
+                SessionEvent::LogoutSent { msg_seq_num: 3 },
+                SessionEvent::StatusChanged {
+                    from: SessionStatus::Active,
+                    to: SessionStatus::Terminated
+                },
+                SessionEvent::Terminated(Termination::Clean),
+            ]
+        );
+    }
+
+    #[test]
+    fn gap_fill_skips_messages() {
+        let (mut initiator, mut acceptor) = logged_on();
+        let mut gap_fill = OutboundMessage::new(b"4");
+        gap_fill.set(GAP_FILL_FLAG, true);
+        gap_fill.set(NEW_SEQ_NO, 10);
+        initiator.send(gap_fill);
+        transmit(&mut initiator, &mut acceptor);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 10);
+    }
+
+    #[test]
+    fn resent_messages_keep_their_msg_seq_nums() {
+        let (mut initiator, mut acceptor) = logged_on();
+        let mut decoder = decoder();
+        let mut store = BTreeMap::new();
+        for msg_seq_num in 2..=8 {
+            if msg_seq_num == 7 {
+                initiator.heartbeat();
+            } else {
+                initiator.send(OutboundMessage::new(b"D"));
+            }
+            let outbound = initiator.next_outgoing().unwrap();
+            assert_eq!(outbound.msg_seq_num(), Some(msg_seq_num));
+            store.insert(msg_seq_num, encode_tagvalue(b"FIX.4.4", &outbound));
+            // Messages 5 to 8 are lost.
+            if msg_seq_num < 5 {
+                let msg = decoder.decode(&store[&msg_seq_num][..]).unwrap();
+                assert_eq!(acceptor.process_incoming(&msg), Ok(Incoming::App));
+            }
+        }
+        let mut resend_request = OutboundMessage::new(b"2");
+        resend_request.set(BEGIN_SEQ_NO, 5);
+        resend_request.set(END_SEQ_NO, 8);
+        acceptor.send(resend_request);
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::ResendRequest(5..9))]
+        );
+
+        let resends = resend_messages(
+            &store,
+            5..9,
+            &mut decoder,
+            &mut encoder(),
+            b"20210101-10:05:00",
+        )
+        .unwrap();
+        let mut resent = Vec::new();
+        for resend in resends {
+            match resend {
+                Resend::Message(data) => resent.push(data),
+                Resend::GapFill {
+                    msg_seq_num,
+                    new_seq_no,
+                } => {
+                    initiator.gap_fill(msg_seq_num..new_seq_no);
+                    let gap_fill = initiator.next_outgoing().unwrap();
+                    resent.push(encode_tagvalue(b"FIX.4.4", &gap_fill));
+                }
+            }
+        }
+        initiator.send(OutboundMessage::new(b"D"));
+        resent.push(encode_tagvalue(
+            b"FIX.4.4",
+            &initiator.next_outgoing().unwrap(),
+        ));
+
+        let mut msg_seq_nums = Vec::new();
+        for data in resent {
+            let msg = decoder.decode(&data[..]).unwrap();
+            msg_seq_nums.push(msg.get::<u64>(MSG_SEQ_NUM).unwrap());
+            assert!(acceptor.process_incoming(&msg).is_ok());
+        }
+        assert_eq!(msg_seq_nums, vec![5, 6, 7, 8, 9]);
+        assert_eq!(initiator.seq_numbers().next_outbound(), 10);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 10);
+    }
+
+    #[test]
+    fn out_of_range_resend_requests_are_rejected() {
+        let (mut initiator, mut acceptor) = logged_on();
+        for (begin_seq_no, end_seq_no) in [(0, 0), (5, 0), (2, 1)] {
+            let mut resend_request = OutboundMessage::new(b"2");
+            resend_request.set(BEGIN_SEQ_NO, begin_seq_no);
+            resend_request.set(END_SEQ_NO, end_seq_no);
+            acceptor.send(resend_request);
+            assert_eq!(
+                transmit(&mut acceptor, &mut initiator),
+                vec![Err(SessionError::MissingField(BEGIN_SEQ_NO))]
+            );
+            let reject = initiator.next_outgoing().unwrap();
+            assert_eq!(reject.msg_type(), b"3");
+            assert!(reject
+                .body_fields()
+                .any(|(tag, value)| tag.get() == SESSION_REJECT_REASON && value == b"5"));
+        }
+        assert_eq!(initiator.status(), SessionStatus::Active);
+
+        // `EndSeqNo <16>` is capped to the messages sent so far.
+        let mut resend_request = OutboundMessage::new(b"2");
+        resend_request.set(BEGIN_SEQ_NO, 2);
+        resend_request.set(END_SEQ_NO, 100);
+        acceptor.send(resend_request);
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::ResendRequest(2..5))]
+        );
+
+        // Never sent, so there's nothing to replay.
+        initiator.replay(5, OutboundMessage::new(b"D"));
+        initiator.gap_fill(5..7);
+        assert_eq!(initiator.next_outgoing(), None);
+        assert_eq!(initiator.seq_numbers().next_outbound(), 5);
+    }
+
+    #[test]
+    fn groups_can_be_modified_after_stamping() {
+        let (mut initiator, _) = logged_on();
+        let md_entry = |entry_type: &str, px: &str| {
+            let mut entry = GroupEntry::new();
+            entry.set(269, entry_type);
+            entry.set(270, px);
+            entry
+        };
+        let mut custom_entry = GroupEntry::new();
+        custom_entry.set(5001, "foo");
+        let mut msg = OutboundMessage::new(b"W");
+        msg.set(55, "EUR/USD");
+        msg.set_group(268, vec![md_entry("0", "1.5"), md_entry("1", "1.75")]);
+        msg.set_group(5000, vec![custom_entry.clone()]);
+        initiator.send(msg);
+
+        let mut msg = initiator.next_outgoing().unwrap();
+        msg.append_group_entry(268, md_entry("2", "1.6"));
+        let mut entries = msg.group_entries(268).unwrap();
+        assert_eq!(entries.len(), 3);
+        entries.remove(0);
+        msg.set_group(268, entries);
+        assert_eq!(msg.group_entries(5000), Some(vec![custom_entry]));
+
+        let data = encode_tagvalue(b"FIX.4.4", &msg);
+        let mut decoder = decoder();
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.get_raw(55), Some(b"EUR/USD" as &[u8]));
+        let group = msg.group(268).unwrap();
+        assert_eq!(group.len(), 2);
+        assert_eq!(group.get(0).unwrap().get_raw(269), Some(b"1" as &[u8]));
+        assert_eq!(group.get(1).unwrap().get_raw(270), Some(b"1.6" as &[u8]));
+        assert_eq!(msg.get_raw(5001), Some(b"foo" as &[u8]));
+    }
+
+    #[test]
+    fn decoded_groups_can_be_filtered_and_forwarded() {
+        let data = b"8=FIX.4.4|9=111|35=W|49=A|56=B|34=7|52=20210101-10:00:00|262=foo|453=0|268=3|269=0|270=1.5|269=1|270=1.75|269=2|270=1.6|58=bar|10=180|";
+        let mut decoder = decoder();
+        let mut msg = OutboundMessage::from_message(&decoder.decode(&data[..]).unwrap());
+        // `Text <58>` belongs to the last entry.
+        let entries = msg.group_entries(268).unwrap();
+        assert_eq!(entries.len(), 3);
+        let bids_and_offers = entries
+            .into_iter()
+            .filter(|entry| entry.fields().next().unwrap().1 != b"2")
+            .collect();
+        msg.set_group(268, bids_and_offers);
+        let mut party = GroupEntry::new();
+        party.set(448, "P");
+        party.set(452, 1);
+        msg.set_group(453, vec![party]);
+
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut encoded = encoder.start_message(b"FIX.4.4", &mut buffer, msg.msg_type());
+        encoded.set_fields(msg.fields());
+        let encoded = encoded.done().0.to_vec();
+        let expected = b"|35=W|49=A|56=B|34=7|52=20210101-10:00:00|262=foo|453=1|448=P|452=1|268=2|269=0|270=1.5|269=1|270=1.75|10=";
+        assert!(encoded
+            .windows(expected.len())
+            .any(|window| window == expected));
+        let decoded = decoder.decode(&encoded[..]).unwrap();
+        assert_eq!(decoded.group(268).unwrap().len(), 2);
+
+        // Forwarding the message replaces its header.
+        let (mut initiator, _) = logged_on();
+        initiator.send(msg);
+        let forwarded = initiator.next_outgoing().unwrap();
+        assert_eq!(forwarded.msg_seq_num(), Some(2));
+        assert_eq!(
+            forwarded.body_fields().next(),
+            Some((TagU32::new(262).unwrap(), b"foo" as &[u8]))
+        );
+        assert_eq!(forwarded.group_entries(268).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn msg_seq_num_too_low_terminates_the_session() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.seq_numbers_mut().next_outbound = 1;
+        initiator.heartbeat();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Err(SessionError::MsgSeqNumTooLow {
+                expected: 2,
+                actual: 1
+            })]
+        );
+        assert_eq!(acceptor.status(), SessionStatus::Terminated);
+        assert_eq!(acceptor.next_outgoing().unwrap().msg_type(), b"5");
+    }
+
+    // Sessions which log on again after the acceptor's `count` outbound
+    // messages were lost.
+    fn reconnected(count: usize) -> (Session<Config>, Session<Config>) {
+        let (initiator, mut acceptor) = logged_on();
+        for _ in 0..count {
+            acceptor.send(OutboundMessage::new(b"8"));
+            acceptor.next_outgoing().unwrap();
+        }
+        let mut reconnected = (
+            Session::new(config("A", "B")),
+            Session::new(config("B", "A")),
+        );
+        for (session, previous) in [
+            (&mut reconnected.0, &initiator),
+            (&mut reconnected.1, &acceptor),
+        ] {
+            session.config.next_expected_msg_seq_num = true;
+            session.config.last_msg_seq_num_processed = true;
+            *session.seq_numbers_mut() = previous.seq_numbers();
+        }
+        reconnected
+    }
+
+    #[test]
+    fn missing_messages_are_resent_on_logon() {
+        let (mut initiator, mut acceptor) = reconnected(2);
+        initiator.logon();
+        let logon = initiator.outbox.front().unwrap();
+        assert!(logon
+            .body_fields()
+            .any(|(tag, value)| tag.get() == NEXT_EXPECTED_MSG_SEQ_NUM && value == b"2"));
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::ResendRequest(2..4))]
+        );
+        let logon = acceptor.outbox.front().unwrap();
+        assert_eq!(logon.msg_seq_num(), Some(4));
+        assert!(logon
+            .header_fields()
+            .any(|(tag, value)| tag.get() == LAST_MSG_SEQ_NUM_PROCESSED && value == b"2"));
+        // The resent messages close the gap, so no `ResendRequest <2>`.
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::SeqGap {
+                expected: 2,
+                actual: 4
+            })]
+        );
+        assert_eq!(initiator.status(), SessionStatus::Active);
+        assert_eq!(initiator.next_outgoing(), None);
+    }
+
+    #[test]
+    fn next_expected_msg_seq_num_too_high_terminates_the_session() {
+        let (mut initiator, mut acceptor) = reconnected(0);
+        initiator.seq_numbers_mut().next_inbound = 5;
+        initiator.logon();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Err(SessionError::NextExpectedMsgSeqNumTooHigh {
+                next_outbound: 2,
+                actual: 5
+            })]
+        );
+        assert_eq!(acceptor.status(), SessionStatus::Terminated);
+        assert_eq!(acceptor.next_outgoing().unwrap().msg_type(), b"5");
+    }
+
+    #[derive(Default)]
+    struct Store {
+        messages: BTreeMap<u64, Vec<u8>>,
+        seq_numbers: Option<SeqNumbers>,
+        flushed: Option<(usize, SeqNumbers)>,
+    }
+
+    impl MessageStore for Store {
+        fn insert(&mut self, msg_seq_num: u64, message: &[u8]) {
+            self.messages.insert(msg_seq_num, message.to_vec());
+        }
+
+        fn get(&self, msg_seq_num: u64) -> Option<&[u8]> {
+            self.messages.get(&msg_seq_num).map(Vec::as_slice)
+        }
+
+        fn set_seq_numbers(&mut self, seq_numbers: SeqNumbers) {
+            self.seq_numbers = Some(seq_numbers);
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushed = Some((self.messages.len(), self.seq_numbers.unwrap()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn store_is_flushed_as_per_policy() {
+        let (mut initiator, mut acceptor) = logged_on();
+        let mut store = Store::default();
+        acceptor.send(OutboundMessage::new(b"8"));
+        let msg = acceptor.next_outgoing().unwrap();
+        acceptor
+            .store(&mut store, 2, &encode_tagvalue(b"FIX.4.4", &msg))
+            .unwrap();
+        assert_eq!(store.flushed, Some((1, acceptor.seq_numbers())));
+
+        let interval = std::time::Duration::from_millis(200);
+        initiator.config.persistence_flush_policy = FlushPolicy::OnInterval(interval);
+        let mut store = Store::default();
+        let store_heartbeat = |initiator: &mut Session<Config>, store: &mut Store| {
+            initiator.heartbeat();
+            let msg = initiator.next_outgoing().unwrap();
+            let data = encode_tagvalue(b"FIX.4.4", &msg);
+            initiator
+                .store(store, msg.msg_seq_num().unwrap(), &data)
+                .unwrap();
+        };
+        initiator.flush_store(&mut store).unwrap();
+        let flushed = Some((0, initiator.seq_numbers()));
+        for _ in 0..3 {
+            store_heartbeat(&mut initiator, &mut store);
+        }
+        assert_eq!(store.flushed, flushed);
+        assert_eq!(store.seq_numbers, Some(initiator.seq_numbers()));
+        std::thread::sleep(interval);
+        store_heartbeat(&mut initiator, &mut store);
+        assert_eq!(store.flushed, Some((4, initiator.seq_numbers())));
+        store_heartbeat(&mut initiator, &mut store);
+        assert_eq!(store.messages.len(), 5);
+        assert_eq!(store.flushed.unwrap().0, 4);
+        initiator.flush_store(&mut store).unwrap();
+        assert_eq!(store.flushed, Some((5, initiator.seq_numbers())));
+    }
+
+    #[test]
+    fn wrong_comp_ids_are_rejected() {
+        let (_, mut acceptor) = initiator_and_acceptor();
+        let mut intruder = Session::new(config("C", "B"));
+        intruder.logon();
+        assert_eq!(
+            transmit(&mut intruder, &mut acceptor),
+            vec![Err(SessionError::CompId(CompIdError::SenderCompId))]
+        );
+        let replies: Vec<_> = std::iter::from_fn(|| acceptor.next_outgoing()).collect();
+        assert_eq!(replies[0].msg_type(), b"3");
+        assert_eq!(replies[1].msg_type(), b"5");
+    }
+
+    #[test]
+    fn sub_ids_are_stamped_and_verified() {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        initiator.config.sender_sub_id = Some("DESK".to_string());
+        initiator.config.target_location_id = Some("LDN".to_string());
+        acceptor.config.target_sub_id = Some("DESK".to_string());
+        acceptor.config.require_sub_ids = true;
+        initiator.logon();
+        let logon = initiator.outbox.front().unwrap();
+        let header: Vec<_> = logon
+            .header_fields()
+            .map(|(tag, value)| (tag.get(), value))
+            .collect();
+        assert_eq!(
+            header[..5],
+            [
+                (49, b"A" as &[u8]),
+                (56, b"B"),
+                (50, b"DESK"),
+                (143, b"LDN"),
+                (34, b"1"),
+            ]
+        );
+        assert_eq!(header[5].0, SENDING_TIME);
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::Admin)]
+        );
+
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        acceptor.config.target_sub_id = Some("DESK".to_string());
+        acceptor.config.require_sub_ids = true;
+        initiator.logon();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Err(SessionError::CompId(CompIdError::SenderSubId))]
+        );
+    }
+
+    #[test]
+    fn logout_handshake() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.logout(None);
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::Logout)]
+        );
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::Logout)]
+        );
+        assert_eq!(initiator.status(), SessionStatus::Terminated);
+        assert_eq!(acceptor.status(), SessionStatus::Terminated);
+        assert_eq!(initiator.termination(), Some(Termination::Clean));
+        assert_eq!(acceptor.termination(), Some(Termination::Clean));
+        assert_eq!(initiator.next_outgoing(), None);
+    }
+
+    #[test]
+    fn sessions_can_be_reused_after_a_disconnect() {
+        let (mut initiator, mut acceptor) = logged_on();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        initiator.set_event_handler(move |event| sender.send(event).unwrap());
+        initiator.heartbeat();
+        initiator.next_outgoing();
+        initiator.heartbeat();
+        // The acceptor notices the gap, then the connection drops.
+        transmit(&mut initiator, &mut acceptor);
+        initiator.test_request(b"foo");
+        for session in [&mut initiator, &mut acceptor] {
+            session.logout(None);
+            session.logout_timed_out();
+            assert_eq!(session.status(), SessionStatus::Terminated);
+            session.reset();
+            assert_eq!(session.status(), SessionStatus::AwaitingLogon);
+            assert_eq!(session.termination(), None);
+            assert_eq!(session.next_outgoing(), None);
+        }
+        assert_eq!(initiator.seq_numbers().next_outbound(), 6);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 2);
+
+        // The same sessions log on again, and the gap is recovered.
+        initiator.logon();
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::SeqGap {
+                expected: 2,
+                actual: 6
+            })]
+        );
+        assert_eq!(acceptor.status(), SessionStatus::Active);
+        // The acceptor's messages which were queued before the reset are lost
+        // too.
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![
+                Ok(Incoming::SeqGap {
+                    expected: 2,
+                    actual: 4
+                }),
+                Ok(Incoming::SeqGap {
+                    expected: 2,
+                    actual: 5
+                })
+            ]
+        );
+        assert_eq!(initiator.status(), SessionStatus::Active);
+        assert!(receiver.try_iter().any(|event| event
+            == SessionEvent::StatusChanged {
+                from: SessionStatus::Terminated,
+                to: SessionStatus::AwaitingLogon
+            }));
+
+        let mut config = config("A", "B");
+        config.reset_on_logon = true;
+        let mut session = Session::new(config);
+        session.logon();
+        session.reset();
+        assert_eq!(session.seq_numbers(), SeqNumbers::default());
+    }
+
+    #[test]
+    fn logout_drains_queued_messages_and_inbound_acks() {
+        let (mut initiator, mut acceptor) = logged_on();
+        // An execution report which is still to be sent by the acceptor.
+        acceptor.send(OutboundMessage::new(b"8"));
+        initiator.send(OutboundMessage::new(b"D"));
+        initiator.logout(None);
+        assert_eq!(
+            transmit(&mut initiator, &mut acceptor),
+            vec![Ok(Incoming::App), Ok(Incoming::Logout)]
+        );
+        assert_eq!(initiator.status(), SessionStatus::LogoutSent);
+        assert_eq!(
+            transmit(&mut acceptor, &mut initiator),
+            vec![Ok(Incoming::App), Ok(Incoming::Logout)]
+        );
+        assert_eq!(initiator.termination(), Some(Termination::Clean));
+        initiator.logout_timed_out();
+        assert_eq!(initiator.termination(), Some(Termination::Clean));
+    }
+
+    #[test]
+    fn protocol_violations_force_termination() {
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        initiator.heartbeat();
+        transmit(&mut initiator, &mut acceptor);
+        assert_eq!(acceptor.termination(), Some(Termination::Forced));
+        assert_eq!(initiator.termination(), None);
+    }
+
+    fn is_not_sending_time(tag: TagU32) -> bool {
+        tag.get() != SENDING_TIME
+    }
+
+    fn header_field(msg: &OutboundMessage, tag: u32) -> Option<String> {
+        msg.header_fields()
+            .find(|(t, _)| t.get() == tag)
+            .map(|(_, value)| String::from_utf8(value.to_vec()).unwrap())
+    }
+
+    #[test]
+    fn sending_time_is_stamped_with_the_configured_precision() {
+        let precisions = [
+            (TimestampPrecision::Seconds, None),
+            (TimestampPrecision::Millis, Some(3)),
+            (TimestampPrecision::Micros, Some(6)),
+            (TimestampPrecision::Nanos, Some(9)),
+        ];
+        for (precision, digits) in precisions {
+            let mut config = config("A", "B");
+            config.sending_time_precision = Some(precision);
+            let mut session = Session::new(config);
+            session.heartbeat();
+            let sending_time = header_field(&session.next_outgoing().unwrap(), 52).unwrap();
+            let (seconds, fraction) = match sending_time.split_once('.') {
+                Some((seconds, fraction)) => (seconds, Some(fraction)),
+                None => (sending_time.as_str(), None),
+            };
+            assert!(chrono::NaiveDateTime::parse_from_str(seconds, "%Y%m%d-%H:%M:%S").is_ok());
+            assert_eq!(fraction.map(str::len), digits);
+            assert!(fraction.map_or(true, |f| f.bytes().all(|b| b.is_ascii_digit())));
+        }
+        assert_eq!(
+            Config::default().sending_time_precision(),
+            TimestampPrecision::Millis
+        );
+    }
+
+    #[test]
+    fn replays_are_stamped_with_orig_sending_time() {
+        let (mut initiator, _) = logged_on();
+        initiator.send(OutboundMessage::new(b"D"));
+        let sent = initiator.next_outgoing().unwrap();
+        let sending_time = header_field(&sent, 52);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        initiator.replay(2, sent);
+        let replayed = initiator.next_outgoing().unwrap();
+        assert_eq!(header_field(&replayed, 43).as_deref(), Some("Y"));
+        assert_eq!(header_field(&replayed, 122), sending_time);
+        assert_ne!(header_field(&replayed, 52), sending_time);
+
+        // Replaying it again keeps the very first `SendingTime <52>`.
+        initiator.replay(2, replayed);
+        let replayed = initiator.next_outgoing().unwrap();
+        assert_eq!(header_field(&replayed, 122), sending_time);
+
+        // Without an original `SendingTime <52>`, the new one is used.
+        initiator.replay(2, OutboundMessage::new(b"D"));
+        let replayed = initiator.next_outgoing().unwrap();
+        assert_eq!(header_field(&replayed, 122), header_field(&replayed, 52));
+    }
+
+    #[test]
+    fn missing_sending_time_is_rejected() {
+        let (mut initiator, mut acceptor) = logged_on();
+        initiator.heartbeat();
+        assert_eq!(
+            transmit_with(&mut initiator, &mut acceptor, is_not_sending_time),
+            vec![Err(SessionError::MissingField(52))]
+        );
+        assert_eq!(acceptor.status(), SessionStatus::Active);
+        assert_eq!(acceptor.seq_numbers().next_inbound(), 3);
+        let reject = acceptor.next_outgoing().unwrap();
+        assert_eq!(reject.msg_type(), b"3");
+        assert_eq!(
+            reject.body_fields().collect::<Vec<_>>(),
+            vec![
+                (TagU32::new(REF_SEQ_NUM).unwrap(), &b"2"[..]),
+                (TagU32::new(REF_TAG_ID).unwrap(), &b"52"[..]),
+                (TagU32::new(REF_MSG_TYPE).unwrap(), &b"0"[..]),
+                (TagU32::new(SESSION_REJECT_REASON).unwrap(), &b"1"[..]),
+            ]
+        );
+        assert_eq!(acceptor.receive_time(), None);
+    }
+
+    #[test]
+    fn missing_sending_time_can_be_accepted() {
+        let mut initiator = Session::new(config("A", "B"));
+        let mut config = config("B", "A");
+        config.require_sending_time = false;
+        let mut acceptor = Session::new(config);
+        initiator.logon();
+        assert_eq!(
+            transmit_with(&mut initiator, &mut acceptor, is_not_sending_time),
+            vec![Ok(Incoming::Admin)]
+        );
+        assert!(acceptor.receive_time().is_some());
+        transmit(&mut acceptor, &mut initiator);
+        initiator.send(OutboundMessage::new(b"D"));
+        assert_eq!(
+            transmit_with(&mut initiator, &mut acceptor, is_not_sending_time),
+            vec![Ok(Incoming::App)]
+        );
+        assert!(acceptor.receive_time().is_some());
+        initiator.heartbeat();
+        transmit(&mut initiator, &mut acceptor);
+        assert_eq!(acceptor.receive_time(), None);
+    }
+
+    #[cfg(feature = "json-encoding")]
+    #[test]
+    fn session_over_json() {
+        use crate::json;
+        use crate::session::SeqNumbers;
+
+        let dict = Dictionary::fix44();
+        let (mut initiator, mut acceptor) = initiator_and_acceptor();
+        initiator.logon();
+        let logon = initiator.next_outgoing().unwrap();
+        let mut encoder = json::Encoder::new();
+        let mut header = encoder.start_message().with_header();
+        header = header.set(&dict.field_by_tag(8).unwrap(), "FIX.4.4");
+        header = header.set(&dict.field_by_tag(35).unwrap(), logon.msg_type());
+        for (tag, value) in logon.header_fields() {
+            header = header.set(&dict.field_by_tag(tag.get()).unwrap(), value);
+        }
+        header = header.set(&dict.field_by_tag(52).unwrap(), "20210101-10:00:00");
+        let mut body = header.with_body();
+        for (tag, value) in logon.body_fields() {
+            body = body.set(&dict.field_by_tag(tag.get()).unwrap(), value);
+        }
+        let data = body.with_trailer().done().to_string();
+
+        let mut decoder = json::Decoder::new(dict);
+        let msg = decoder.decode(data.as_bytes()).unwrap();
+        assert_eq!(acceptor.process_incoming(&msg), Ok(Incoming::Admin));
+        assert_eq!(acceptor.status(), SessionStatus::Active);
+        let SeqNumbers {
+            next_inbound,
+            next_outbound,
+        } = acceptor.seq_numbers();
+        assert_eq!((next_inbound, next_outbound), (2, 2));
+    }
+}
@@ -0,0 +1,489 @@
+//! Decoding of SOFH-framed messages with mixed encodings.
+//!
+//! Some venues switch from FIX tag-value or FIX JSON to binary encodings (e.g.
+//! SBE) after `Logon <A>`, while still wrapping all messages within a *Simple
+//! Open Framing Header* (SOFH). [`Decoder`] routes each frame by its encoding
+//! type: FIX encodings are decoded, and everything else is handed over as raw
+//! bytes. [`StreamReader`] does the same for a stream of concatenated frames.
+//! See [`fesofh`] for SOFH framing alone.
+
+use crate::tagvalue;
+use crate::Dictionary;
+use fesofh::{EncodingType, Frame};
+use futures::{AsyncRead, AsyncReadExt};
+use std::io::{self, Read};
+
+#[cfg(feature = "json-encoding")]
+use crate::json;
+
+/// A SOFH-framed message, as returned by [`Decoder::decode`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum FramedMessage<'a> {
+    /// A message with [`EncodingType::TagValue`].
+    TagValue(tagvalue::Message<'a, &'a [u8]>),
+    /// A message with [`EncodingType::Json`].
+    #[cfg(feature = "json-encoding")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "json-encoding")))]
+    Json(json::Message<'a>),
+    /// A message with any other encoding type (e.g. SBE), which is not
+    /// decoded.
+    Passthrough {
+        /// The 16-bits encoding type of the frame. See
+        /// [`EncodingType::new`].
+        encoding_type: u16,
+        /// The payload of the frame, i.e. without its header.
+        payload: &'a [u8],
+    },
+}
+
+impl<'a> FramedMessage<'a> {
+    /// Returns the 16-bits encoding type of the frame of `self`.
+    pub fn encoding_type(&self) -> u16 {
+        match self {
+            FramedMessage::TagValue(_) => EncodingType::TagValue.into(),
+            #[cfg(feature = "json-encoding")]
+            FramedMessage::Json(_) => EncodingType::Json.into(),
+            FramedMessage::Passthrough { encoding_type, .. } => *encoding_type,
+        }
+    }
+}
+
+/// The type returned in the event of an error by [`Decoder::decode`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The SOFH frame itself is invalid or incomplete.
+    #[error("Invalid SOFH frame: {0}")]
+    Sofh(#[from] fesofh::Error),
+    /// The payload of a frame with [`EncodingType::TagValue`] is invalid.
+    #[error("Invalid tag-value payload: {0}")]
+    TagValue(#[from] tagvalue::DecodeError),
+    /// The payload of a frame with [`EncodingType::Json`] is invalid.
+    #[cfg(feature = "json-encoding")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "json-encoding")))]
+    #[error("Invalid JSON payload: {0}")]
+    Json(#[from] json::DecodeError),
+    /// The underlying reader of a [`StreamReader`] failed, or the stream
+    /// ended in the middle of a frame.
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Decodes SOFH-framed messages in any encoding, passing through those that
+/// can't be decoded.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::sofh::{Decoder, FramedMessage};
+/// use fefix::prelude::*;
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.tagvalue_mut().config_mut().separator = b'|';
+/// decoder.tagvalue_mut().config_mut().verify_checksum = false;
+///
+/// let mut data = Vec::new();
+/// let payload = b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=000|" as &[u8];
+/// fesofh::Frame::new(0xF000, payload).serialize(&mut data).unwrap();
+/// match decoder.decode(&data).unwrap() {
+///     FramedMessage::TagValue(msg) => assert_eq!(msg.get(fix44::SENDER_COMP_ID), Ok("A")),
+///     msg => panic!("{:?}", msg),
+/// }
+///
+/// // SBE v1.0, little-endian.
+/// let mut data = Vec::new();
+/// fesofh::Frame::new(0xEB50, &[1u8, 2, 3] as &[u8]).serialize(&mut data).unwrap();
+/// match decoder.decode(&data).unwrap() {
+///     FramedMessage::Passthrough { encoding_type, payload } => {
+///         assert_eq!(encoding_type, 0xEB50);
+///         assert_eq!(payload, &[1, 2, 3]);
+///     }
+///     msg => panic!("{:?}", msg),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Decoder {
+    tagvalue: tagvalue::Decoder,
+    #[cfg(feature = "json-encoding")]
+    json: json::Decoder,
+}
+
+impl Decoder {
+    /// Creates a new [`Decoder`] which uses `dict` for all FIX encodings.
+    pub fn new(dict: Dictionary) -> Self {
+        Self {
+            tagvalue: tagvalue::Decoder::new(&dict),
+            #[cfg(feature = "json-encoding")]
+            json: json::Decoder::new(dict),
+        }
+    }
+
+    /// Returns a mutable reference to the [`tagvalue::Decoder`] of `self`,
+    /// e.g. to change its configuration.
+    pub fn tagvalue_mut(&mut self) -> &mut tagvalue::Decoder {
+        &mut self.tagvalue
+    }
+
+    /// Returns a mutable reference to the [`json::Decoder`] of `self`, e.g.
+    /// to change its configuration.
+    #[cfg(feature = "json-encoding")]
+    #[cfg_attr(doc_cfg, doc(cfg(feature = "json-encoding")))]
+    pub fn json_mut(&mut self) -> &mut json::Decoder {
+        &mut self.json
+    }
+
+    /// Decodes the SOFH-framed message at the start of `data`. Any trailing
+    /// bytes after the frame are ignored.
+    pub fn decode<'a>(&'a mut self, data: &'a [u8]) -> Result<FramedMessage<'a>, DecodeError> {
+        let frame = Frame::<&[u8]>::deserialize(data)?;
+        self.decode_frame(frame)
+    }
+
+    /// Like [`Decoder::decode`], but for an already deserialized [`Frame`].
+    pub fn decode_frame<'a>(
+        &'a mut self,
+        frame: Frame<&'a [u8]>,
+    ) -> Result<FramedMessage<'a>, DecodeError> {
+        let payload = *frame.payload();
+        match EncodingType::new(frame.encoding_type()) {
+            Some(EncodingType::TagValue) => {
+                Ok(FramedMessage::TagValue(self.tagvalue.decode(payload)?))
+            }
+            #[cfg(feature = "json-encoding")]
+            Some(EncodingType::Json) => Ok(FramedMessage::Json(self.json.decode(payload)?)),
+            _ => Ok(FramedMessage::Passthrough {
+                encoding_type: frame.encoding_type(),
+                payload,
+            }),
+        }
+    }
+}
+
+// Reads from the underlying stream are never smaller than this, so that
+// small frames don't take one read each.
+const MIN_READ_LEN: usize = 4096;
+// ... and never larger than this. The length header comes from the peer, so
+// the buffer only grows as bytes actually arrive.
+const MAX_READ_LEN: usize = 64 * 1024;
+const HEADER_LEN: usize = 6;
+
+enum FrameStatus {
+    // The length of the frame at the start of the buffer, header included.
+    Complete(usize),
+    // The number of bytes that are missing from the frame.
+    Incomplete(usize),
+}
+
+/// Reads SOFH-framed messages from a stream of concatenated frames, e.g. a
+/// TCP connection, and decodes them with a [`Decoder`].
+///
+/// Frames are delimited by the SOFH message length alone, so that
+/// `BodyLength <9>` of tag-value payloads plays no role in framing. Bytes are
+/// buffered until a whole frame is available. [`StreamReader::read_message`]
+/// works with [`std::io::Read`] and [`StreamReader::read_message_async`] with
+/// [`futures::AsyncRead`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::sofh::{Decoder, FramedMessage, StreamReader};
+/// use fefix::prelude::*;
+///
+/// let mut data = Vec::new();
+/// let payload = b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=000|" as &[u8];
+/// fesofh::Frame::new(0xF000, payload).serialize(&mut data).unwrap();
+/// fesofh::Frame::new(0xEB50, &[1u8, 2, 3] as &[u8]).serialize(&mut data).unwrap();
+///
+/// let mut reader = StreamReader::new(Decoder::new(Dictionary::fix44()), &data[..]);
+/// reader.decoder_mut().tagvalue_mut().config_mut().separator = b'|';
+/// reader.decoder_mut().tagvalue_mut().config_mut().verify_checksum = false;
+/// match reader.read_message().unwrap() {
+///     Some(FramedMessage::TagValue(msg)) => assert_eq!(msg.get(fix44::SENDER_COMP_ID), Ok("A")),
+///     msg => panic!("{:?}", msg),
+/// }
+/// let msg = reader.read_message().unwrap().unwrap();
+/// assert_eq!(msg.encoding_type(), 0xEB50);
+/// assert!(reader.read_message().unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct StreamReader<R> {
+    decoder: Decoder,
+    reader: R,
+    buffer: Vec<u8>,
+    // The length of the last returned frame, which is still at the start of
+    // `buffer`.
+    consumed: usize,
+}
+
+impl<R> StreamReader<R> {
+    /// Creates a new [`StreamReader`] which reads frames from `reader` and
+    /// decodes them with `decoder`.
+    pub fn new(decoder: Decoder, reader: R) -> Self {
+        Self {
+            decoder,
+            reader,
+            buffer: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Returns a mutable reference to the [`Decoder`] of `self`, e.g. to
+    /// change its configuration.
+    pub fn decoder_mut(&mut self) -> &mut Decoder {
+        &mut self.decoder
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying reader. Any buffered bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads and decodes the next frame, blocking until it's complete.
+    /// Returns [`None`] if the stream ends right after the previous frame.
+    pub fn read_message(&mut self) -> Result<Option<FramedMessage<'_>>, DecodeError>
+    where
+        R: Read,
+    {
+        let frame_len = loop {
+            match self.frame_status()? {
+                FrameStatus::Complete(len) => break len,
+                FrameStatus::Incomplete(needed) => {
+                    let start = self.buffer.len();
+                    self.buffer
+                        .resize(start + needed.clamp(MIN_READ_LEN, MAX_READ_LEN), 0);
+                    let result = self.reader.read(&mut self.buffer[start..]);
+                    if !self.on_read(start, result)? {
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+        self.decode(frame_len).map(Some)
+    }
+
+    /// Like [`StreamReader::read_message`], but for asynchronous readers.
+    pub async fn read_message_async(&mut self) -> Result<Option<FramedMessage<'_>>, DecodeError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let frame_len = loop {
+            match self.frame_status()? {
+                FrameStatus::Complete(len) => break len,
+                FrameStatus::Incomplete(needed) => {
+                    let start = self.buffer.len();
+                    self.buffer
+                        .resize(start + needed.clamp(MIN_READ_LEN, MAX_READ_LEN), 0);
+                    let result = self.reader.read(&mut self.buffer[start..]).await;
+                    if !self.on_read(start, result)? {
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+        self.decode(frame_len).map(Some)
+    }
+
+    fn frame_status(&mut self) -> Result<FrameStatus, DecodeError> {
+        self.buffer.drain(..self.consumed);
+        self.consumed = 0;
+        match Frame::<&[u8]>::deserialize(&self.buffer) {
+            Ok(frame) => Ok(FrameStatus::Complete(HEADER_LEN + frame.payload().len())),
+            Err(fesofh::Error::Incomplete { needed }) => Ok(FrameStatus::Incomplete(needed)),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Trims the unused part of the buffer after a read at `start`. Returns
+    /// `false` at the end of the stream.
+    fn on_read(&mut self, start: usize, result: io::Result<usize>) -> Result<bool, DecodeError> {
+        match result {
+            Ok(0) => {
+                self.buffer.truncate(start);
+                if start == 0 {
+                    Ok(false)
+                } else {
+                    Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+                }
+            }
+            Ok(len) => {
+                self.buffer.truncate(start + len);
+                Ok(true)
+            }
+            Err(err) => {
+                self.buffer.truncate(start);
+                if err.kind() == io::ErrorKind::Interrupted {
+                    Ok(true)
+                } else {
+                    Err(err.into())
+                }
+            }
+        }
+    }
+
+    fn decode(&mut self, frame_len: usize) -> Result<FramedMessage<'_>, DecodeError> {
+        self.consumed = frame_len;
+        let frame = Frame::<&[u8]>::deserialize(&self.buffer[..frame_len])?;
+        self.decoder.decode_frame(frame)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{FieldMap, GetConfig};
+
+    fn frame(encoding_type: u16, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::new();
+        Frame::new(encoding_type, payload)
+            .serialize(&mut data)
+            .unwrap();
+        data
+    }
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.tagvalue_mut().config_mut().separator = b'|';
+        decoder.tagvalue_mut().config_mut().verify_checksum = false;
+        decoder
+    }
+
+    #[test]
+    fn unknown_encoding_types_are_passed_through() {
+        let mut decoder = decoder();
+        for encoding_type in [0x0, 0x5BE0, 0xEB50, 0xFA01, 0xFFFF] {
+            let data = frame(encoding_type, b"8=FIX.4.4|");
+            let msg = decoder.decode(&data).unwrap();
+            assert_eq!(msg.encoding_type(), encoding_type);
+            assert!(matches!(
+                msg,
+                FramedMessage::Passthrough {
+                    payload: b"8=FIX.4.4|",
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_tagvalue_payload() {
+        let mut decoder = decoder();
+        let data = frame(0xF000, b"garbage");
+        assert!(matches!(
+            decoder.decode(&data),
+            Err(DecodeError::TagValue(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let mut decoder = decoder();
+        let data = frame(0xF000, b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=000|");
+        assert!(matches!(
+            decoder.decode(&data[..20]),
+            Err(DecodeError::Sofh(fesofh::Error::Incomplete { .. }))
+        ));
+        let msg = decoder.decode(&data).unwrap();
+        assert!(matches!(msg, FramedMessage::TagValue(msg) if msg.get_raw(56) == Some(b"B")));
+    }
+
+    #[cfg(feature = "json-encoding")]
+    #[test]
+    fn json_payload() {
+        let mut decoder = decoder();
+        let json = br#"{
+            "Header": { "BeginString": "FIX.4.4", "MsgType": "0", "SenderCompID": "A" },
+            "Body": {},
+            "Trailer": {}
+        }"#;
+        let data = frame(0xF500, json);
+        let msg = decoder.decode(&data).unwrap();
+        assert_eq!(msg.encoding_type(), 0xF500);
+        assert!(matches!(msg, FramedMessage::Json(msg) if msg.get_raw(49) == Some(b"A")));
+    }
+
+    // Hands out at most one byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    fn concatenated_frames() -> Vec<u8> {
+        let mut data = frame(0xF000, b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=000|");
+        data.extend(frame(0xEB50, &[1, 2, 3]));
+        data.extend(frame(0xF000, b"8=FIX.4.4|9=15|35=0|49=C|56=D|10=000|"));
+        data
+    }
+
+    #[test]
+    fn stream_of_frames() {
+        let data = concatenated_frames();
+        let mut reader = StreamReader::new(decoder(), Trickle(&data));
+        let msg = reader.read_message().unwrap().unwrap();
+        assert!(matches!(msg, FramedMessage::TagValue(msg) if msg.get_raw(49) == Some(b"A")));
+        let msg = reader.read_message().unwrap().unwrap();
+        assert!(matches!(
+            msg,
+            FramedMessage::Passthrough {
+                encoding_type: 0xEB50,
+                payload: &[1, 2, 3],
+            }
+        ));
+        let msg = reader.read_message().unwrap().unwrap();
+        assert!(matches!(msg, FramedMessage::TagValue(msg) if msg.get_raw(49) == Some(b"C")));
+        assert!(reader.read_message().unwrap().is_none());
+    }
+
+    #[test]
+    fn stream_ends_within_frame() {
+        let data = concatenated_frames();
+        let mut reader = StreamReader::new(decoder(), &data[..50]);
+        assert!(reader.read_message().unwrap().is_some());
+        assert!(matches!(
+            reader.read_message(),
+            Err(DecodeError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn oversized_length_headers_dont_allocate_upfront() {
+        let mut data = vec![0xFF, 0xFF, 0xFF, 0xFF, 0xF0, 0x00];
+        data.extend_from_slice(b"8=FIX.4.4|");
+        let mut reader = StreamReader::new(decoder(), &data[..]);
+        let err = reader.read_message().map(|_| ()).unwrap_err();
+        assert!(matches!(err, DecodeError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof));
+        assert!(reader.buffer.capacity() <= MAX_READ_LEN * 2);
+    }
+
+    #[test]
+    fn async_stream_of_frames() {
+        let data = concatenated_frames();
+        let mut reader = StreamReader::new(decoder(), futures::io::Cursor::new(&data));
+        futures::executor::block_on(async {
+            let msg = reader.read_message_async().await.unwrap().unwrap();
+            assert!(matches!(msg, FramedMessage::TagValue(msg) if msg.get_raw(49) == Some(b"A")));
+            let msg = reader.read_message_async().await.unwrap().unwrap();
+            assert!(matches!(
+                msg,
+                FramedMessage::Passthrough {
+                    encoding_type: 0xEB50,
+                    payload: &[1, 2, 3],
+                }
+            ));
+            let msg = reader.read_message_async().await.unwrap().unwrap();
+            assert!(matches!(msg, FramedMessage::TagValue(msg) if msg.get_raw(49) == Some(b"C")));
+            assert!(reader.read_message_async().await.unwrap().is_none());
+        });
+    }
+}
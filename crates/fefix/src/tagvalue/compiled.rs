@@ -0,0 +1,260 @@
+use super::Config;
+use crate::field_types::CheckSum;
+use crate::{Buffer, Dictionary, FieldType};
+
+// Same as `Encoder`, which reserves eight zero-padded digits.
+const BODY_LENGTH_DIGITS: usize = 8;
+
+/// The type returned in the event of an error by [`CompiledMessage::new`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum CompileError {
+    /// `MsgType <35>` is not defined in the [`Dictionary`].
+    #[error("Unknown `MsgType <35>`.")]
+    UnknownMsgType,
+    /// The layout contains tag 0 or a field which is written by
+    /// [`CompiledMessage::emit`] itself, i.e. `BeginString <8>`,
+    /// `BodyLength <9>`, `CheckSum <10>`, or `MsgType <35>`.
+    #[error("Tag {0} can't be part of a compiled layout.")]
+    InvalidTag(u32),
+    /// The layout contains a `NumInGroup` field. Repeating groups have a
+    /// variable number of fields and can't be compiled.
+    #[error("Repeating group {0} can't be part of a compiled layout.")]
+    RepeatingGroup(u32),
+}
+
+/// A precompiled layout of a FIX message with a fixed set of fields, e.g.
+/// `Heartbeat <0>` or order acknowledgements.
+///
+/// All tags and the checksum of everything but field values are computed by
+/// [`CompiledMessage::new`], so that [`CompiledMessage::emit`] only needs to
+/// copy values into the buffer. No allocations happen if the buffer has
+/// enough capacity, and no [`Dictionary`] lookups at all. The output is
+/// byte-identical to that of [`Encoder`](super::Encoder), given the same
+/// fields in the same order.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{CompiledMessage, Config};
+/// use fefix::prelude::*;
+///
+/// let mut config = Config::default();
+/// config.separator = b'|';
+/// let heartbeat = CompiledMessage::new(
+///     &Dictionary::fix44(),
+///     &config,
+///     b"FIX.4.4",
+///     b"0",
+///     &[49, 56, 34, 52],
+/// )
+/// .unwrap();
+///
+/// let mut buffer = Vec::new();
+/// let (msg, _) = heartbeat.emit(&mut buffer, &[b"A", b"B", b"2", b"20210101-10:00:00"]);
+/// assert_eq!(msg, b"8=FIX.4.4|9=00000041|35=0|49=A|56=B|34=2|52=20210101-10:00:00|10=236|");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledMessage {
+    separator: u8,
+    // `8=...|9=`
+    header: Vec<u8>,
+    // `|35=...|`, i.e. from the end of `BodyLength <9>`'s value up to the
+    // first field of the layout.
+    msg_type: Vec<u8>,
+    // `49=`, `56=`, etc. in order.
+    field_prefixes: Vec<Vec<u8>>,
+    // The length and checksum of the body, without any field values.
+    constant_body_len: usize,
+    constant_checksum: u8,
+}
+
+impl CompiledMessage {
+    /// Compiles the layout of messages with `begin_string` and `msg_type`,
+    /// which have the fields with `tags` in the given order. `dict` is only
+    /// used to check the layout, and the field separator is taken from
+    /// `config`.
+    pub fn new(
+        dict: &Dictionary,
+        config: &Config,
+        begin_string: &[u8],
+        msg_type: &[u8],
+        tags: &[u32],
+    ) -> Result<Self, CompileError> {
+        let msg_type_str =
+            std::str::from_utf8(msg_type).map_err(|_| CompileError::UnknownMsgType)?;
+        if dict.message_by_msgtype(msg_type_str).is_none() {
+            return Err(CompileError::UnknownMsgType);
+        }
+        let separator = config.separator;
+        let mut field_prefixes = Vec::with_capacity(tags.len());
+        for &tag in tags {
+            if matches!(tag, 0 | 8 | 9 | 10 | 35) {
+                return Err(CompileError::InvalidTag(tag));
+            }
+            if dict
+                .field_by_tag(tag)
+                .is_some_and(|field| field.is_num_in_group())
+            {
+                return Err(CompileError::RepeatingGroup(tag));
+            }
+            let mut prefix = Vec::new();
+            tag.serialize(&mut prefix);
+            prefix.push(b'=');
+            field_prefixes.push(prefix);
+        }
+        let mut header = b"8=".to_vec();
+        header.extend_from_slice(begin_string);
+        header.push(separator);
+        header.extend_from_slice(b"9=");
+        let mut msg_type_field = vec![separator];
+        msg_type_field.extend_from_slice(b"35=");
+        msg_type_field.extend_from_slice(msg_type);
+        msg_type_field.push(separator);
+
+        // Everything after the separator of `BodyLength <9>`.
+        let constant_body_len = msg_type_field.len() - 1
+            + field_prefixes
+                .iter()
+                .map(|prefix| prefix.len() + 1)
+                .sum::<usize>();
+        let mut checksum = CheckSum::compute(&header).0;
+        checksum = checksum.wrapping_add(CheckSum::compute(&msg_type_field).0);
+        for prefix in field_prefixes.iter() {
+            checksum = checksum
+                .wrapping_add(CheckSum::compute(prefix).0)
+                .wrapping_add(separator);
+        }
+        Ok(Self {
+            separator,
+            header,
+            msg_type: msg_type_field,
+            field_prefixes,
+            constant_body_len,
+            constant_checksum: checksum,
+        })
+    }
+
+    /// Returns the number of fields in the layout of `self`, i.e. the number
+    /// of values that [`CompiledMessage::emit`] expects.
+    pub fn len(&self) -> usize {
+        self.field_prefixes.len()
+    }
+
+    /// Returns `true` if `self` has no fields other than `BeginString <8>`,
+    /// `BodyLength <9>`, `MsgType <35>`, and `CheckSum <10>`.
+    pub fn is_empty(&self) -> bool {
+        self.field_prefixes.is_empty()
+    }
+
+    /// Appends a whole FIX message to `buffer`, with `values` as the values
+    /// of the fields of the layout (in the same order). Returns the contents
+    /// of `buffer` and the offset of the message within it, like
+    /// [`EncoderHandle::done`](super::EncoderHandle::done).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of `values` doesn't match
+    /// [`CompiledMessage::len`].
+    pub fn emit<'a, B>(&self, buffer: &'a mut B, values: &[&[u8]]) -> (&'a [u8], usize)
+    where
+        B: Buffer,
+    {
+        assert_eq!(
+            values.len(),
+            self.field_prefixes.len(),
+            "wrong number of field values"
+        );
+        let start = buffer.len();
+        let mut body_len = self.constant_body_len;
+        let mut checksum = self.constant_checksum;
+        for value in values {
+            body_len += value.len();
+            checksum = checksum.wrapping_add(CheckSum::compute(value).0);
+        }
+        let mut body_len_digits = [b'0'; BODY_LENGTH_DIGITS];
+        let mut n = body_len;
+        for digit in body_len_digits.iter_mut().rev() {
+            *digit = b'0' + (n % 10) as u8;
+            n /= 10;
+        }
+        debug_assert_eq!(n, 0);
+        checksum = checksum.wrapping_add(CheckSum::compute(&body_len_digits).0);
+
+        buffer.extend_from_slice(&self.header);
+        buffer.extend_from_slice(&body_len_digits);
+        buffer.extend_from_slice(&self.msg_type);
+        for (prefix, value) in self.field_prefixes.iter().zip(values) {
+            buffer.extend_from_slice(prefix);
+            buffer.extend_from_slice(value);
+            buffer.extend_from_slice(&[self.separator]);
+        }
+        buffer.extend_from_slice(b"10=");
+        CheckSum(checksum).serialize(buffer);
+        buffer.extend_from_slice(&[self.separator]);
+        (buffer.as_slice(), start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Encoder;
+    use crate::{GetConfig, SetField};
+
+    const TAGS: &[u32] = &[49, 56, 34, 52, 37, 11, 17, 150, 39, 55, 54, 151, 14, 6];
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.separator = b'|';
+        config
+    }
+
+    #[test]
+    fn output_is_identical_to_encoder() {
+        let values: [&[u8]; 14] = [
+            b"BROKER",
+            b"CLIENT12",
+            b"1000",
+            b"20210101-10:00:00.000",
+            b"ORDER-1",
+            b"13346",
+            b"EXEC-1",
+            b"0",
+            b"0",
+            b"EUR/USD",
+            b"1",
+            b"1000000",
+            b"0",
+            b"0",
+        ];
+        let compiled =
+            CompiledMessage::new(&Dictionary::fix44(), &config(), b"FIX.4.4", b"8", TAGS).unwrap();
+        let mut buffer = b"garbage".to_vec();
+        let (data, start) = compiled.emit(&mut buffer, &values);
+        let compiled_msg = data[start..].to_vec();
+
+        let mut encoder = Encoder::new();
+        *encoder.config_mut() = config();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"8");
+        for (tag, value) in TAGS.iter().zip(values) {
+            msg.set(*tag, value);
+        }
+        assert_eq!(compiled_msg, msg.done().0);
+    }
+
+    #[test]
+    fn invalid_layouts() {
+        let dict = Dictionary::fix44();
+        let compile = |msg_type: &[u8], tags: &[u32]| {
+            CompiledMessage::new(&dict, &config(), b"FIX.4.4", msg_type, tags).unwrap_err()
+        };
+        assert_eq!(compile(b"??", &[49]), CompileError::UnknownMsgType);
+        assert_eq!(compile(b"0", &[49, 10]), CompileError::InvalidTag(10));
+        assert_eq!(
+            compile(b"X", &[268, 279]),
+            CompileError::RepeatingGroup(268)
+        );
+    }
+}
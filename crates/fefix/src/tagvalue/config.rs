@@ -1,5 +1,8 @@
+use super::SigningConvention;
+
 const SOH: u8 = 0x1;
 const DEFAULT_MAX_MESSAGE_SIZE: usize = 0xffff;
+const DEFAULT_MAX_GROUP_ENTRIES: usize = 100_000;
 
 /// Configuration options for [`Encoder`](super::Encoder) and
 /// [`Decoder`](super::Decoder).
@@ -15,13 +18,125 @@ pub struct Config {
     /// The maximum allowed size for any single FIX message. No restrictions are
     /// imposed when it is [`None`].
     pub max_message_size: Option<usize>,
+    /// The maximum allowed number of entries for any single repeating group,
+    /// as declared by its `NumInGroup` field. No restrictions are imposed when
+    /// it is [`None`].
+    ///
+    /// This setting has no effect when encoding FIX messages. It is 100,000 by
+    /// default.
+    pub max_group_entries: Option<usize>,
     /// Determines whether or not `CheckSum(10)` should be verified.
     ///
     /// This setting has no effect when encoding FIX messages.
     pub verify_checksum: bool,
-    /// Determines whether or not the decoder needs to have access to
-    /// associative FIX fields. If turned off, only linear access is possible.
+    /// Determines whether or not the decoder builds the index of fields by tag
+    /// at decode time. If turned off, the index is built lazily upon the first
+    /// lookup (e.g. [`FieldMap::get`](crate::FieldMap::get)) instead, which is
+    /// cheaper for messages that are only iterated over or rarely looked into.
+    /// In both cases, lookups are O(1) and the index is only valid for the
+    /// message at hand: it is discarded by the next call to `decode`.
+    ///
+    /// It is enabled by default.
     pub should_decode_associative: bool,
+    /// Determines whether or not the decoder checks the values of fields
+    /// with datatype `int`, `Length`, `NumInGroup`, `SeqNum`, and `TagNum`
+    /// against the range and sign constraints of their datatype (see
+    /// [`check_int_subtype`](crate::field_types::check_int_subtype)). The
+    /// datatype of each field is looked up in the dictionary.
+    ///
+    /// This setting has no effect when encoding FIX messages. It is disabled
+    /// by default, so that such values are left to validation and typed
+    /// access.
+    pub should_check_int_subtypes: bool,
+    /// Determines whether or not the decoder refuses leading zeros in the
+    /// values of fields whose datatype forbids them, i.e. `TagNum` (see
+    /// [`check_leading_zeros`](crate::field_types::check_leading_zeros)).
+    /// Leading zeros are allowed in all other integer fields, e.g.
+    /// `BodyLength <9>` and `MsgSeqNum <34>`, regardless of this setting.
+    ///
+    /// This setting has no effect when encoding FIX messages. It is disabled
+    /// by default.
+    pub should_check_leading_zeros: bool,
+    /// Determines whether or not the decoder removes the leading zeros of the
+    /// values of all integer fields, e.g. `MsgSeqNum <34>` `0042` becomes
+    /// `42`, so that re-encoded messages have canonical values. `BodyLength
+    /// <9>` is always computed anew by [`Encoder`](super::Encoder).
+    ///
+    /// This setting has no effect when encoding FIX messages. It is disabled
+    /// by default.
+    pub should_strip_leading_zeros: bool,
+    /// Determines whether or not fields with datatype `data` must be
+    /// immediately preceded by their `Length` field, as mandated by the FIX
+    /// specification. When turned off, the value of `data` fields without a
+    /// `Length` field simply ends at the next separator, which allows to
+    /// decode some nonstandard feeds.
+    ///
+    /// This setting has no effect when encoding FIX messages. It is enabled by
+    /// default.
+    pub should_check_data_lengths: bool,
+    /// Determines whether or not the decoder validates the trailer of
+    /// messages, i.e. that `CheckSum <10>` is present right after the body,
+    /// that it's the last field, and that its value has three digits.
+    /// Violations result in [`DecodeError::MissingCheckSum`],
+    /// [`DecodeError::FieldAfterCheckSum`], and [`DecodeError::CheckSum`]
+    /// respectively. When turned off, the last bytes of the message are assumed
+    /// to be `CheckSum <10>` without further inspection.
+    ///
+    /// [`DecodeError::MissingCheckSum`]: super::DecodeError::MissingCheckSum
+    /// [`DecodeError::FieldAfterCheckSum`]: super::DecodeError::FieldAfterCheckSum
+    /// [`DecodeError::CheckSum`]: super::DecodeError::CheckSum
+    ///
+    /// This setting has no effect when encoding FIX messages. It is enabled by
+    /// default.
+    pub should_check_trailer: bool,
+    /// Determines whether or not the decoder records the byte range of every
+    /// field value within the original message, as returned by
+    /// [`Message::field_spans`](super::Message::field_spans). This is useful
+    /// for tooling that must map fields back to raw bytes, e.g. to highlight
+    /// them. When turned off, no spans are recorded at all.
+    ///
+    /// This setting has no effect when encoding FIX messages. It is disabled by
+    /// default.
+    pub should_record_spans: bool,
+    /// Determines whether or not [`Decoder::decode`](super::Decoder::decode)
+    /// corrects recoverable framing errors instead of rejecting them: wrong
+    /// or missing `BodyLength <9>` and `CheckSum <10>` values are computed
+    /// anew, and a missing separator after the last field is added. All
+    /// corrections are listed by
+    /// [`Message::repairs`](super::Message::repairs), and
+    /// [`Message::as_bytes`](super::Message::as_bytes) returns the repaired
+    /// message. This doesn't apply to streaming decoders, which rely on
+    /// `BodyLength <9>` to find the end of each message.
+    ///
+    /// This is meant for recovering archived or mis-generated data, and it
+    /// should never be used for messages received over a FIX session. This
+    /// setting has no effect when encoding FIX messages. It is disabled by
+    /// default.
+    pub should_repair_framing: bool,
+    /// Determines which bytes of messages are covered by `Signature <89>`,
+    /// both for [`Decoder::set_signature_verifier`](super::Decoder::set_signature_verifier)
+    /// and [`Message::signable_bytes`](super::Message::signable_bytes). By
+    /// default, that's everything up to `SignatureLength <93>`.
+    ///
+    /// This setting has no effect when encoding FIX messages.
+    pub signing_convention: SigningConvention,
+    /// Determines whether or not
+    /// [`Message::group_count`](super::Message::group_count) fails with
+    /// [`GroupCountError::Mismatch`](super::GroupCountError::Mismatch) when
+    /// the `NumInGroup` field of a repeating group doesn't match the number
+    /// of its entries. When turned off, mismatches are only logged as
+    /// warnings.
+    ///
+    /// This setting has no effect when encoding FIX messages. It is enabled by
+    /// default.
+    pub should_check_group_counts: bool,
+    /// Determines whether or not the `BeginString <8>` override of
+    /// [`Encoder::encode_with_begin_string`](super::Encoder::encode_with_begin_string)
+    /// must look like `FIX.x.y` or `FIXT.1.1`.
+    ///
+    /// This setting has no effect when decoding FIX messages. It is enabled by
+    /// default.
+    pub should_check_begin_string: bool,
 }
 
 impl Default for Config {
@@ -29,8 +144,19 @@ impl Default for Config {
         Self {
             separator: SOH,
             max_message_size: Some(DEFAULT_MAX_MESSAGE_SIZE),
+            max_group_entries: Some(DEFAULT_MAX_GROUP_ENTRIES),
             verify_checksum: true,
             should_decode_associative: true,
+            should_check_int_subtypes: false,
+            should_check_leading_zeros: false,
+            should_strip_leading_zeros: false,
+            should_check_data_lengths: true,
+            should_check_trailer: true,
+            should_record_spans: false,
+            should_repair_framing: false,
+            signing_convention: SigningConvention::default(),
+            should_check_group_counts: true,
+            should_check_begin_string: true,
         }
     }
 }
@@ -1,15 +1,28 @@
-use super::{Config, DecodeError, RawDecoder, RawDecoderStreaming, RawFrame};
-use crate::dict::{FixDatatype, IsFieldDefinition};
+use super::{
+    repair, utils, Config, DecodeError, FieldTransform, FramingRepair, RawDecoder,
+    RawDecoderStreaming, RawFrame, TextPolicy,
+};
+use crate::dict::{
+    AppVersion, FieldLocation, FixDatatype, IsFieldDefinition, LayoutItem, LayoutItemKind,
+    VersionInfo,
+};
+use crate::field_types::{
+    check_int_subtype, check_leading_zeros, is_int_datatype, leading_zeros, CheckSum,
+    IntSubtypeError, TypedValue,
+};
 use crate::{
     Buffer, Dictionary, FieldMap, FieldType, FieldValueError, GetConfig, RepeatingGroup,
     StreamingDecoder, TagU32,
 };
-use nohash_hasher::IntMap;
+use nohash_hasher::{IntMap, IntSet};
+use std::borrow::{Borrow, Cow};
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::Debug;
 use std::iter::FusedIterator;
 use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::OnceLock;
 
 /// Univocally locates a tag within a FIX message, even with nested groups.
 ///
@@ -34,12 +47,85 @@ enum FieldLocatorContext {
     },
 }
 
+/// Describes where a field is located within a FIX message. See
+/// [`Message::for_each_with_context`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FieldContext<'a> {
+    /// Whether the field belongs to the `StandardHeader`, the body, or the
+    /// `StandardTrailer`.
+    pub location: FieldLocation,
+    /// All repeating group entries that enclose the field, from the outermost
+    /// to the innermost one. This is empty for fields outside of groups.
+    pub groups: &'a [GroupEntryContext],
+}
+
+/// A single repeating group entry within a [`FieldContext`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct GroupEntryContext {
+    /// The `NumInGroup` field that starts the repeating group, e.g.
+    /// `NoMDEntries <268>`.
+    pub num_in_group_tag: TagU32,
+    /// The zero-based index of the entry within its repeating group.
+    pub entry_index: usize,
+}
+
+/// The type returned in the event of an error by [`Message::group_count`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum GroupCountError {
+    /// The `NumInGroup` field doesn't match the number of group entries in
+    /// the message.
+    #[error("Repeating group {tag} declares {declared} entries, but has {actual}.")]
+    Mismatch {
+        /// The `NumInGroup` tag of the repeating group.
+        tag: u32,
+        /// The value of the `NumInGroup` field.
+        declared: u32,
+        /// The number of entries that were actually decoded.
+        actual: u32,
+    },
+}
+
+/// The byte range of a field value within the message it was decoded from.
+/// See [`Message::field_spans`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FieldSpan {
+    /// The tag of the field.
+    pub tag: TagU32,
+    /// The offset of the first byte of the field value.
+    pub value_start: usize,
+    /// The offset of the first byte after the field value, i.e. the
+    /// separator.
+    pub value_end: usize,
+}
+
+// A rough estimate of the average size of a field, e.g. `55=EUR/USD|`. It
+// doesn't need to be accurate at all, as it's only used to pre-allocate field
+// storage.
+const AVG_FIELD_LEN_IN_BYTES: usize = 8;
+
 // Number of bytes before the start of the `BeginString` field:
 //
 //   ~~
 //   8=FIX.4.2|...
 const BEGIN_STRING_OFFSET: usize = 2;
 
+// Tags have at most 9 digits, which always fit in a `u32`.
+const MAX_TAG_LEN: usize = 9;
+
+// Longer field values are truncated by `Debug`.
+const DEBUG_MAX_FIELD_VALUE_LEN: usize = 64;
+
+// The shortest possible group entry is a single field like `1=a|`.
+const MIN_GROUP_ENTRY_LEN: usize = 4;
+
+// A `SeqNum`, except that `0` stands for infinity.
+const END_SEQ_NO: u32 = 16;
+const SIGNATURE_LENGTH: u32 = 93;
+const SIGNATURE: u32 = 89;
+const APPL_VER_ID: u32 = 1128;
+const CSTM_APPL_VER_ID: u32 = 1129;
+
 /// FIX message decoder.
 ///
 /// One should create a [`Decoder`] per stream of FIX messages.
@@ -47,35 +133,232 @@ const BEGIN_STRING_OFFSET: usize = 2;
 pub struct Decoder {
     builder: MessageBuilder<'static>,
     raw_decoder: RawDecoder,
-    tag_lookup: IntMap<u32, FixDatatype>,
+    // The first one is for the default dictionary, the others for
+    // `ApplVerID <1128>` and `CstmApplVerID <1129>` overrides.
+    tag_lookups: Vec<TagLookup>,
+    i_tag_lookup: usize,
+    is_cstm_appl_ver_id_active: bool,
+    appl_ver_ids: IntMap<u32, HashMap<Vec<u8>, usize>>,
+    section_lookup: IntMap<u32, FieldLocation>,
+    signature_verifier: Option<SignatureVerifier>,
+    field_transforms: IntMap<u32, FieldTransform>,
+    text_policy: Option<TextPolicy>,
+    // The values of the last message that were changed by `field_transforms`
+    // or `text_policy`.
+    transformed_values: Vec<Vec<u8>>,
+    // The last message given to `Decoder::decode_lenient_fixture`.
+    fixture: Vec<u8>,
+    // The last message decoded with `Config::should_repair_framing`, and its
+    // repairs until `Decoder::from_frame` moves them to `builder`.
+    repaired: Vec<u8>,
+    repairs: Vec<FramingRepair>,
+}
+
+type VerifySignature = dyn FnMut(&[u8], &[u8]) -> bool + Send;
+
+struct SignatureVerifier(Box<VerifySignature>);
+
+impl Debug for SignatureVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SignatureVerifier")
+    }
 }
 
 impl Decoder {
     /// Creates a new [`Decoder`] for the tag-value format. `dict` is used to parse
-    /// messages.
-    pub fn new(dict: Dictionary) -> Self {
+    /// messages. It can be either an owned [`Dictionary`] or a shared one, e.g.
+    /// from [`Dictionary::precompiled`].
+    pub fn new<D>(dict: D) -> Self
+    where
+        D: Borrow<Dictionary>,
+    {
+        let dict = dict.borrow();
+        let mut section_lookup = IntMap::default();
+        for (component_name, location) in [
+            ("StandardHeader", FieldLocation::Header),
+            ("StandardTrailer", FieldLocation::Trailer),
+        ] {
+            if let Some(component) = dict.component_by_name(component_name) {
+                let mut tags = vec![];
+                collect_tags(dict, component.items(), &mut tags);
+                section_lookup.extend(tags.into_iter().map(|tag| (tag, location)));
+            }
+        }
         Self {
             builder: MessageBuilder::default(),
             raw_decoder: RawDecoder::default(),
-            tag_lookup: dict
-                .fields()
-                .iter()
-                .filter_map(|field| {
-                    let mut fix_type = field.data_type().basetype();
-                    if field.is_num_in_group() {
-                        fix_type = FixDatatype::NumInGroup;
-                    }
-
-                    if fix_type == FixDatatype::Length || fix_type == FixDatatype::NumInGroup {
-                        Some((field.tag().get(), fix_type))
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
+            tag_lookups: vec![tag_lookup(dict)],
+            i_tag_lookup: 0,
+            is_cstm_appl_ver_id_active: false,
+            appl_ver_ids: IntMap::default(),
+            section_lookup,
+            signature_verifier: None,
+            field_transforms: IntMap::default(),
+            text_policy: None,
+            transformed_values: Vec::new(),
+            fixture: Vec::new(),
+            repaired: Vec::new(),
+            repairs: Vec::new(),
         }
     }
 
+    /// Requires all messages to be signed, and checks their signature with
+    /// `verifier`. The first argument of `verifier` is the signed byte range
+    /// as per [`Config::signing_convention`], by default everything from
+    /// `BeginString <8>` up to and including the separator before
+    /// `SignatureLength <93>`; see [`Message::signable_bytes`]. The second
+    /// argument is the value of `Signature <89>`. Messages without a
+    /// signature, or for which `verifier` returns `false`, result in
+    /// [`DecodeError::SignatureInvalid`].
+    ///
+    /// FerrumFIX doesn't implement any cryptographic algorithms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{DecodeError, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// // A very insecure signature algorithm.
+    /// decoder.set_signature_verifier(|data, signature| {
+    ///     signature == format!("{}", data.len()).as_bytes()
+    /// });
+    ///
+    /// let msg = b"8=FIX.4.4|9=31|35=0|49=A|56=B|34=2|93=2|89=35|10=000|";
+    /// assert!(decoder.decode(msg).is_ok());
+    /// let msg = b"8=FIX.4.4|9=31|35=0|49=A|56=B|34=2|93=2|89=99|10=000|";
+    /// assert!(matches!(decoder.decode(msg), Err(DecodeError::SignatureInvalid)));
+    /// ```
+    pub fn set_signature_verifier<F>(&mut self, verifier: F)
+    where
+        F: FnMut(&[u8], &[u8]) -> bool + Send + 'static,
+    {
+        self.signature_verifier = Some(SignatureVerifier(Box::new(verifier)));
+    }
+
+    /// Rewrites the values of all fields with `tag` (within repeating groups
+    /// too) with the decoding half of `transform`, replacing any previous
+    /// transform for `tag`. If the field is of type `data`, its `Length` field
+    /// is updated accordingly.
+    ///
+    /// Values are transformed after everything else, i.e. `BodyLength <9>`,
+    /// `CheckSum <10>`, signatures, and repeating groups are all validated
+    /// against the bytes on the wire. The same goes for
+    /// [`Message::as_bytes`], [`Message::checksum`], and
+    /// [`Message::field_spans`]. Transforms for `BeginString <8>`,
+    /// `BodyLength <9>`, `MsgType <35>`, and `CheckSum <10>` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, FieldTransform};
+    /// use fefix::prelude::*;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// decoder.add_field_transform(
+    ///     55,
+    ///     FieldTransform::new(
+    ///         |symbol| Cow::Owned([symbol, b".N"].concat()),
+    ///         |symbol| Cow::Borrowed(symbol.strip_suffix(b".N").unwrap_or(symbol)),
+    ///     ),
+    /// );
+    /// let msg = decoder.decode(b"8=FIX.4.4|9=14|35=D|55=ABC.N|10=000|").unwrap();
+    /// assert_eq!(msg.get(fix44::SYMBOL), Ok("ABC"));
+    /// ```
+    pub fn add_field_transform(&mut self, tag: u32, transform: FieldTransform) {
+        self.field_transforms.insert(tag, transform);
+    }
+
+    /// Applies `policy` to the values of free-text fields, after
+    /// [`Decoder::add_field_transform`]. Fields with control characters
+    /// result in [`DecodeError::Text`] under [`ControlChars::Reject`], and
+    /// are otherwise rewritten like transformed fields. There's no text
+    /// policy by default.
+    ///
+    /// [`ControlChars::Reject`]: super::ControlChars::Reject
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{ControlChars, Decoder, TextPolicy};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// decoder.set_text_policy(TextPolicy::new(ControlChars::Strip));
+    /// let msg = decoder.decode(b"8=FIX.4.4|9=13|35=0|58=ab\x07c|10=000|").unwrap();
+    /// assert_eq!(msg.get(fix44::TEXT), Ok("abc"));
+    /// ```
+    pub fn set_text_policy(&mut self, policy: TextPolicy) {
+        self.text_policy = Some(policy);
+    }
+
+    /// Instructs `self` to decode the body of messages with
+    /// `ApplVerID <1128>` equal to `appl_ver_id` according to `dict`, instead
+    /// of the [`Dictionary`] given to [`Decoder::new`]. The latter is still
+    /// used for the header and for all other messages.
+    ///
+    /// This is useful for FIXT sessions which mix several application
+    /// versions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::AppVersion;
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.add_appl_ver_id(b"9", Dictionary::precompiled(AppVersion::Fix44));
+    /// ```
+    pub fn add_appl_ver_id<D>(&mut self, appl_ver_id: &[u8], dict: D)
+    where
+        D: Borrow<Dictionary>,
+    {
+        self.add_tag_lookup(APPL_VER_ID, appl_ver_id, dict.borrow());
+    }
+
+    /// Like [`Decoder::add_appl_ver_id`], but for `CstmApplVerID <1129>`.
+    /// `CstmApplVerID <1129>` takes precedence over `ApplVerID <1128>` when
+    /// both are present.
+    pub fn add_cstm_appl_ver_id<D>(&mut self, cstm_appl_ver_id: &[u8], dict: D)
+    where
+        D: Borrow<Dictionary>,
+    {
+        self.add_tag_lookup(CSTM_APPL_VER_ID, cstm_appl_ver_id, dict.borrow());
+    }
+
+    fn add_tag_lookup(&mut self, tag: u32, value: &[u8], dict: &Dictionary) {
+        self.tag_lookups.push(tag_lookup(dict));
+        self.appl_ver_ids
+            .entry(tag)
+            .or_default()
+            .insert(value.to_vec(), self.tag_lookups.len() - 1);
+    }
+
+    /// Discards the last decoded message, so that `self` can be reused e.g.
+    /// after a [`DecodeError`] or across connections.
+    ///
+    /// Only per-message state is cleared, i.e. fields, repeating groups, and
+    /// the active [`Decoder::add_appl_ver_id`] and
+    /// [`Decoder::add_cstm_appl_ver_id`] override. Dictionaries and their
+    /// overrides, the [`Decoder::set_signature_verifier`] callback, field
+    /// transforms and the text policy, configuration options, and the
+    /// capacity of internal buffers are preserved.
+    pub fn reset(&mut self) {
+        self.builder.clear();
+        self.transformed_values.clear();
+        self.i_tag_lookup = 0;
+        self.is_cstm_appl_ver_id_active = false;
+    }
+
     /// Adds a [`Buffer`] to `self`, turning it into a [`StreamingDecoder`].
     pub fn streaming<B>(self, buffer: B) -> DecoderStreaming<B>
     where
@@ -111,10 +394,230 @@ impl Decoder {
     where
         T: AsRef<[u8]>,
     {
+        trace_span!("decode", len = bytes.as_ref().len());
+        if self.config().should_repair_framing {
+            let separator = self.config().separator;
+            repair::repair_framing(
+                bytes.as_ref(),
+                separator,
+                &mut self.repaired,
+                &mut self.repairs,
+            )?;
+            // `self.repaired` is only modified above, so it can't change
+            // while the returned message borrows `self`.
+            let data: &[u8] = unsafe { &*(self.repaired.as_slice() as *const [u8]) };
+            let frame = self.raw_decoder.decode(data)?;
+            let message = self.from_frame(frame)?;
+            return Ok(Message {
+                builder: message.builder,
+                phantom: PhantomData,
+                field_locator_context: message.field_locator_context,
+            });
+        }
         let frame = self.raw_decoder.decode(bytes)?;
         self.from_frame(frame)
     }
 
+    /// Decodes `fixture`, a FIX message written by hand e.g. for tests, in the
+    /// style of QuickFIX test suites. Fields can be delimited by SOH
+    /// (`\x01`), `|`, or `^A`, regardless of [`Config::separator`], and
+    /// `BodyLength <9>` and `CheckSum <10>` can be omitted or wrong, as they're
+    /// computed anew. Everything else is decoded just like
+    /// [`Decoder::decode`] would.
+    ///
+    /// This is a convenience for authoring test data, and it should never be
+    /// used for messages received over the network.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// let message = decoder
+    ///     .decode_lenient_fixture("8=FIX.4.4^A35=0^A49=A^A56=B^A34=12^A")
+    ///     .unwrap();
+    /// assert_eq!(message.get(fix44::MSG_SEQ_NUM), Ok(12));
+    /// let data = b"8=FIX.4.4\x019=21\x0135=0\x0149=A\x0156=B\x0134=12\x0110=176\x01";
+    /// assert_eq!(message.as_bytes(), data);
+    /// ```
+    pub fn decode_lenient_fixture(
+        &mut self,
+        fixture: &str,
+    ) -> Result<Message<'_, &[u8]>, DecodeError> {
+        let separator = self.config().separator;
+        utils::normalize_fixture(fixture, separator, &mut self.fixture)?;
+        // `self.fixture` is only modified above, so it can't change while the
+        // returned message borrows `self`.
+        let data: &[u8] = unsafe { &*(self.fixture.as_slice() as *const [u8]) };
+        self.decode(data)
+    }
+
+    /// Decodes a message in the `tag=value|` form of
+    /// [`Message::to_tagvalue_string`], with the separator of
+    /// [`Config::separator`]. This is [`Decoder::decode_lenient_fixture`]
+    /// under another name, so SOH and `^A` are accepted as well, and
+    /// `BodyLength <9>` and `CheckSum <10>` are computed anew. Values that
+    /// contain `|` can't be told apart from separators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// let message = decoder
+    ///     .decode_tagvalue_string("8=FIX.4.4|9=21|35=0|49=A|56=B|34=12|10=146|")
+    ///     .unwrap();
+    /// assert_eq!(message.get(fix44::SENDER_COMP_ID), Ok("A"));
+    /// ```
+    pub fn decode_tagvalue_string(
+        &mut self,
+        string: &str,
+    ) -> Result<Message<'_, &[u8]>, DecodeError> {
+        self.decode_lenient_fixture(string)
+    }
+
+    /// Decodes only the header of the message in `data`, e.g. for routing
+    /// decisions which don't need the body. Fields are parsed up to the first
+    /// one which is not part of `StandardHeader` according to the
+    /// [`Dictionary`], so that the cost is independent of the size of the
+    /// body.
+    ///
+    /// The frame boundary is still validated, i.e. `BodyLength <9>` must match
+    /// the length of `data` and `CheckSum <10>` must follow the body, but
+    /// the checksum itself is not verified. Dictionary overrides, signature
+    /// verification, and field transforms don't apply either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=73|35=W|49=A|56=B|34=12|52=20100304-07:59:30|55=EUR/USD|268=1|269=0|270=1.5|10=000|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    ///
+    /// let header = decoder.decode_header(DATA).unwrap();
+    /// assert_eq!(header.msg_type(), b"W");
+    /// assert_eq!(header.sender_comp_id(), Some(b"A" as &[u8]));
+    /// assert_eq!(header.target_comp_id(), Some(b"B" as &[u8]));
+    /// assert_eq!(header.msg_seq_num(), Some(12));
+    /// assert_eq!(header.get_raw(55), None);
+    /// assert!(decoder.decode_header(&DATA[..40]).is_err());
+    /// ```
+    pub fn decode_header<'a>(&self, data: &'a [u8]) -> Result<HeaderView<'a>, DecodeError> {
+        let mut raw_decoder = self.raw_decoder.clone();
+        raw_decoder.config_mut().verify_checksum = false;
+        let frame = raw_decoder.decode(data)?;
+        let mut fields = Vec::new();
+        self.scan_fields(data, frame.payload, |tag, value| {
+            let is_header = tag.get() == 35
+                || self.section_lookup.get(&tag.get()) == Some(&FieldLocation::Header);
+            if is_header {
+                fields.push((tag, value));
+            }
+            is_header
+        })?;
+        let msg_type = fields
+            .iter()
+            .find(|(tag, _)| tag.get() == 35)
+            .map(|(_, value)| *value)
+            .ok_or(DecodeError::FieldPresence)?;
+        Ok(HeaderView {
+            bytes: data,
+            begin_string: &data[frame.begin_string],
+            msg_type,
+            fields,
+        })
+    }
+
+    /// Decodes the message in `data` into a plain map from tags to values,
+    /// e.g. for quick inspection in scripts. `BeginString <8>` is included,
+    /// but `BodyLength <9>` and `CheckSum <10>` are not.
+    ///
+    /// Framing is validated just like [`Decoder::decode`] would, but neither
+    /// field types nor repeating groups are. The map is lossy for repeated
+    /// tags, e.g. within repeating groups: the last value wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=66|35=W|49=A|56=B|34=12|55=EUR/USD|268=2|269=0|270=1.5|269=1|270=1.6|10=000|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    ///
+    /// let map = decoder.decode_to_map(DATA).unwrap();
+    /// assert_eq!(map[&8], b"FIX.4.4");
+    /// assert_eq!(map[&55], b"EUR/USD");
+    /// assert_eq!(map[&270], b"1.6");
+    /// assert!(!map.contains_key(&10));
+    /// ```
+    pub fn decode_to_map(&self, data: &[u8]) -> Result<HashMap<u32, Vec<u8>>, DecodeError> {
+        let frame = self.raw_decoder.decode(data)?;
+        let mut map = HashMap::new();
+        map.insert(8, data[frame.begin_string.clone()].to_vec());
+        self.scan_fields(data, frame.payload, |tag, value| {
+            map.insert(tag.get(), value.to_vec());
+            true
+        })?;
+        Ok(map)
+    }
+
+    // Calls `visit` for each field of `data` within `payload`, until it
+    // returns `false`. `data` fields are delimited by their `Length` fields
+    // alone, as per the `Dictionary`.
+    fn scan_fields<'a>(
+        &self,
+        data: &'a [u8],
+        payload: Range<usize>,
+        mut visit: impl FnMut(TagU32, &'a [u8]) -> bool,
+    ) -> Result<(), DecodeError> {
+        let separator = self.config().separator;
+        let datatypes = &self.tag_lookups[0].datatypes;
+        let mut data_field_length = None;
+        let mut i = payload.start;
+        while i < payload.end {
+            let i_eq = i + data[i..payload.end]
+                .iter()
+                .position(|byte| *byte == b'=')
+                .ok_or(DecodeError::Invalid)?;
+            let tag = parse_tag(&data[i..i_eq]).ok_or(DecodeError::MalformedTag { offset: i })?;
+            let value_start = i_eq + 1;
+            let value_len = match (data_field_length.take(), datatypes.get(&tag.get())) {
+                (Some(len), Some(FixDatatype::Data)) => len,
+                _ => data[value_start..payload.end]
+                    .iter()
+                    .position(|byte| *byte == separator)
+                    .ok_or(DecodeError::Invalid)?,
+            };
+            // `Length` values come straight from the wire, so they mustn't
+            // take us past the end of the payload.
+            let value_end = value_start
+                .checked_add(value_len)
+                .filter(|end| *end <= payload.end)
+                .ok_or(DecodeError::Invalid)?;
+            let value = &data[value_start..value_end];
+            if datatypes.get(&tag.get()) == Some(&FixDatatype::Length) {
+                data_field_length = usize::deserialize(value).ok();
+            }
+            if !visit(tag, value) {
+                break;
+            }
+            i = value_end + 1;
+        }
+        Ok(())
+    }
+
     fn message_builder_mut<'a>(&'a mut self) -> &'a mut MessageBuilder<'a> {
         unsafe { std::mem::transmute(&mut self.builder) }
     }
@@ -124,15 +627,30 @@ impl Decoder {
         T: AsRef<[u8]>,
     {
         self.builder.clear();
+        self.transformed_values.clear();
+        self.i_tag_lookup = 0;
+        self.is_cstm_appl_ver_id_active = false;
+        std::mem::swap(&mut self.builder.repairs, &mut self.repairs);
         self.message_builder_mut().bytes = frame.as_bytes();
         let separator = self.config().separator;
         let payload = frame.payload();
+        self.builder.reserve(payload.len());
+        self.builder.is_associative = self.config().should_decode_associative;
+        let should_record_spans = self.config().should_record_spans;
         self.store_field(
             TagU32::new(8).unwrap(),
             frame.as_bytes(),
             BEGIN_STRING_OFFSET,
             frame.begin_string().len(),
-        );
+        )?;
+        if should_record_spans {
+            self.builder.field_spans.push(FieldSpan {
+                tag: TagU32::new(8).unwrap(),
+                value_start: BEGIN_STRING_OFFSET,
+                value_end: BEGIN_STRING_OFFSET + frame.begin_string().len(),
+            });
+        }
+        self.builder.payload_start = frame.payload.start;
         let mut i = 0;
         while i < payload.len() {
             let index_of_next_equal_sign = {
@@ -147,7 +665,11 @@ impl Decoder {
                 i_eq.unwrap()
             };
             let field_value_len = if let Some(len) = self.builder.state.data_field_length {
-                self.builder.state.data_field_length = None;
+                // `data` fields may contain separators, so we must trust the
+                // preceding `Length` field... up to a point.
+                if payload.len() - index_of_next_equal_sign - 1 < len {
+                    return Err(DecodeError::Invalid);
+                }
                 len
             } else {
                 let len = (&payload[index_of_next_equal_sign + 1..])
@@ -159,32 +681,123 @@ impl Decoder {
                 }
                 len.unwrap()
             };
-            let tag_num = {
-                let mut tag = 0u32;
-                for byte in (&payload[i..index_of_next_equal_sign]).iter().copied() {
-                    tag = tag * 10 + (byte as u32 - b'0' as u32);
-                }
-                if let Some(tag) = TagU32::new(tag) {
-                    tag
-                } else {
-                    break;
-                }
-            };
+            let tag_num = parse_tag(&payload[i..index_of_next_equal_sign]).ok_or(
+                DecodeError::MalformedTag {
+                    offset: frame.payload.start + i,
+                },
+            )?;
+            if tag_num.get() == SIGNATURE_LENGTH {
+                let field_end = index_of_next_equal_sign + 1 + field_value_len + 1;
+                self.builder.signature_length =
+                    Some(frame.payload.start + i..frame.payload.start + field_end);
+            }
             self.store_field(
                 tag_num,
                 frame.payload(),
                 index_of_next_equal_sign + 1,
                 field_value_len,
-            );
+            )?;
+            if should_record_spans {
+                let value_start = frame.payload.start + index_of_next_equal_sign + 1;
+                self.builder.field_spans.push(FieldSpan {
+                    tag: tag_num,
+                    value_start,
+                    value_end: value_start + field_value_len,
+                });
+            }
             // Equal sign                ~~~
             // Separator                                       ~~~
             i = index_of_next_equal_sign + 1 + field_value_len + 1;
         }
-        Ok(Message {
+        self.builder.end_sections();
+        let config = *self.config();
+        if let Some(SignatureVerifier(verifier)) = self.signature_verifier.as_mut() {
+            let signature = self.builder.top_level_raw(SIGNATURE);
+            let is_valid = match (&self.builder.signature_length, signature) {
+                (Some(_), Some(signature)) => {
+                    verifier(self.builder.signed_bytes(&config), signature)
+                }
+                _ => false,
+            };
+            if !is_valid {
+                return Err(DecodeError::SignatureInvalid);
+            }
+        }
+        if !self.field_transforms.is_empty() {
+            self.apply_field_transforms();
+        }
+        if self.text_policy.is_some() {
+            self.apply_text_policy()?;
+        }
+        self.builder.version_info = self.tag_lookups[self.i_tag_lookup].version_info;
+        let message = Message {
             builder: self.message_builder_mut(),
             phantom: PhantomData::default(),
             field_locator_context: FieldLocatorContext::TopLevel,
-        })
+        };
+        trace_event!(
+            debug,
+            msg_type = %String::from_utf8_lossy(message.builder.top_level_raw(35).unwrap_or_default()),
+            msg_seq_num = message.builder.top_level_raw(34).and_then(|s| u64::deserialize(s).ok()),
+            len = message.as_bytes().len(),
+            "FIX message decoded"
+        );
+        Ok(message)
+    }
+
+    fn apply_field_transforms(&mut self) {
+        for i in 0..self.builder.field_values.len() {
+            let tag = self.builder.field_locators[i].tag.get();
+            if matches!(tag, 8 | 9 | 10 | 35) {
+                continue;
+            }
+            let value = self.builder.field_values[i];
+            let value = match self
+                .field_transforms
+                .get(&tag)
+                .and_then(|transform| transform.decode(value))
+            {
+                Some(value) => value,
+                None => continue,
+            };
+            if self.builder.data_field_indices.contains(&i) {
+                let len = self.store_transformed_value(value.len().to_bytes());
+                self.builder.replace_value(i - 1, len);
+            }
+            let value = self.store_transformed_value(value);
+            self.builder.replace_value(i, value);
+        }
+    }
+
+    fn apply_text_policy(&mut self) -> Result<(), DecodeError> {
+        let separator = self.config().separator;
+        for i in 0..self.builder.field_values.len() {
+            let tag = self.builder.field_locators[i].tag.get();
+            let policy = match self.text_policy.as_ref() {
+                Some(policy) if policy.applies_to(tag) && !matches!(tag, 8 | 9 | 10 | 35) => policy,
+                _ => continue,
+            };
+            let value = match policy.sanitize(tag, self.builder.field_values[i], separator)? {
+                Cow::Borrowed(_) => continue,
+                Cow::Owned(value) => value,
+            };
+            if self.builder.data_field_indices.contains(&i) {
+                let len = self.store_transformed_value(value.len().to_bytes());
+                self.builder.replace_value(i - 1, len);
+            }
+            let value = self.store_transformed_value(value);
+            self.builder.replace_value(i, value);
+        }
+        Ok(())
+    }
+
+    fn store_transformed_value(&mut self, value: Vec<u8>) -> &'static [u8] {
+        // SAFETY: the heap allocation of `value` doesn't move when
+        // `transformed_values` grows, and it's only freed after `builder` is
+        // cleared, i.e. when no message refers to it anymore.
+        let value_ref = unsafe { std::slice::from_raw_parts(value.as_ptr(), value.len()) };
+        self.transformed_values.push(value);
+        value_ref
     }
 
     fn store_field<'a>(
@@ -193,40 +806,230 @@ impl Decoder {
         raw_message: &'a [u8],
         field_value_start: usize,
         field_value_len: usize,
-    ) {
+    ) -> Result<(), DecodeError> {
         let config_assoc = self.config().should_decode_associative;
+        let config_check_int_subtypes = self.config().should_check_int_subtypes;
+        let config_check_leading_zeros = self.config().should_check_leading_zeros;
+        let config_strip_leading_zeros = self.config().should_strip_leading_zeros;
+        let config_max_group_entries = self.config().max_group_entries;
+        let config_check_data_lengths = self.config().should_check_data_lengths;
         let field_value = &raw_message[field_value_start..][..field_value_len];
+        let has_data_length = self.builder.state.data_field_length.take().is_some();
         if self.builder.state.new_group.is_some() {
             // We are entering a new group, but we still don't know which tag
             // will be the first one in each entry.
             self.builder.state.set_new_group(tag);
-        } else if let Some(group_info) = self.builder.state.group_information.last_mut() {
-            if group_info.current_entry_i >= group_info.num_entries {
-                self.builder.state.group_information.pop();
-            } else if tag == group_info.first_tag_of_every_group_entry {
-                group_info.current_entry_i += 1;
+        } else {
+            let group_members = &self.tag_lookups[self.i_tag_lookup].group_members;
+            while let Some(group_info) = self.builder.state.group_information.last_mut() {
+                // Fields which can't appear within entries of this group mark
+                // its end, and possibly that of its parent groups too.
+                let is_group_member = !matches!(
+                    group_members.get(&group_info.tag.get()),
+                    Some(members) if !members.contains(&tag.get())
+                );
+                if group_info.current_entry_i >= group_info.num_entries || !is_group_member {
+                    self.builder.state.group_information.pop();
+                } else {
+                    if tag == group_info.first_tag_of_every_group_entry {
+                        group_info.current_entry_i += 1;
+                    }
+                    break;
+                }
             }
         }
-        self.message_builder_mut()
-            .add_field(
-                tag,
-                &raw_message[field_value_start..][..field_value_len],
-                config_assoc,
-            )
-            .unwrap();
-        let fix_type = self.tag_lookup.get(&tag.get());
+        self.message_builder_mut().add_field(
+            tag,
+            &raw_message[field_value_start..][..field_value_len],
+            config_assoc,
+        )?;
+        if has_data_length {
+            let i = self.builder.field_locators.len() - 1;
+            self.builder.data_field_indices.push(i);
+        }
+        let location = self.section_lookup.get(&tag.get()).copied();
+        self.builder.update_section(location);
+        self.switch_tag_lookup(tag, field_value);
+        let fix_type = self.tag_lookups[self.i_tag_lookup]
+            .datatypes
+            .get(&tag.get());
+        let is_infinite_end_seq_no = tag.get() == END_SEQ_NO && field_value == b"0";
+        if let (true, Some(datatype), false) =
+            (config_check_int_subtypes, fix_type, is_infinite_end_seq_no)
+        {
+            check_int_subtype(*datatype, field_value)
+                .map_err(|reason| DecodeError::FieldValue { tag, reason })?;
+        }
+        if let (true, Some(datatype)) = (config_check_leading_zeros, fix_type) {
+            check_leading_zeros(*datatype, field_value)
+                .map_err(|reason| DecodeError::FieldValue { tag, reason })?;
+        }
+        let is_int = fix_type.is_some_and(|datatype| is_int_datatype(*datatype));
         if fix_type == Some(&FixDatatype::NumInGroup) {
-            self.builder
-                .state
-                .add_group(tag, self.builder.field_locators.len() - 1, field_value);
+            let num_entries = parse_usize(tag, field_value)?;
+            let remaining_len = raw_message.len() - (field_value_start + field_value_len);
+            if config_max_group_entries.is_some_and(|max| num_entries > max)
+                || num_entries > remaining_len / MIN_GROUP_ENTRY_LEN
+            {
+                return Err(DecodeError::GroupTooLarge { tag, num_entries });
+            }
+            let i = self.builder.field_locators.len() - 1;
+            self.builder.group_count_indices.push(i);
+            self.builder.state.add_group(tag, i, num_entries);
         } else if fix_type == Some(&FixDatatype::Length) {
-            // FIXME
-            let last_field_locator = self.builder.field_locators.last().unwrap();
-            let last_field = self.builder.fields.get(last_field_locator).unwrap();
-            let last_field_value = last_field.1;
-            let s = std::str::from_utf8(last_field_value).unwrap();
-            let data_field_length = str::parse(s).unwrap();
+            let data_field_length = parse_usize(tag, field_value)?;
             self.builder.state.data_field_length = Some(data_field_length);
+        } else if fix_type == Some(&FixDatatype::Data) && !has_data_length {
+            if config_check_data_lengths {
+                return Err(DecodeError::DataLength { tag });
+            }
+            trace_event!(
+                warn,
+                tag = tag.get(),
+                "`data` field without a preceding `Length` field, scanning for separator"
+            );
+        }
+        if config_strip_leading_zeros && is_int {
+            self.strip_leading_zeros(field_value);
+        }
+        Ok(())
+    }
+
+    // Replaces the value of the last field, which is an integer, with its
+    // canonical form.
+    fn strip_leading_zeros(&mut self, field_value: &[u8]) {
+        let zeros = leading_zeros(field_value);
+        if zeros == 0 {
+            return;
+        }
+        let i = self.builder.field_locators.len() - 1;
+        let value = match field_value.split_first() {
+            Some((b'-', digits)) => self.store_transformed_value([b"-", &digits[zeros..]].concat()),
+            _ => &field_value[zeros..],
+        };
+        self.message_builder_mut().replace_value(i, value);
+    }
+
+    fn switch_tag_lookup(&mut self, tag: TagU32, field_value: &[u8]) {
+        let i = self
+            .appl_ver_ids
+            .get(&tag.get())
+            .and_then(|lookups| lookups.get(field_value));
+        if let Some(i) = i {
+            // `CstmApplVerID <1129>` wins, regardless of field order.
+            if tag.get() == CSTM_APPL_VER_ID || !self.is_cstm_appl_ver_id_active {
+                self.i_tag_lookup = *i;
+                self.is_cstm_appl_ver_id_active = tag.get() == CSTM_APPL_VER_ID;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TagLookup {
+    version_info: VersionInfo,
+    datatypes: IntMap<u32, FixDatatype>,
+    // All tags that may appear within entries of each repeating group, by
+    // `NumInGroup` tag, across all messages.
+    group_members: IntMap<u32, IntSet<u32>>,
+}
+
+fn tag_lookup(dict: &Dictionary) -> TagLookup {
+    let datatypes = dict
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let mut fix_type = field.data_type().basetype();
+            if field.is_num_in_group() {
+                fix_type = FixDatatype::NumInGroup;
+            }
+
+            if matches!(
+                fix_type,
+                FixDatatype::Int
+                    | FixDatatype::Length
+                    | FixDatatype::NumInGroup
+                    | FixDatatype::SeqNum
+                    | FixDatatype::TagNum
+                    | FixDatatype::Data
+            ) {
+                Some((field.tag().get(), fix_type))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut group_members = IntMap::default();
+    for message in dict.messages() {
+        collect_group_members(dict, message.layout(), &mut group_members);
+    }
+    for component in dict.components() {
+        collect_group_members(dict, component.items(), &mut group_members);
+    }
+    TagLookup {
+        version_info: dict.version_info(),
+        datatypes,
+        group_members,
+    }
+}
+
+/// Parses the ASCII digits of a tag number. Leading zeros aside, tags longer
+/// than [`MAX_TAG_LEN`] digits are rejected, so adversarial input can't cause
+/// overflows.
+fn parse_tag(digits: &[u8]) -> Option<TagU32> {
+    let digits = &digits[digits.iter().take_while(|byte| **byte == b'0').count()..];
+    if digits.len() > MAX_TAG_LEN || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let tag = digits
+        .iter()
+        .fold(0u32, |tag, byte| tag * 10 + u32::from(byte - b'0'));
+    TagU32::new(tag)
+}
+
+fn parse_usize(tag: TagU32, field_value: &[u8]) -> Result<usize, DecodeError> {
+    usize::deserialize(field_value).map_err(|_| DecodeError::FieldValue {
+        tag,
+        reason: IntSubtypeError::NotAnInteger,
+    })
+}
+
+fn collect_tags<'a>(
+    dict: &Dictionary,
+    items: impl Iterator<Item = LayoutItem<'a>>,
+    tags: &mut Vec<u32>,
+) {
+    for item in items {
+        match item.kind() {
+            LayoutItemKind::Field(field) => tags.push(field.tag().get()),
+            LayoutItemKind::Group(field, items) => {
+                tags.push(field.tag().get());
+                collect_tags(dict, items.into_iter(), tags);
+            }
+            LayoutItemKind::Component(component) => {
+                if let Some(component) = dict.component_by_name(component.name()) {
+                    collect_tags(dict, component.items(), tags);
+                }
+            }
+        }
+    }
+}
+
+// Components are not visited, as they're collected on their own.
+fn collect_group_members<'a>(
+    dict: &Dictionary,
+    items: impl Iterator<Item = LayoutItem<'a>>,
+    group_members: &mut IntMap<u32, IntSet<u32>>,
+) {
+    for item in items {
+        if let LayoutItemKind::Group(field, items) = item.kind() {
+            let mut tags = vec![];
+            collect_tags(dict, items.iter().cloned(), &mut tags);
+            group_members
+                .entry(field.tag().get())
+                .or_default()
+                .extend(tags);
+            collect_group_members(dict, items.into_iter(), group_members);
         }
     }
 }
@@ -296,6 +1099,17 @@ impl<B> DecoderStreaming<B>
 where
     B: Buffer,
 {
+    /// Brings `self` back to the state it had right after
+    /// [`Decoder::streaming`], e.g. after a [`DecodeError`] or a disconnect.
+    ///
+    /// Buffered bytes, any partially received message, and the last decoded
+    /// message are discarded (see [`Decoder::reset`]). Dictionaries,
+    /// configuration options, and the capacity of the buffer are preserved.
+    pub fn reset(&mut self) {
+        self.clear();
+        self.decoder.reset();
+    }
+
     /// # Panics
     ///
     /// Panics if [`DecoderStreaming::try_parse()`] didn't return [`Ok(Some(()))`].
@@ -323,6 +1137,64 @@ impl<B> GetConfig for DecoderStreaming<B> {
     }
 }
 
+/// The header of a FIX message, as returned by [`Decoder::decode_header`].
+#[derive(Debug, Clone)]
+pub struct HeaderView<'a> {
+    bytes: &'a [u8],
+    begin_string: &'a [u8],
+    msg_type: &'a [u8],
+    fields: Vec<(TagU32, &'a [u8])>,
+}
+
+impl<'a> HeaderView<'a> {
+    /// Returns the whole message, including its body and trailer.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the value of `BeginString <8>`.
+    pub fn begin_string(&self) -> &'a [u8] {
+        self.begin_string
+    }
+
+    /// Returns the value of `MsgType <35>`.
+    pub fn msg_type(&self) -> &'a [u8] {
+        self.msg_type
+    }
+
+    /// Returns the value of `SenderCompID <49>`, if present.
+    pub fn sender_comp_id(&self) -> Option<&'a [u8]> {
+        self.get_raw(49)
+    }
+
+    /// Returns the value of `TargetCompID <56>`, if present.
+    pub fn target_comp_id(&self) -> Option<&'a [u8]> {
+        self.get_raw(56)
+    }
+
+    /// Returns the value of `MsgSeqNum <34>`, if present and valid.
+    pub fn msg_seq_num(&self) -> Option<u64> {
+        self.get_raw(34)
+            .and_then(|value| u64::deserialize(value).ok())
+    }
+
+    /// Returns the value of the header field with `tag`, if present.
+    /// `BeginString <8>` and `BodyLength <9>` are not included.
+    pub fn get_raw(&self, tag: u32) -> Option<&'a [u8]> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(field_tag, _)| field_tag.get() == tag)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns an [`Iterator`] over all header fields but `BeginString <8>`
+    /// and `BodyLength <9>`, in the order in which they appear in the message.
+    pub fn fields(&self) -> impl Iterator<Item = (TagU32, &'a [u8])> + '_ {
+        self.fields.iter().copied()
+    }
+}
+
 /// A repeating group within a [`Message`].
 #[derive(Debug, Clone)]
 pub struct MessageGroup<'a, T>
@@ -361,7 +1233,11 @@ where
 }
 
 /// A FIX message returned by [`Decoder`] or [`DecoderStreaming`].
-#[derive(Debug, Copy, Clone)]
+///
+/// [`Debug`] renders all fields in the `tag=value|` form, regardless of the
+/// separator in use. Non-printable bytes are escaped and very long values
+/// are truncated.
+#[derive(Copy, Clone)]
 pub struct Message<'a, T> {
     builder: &'a MessageBuilder<'a>,
     phantom: PhantomData<T>,
@@ -395,7 +1271,526 @@ impl<'a, T> Message<'a, T> {
         }
     }
 
-    /// Returns the underlying byte contents of `self`.
+    /// Returns an [`Iterator`] over the values of all top-level fields with
+    /// `tag`, in the order in which they appear in the message. Unlike
+    /// [`FieldMap::get_raw`], which only returns the last occurrence, this
+    /// also works for tags that are (illegally) repeated outside of any
+    /// repeating group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=35|35=0|49=A|56=B|34=12|58=foo|58=bar|10=092|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let texts: Vec<&[u8]> = message.get_all(58).collect();
+    ///
+    /// assert_eq!(texts, vec![b"foo" as &[u8], b"bar"]);
+    /// assert_eq!(message.get_all(1).count(), 0);
+    /// ```
+    pub fn get_all(&self, tag: u32) -> impl Iterator<Item = &'a [u8]> {
+        let builder = self.builder;
+        builder
+            .field_locators
+            .iter()
+            .zip(&builder.field_values)
+            .filter(move |(field_locator, _)| {
+                field_locator.tag.get() == tag
+                    && field_locator.context == FieldLocatorContext::TopLevel
+            })
+            .map(|(_, field_value)| *field_value)
+    }
+
+    /// Returns the number of entries of the repeating group with `count_tag`,
+    /// i.e. the parsed value of its `NumInGroup` field, or [`None`] if the
+    /// field is absent or if `count_tag` is not a `NumInGroup` field as per
+    /// the [`Dictionary`] of the [`Decoder`]. This is a cheap way to branch on the size of a group,
+    /// e.g. `NoMDEntries <268>`, without going through [`FieldMap::group`].
+    ///
+    /// The declared count is cross-checked against the number of entries
+    /// that were actually decoded. A mismatch results in
+    /// [`GroupCountError::Mismatch`] if [`Config::should_check_group_counts`]
+    /// is enabled, or in a warning otherwise. Malformed `NumInGroup` values
+    /// never get this far: they fail [`Decoder::decode`] with
+    /// [`DecodeError::FieldValue`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder, GroupCountError};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let config = *decoder.config();
+    ///
+    /// let msg = decoder
+    ///     .decode(b"8=FIX.4.4|9=33|35=X|49=A|56=B|268=2|279=0|279=1|10=000|")
+    ///     .unwrap();
+    /// assert_eq!(msg.group_count(268, &config), Ok(Some(2)));
+    /// assert_eq!(msg.group_count(146, &config), Ok(None));
+    ///
+    /// let msg = decoder
+    ///     .decode(b"8=FIX.4.4|9=35|35=X|49=A|56=B|268=2|279=0|270=1.5|10=000|")
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     msg.group_count(268, &config),
+    ///     Err(GroupCountError::Mismatch { tag: 268, declared: 2, actual: 1 })
+    /// );
+    /// ```
+    pub fn group_count(
+        &self,
+        count_tag: u32,
+        config: &Config,
+    ) -> Result<Option<u32>, GroupCountError> {
+        let field = TagU32::new(count_tag).and_then(|tag| {
+            self.builder.field(&FieldLocator {
+                tag,
+                context: self.field_locator_context,
+            })
+        });
+        let (_, value, index_of_group_tag) = match field {
+            Some(field) if self.builder.group_count_indices.contains(&field.2) => field,
+            _ => return Ok(None),
+        };
+        // Decoding has already validated the count, and bounded it by the
+        // message length.
+        let declared = u32::deserialize(value).unwrap_or(u32::MAX);
+        let actual = self.builder.field_locators[index_of_group_tag..]
+            .iter()
+            .filter_map(|field_locator| match field_locator.context {
+                FieldLocatorContext::WithinGroup {
+                    index_of_group_tag: i,
+                    entry_index,
+                } if i as usize == index_of_group_tag => Some(entry_index + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        if declared != actual {
+            if config.should_check_group_counts {
+                return Err(GroupCountError::Mismatch {
+                    tag: count_tag,
+                    declared,
+                    actual,
+                });
+            }
+            trace_event!(
+                warn,
+                tag = count_tag,
+                declared,
+                actual,
+                "`NumInGroup` field doesn't match the number of group entries"
+            );
+        }
+        Ok(Some(declared))
+    }
+
+    /// Returns an [`Iterator`] over all fields of `self` (including those
+    /// within repeating groups) in wire order, with values parsed according
+    /// to their datatype in `dict`. Values which are invalid for their
+    /// datatype, and fields unknown to `dict`, are returned as
+    /// [`TypedValue::Raw`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::field_types::TypedValue;
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=40|35=D|49=A|56=B|34=12|44=1.5|54=1|5001=x|10=000|";
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let mut decoder = Decoder::new(&dict);
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let fields: Vec<_> = message
+    ///     .iter_typed(&dict)
+    ///     .skip_while(|(tag, _)| *tag != 44)
+    ///     .collect();
+    /// assert_eq!(
+    ///     fields,
+    ///     vec![
+    ///         (44, TypedValue::Decimal("1.5".to_string())),
+    ///         (54, TypedValue::Char(b'1')),
+    ///         (5001, TypedValue::Raw(b"x".to_vec())),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_typed(
+        &'a self,
+        dict: &'a Dictionary,
+    ) -> impl Iterator<Item = (u32, TypedValue)> + 'a {
+        self.fields().map(move |(tag, value)| {
+            let value = match dict.field_by_tag(tag.get()) {
+                Some(field) => TypedValue::parse(field.fix_datatype(), value),
+                None => TypedValue::Raw(value.to_vec()),
+            };
+            (tag.get(), value)
+        })
+    }
+
+    /// Returns the underlying byte contents of `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// assert_eq!(message.as_bytes(), DATA);
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        self.builder.bytes
+    }
+
+    /// Returns `self` in the human-readable `tag=value|` form, regardless of
+    /// [`Config::separator`], e.g. for logs or FIX analyzers. Values are
+    /// written exactly as they were decoded. `BodyLength <9>` and
+    /// `CheckSum <10>` are computed anew for the `|` separator, so that the
+    /// result is a well-formed message in itself. Like [`Debug`], this covers
+    /// the whole message even for repeating group entries.
+    ///
+    /// [`Decoder::decode_tagvalue_string`] parses the result back, unless some
+    /// values contain `|`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4\x019=21\x0135=0\x0149=A\x0156=B\x0134=12\x0110=176\x01";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let bytes = message.to_tagvalue_bytes();
+    /// assert_eq!(bytes, b"8=FIX.4.4|9=21|35=0|49=A|56=B|34=12|10=146|");
+    /// ```
+    pub fn to_tagvalue_bytes(&self) -> Vec<u8> {
+        let mut begin_string: &[u8] = &[];
+        let mut body = Vec::with_capacity(self.builder.bytes.len());
+        let fields = self
+            .builder
+            .field_locators
+            .iter()
+            .zip(&self.builder.field_values);
+        for (field_locator, value) in fields {
+            match field_locator.tag.get() {
+                8 => begin_string = value,
+                9 | 10 => {}
+                _ => {
+                    field_locator.tag.serialize(&mut body);
+                    body.push(b'=');
+                    body.extend_from_slice(value);
+                    body.push(b'|');
+                }
+            }
+        }
+        let mut bytes = Vec::with_capacity(body.len() + 32);
+        bytes.extend_from_slice(b"8=");
+        bytes.extend_from_slice(begin_string);
+        bytes.extend_from_slice(b"|9=");
+        body.len().serialize(&mut bytes);
+        bytes.push(b'|');
+        bytes.extend_from_slice(&body);
+        let checksum = CheckSum::compute(&bytes);
+        bytes.extend_from_slice(b"10=");
+        checksum.serialize(&mut bytes);
+        bytes.push(b'|');
+        bytes
+    }
+
+    /// Like [`Message::to_tagvalue_bytes`], but as a [`String`]. Fails if any
+    /// value is not valid UTF-8, e.g. binary `data` fields: replacing bytes
+    /// would break their `Length` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4\x019=21\x0135=0\x0149=A\x0156=B\x0134=12\x0110=176\x01";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let string = message.to_tagvalue_string().unwrap();
+    /// assert_eq!(string, "8=FIX.4.4|9=21|35=0|49=A|56=B|34=12|10=146|");
+    ///
+    /// let message = decoder.decode_tagvalue_string(&string).unwrap();
+    /// assert_eq!(message.as_bytes(), DATA);
+    /// ```
+    pub fn to_tagvalue_string(&self) -> Result<String, std::string::FromUtf8Error> {
+        String::from_utf8(self.to_tagvalue_bytes())
+    }
+
+    /// Returns the bytes of `self` which are covered by `Signature <89>` as
+    /// per [`Config::signing_convention`], i.e. exactly what a signer signs
+    /// and what [`Decoder::set_signature_verifier`] verifies. For messages
+    /// without a signature, that's what would be signed if
+    /// `SignatureLength <93>` and `Signature <89>` came right before
+    /// `CheckSum <10>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let msg = decoder.decode(b"8=FIX.4.4|9=31|35=0|49=A|56=B|34=2|93=2|89=35|10=000|").unwrap();
+    ///
+    /// let mut config = Config::default();
+    /// config.separator = b'|';
+    /// assert_eq!(msg.signable_bytes(&config), b"8=FIX.4.4|9=31|35=0|49=A|56=B|34=2|");
+    /// config.signing_convention.include_framing = false;
+    /// config.signing_convention.include_signature_length = true;
+    /// assert_eq!(msg.signable_bytes(&config), b"35=0|49=A|56=B|34=2|93=2|");
+    /// ```
+    pub fn signable_bytes(&self, config: &Config) -> &[u8] {
+        self.builder.signed_bytes(config)
+    }
+
+    /// Returns the [`VersionInfo`] of the [`Dictionary`] that was used to
+    /// decode the body of `self`, taking `ApplVerID <1128>` and
+    /// `CstmApplVerID <1129>` into account. See [`Decoder::add_appl_ver_id`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::dict::AppVersion;
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// assert_eq!(message.version_info().app_version, Some(AppVersion::Fix44));
+    /// assert_eq!(message.app_version(), Some(AppVersion::Fix44));
+    /// ```
+    pub fn version_info(&self) -> VersionInfo {
+        self.builder.version_info
+    }
+
+    /// Returns the [`AppVersion`] of the [`Dictionary`] that was used to
+    /// decode the body of `self`, if known.
+    pub fn app_version(&self) -> Option<AppVersion> {
+        self.builder.version_info.app_version
+    }
+
+    /// Returns the `BodyLength <9>` that [`Encoder`](super::Encoder) would
+    /// write when re-encoding all fields of `self`, e.g. with
+    /// [`EncoderHandle::set_fields`](super::EncoderHandle::set_fields).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// assert_eq!(message.body_length(), 42);
+    /// ```
+    pub fn body_length(&self) -> usize {
+        self.body_fields()
+            .map(|(tag, value)| tag.ilog10() as usize + value.len() + 3)
+            .sum()
+    }
+
+    /// Returns the `CheckSum <10>` that [`Encoder`](super::Encoder) would
+    /// write when re-encoding all fields of `self` with `config`. Note that
+    /// [`Encoder`](super::Encoder) always pads `BodyLength <9>` to eight
+    /// digits, so this is generally different from the original
+    /// `CheckSum <10>` of `self`.
+    pub fn checksum(&self, config: &Config) -> CheckSum {
+        let separator = [config.separator];
+        let mut checksum = 0u8;
+        let mut add = |bytes: &[u8]| checksum = checksum.wrapping_add(CheckSum::compute(bytes).0);
+        add(b"8=");
+        add(self.get_all(8).next().unwrap_or_default());
+        add(&separator);
+        add(format!("9={:08}", self.body_length()).as_bytes());
+        add(&separator);
+        for (tag, value) in self.body_fields() {
+            add(&tag.to_bytes());
+            add(b"=");
+            add(value);
+            add(&separator);
+        }
+        CheckSum(checksum)
+    }
+
+    /// Returns the byte range of every field value within
+    /// [`Message::as_bytes`], in the same order as [`Message::fields`]. Spans
+    /// are only recorded if [`Config::should_record_spans`] is enabled, and
+    /// this is empty otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().should_record_spans = true;
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let span = message.field_spans()[2];
+    /// assert_eq!(span.tag.get(), 49);
+    /// assert_eq!(&DATA[span.value_start..span.value_end], b"A");
+    /// ```
+    pub fn field_spans(&self) -> &'a [FieldSpan] {
+        &self.builder.field_spans[..]
+    }
+
+    /// Returns all corrections that were made to the framing of `self` before
+    /// decoding it, if [`Config::should_repair_framing`] is enabled. A
+    /// well-formed message has none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, FramingRepair};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().should_repair_framing = true;
+    ///
+    /// let message = decoder.decode(b"8=FIX.4.4|9=99|35=0|49=A|56=B|34=12").unwrap();
+    /// assert_eq!(message.get(fix44::MSG_SEQ_NUM), Ok(12));
+    /// assert_eq!(message.as_bytes(), b"8=FIX.4.4|9=21|35=0|49=A|56=B|34=12|10=146|");
+    /// assert_eq!(
+    ///     message.repairs(),
+    ///     &[
+    ///         FramingRepair::MissingSeparator,
+    ///         FramingRepair::BodyLength { declared: Some(99), actual: 21 },
+    ///         FramingRepair::CheckSum { declared: None, actual: 146 },
+    ///     ]
+    /// );
+    /// ```
+    pub fn repairs(&self) -> &'a [FramingRepair] {
+        &self.builder.repairs[..]
+    }
+
+    /// Returns the indices (as in [`Message::fields`]) of all `data` fields
+    /// in `self` that are immediately preceded by their `Length` field.
+    pub(super) fn data_field_indices(&self) -> &'a [usize] {
+        &self.builder.data_field_indices[..]
+    }
+
+    /// Returns an [`Iterator`] over the application fields of `self`, i.e.
+    /// those in the body (repeating groups included) which aren't part of the
+    /// standard header or trailer according to `dict`, in wire order. Session
+    /// fields like `SenderCompID <49>` and `MsgSeqNum <34>` are thus skipped,
+    /// even if they're misplaced in the body. Fields which are not part of the
+    /// definition of the message in `dict` are still application data and they
+    /// are yielded too; see [`Message::is_defined`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=44|35=D|49=A|56=B|34=12|11=X|55=EUR/USD|5001=x|10=000|";
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let mut decoder = Decoder::new(&dict);
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    ///
+    /// let message = decoder.decode(DATA).unwrap();
+    /// let tags: Vec<u32> = message.application_fields(&dict).map(|(tag, _)| tag).collect();
+    /// assert_eq!(tags, vec![11, 55, 5001]);
+    /// assert!(message.is_defined(&dict, 55));
+    /// assert!(!message.is_defined(&dict, 5001));
+    /// ```
+    pub fn application_fields(&self, dict: &Dictionary) -> impl Iterator<Item = (u32, &'a [u8])> {
+        let mut session_tags = vec![];
+        for component_name in ["StandardHeader", "StandardTrailer"] {
+            if let Some(component) = dict.component_by_name(component_name) {
+                collect_tags(dict, component.items(), &mut session_tags);
+            }
+        }
+        let builder = self.builder;
+        let body = builder.len_end_header..builder.len_end_body;
+        builder.field_locators[body.clone()]
+            .iter()
+            .zip(&builder.field_values[body])
+            .map(|(field_locator, field_value)| (field_locator.tag.get(), *field_value))
+            .filter(move |(tag, _)| !session_tags.contains(tag))
+    }
+
+    /// Returns `true` if the field with `tag` is part of the definition in
+    /// `dict` of the message type of `self`, within repeating groups and
+    /// components included. See [`Message::application_fields`].
+    pub fn is_defined(&self, dict: &Dictionary, tag: u32) -> bool {
+        let msg_type = self
+            .get_all(35)
+            .next()
+            .and_then(|msg_type| std::str::from_utf8(msg_type).ok());
+        match msg_type.and_then(|msg_type| dict.message_by_msgtype(msg_type)) {
+            Some(message) => {
+                let mut tags = vec![];
+                collect_tags(dict, message.layout(), &mut tags);
+                tags.contains(&tag)
+            }
+            None => false,
+        }
+    }
+
+    /// Like [`FieldMap::get_raw`], but the value borrows from the message
+    /// rather than from `self`.
+    pub(super) fn field_value(&self, tag: u32) -> Option<&'a [u8]> {
+        let field_locator = FieldLocator {
+            tag: TagU32::new(tag)?,
+            context: self.field_locator_context,
+        };
+        self.builder.field(&field_locator).map(|field| field.1)
+    }
+
+    fn body_fields(&self) -> impl Iterator<Item = (TagU32, &'a [u8])> {
+        let builder = self.builder;
+        builder
+            .field_locators
+            .iter()
+            .zip(&builder.field_values)
+            .map(|(field_locator, field_value)| (field_locator.tag, *field_value))
+            .filter(|(tag, _)| !matches!(tag.get(), 8..=10))
+    }
+
+    /// Returns the number of FIX tags contained in `self`.
     ///
     /// # Examples
     ///
@@ -409,46 +1804,90 @@ impl<'a, T> Message<'a, T> {
     /// decoder.config_mut().separator = b'|';
     ///
     /// let message = decoder.decode(DATA).unwrap();
-    /// assert_eq!(message.as_bytes(), DATA);
+    /// assert_eq!(message.len(), message.fields().count());
     /// ```
-    pub fn as_bytes(&self) -> &[u8] {
-        self.builder.bytes
+    pub fn len(&self) -> usize {
+        self.builder.field_locators.len()
     }
 
-    /// Returns the number of FIX tags contained in `self`.
+    /// Calls `f` on every field in `self`, in sequential order, together with
+    /// a [`FieldContext`] that describes its section (header, body, or
+    /// trailer) and its enclosing repeating group entries, if any. This is
+    /// useful when `self` must be rebuilt in a structured format (e.g. JSON)
+    /// rather than as a flat sequence of fields like [`Message::fields`].
     ///
     /// # Examples
     ///
     /// ```
+    /// use fefix::dict::FieldLocation;
     /// use fefix::tagvalue::{Config, Decoder};
     /// use fefix::prelude::*;
     ///
-    /// const DATA: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=33|35=X|49=A|56=B|268=2|279=0|279=1|10=000|";
     ///
     /// let mut decoder = Decoder::new(Dictionary::fix44());
     /// decoder.config_mut().separator = b'|';
     ///
     /// let message = decoder.decode(DATA).unwrap();
-    /// assert_eq!(message.len(), message.fields().count());
+    /// message.for_each_with_context(|context, tag, _value| {
+    ///     if tag.get() == 279 {
+    ///         assert_eq!(context.location, FieldLocation::Body);
+    ///         assert_eq!(context.groups[0].num_in_group_tag.get(), 268);
+    ///     }
+    /// });
     /// ```
-    pub fn len(&self) -> usize {
-        self.builder.field_locators.len()
+    pub fn for_each_with_context<F>(&self, mut f: F)
+    where
+        F: FnMut(FieldContext, TagU32, &'a [u8]),
+    {
+        let builder = self.builder;
+        let mut groups = Vec::new();
+        for (i, field_locator) in builder.field_locators.iter().enumerate() {
+            groups.clear();
+            let mut context = field_locator.context;
+            while let FieldLocatorContext::WithinGroup {
+                index_of_group_tag,
+                entry_index,
+            } = context
+            {
+                let group_locator = builder.field_locators[index_of_group_tag as usize];
+                groups.push(GroupEntryContext {
+                    num_in_group_tag: group_locator.tag,
+                    entry_index: entry_index as usize,
+                });
+                context = group_locator.context;
+            }
+            groups.reverse();
+            let location = if i < builder.len_end_header {
+                FieldLocation::Header
+            } else if i < builder.len_end_body {
+                FieldLocation::Body
+            } else {
+                FieldLocation::Trailer
+            };
+            if let Some(field) = builder.field(field_locator) {
+                let context = FieldContext {
+                    location,
+                    groups: &groups[..],
+                };
+                f(context, field.0, field.1);
+            }
+        }
     }
 }
 
-impl<'a, T> PartialEq for Message<'a, T> {
-    fn eq(&self, other: &Self) -> bool {
-        // Two messages are equal *if and only if* messages are exactly the
-        // same. Fields must also have the same order (things get complicated
-        // when you allow for different order of fields).
-        self.fields().eq(other.fields())
+impl<'a, T> IntoIterator for &'a Message<'a, T> {
+    type Item = (TagU32, &'a [u8]);
+    type IntoIter = Fields<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields()
     }
 }
 
-impl<'a, T> Eq for Message<'a, T> {}
-
 #[derive(Debug, Copy, Clone)]
 struct DecoderGroupState {
+    tag: TagU32,
     first_tag_of_every_group_entry: TagU32,
     num_entries: usize,
     current_entry_i: usize,
@@ -467,6 +1906,7 @@ struct DecoderState {
     group_information: Vec<DecoderGroupState>,
     new_group: Option<DecoderStateNewGroup>,
     data_field_length: Option<usize>,
+    section: FieldLocation,
 }
 
 impl DecoderState {
@@ -487,6 +1927,7 @@ impl DecoderState {
         assert!(self.new_group.is_some());
         let new_group = self.new_group.take().unwrap();
         self.group_information.push(DecoderGroupState {
+            tag: new_group.tag,
             first_tag_of_every_group_entry: tag,
             num_entries: new_group.num_entries,
             current_entry_i: 0,
@@ -494,9 +1935,7 @@ impl DecoderState {
         });
     }
 
-    fn add_group(&mut self, tag: TagU32, index_of_group_tag: usize, field_value: &[u8]) {
-        let field_value_str = std::str::from_utf8(field_value).unwrap();
-        let num_entries = str::parse(field_value_str).unwrap();
+    fn add_group(&mut self, tag: TagU32, index_of_group_tag: usize, num_entries: usize) {
         if num_entries > 0 {
             self.new_group = Some(DecoderStateNewGroup {
                 tag,
@@ -512,14 +1951,32 @@ impl DecoderState {
 struct MessageBuilder<'a> {
     state: DecoderState,
     raw: &'a [u8],
+    is_associative: bool,
     fields: HashMap<FieldLocator, (TagU32, &'a [u8], usize)>,
+    // Indices into `field_locators`, built upon the first lookup when
+    // `fields` isn't populated at decode time, i.e. when
+    // `Config::should_decode_associative` is off.
+    lazy_fields: OnceLock<HashMap<FieldLocator, usize>>,
     field_locators: Vec<FieldLocator>,
+    field_values: Vec<&'a [u8]>,
+    // Indices of `data` fields that are immediately preceded by their
+    // `Length` field.
+    data_field_indices: Vec<usize>,
+    // Indices of `NumInGroup` fields, as per the dictionary.
+    group_count_indices: Vec<usize>,
+    field_spans: Vec<FieldSpan>,
     i_first_cell: usize,
     i_last_cell: usize,
     len_end_header: usize,
     len_end_body: usize,
     len_end_trailer: usize,
     bytes: &'a [u8],
+    // The offset of `MsgType <35>` within `bytes`.
+    payload_start: usize,
+    // The byte range of the whole `SignatureLength <93>` field, if any.
+    signature_length: Option<Range<usize>>,
+    version_info: VersionInfo,
+    repairs: Vec<FramingRepair>,
 }
 
 impl<'a> Default for MessageBuilder<'a> {
@@ -529,23 +1986,149 @@ impl<'a> Default for MessageBuilder<'a> {
                 group_information: Vec::new(),
                 new_group: None,
                 data_field_length: None,
+                section: FieldLocation::Header,
             },
             raw: b"",
             field_locators: Vec::new(),
+            field_values: Vec::new(),
+            data_field_indices: Vec::new(),
+            group_count_indices: Vec::new(),
+            field_spans: Vec::new(),
+            is_associative: true,
             fields: HashMap::new(),
+            lazy_fields: OnceLock::new(),
             i_first_cell: 0,
             i_last_cell: 0,
             len_end_body: 0,
             len_end_trailer: 0,
             len_end_header: 0,
             bytes: b"",
+            payload_start: 0,
+            signature_length: None,
+            version_info: VersionInfo::default(),
+            repairs: Vec::new(),
         }
     }
 }
 
 impl<'a> MessageBuilder<'a> {
+    /// Resets `self` to its [`Default`] state, but keeps allocated memory
+    /// around for the next message.
     fn clear(&mut self) {
-        *self = Self::default();
+        self.state.group_information.clear();
+        self.state.new_group = None;
+        self.state.data_field_length = None;
+        self.state.section = FieldLocation::Header;
+        self.raw = b"";
+        self.is_associative = true;
+        self.fields.clear();
+        self.lazy_fields.take();
+        self.field_locators.clear();
+        self.field_values.clear();
+        self.data_field_indices.clear();
+        self.group_count_indices.clear();
+        self.field_spans.clear();
+        self.repairs.clear();
+        self.i_first_cell = 0;
+        self.i_last_cell = 0;
+        self.len_end_header = 0;
+        self.len_end_body = 0;
+        self.len_end_trailer = 0;
+        self.bytes = b"";
+        self.payload_start = 0;
+        self.signature_length = None;
+        self.version_info = VersionInfo::default();
+    }
+
+    fn signed_bytes(&self, config: &Config) -> &'a [u8] {
+        let range = config.signing_convention.signed_range(
+            self.bytes.len(),
+            self.payload_start,
+            self.signature_length.clone(),
+        );
+        &self.bytes[range]
+    }
+
+    /// Makes sure there's enough capacity for a message with a payload of
+    /// `payload_len` bytes, so that no reallocations are needed on the fly in
+    /// the common case.
+    fn reserve(&mut self, payload_len: usize) {
+        let num_fields = payload_len / AVG_FIELD_LEN_IN_BYTES + 1;
+        self.fields.reserve(num_fields);
+        self.field_locators.reserve(num_fields);
+        self.field_values.reserve(num_fields);
+    }
+
+    /// Keeps track of section boundaries, given the expected `location` of
+    /// the last field that was added. The header ends with the first
+    /// non-header field, and the trailer starts with the first trailer field.
+    fn update_section(&mut self, location: Option<FieldLocation>) {
+        let i = self.field_locators.len() - 1;
+        match (self.state.section, location) {
+            (FieldLocation::Trailer, _) => {}
+            (section, Some(FieldLocation::Trailer)) => {
+                if section == FieldLocation::Header {
+                    self.len_end_header = i;
+                }
+                self.len_end_body = i;
+                self.state.section = FieldLocation::Trailer;
+            }
+            (FieldLocation::Header, None | Some(FieldLocation::Body)) => {
+                self.len_end_header = i;
+                self.state.section = FieldLocation::Body;
+            }
+            _ => {}
+        }
+    }
+
+    fn end_sections(&mut self) {
+        let len = self.field_locators.len();
+        match self.state.section {
+            FieldLocation::Header => {
+                self.len_end_header = len;
+                self.len_end_body = len;
+            }
+            FieldLocation::Body => {
+                self.len_end_body = len;
+            }
+            FieldLocation::Trailer => {}
+        }
+        self.len_end_trailer = len;
+    }
+
+    fn top_level_raw(&self, tag: u32) -> Option<&'a [u8]> {
+        let field_locator = FieldLocator {
+            tag: TagU32::new(tag)?,
+            context: FieldLocatorContext::TopLevel,
+        };
+        self.field(&field_locator).map(|field| field.1)
+    }
+
+    /// Looks up the field at `field_locator`, building the index of all
+    /// fields first if it wasn't at decode time.
+    fn field(&self, field_locator: &FieldLocator) -> Option<(TagU32, &'a [u8], usize)> {
+        if self.is_associative {
+            return self.fields.get(field_locator).copied();
+        }
+        let fields = self.lazy_fields.get_or_init(|| {
+            let mut fields = HashMap::with_capacity(self.field_locators.len());
+            for (i, field_locator) in self.field_locators.iter().enumerate() {
+                fields.insert(*field_locator, i);
+            }
+            fields
+        });
+        let i = *fields.get(field_locator)?;
+        Some((field_locator.tag, self.field_values[i], i))
+    }
+
+    /// Replaces the value of the `i`-th field.
+    fn replace_value(&mut self, i: usize, value: &'a [u8]) {
+        self.field_values[i] = value;
+        if let Some(field) = self.fields.get_mut(&self.field_locators[i]) {
+            if field.2 == i {
+                field.1 = value;
+            }
+        }
     }
 
     fn add_field(
@@ -560,6 +2143,7 @@ impl<'a> MessageBuilder<'a> {
             self.fields.insert(field_locator, (tag, field_value, i));
         }
         self.field_locators.push(field_locator);
+        self.field_values.push(field_value);
         Ok(())
     }
 }
@@ -586,10 +2170,10 @@ impl<'a, T> Iterator for Fields<'a, T> {
         if self.i == self.message.len() {
             None
         } else {
-            let context = self.message.builder.field_locators[self.i];
-            let field = self.message.builder.fields.get(&context).unwrap();
+            let field_locator = self.message.builder.field_locators[self.i];
+            let field_value = self.message.builder.field_values[self.i];
             self.i += 1;
-            Some((field.0, field.1))
+            Some((field_locator.tag, field_value))
         }
     }
 }
@@ -608,8 +2192,7 @@ where
         };
         let num_in_group = self
             .builder
-            .fields
-            .get(&field_locator_of_group_tag)
+            .field(&field_locator_of_group_tag)
             .ok_or(FieldValueError::Missing)?;
         let num_entries = usize::deserialize(num_in_group.1).map_err(FieldValueError::Invalid)?;
         let index_of_group_tag = num_in_group.2 as u32;
@@ -631,7 +2214,7 @@ where
             context: self.field_locator_context,
         };
         dbglog!("looking for {:?}", field_locator);
-        self.builder.fields.get(&field_locator).map(|field| field.1)
+        self.builder.field(&field_locator).map(|field| field.1)
     }
 }
 
@@ -654,6 +2237,27 @@ where
     }
 }
 
+impl<'a, T> Debug for Message<'a, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Message(")?;
+        let fields = self
+            .builder
+            .field_locators
+            .iter()
+            .zip(&self.builder.field_values);
+        for (field_locator, value) in fields {
+            write!(f, "{}=", field_locator.tag)?;
+            if value.len() > DEBUG_MAX_FIELD_VALUE_LEN {
+                let prefix = &value[..DEBUG_MAX_FIELD_VALUE_LEN];
+                write!(f, "{}...({} bytes)|", prefix.escape_ascii(), value.len())?;
+            } else {
+                write!(f, "{}|", value.escape_ascii())?;
+            }
+        }
+        write!(f, ")")
+    }
+}
+
 #[cfg(feature = "utils-slog")]
 #[cfg_attr(doc_cfg, doc(cfg(feature = "utils-slog")))]
 impl<'a, T> slog::Value for Message<'a, T>
@@ -699,6 +2303,10 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::dict::AppVersion;
+    use crate::tagvalue::Encoder;
+    use crate::SetField;
+    use quickcheck_macros::quickcheck;
 
     // Use http://www.validfix.com/fix-analyzer.html for testing.
 
@@ -717,7 +2325,7 @@ mod test {
     }
 
     fn decoder() -> Decoder {
-        let mut decoder = Decoder::new(Dictionary::fix44());
+        let mut decoder = Decoder::new(Dictionary::precompiled(AppVersion::Fix44));
         decoder.config_mut().separator = b'|';
         decoder
     }
@@ -738,6 +2346,68 @@ mod test {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn header_ends_at_first_body_field() {
+        let decoder = decoder();
+        let data = b"8=FIX.4.4|9=48|35=D|49=A|56=B|34=12|90=3|91=a|b|43=Y|11=x|44=2|10=000|";
+        let header = decoder.decode_header(&data[..]).unwrap();
+        let tags: Vec<u32> = header.fields().map(|(tag, _)| tag.get()).collect();
+        assert_eq!(tags, vec![35, 49, 56, 34, 90, 91, 43]);
+        assert_eq!(header.get_raw(91), Some(b"a|b" as &[u8]));
+        assert_eq!(header.as_bytes(), &data[..]);
+        assert!(matches!(
+            decoder.decode_header(b"8=FIX.4.4|9=16|49=A|56=B|34=12|10=000|"),
+            Err(DecodeError::FieldPresence)
+        ));
+    }
+
+    #[test]
+    fn decode_to_map_respects_data_fields() {
+        let decoder = decoder();
+        let data = b"8=FIX.4.4|9=48|35=D|49=A|56=B|34=12|90=3|91=a|b|43=Y|11=x|44=2|10=000|";
+        let map = decoder.decode_to_map(&data[..]).unwrap();
+        assert_eq!(map.len(), 10);
+        assert_eq!(map[&91], b"a|b");
+        assert_eq!(map[&44], b"2");
+        assert!(decoder.decode_to_map(&data[..40]).is_err());
+    }
+
+    #[test]
+    fn oversized_data_lengths_are_rejected() {
+        let decoder = decoder();
+        let data = b"8=FIX.4.4|9=52|35=D|49=A|56=B|34=12|90=18446744073709551615|91=a|b|10=000|";
+        assert!(matches!(
+            decoder.decode_to_map(&data[..]),
+            Err(DecodeError::Invalid)
+        ));
+        assert!(matches!(
+            decoder.decode_header(&data[..]),
+            Err(DecodeError::Invalid)
+        ));
+        // The value may not spill over into `CheckSum <10>` either.
+        let data = b"8=FIX.4.4|9=33|35=D|49=A|56=B|34=12|90=6|91=a|b|10=000|";
+        assert!(matches!(
+            decoder.decode_to_map(&data[..]),
+            Err(DecodeError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn repair_mode_is_opt_in() {
+        let sloppy = b"8=FIX.4.2|9=1|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=000|";
+        let well_formed = b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=018|";
+        let mut decoder = decoder();
+        assert!(decoder.decode(&sloppy[..]).is_err());
+        decoder.config_mut().should_repair_framing = true;
+        let message = decoder.decode(&sloppy[..]).unwrap();
+        assert_eq!(message.get_raw(49), Some(b"AFUNDMGR" as &[u8]));
+        assert_eq!(message.repairs().len(), 2);
+        // Repairs don't leak into the next message.
+        let message = decoder.decode(&well_formed[..]).unwrap();
+        assert_eq!(message.repairs(), &[]);
+        assert_eq!(message.as_bytes(), well_formed);
+    }
+
     #[test]
     fn repeating_group_entries() {
         let bytes = b"8=FIX.4.2|9=196|35=X|49=A|56=B|34=12|52=20100318-03:21:11.364|262=A|268=2|279=0|269=0|278=BID|55=EUR/USD|270=1.37215|15=EUR|271=2500000|346=1|279=0|269=1|278=OFFER|55=EUR/USD|270=1.37224|15=EUR|271=2503200|346=1|10=171|";
@@ -748,6 +2418,23 @@ mod test {
         assert_eq!(group.get(0).unwrap().get_raw(278).unwrap(), b"BID" as &[u8]);
     }
 
+    #[test]
+    fn nested_repeating_groups_end_where_members_end() {
+        let bytes = b"8=FIX.4.4|9=83|35=V|262=foo|263=1|264=0|146=2|55=A|454=2|455=X|456=8|455=Y|456=4|55=B|267=1|269=0|10=000|";
+        let mut decoder = decoder();
+        let message = decoder.decode(&bytes).unwrap();
+        let related_sym = message.group(146).unwrap();
+        assert_eq!(related_sym.len(), 2);
+        let security_alt_ids = related_sym.get(0).unwrap().group(454).unwrap();
+        assert_eq!(security_alt_ids.len(), 2);
+        assert_eq!(
+            security_alt_ids.get(1).unwrap().get_raw(455),
+            Some(&b"Y"[..])
+        );
+        assert_eq!(related_sym.get(1).unwrap().get_raw(55), Some(&b"B"[..]));
+        assert_eq!(message.group(267).unwrap().len(), 1);
+    }
+
     #[test]
     fn top_level_tag_after_empty_group() {
         let bytes = b"8=FIX.4.4|9=17|35=X|268=0|346=1|10=171|";
@@ -769,6 +2456,386 @@ mod test {
         }
     }
 
+    /// Tags with special meaning for the decoder, as well as some ordinary
+    /// ones.
+    const INTERESTING_TAGS: &[&[u8]] = &[
+        b"",
+        b"0",
+        b"34",
+        b"35",
+        b"95",
+        b"96",
+        b"268",
+        b"269",
+        b"279",
+        b"1000",
+        b"99999999999",
+    ];
+
+    fn decoder_without_checksum() -> Decoder {
+        let mut decoder = decoder();
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    fn message_with_payload(payload: &[u8]) -> Vec<u8> {
+        let mut msg = format!("8=FIX.4.4|9={}|", payload.len()).into_bytes();
+        msg.extend_from_slice(payload);
+        msg.extend_from_slice(b"10=000|");
+        msg
+    }
+
+    #[test]
+    fn group_counts_are_cross_checked() {
+        let mut decoder = decoder_without_checksum();
+        let mut config = *decoder.config();
+        let data = message_with_payload(b"35=X|49=A|268=1|279=0|269=0|279=1|269=1|5001=x|");
+        let message = decoder.decode(&data).unwrap();
+        assert_eq!(
+            message.group_count(268, &config),
+            Err(GroupCountError::Mismatch {
+                tag: 268,
+                declared: 1,
+                actual: 2
+            })
+        );
+        // Present, but not `NumInGroup` fields.
+        assert_eq!(message.group_count(5001, &config), Ok(None));
+        assert_eq!(message.group_count(49, &config), Ok(None));
+        assert_eq!(message.group_count(146, &config), Ok(None));
+        config.should_check_group_counts = false;
+        assert_eq!(message.group_count(268, &config), Ok(Some(1)));
+
+        let data = message_with_payload(b"35=X|49=A|268=0|");
+        let message = decoder.decode(&data).unwrap();
+        assert_eq!(message.group_count(268, &config), Ok(Some(0)));
+
+        // Malformed counts fail decoding instead.
+        let data = message_with_payload(b"35=X|49=A|268=x|279=0|");
+        assert!(matches!(
+            decoder.decode(&data),
+            Err(DecodeError::FieldValue {
+                reason: IntSubtypeError::NotAnInteger,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn application_fields_skip_session_fields() {
+        let dict = Dictionary::fix44();
+        let mut decoder = decoder_without_checksum();
+        let data =
+            message_with_payload(b"35=X|49=A|56=B|268=1|279=0|269=0|34=2|5001=x|93=2|89=ab|");
+        let message = decoder.decode(&data).unwrap();
+        let fields: Vec<(u32, &[u8])> = message.application_fields(&dict).collect();
+        assert_eq!(
+            fields,
+            vec![(268, b"1" as &[u8]), (279, b"0"), (269, b"0"), (5001, b"x")]
+        );
+        assert!(message.is_defined(&dict, 269));
+        assert!(!message.is_defined(&dict, 5001));
+        assert!(!message.is_defined(&dict, 34));
+    }
+
+    #[test]
+    fn leading_zeros_are_tolerated_and_stripped() {
+        let data = b"8=FIX.4.4|9=00024|35=0|49=A|56=B|34=00042|10=000|";
+        let mut decoder = decoder_without_checksum();
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(message.get::<u64>(34), Ok(42));
+        assert_eq!(message.get_raw(34), Some(b"00042" as &[u8]));
+
+        decoder.config_mut().should_strip_leading_zeros = true;
+        let message = decoder.decode(&data[..]).unwrap();
+        assert_eq!(message.get_raw(34), Some(b"42" as &[u8]));
+        let mut encoder = Encoder::default();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let (encoded, _) = encoder
+            .encode_with_begin_string(&mut buffer, &message, b"FIX.4.4")
+            .unwrap();
+        assert!(encoded.starts_with(b"8=FIX.4.4|9=00000021|35=0|49=A|56=B|34=42|10="));
+
+        // Negative values and zero.
+        let data =
+            message_with_payload(b"35=W|49=A|56=B|34=2|262=x|268=1|269=0|270=1|290=-007|346=00|");
+        let message = decoder.decode(&data[..]).unwrap();
+        let entry = message.group(268).unwrap().get(0).unwrap();
+        assert_eq!(entry.get_raw(290), Some(b"-7" as &[u8]));
+        assert_eq!(entry.get_raw(346), Some(b"0" as &[u8]));
+    }
+
+    #[test]
+    fn leading_zeros_in_tag_num_fields_are_refused() {
+        let spec = include_str!("../../../fefix-dictionary/src/resources/quickfix/FIX-4.4.xml")
+            .replace(
+                "number='371' name='RefTagID' type='INT'",
+                "number='371' name='RefTagID' type='TAGNUM'",
+            );
+        let mut decoder = Decoder::new(Dictionary::from_quickfix_spec(&spec).unwrap());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let data = message_with_payload(b"35=3|49=A|56=B|34=2|45=1|371=055|");
+        assert!(decoder.decode(&data[..]).is_ok());
+        decoder.config_mut().should_check_leading_zeros = true;
+        assert!(matches!(
+            decoder.decode(&data[..]),
+            Err(DecodeError::FieldValue {
+                reason: IntSubtypeError::LeadingZeros(FixDatatype::TagNum),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn tagvalue_strings_are_well_formed_messages() {
+        let mut decoder = decoder_without_checksum();
+        let data = message_with_payload(b"35=W|49=A|56=B|34=2|268=2|269=0|269=1|");
+        let string = {
+            let message = decoder.decode(&data[..]).unwrap();
+            let entry = message.group(268).unwrap().get(1).unwrap();
+            assert_eq!(entry.to_tagvalue_string(), message.to_tagvalue_string());
+            message.to_tagvalue_string().unwrap()
+        };
+        assert!(string.ends_with("|268=2|269=0|269=1|10=048|"));
+        decoder.config_mut().verify_checksum = true;
+        let message = decoder.decode(string.as_bytes()).unwrap();
+        assert_eq!(message.to_tagvalue_string(), Ok(string));
+    }
+
+    #[test]
+    fn binary_data_fields_are_written_verbatim() {
+        let mut decoder = decoder_without_checksum();
+        let data = message_with_payload(b"35=0|49=A|56=B|34=2|93=2|89=\xff\xfe|");
+        let message = decoder.decode(&data[..]).unwrap();
+        let bytes = message.to_tagvalue_bytes();
+        assert!(bytes.ends_with(b"|93=2|89=\xff\xfe|10=224|"));
+        assert!(message.to_tagvalue_string().is_err());
+        decoder.config_mut().verify_checksum = true;
+        let message = decoder.decode(&bytes[..]).unwrap();
+        assert_eq!(message.get_raw(89), Some(b"\xff\xfe" as &[u8]));
+    }
+
+    #[test]
+    fn lookups_without_associative_decoding() {
+        let mut decoder = decoder_without_checksum();
+        decoder.config_mut().should_decode_associative = false;
+        let data = message_with_payload(b"35=X|49=A|268=2|279=0|269=0|279=1|269=1|");
+        let message = decoder.decode(&data).unwrap();
+        assert_eq!(message.get_raw(49), Some(b"A" as &[u8]));
+        assert_eq!(message.get_raw(56), None);
+        let group = message.group(268).unwrap();
+        assert_eq!(group.get(1).unwrap().get_raw(279), Some(b"1" as &[u8]));
+        let message = decoder.decode(message_with_payload(b"35=0|49=B|")).unwrap();
+        assert_eq!(message.get_raw(49), Some(b"B" as &[u8]));
+        assert_eq!(message.get_raw(268), None);
+    }
+
+    #[quickcheck]
+    fn decode_arbitrary_bytes_never_panics(bytes: Vec<u8>) -> bool {
+        let mut decoder = decoder_without_checksum();
+        let _ = decoder.decode(&bytes[..]);
+        let _ = decoder.decode(message_with_payload(&bytes[..]));
+        true
+    }
+
+    #[quickcheck]
+    fn decode_arbitrary_fields_never_panics(fields: Vec<(u8, Vec<u8>)>) -> bool {
+        const ALPHABET: &[u8] = b"0123456789-=|A";
+        let mut payload = Vec::new();
+        for (tag, value) in fields {
+            payload.extend_from_slice(INTERESTING_TAGS[tag as usize % INTERESTING_TAGS.len()]);
+            payload.push(b'=');
+            payload.extend(value.iter().map(|b| ALPHABET[*b as usize % ALPHABET.len()]));
+            payload.push(b'|');
+        }
+        let mut decoder = decoder_without_checksum();
+        if let Ok(message) = decoder.decode(message_with_payload(&payload[..])) {
+            message.for_each_with_context(|_, _, _| {});
+            let _ = message.group(268);
+        }
+        decoder.config_mut().should_check_int_subtypes = true;
+        let _ = decoder.decode(message_with_payload(&payload[..]));
+        true
+    }
+
+    #[quickcheck]
+    fn decode_mutated_message_never_panics(i: usize, j: usize, byte: u8) -> bool {
+        let mut msg = RANDOM_MESSAGES[i % RANDOM_MESSAGES.len()]
+            .as_bytes()
+            .to_vec();
+        let len = msg.len();
+        msg[j % len] = byte;
+        let mut decoder = decoder_without_checksum();
+        let _ = decoder.decode(&msg[..]);
+        let mut decoder = decoder_without_checksum().streaming(vec![]);
+        for _ in 0..4 {
+            let fillable = decoder.fillable();
+            let n = fillable.len().min(msg.len());
+            fillable[..n].copy_from_slice(&msg[..n]);
+            msg.drain(..n);
+            if !matches!(decoder.try_parse(), Ok(None)) {
+                break;
+            }
+        }
+        true
+    }
+
+    #[quickcheck]
+    fn encode_then_decode_is_identity(values: Vec<String>) -> bool {
+        let values: Vec<String> = values
+            .into_iter()
+            .take(500)
+            .map(|v| v.chars().filter(char::is_ascii_alphanumeric).collect())
+            .filter(|v: &String| !v.is_empty())
+            .collect();
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        for (i, value) in values.iter().enumerate() {
+            msg.set(5000 + i as u32, value.as_str());
+        }
+        let (bytes, _) = msg.done();
+        let mut decoder = decoder();
+        let message = decoder.decode(bytes).unwrap();
+        message.get_raw(8) == Some(b"FIX.4.4" as &[u8])
+            && message.get_raw(35) == Some(b"0" as &[u8])
+            && message
+                .fields()
+                .skip(2)
+                .map(|(tag, value)| (tag.get(), value))
+                .eq(values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (5000 + i as u32, v.as_bytes())))
+    }
+
+    #[test]
+    fn zero_seq_num_is_invalid() {
+        let mut decoder = decoder();
+        decoder.config_mut().verify_checksum = false;
+        decoder.config_mut().should_check_int_subtypes = true;
+        let message = "8=FIX.4.2|9=41|35=0|49=A|56=B|34=0|52=20100304-07:59:30|10=000|";
+        let result = decoder.decode(message.as_bytes());
+        assert!(matches!(
+            result,
+            Err(DecodeError::FieldValue {
+                reason: IntSubtypeError::NotPositive(FixDatatype::SeqNum),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn zero_end_seq_no_is_valid() {
+        let mut decoder = decoder();
+        decoder.config_mut().should_check_int_subtypes = true;
+        let message = "8=FIX.4.4|9=29|35=2|49=A|56=B|34=2|7=1|16=0|10=000|";
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(message.get(16), Ok(0u64));
+    }
+
+    #[test]
+    fn negative_num_in_group_is_invalid() {
+        let mut decoder = decoder();
+        decoder.config_mut().verify_checksum = false;
+        decoder.config_mut().should_check_int_subtypes = true;
+        let message =
+            "8=FIX.4.4|9=59|35=X|49=A|56=B|34=12|52=20100318-03:21:11.364|262=A|268=-1|10=000|";
+        let result = decoder.decode(message.as_bytes());
+        match result {
+            Err(DecodeError::FieldValue { tag, reason }) => {
+                assert_eq!(tag.get(), 268);
+                assert_eq!(reason, IntSubtypeError::Negative(FixDatatype::NumInGroup));
+            }
+            _ => panic!("expected a field value error"),
+        }
+    }
+
+    #[test]
+    fn int_subtype_checks_are_opt_in() {
+        let mut decoder = decoder();
+        decoder.config_mut().verify_checksum = false;
+        let message = "8=FIX.4.2|9=41|35=0|49=A|56=B|34=0|52=20100304-07:59:30|10=000|";
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(message.get(34), Ok(0u64));
+    }
+
+    #[test]
+    #[cfg(feature = "fix42")]
+    fn appl_ver_id_overrides_dictionary() {
+        let message =
+            "8=FIX.4.2|9=57|35=D|49=A|56=B|34=2|1128=6|453=2|448=X|447=D|448=Y|447=D|10=000|";
+        let mut decoder = Decoder::new(Dictionary::precompiled(AppVersion::Fix42));
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        // FIX 4.2 has no `NoPartyIDs <453>`, so `PartyID <448>` is top-level.
+        let message_fix42 = decoder.decode(message.as_bytes()).unwrap();
+        assert!(message_fix42.get_raw(448).is_some());
+        decoder.add_appl_ver_id(b"6", Dictionary::precompiled(AppVersion::Fix44));
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert_eq!(message.get_raw(448), None);
+        assert_eq!(message.app_version(), Some(AppVersion::Fix44));
+        let party_ids = message.group(453).unwrap();
+        assert_eq!(party_ids.get(1).unwrap().get_raw(448), Some(b"Y" as &[u8]));
+        let message =
+            "8=FIX.4.2|9=57|35=D|49=A|56=B|34=2|1128=7|453=2|448=X|447=D|448=Y|447=D|10=000|";
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert!(message.get_raw(448).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "fix42")]
+    fn cstm_appl_ver_id_takes_precedence() {
+        let mut decoder = Decoder::new(Dictionary::precompiled(AppVersion::Fix44));
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder.add_appl_ver_id(b"4", Dictionary::precompiled(AppVersion::Fix42));
+        decoder.add_cstm_appl_ver_id(b"custom", Dictionary::precompiled(AppVersion::Fix44));
+        for message in [
+            "8=FIX.4.4|9=69|35=D|49=A|56=B|34=2|1129=custom|1128=4|453=2|448=X|447=D|448=Y|447=D|10=000|",
+            "8=FIX.4.4|9=69|35=D|49=A|56=B|34=2|1128=4|1129=custom|453=2|448=X|447=D|448=Y|447=D|10=000|",
+        ] {
+            let message = decoder.decode(message.as_bytes()).unwrap();
+            assert_eq!(message.get_raw(448), None);
+        }
+        let message =
+            "8=FIX.4.4|9=57|35=D|49=A|56=B|34=2|1128=4|453=2|448=X|447=D|448=Y|447=D|10=000|";
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        assert!(message.get_raw(448).is_some());
+    }
+
+    #[test]
+    fn repeated_top_level_fields_are_all_kept() {
+        let mut decoder = decoder();
+        decoder.config_mut().verify_checksum = false;
+        let message = "8=FIX.4.4|9=48|35=D|49=A|56=B|34=2|58=a|58=b|453=1|448=X|447=D|10=000|";
+        let message = decoder.decode(message.as_bytes()).unwrap();
+        let texts: Vec<&[u8]> = message.get_all(58).collect();
+        assert_eq!(texts, vec![b"a" as &[u8], b"b"]);
+        assert_eq!(message.get_raw(58), Some(b"b" as &[u8]));
+        assert_eq!(message.get_all(448).count(), 0);
+        let fields: Vec<_> = message
+            .fields()
+            .filter(|(tag, _)| tag.get() == 58)
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(fields, texts);
+    }
+
+    #[test]
+    fn reused_decoder_has_no_leftovers() {
+        let mut codec = decoder();
+        let message = codec.decode(RANDOM_MESSAGES[1].as_bytes()).unwrap();
+        assert_eq!(message.get_raw(55), Some(b"SPMI.MI" as &[u8]));
+        let message = codec.decode(RANDOM_MESSAGES[0].as_bytes()).unwrap();
+        assert_eq!(message.get_raw(55), None);
+        assert_eq!(message.len(), message.fields().count());
+        assert_eq!(message.get(34), Ok(12));
+    }
+
     #[test]
     fn heartbeat_message_fields_are_ok() {
         let mut codec = decoder();
@@ -800,9 +2867,163 @@ mod test {
         let msg = "8=FIX.4.4|9=37|35=D|49=AFUNDMGR|56=ABROKERt|15=USD|59=0|";
         let mut codec = decoder();
         let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::MissingCheckSum)));
+    }
+
+    #[test]
+    fn field_after_checksum() {
+        let msg = "8=FIX.4.4|9=5|35=0|10=000|58=x|";
+        let mut codec = decoder();
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::FieldAfterCheckSum)));
+        codec.config_mut().should_check_trailer = false;
+        let result = codec.decode(msg.as_bytes());
         assert!(matches!(result, Err(DecodeError::Invalid)));
     }
 
+    #[test]
+    fn non_digit_tag_is_malformed() {
+        let msg = "8=FIX.4.4|9=20|35=0|49=A|56=B|3A=x|10=000|";
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(
+            result,
+            Err(DecodeError::MalformedTag { offset: 30 })
+        ));
+    }
+
+    #[test]
+    fn overflowing_tag_is_malformed() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let msg = "8=FIX.4.4|9=29|35=0|49=A|56=B|99999999999=x|10=000|";
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::MalformedTag { .. })));
+        let msg = "8=FIX.4.4|9=19|35=0|49=A|56=B|0=x|10=000|";
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::MalformedTag { .. })));
+        let msg = "8=FIX.4.4|9=23|35=0|49=A|56=B|00058=x|10=000|";
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(message.get_raw(58), Some(b"x" as &[u8]));
+    }
+
+    #[test]
+    fn debug_renders_fields_with_pipes() {
+        let mut codec = decoder();
+        codec.config_mut().separator = 0x1;
+        codec.config_mut().verify_checksum = false;
+        let msg = b"8=FIX.4.4\x019=20\x0135=0\x0149=A\x0156=B\x0134=2\x0110=000\x01";
+        let message = codec.decode(msg).unwrap();
+        assert_eq!(
+            format!("{:?}", message),
+            "Message(8=FIX.4.4|35=0|49=A|56=B|34=2|)"
+        );
+    }
+
+    #[test]
+    fn debug_truncates_long_data_fields() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let data = "\x01".repeat(100);
+        let msg = format!("8=FIX.4.4|9=126|35=0|49=A|56=B|95=100|96={}|10=000|", data);
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        let debug = format!("{:?}", message);
+        assert!(debug.ends_with(&format!("|96={}...(100 bytes)|)", "\\x01".repeat(64))));
+    }
+
+    #[test]
+    fn signature_covers_everything_before_signature_length() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let signed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let signed_clone = signed.clone();
+        codec.set_signature_verifier(move |data, signature| {
+            *signed_clone.lock().unwrap() = data.to_vec();
+            signature == b"secret"
+        });
+        let msg = "8=FIX.4.4|9=35|35=0|49=A|56=B|34=2|93=6|89=secret|10=000|";
+        assert!(codec.decode(msg.as_bytes()).is_ok());
+        assert_eq!(
+            &signed.lock().unwrap()[..],
+            b"8=FIX.4.4|9=35|35=0|49=A|56=B|34=2|"
+        );
+    }
+
+    #[test]
+    fn verifier_and_signable_bytes_agree() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        codec.config_mut().signing_convention.include_framing = false;
+        let signed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let signed_clone = signed.clone();
+        codec.set_signature_verifier(move |data, _| {
+            *signed_clone.lock().unwrap() = data.to_vec();
+            true
+        });
+        let msg = "8=FIX.4.4|9=35|35=0|49=A|56=B|34=2|93=6|89=se|r|t|10=000|";
+        let config = *codec.config();
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(message.signable_bytes(&config), b"35=0|49=A|56=B|34=2|");
+        assert_eq!(&signed.lock().unwrap()[..], message.signable_bytes(&config));
+
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let msg = "8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|10=000|";
+        let config = *codec.config();
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(
+            message.signable_bytes(&config),
+            b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|"
+        );
+    }
+
+    #[test]
+    fn unsigned_message_is_rejected_by_verifier() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        codec.set_signature_verifier(|_, _| true);
+        let msg = "8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|10=000|";
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::SignatureInvalid)));
+    }
+
+    #[test]
+    fn group_with_too_many_entries_is_rejected() {
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let msg = "8=FIX.4.4|9=50|35=W|49=A|56=B|34=2|262=x|268=1000000|269=0|270=1|10=000|";
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(
+            result,
+            Err(DecodeError::GroupTooLarge {
+                num_entries: 1000000,
+                ..
+            })
+        ));
+        let msg = "8=FIX.4.4|9=56|35=W|49=A|56=B|34=2|262=x|268=2|269=0|270=1|269=1|270=2|10=000|";
+        assert!(codec.decode(msg.as_bytes()).is_ok());
+        codec.config_mut().max_group_entries = Some(1);
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(result, Err(DecodeError::GroupTooLarge { .. })));
+    }
+
+    #[test]
+    fn data_field_requires_preceding_length() {
+        let msg = "8=FIX.4.4|9=48|35=D|49=AFUNDMGR|56=ABROKERt|15=USD|89=foo|93=3|10=000|";
+        let mut codec = decoder();
+        codec.config_mut().verify_checksum = false;
+        let result = codec.decode(msg.as_bytes());
+        assert!(matches!(
+            result,
+            Err(DecodeError::DataLength { tag }) if tag.get() == 89
+        ));
+        codec.config_mut().should_check_data_lengths = false;
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        assert_eq!(message.get_raw(89), Some(b"foo" as &[u8]));
+        assert_eq!(message.get(93), Ok(3));
+    }
+
     #[test]
     fn message_with_data_field() {
         let msg =
@@ -848,4 +3069,110 @@ mod test {
             codec.clear();
         }
     }
+
+    #[test]
+    fn streaming_decoder_accepts_one_byte_at_a_time() {
+        // The value of `RawData <96>` contains both separators and `=`.
+        let msg = message_with_payload(b"35=0|49=A|56=B|34=2|95=7|96=a|b=c|d|");
+        let mut codec = decoder_without_checksum().streaming(vec![]);
+        for (i, byte) in msg.iter().enumerate() {
+            assert!(codec.num_bytes_required() > codec.buffer().len());
+            codec.buffer().push(*byte);
+            let is_complete = codec.try_parse().unwrap().is_some();
+            assert_eq!(is_complete, i == msg.len() - 1);
+        }
+        assert_eq!(codec.message().get_raw(96), Some(&b"a|b=c|d"[..]));
+    }
+
+    #[test]
+    fn streaming_decoder_can_be_reused_after_reset() {
+        let msg = message_with_payload(b"35=0|49=A|56=B|34=2|");
+        let mut codec = decoder_without_checksum().streaming(vec![]);
+        codec.buffer().extend_from_slice(b"8=FIX.4.4|garbage|");
+        assert!(codec.try_parse().is_err());
+        codec.reset();
+        assert!(codec.buffer().is_empty());
+        codec.buffer().extend_from_slice(&msg[..msg.len() / 2]);
+        assert!(codec.try_parse().unwrap().is_none());
+        // A partially received message is discarded, too.
+        codec.reset();
+        codec.buffer().extend_from_slice(&msg);
+        assert!(codec.try_parse().unwrap().is_some());
+        assert_eq!(codec.message().get_raw(34), Some(&b"2"[..]));
+    }
+
+    #[test]
+    fn body_length_and_checksum_match_encoder() {
+        let msg = message_with_payload(
+            b"35=W|49=A|56=B|34=2|268=2|269=0|270=1.5|269=1|270=2|95=3|96=a|b|",
+        );
+        let mut decoder = decoder_without_checksum();
+        let message = decoder.decode(&msg[..]).unwrap();
+        for separator in [b'|', 0x1] {
+            let mut encoder = Encoder::new();
+            encoder.config_mut().separator = separator;
+            let config = *encoder.config();
+            let mut buffer = Vec::new();
+            let mut reencoded = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+            reencoded.set_fields(message.fields());
+            let reencoded = reencoded.done().0;
+            let body_length = format!("9={:08}", message.body_length());
+            let checksum = format!("10={:03}", message.checksum(&config).0);
+            // Skip `BeginString <8>`.
+            let fields: Vec<&[u8]> = reencoded.split(|b| *b == separator).skip(1).collect();
+            assert_eq!(fields[0], body_length.as_bytes());
+            assert_eq!(fields[fields.len() - 2], checksum.as_bytes());
+        }
+    }
+
+    #[test]
+    fn fields_with_context() {
+        let msg = "8=FIX.4.4|9=112|35=X|49=A|56=B|34=12|52=20100318-03:21:11.364|262=A|268=2|279=0|269=0|278=BID|279=0|269=1|278=OFFER|93=3|89=abc|10=000|";
+        let mut codec = decoder();
+        let message = codec.decode(msg.as_bytes()).unwrap();
+        let mut fields = vec![];
+        message.for_each_with_context(|context, tag, value| {
+            fields.push((
+                context.location,
+                context.groups.to_vec(),
+                tag.get(),
+                value.to_vec(),
+            ));
+        });
+        let group_entry = |entry_index| GroupEntryContext {
+            num_in_group_tag: TagU32::new(268).unwrap(),
+            entry_index,
+        };
+        assert_eq!(fields.len(), message.len());
+        assert_eq!(fields[0].0, FieldLocation::Header);
+        assert_eq!(fields[0].2, 8);
+        assert_eq!(fields[5].0, FieldLocation::Header);
+        assert_eq!(fields[5].2, 52);
+        assert_eq!(fields[6].0, FieldLocation::Body);
+        assert_eq!(fields[6].2, 262);
+        assert!(fields[7].1.is_empty());
+        assert_eq!(fields[10].1, vec![group_entry(0)]);
+        assert_eq!(fields[10].3, b"BID");
+        assert_eq!(fields[13].1, vec![group_entry(1)]);
+        assert_eq!(fields[13].3, b"OFFER");
+        assert_eq!(fields[14].0, FieldLocation::Trailer);
+        assert_eq!(fields[15].0, FieldLocation::Trailer);
+        assert_eq!(fields[15].3, b"abc");
+    }
+
+    #[test]
+    fn field_spans_point_to_field_values() {
+        let msg = message_with_payload(b"35=W|49=A|56=B|34=2|268=1|269=0|95=3|96=a|b|");
+        let mut codec = decoder_without_checksum();
+        assert!(codec.decode(&msg[..]).unwrap().field_spans().is_empty());
+        codec.config_mut().should_record_spans = true;
+        let message = codec.decode(&msg[..]).unwrap();
+        let spans = message.field_spans();
+        assert_eq!(spans.len(), message.len());
+        for (span, (tag, value)) in spans.iter().zip(message.fields()) {
+            assert_eq!(span.tag, tag);
+            assert_eq!(&msg[span.value_start..span.value_end], value);
+        }
+        assert_eq!(&msg[spans[8].value_start..spans[8].value_end], b"a|b");
+    }
 }
@@ -0,0 +1,385 @@
+use super::{GroupEntryContext, Message};
+use crate::{Dictionary, TagU32};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// The tags of the fields which usually vary from one run to another of the
+/// same test or conformance scenario: `BodyLength <9>`, `CheckSum <10>`,
+/// `MsgSeqNum <34>`, and `SendingTime <52>`. See
+/// [`Message::assert_eq_ignoring`].
+pub const VOLATILE_TAGS: &[u32] = &[9, 10, 34, 52];
+
+/// A single difference between two FIX messages, as reported by
+/// [`Message::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff<'a> {
+    /// The tag of the field that differs.
+    pub tag: TagU32,
+    /// The name of the field, if it's present in the [`Dictionary`].
+    pub name: Option<&'a str>,
+    /// All repeating group entries that enclose the field. See
+    /// [`FieldContext::groups`](super::FieldContext::groups).
+    pub groups: Vec<GroupEntryContext>,
+    /// The field value in the left-hand side message, if present.
+    pub left: Option<&'a [u8]>,
+    /// The field value in the right-hand side message, if present.
+    pub right: Option<&'a [u8]>,
+}
+
+/// Renders `self` on a single line, e.g.
+/// `MDEntryPx <270> (268[1]): "1.5" != "1.6"` for a field within the second
+/// entry of `NoMDEntries <268>`, or `Text <58>: "foo" != (missing)`.
+impl<'a> fmt::Display for FieldDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{} <{}>", name, self.tag)?,
+            None => write!(f, "<{}>", self.tag)?,
+        }
+        if !self.groups.is_empty() {
+            let groups: Vec<String> = self
+                .groups
+                .iter()
+                .map(|group| format!("{}[{}]", group.num_in_group_tag, group.entry_index))
+                .collect();
+            write!(f, " ({})", groups.join(" > "))?;
+        }
+        let value = |value: Option<&[u8]>| match value {
+            Some(value) => format!("\"{}\"", value.escape_ascii()),
+            None => "(missing)".to_string(),
+        };
+        write!(f, ": {} != {}", value(self.left), value(self.right))
+    }
+}
+
+type FieldKey = (Vec<GroupEntryContext>, TagU32);
+
+fn collect_fields<'a, T>(message: &'a Message<'a, T>) -> Vec<(FieldKey, &'a [u8])> {
+    let mut fields = Vec::with_capacity(message.len());
+    message.for_each_with_context(|context, tag, value| {
+        fields.push(((context.groups.to_vec(), tag), value));
+    });
+    fields
+}
+
+fn field_counts<'a, T>(
+    message: &'a Message<'a, T>,
+    ignore: &[u32],
+) -> HashMap<(FieldKey, &'a [u8]), usize> {
+    let mut counts = HashMap::with_capacity(message.len());
+    for field in collect_fields(message) {
+        if !ignore.contains(&(field.0).1.get()) {
+            *counts.entry(field).or_default() += 1;
+        }
+    }
+    counts
+}
+
+impl<'a, T> Message<'a, T> {
+    /// Like `==`, but skips fields with tags in `ignore`, e.g.
+    /// `SendingTime <52>` and `MsgSeqNum <34>` when comparing a resent message
+    /// against the original one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Decoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder_1 = Decoder::new(Dictionary::fix44());
+    /// let mut decoder_2 = Decoder::new(Dictionary::fix44());
+    /// decoder_1.config_mut().separator = b'|';
+    /// decoder_2.config_mut().separator = b'|';
+    ///
+    /// let msg_1 = decoder_1
+    ///     .decode(b"8=FIX.4.4|9=20|35=0|49=A|56=B|52=1|10=000|")
+    ///     .unwrap();
+    /// let msg_2 = decoder_2
+    ///     .decode(b"8=FIX.4.4|9=20|35=0|52=2|56=B|49=A|10=000|")
+    ///     .unwrap();
+    ///
+    /// assert!(msg_1 != msg_2);
+    /// assert!(msg_1.eq_ignoring(&msg_2, &[52]));
+    /// assert!(!msg_1.eq_ordered(&msg_2));
+    /// ```
+    pub fn eq_ignoring<U>(&self, other: &Message<'_, U>, ignore: &[u32]) -> bool {
+        field_counts(self, ignore) == field_counts(other, ignore)
+    }
+
+    /// Returns `true` if and only if `self` and `other` have exactly the same
+    /// fields in exactly the same order. Unlike `==`, this is sensitive to
+    /// field order.
+    pub fn eq_ordered<U>(&self, other: &Message<'_, U>) -> bool {
+        self.fields().eq(other.fields())
+    }
+
+    /// Compares `self` against `other` field by field and returns all
+    /// differences: fields that are only present in one of the two messages
+    /// and fields with different values. Field order is irrelevant, unless
+    /// within repeating groups where entries are compared by their index.
+    /// Fields with tags in `ignore` (e.g. `SendingTime <52>`) are skipped.
+    ///
+    /// The result is sorted by order of appearance in `self` first, and then
+    /// in `other`. `dict` is only used to look up field names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Config, Decoder};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder_1 = Decoder::new(Dictionary::fix44());
+    /// let mut decoder_2 = Decoder::new(Dictionary::fix44());
+    /// decoder_1.config_mut().separator = b'|';
+    /// decoder_2.config_mut().separator = b'|';
+    ///
+    /// let msg_1 = decoder_1
+    ///     .decode(b"8=FIX.4.4|9=20|35=0|49=A|56=B|52=1|10=000|")
+    ///     .unwrap();
+    /// let msg_2 = decoder_2
+    ///     .decode(b"8=FIX.4.4|9=20|35=0|49=A|56=C|52=2|10=000|")
+    ///     .unwrap();
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let diffs = msg_1.diff(&msg_2, &dict, &[52]);
+    /// assert_eq!(diffs.len(), 1);
+    /// assert_eq!(diffs[0].name, Some("TargetCompID"));
+    /// assert_eq!(diffs[0].left, Some(b"B" as &[u8]));
+    /// assert_eq!(diffs[0].right, Some(b"C" as &[u8]));
+    /// ```
+    pub fn diff<'b, U>(
+        &'b self,
+        other: &'b Message<'b, U>,
+        dict: &'b Dictionary,
+        ignore: &[u32],
+    ) -> Vec<FieldDiff<'b>> {
+        let left = collect_fields(self);
+        let right = collect_fields(other);
+        let mut right_indices: HashMap<&FieldKey, Vec<usize>> = HashMap::new();
+        for (i, (key, _)) in right.iter().enumerate().rev() {
+            right_indices.entry(key).or_default().push(i);
+        }
+        let mut right_is_matched = vec![false; right.len()];
+        let mut diffs = Vec::new();
+        let mut push_diff = |key: &FieldKey, left, right| {
+            diffs.push(FieldDiff {
+                tag: key.1,
+                name: dict.field_by_tag(key.1.get()).map(|f| f.name()),
+                groups: key.0.clone(),
+                left,
+                right,
+            });
+        };
+        for (key, left_value) in left.iter() {
+            if ignore.contains(&key.1.get()) {
+                continue;
+            }
+            match right_indices.get_mut(key).and_then(|indices| indices.pop()) {
+                Some(i) => {
+                    right_is_matched[i] = true;
+                    if *left_value != right[i].1 {
+                        push_diff(key, Some(*left_value), Some(right[i].1));
+                    }
+                }
+                None => push_diff(key, Some(*left_value), None),
+            }
+        }
+        for ((key, right_value), is_matched) in right.iter().zip(right_is_matched) {
+            if !is_matched && !ignore.contains(&key.1.get()) {
+                push_diff(key, None, Some(*right_value));
+            }
+        }
+        diffs
+    }
+
+    /// Like [`Message::diff`], but meant for assertions in tests, with
+    /// `expected` on the right-hand side: it returns all differences as an
+    /// error, if any. [`VOLATILE_TAGS`] is a sensible default for `ignore`.
+    /// [`FieldDiff`] implements [`Display`](fmt::Display) for test failure
+    /// messages.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, VOLATILE_TAGS};
+    /// use fefix::prelude::*;
+    ///
+    /// let dict = Dictionary::fix44();
+    /// let mut decoder_1 = Decoder::new(&dict);
+    /// let mut decoder_2 = Decoder::new(&dict);
+    /// decoder_1.config_mut().separator = b'|';
+    /// decoder_2.config_mut().separator = b'|';
+    ///
+    /// let msg = decoder_1
+    ///     .decode_lenient_fixture("8=FIX.4.4|35=0|49=A|56=B|34=2|52=20210101-10:00:00|58=foo|")
+    ///     .unwrap();
+    /// let expected = decoder_2
+    ///     .decode_lenient_fixture("8=FIX.4.4|35=0|49=A|56=B|34=7|52=20210101-11:00:00|")
+    ///     .unwrap();
+    ///
+    /// let diffs = msg.assert_eq_ignoring(&expected, &dict, VOLATILE_TAGS).unwrap_err();
+    /// let report: Vec<String> = diffs.iter().map(ToString::to_string).collect();
+    /// assert_eq!(report, vec![r#"Text <58>: "foo" != (missing)"#]);
+    /// ```
+    pub fn assert_eq_ignoring<'b, U>(
+        &'b self,
+        expected: &'b Message<'b, U>,
+        dict: &'b Dictionary,
+        ignore: &[u32],
+    ) -> Result<(), Vec<FieldDiff<'b>>> {
+        let diffs = self.diff(expected, dict, ignore);
+        if diffs.is_empty() {
+            Ok(())
+        } else {
+            Err(diffs)
+        }
+    }
+}
+
+/// Two messages are equal if they have the same fields with the same values,
+/// regardless of field order. Fields within repeating groups are matched by
+/// the index of their group entry, so the order of entries matters but the
+/// order of fields within each entry doesn't.
+///
+/// All fields are compared, including `BeginString <8>`, `BodyLength <9>`,
+/// `SendingTime <52>`, and `CheckSum <10>` (which don't depend on field order
+/// either). See [`Message::eq_ignoring`] to skip some fields and
+/// [`Message::eq_ordered`] for order-sensitive comparisons.
+impl<'a, T> PartialEq for Message<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_ignoring(other, &[])
+    }
+}
+
+impl<'a, T> Eq for Message<'a, T> {}
+
+/// Consistent with [`PartialEq`], i.e. it doesn't depend on field order.
+impl<'a, T> Hash for Message<'a, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // A commutative combination of the hashes of all fields.
+        let mut sum = 0u64;
+        for field in collect_fields(self) {
+            let mut hasher = DefaultHasher::new();
+            field.hash(&mut hasher);
+            sum = sum.wrapping_add(hasher.finish());
+        }
+        state.write_usize(self.len());
+        state.write_u64(sum);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Decoder;
+    use crate::GetConfig;
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder
+    }
+
+    #[test]
+    fn identical_messages_have_no_diffs() {
+        let msg = b"8=FIX.4.4|9=20|35=0|49=A|56=B|52=1|10=000|";
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1.decode(msg).unwrap();
+        let msg_2 = decoder_2.decode(msg).unwrap();
+        assert!(msg_1.diff(&msg_2, &Dictionary::fix44(), &[]).is_empty());
+    }
+
+    #[test]
+    fn field_order_is_irrelevant() {
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1
+            .decode(b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=000|")
+            .unwrap();
+        let msg_2 = decoder_2
+            .decode(b"8=FIX.4.4|9=15|35=0|56=B|49=A|10=000|")
+            .unwrap();
+        assert!(msg_1.diff(&msg_2, &Dictionary::fix44(), &[]).is_empty());
+    }
+
+    #[test]
+    fn equality_ignores_field_order() {
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1
+            .decode(b"8=FIX.4.4|9=28|35=X|49=A|268=2|279=0|279=1|10=000|")
+            .unwrap();
+        let msg_2 = decoder_2
+            .decode(b"8=FIX.4.4|9=28|35=X|268=2|279=0|279=1|49=A|10=000|")
+            .unwrap();
+        assert_eq!(msg_1, msg_2);
+        assert!(!msg_1.eq_ordered(&msg_2));
+        let (mut hasher_1, mut hasher_2) = (DefaultHasher::new(), DefaultHasher::new());
+        msg_1.hash(&mut hasher_1);
+        msg_2.hash(&mut hasher_2);
+        assert_eq!(hasher_1.finish(), hasher_2.finish());
+    }
+
+    #[test]
+    fn equality_respects_group_entries() {
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1
+            .decode(b"8=FIX.4.4|9=28|35=X|49=A|268=2|279=0|279=1|10=000|")
+            .unwrap();
+        let msg_2 = decoder_2
+            .decode(b"8=FIX.4.4|9=28|35=X|49=A|268=2|279=1|279=0|10=000|")
+            .unwrap();
+        assert_ne!(msg_1, msg_2);
+        assert!(msg_1.eq_ignoring(&msg_2, &[279]));
+    }
+
+    #[test]
+    fn missing_fields_and_group_entries() {
+        let dict = Dictionary::fix44();
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1
+            .decode(b"8=FIX.4.4|9=28|35=X|49=A|268=2|279=0|279=1|10=000|")
+            .unwrap();
+        let msg_2 = decoder_2
+            .decode(b"8=FIX.4.4|9=28|35=X|56=B|268=2|279=0|279=2|10=000|")
+            .unwrap();
+        let diffs = msg_1.diff(&msg_2, &dict, &[]);
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].name, Some("SenderCompID"));
+        assert_eq!(diffs[0].right, None);
+        assert_eq!(diffs[1].tag.get(), 279);
+        assert_eq!(diffs[1].groups[0].entry_index, 1);
+        assert_eq!(diffs[1].left, Some(b"1" as &[u8]));
+        assert_eq!(diffs[1].right, Some(b"2" as &[u8]));
+        assert_eq!(diffs[2].name, Some("TargetCompID"));
+        assert_eq!(diffs[2].left, None);
+        assert!(msg_1.diff(&msg_2, &dict, &[49, 56, 279]).is_empty());
+    }
+
+    #[test]
+    fn diffs_are_human_readable() {
+        let dict = Dictionary::fix44();
+        let (mut decoder_1, mut decoder_2) = (decoder(), decoder());
+        let msg_1 = decoder_1
+            .decode_lenient_fixture(
+                "8=FIX.4.4|35=W|49=A|56=B|34=2|268=2|269=0|270=1.5|269=1|270=1.6|5001=\x7f|",
+            )
+            .unwrap();
+        let msg_2 = decoder_2
+            .decode_lenient_fixture("8=FIX.4.4|35=W|49=A|56=B|34=3|268=2|269=0|270=1.5|269=1|")
+            .unwrap();
+        assert!(msg_1.assert_eq_ignoring(&msg_1, &dict, &[]).is_ok());
+        let report: Vec<String> = msg_1
+            .assert_eq_ignoring(&msg_2, &dict, VOLATILE_TAGS)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            report,
+            vec![
+                r#"MDEntryPx <270> (268[1]): "1.6" != (missing)"#,
+                r#"<5001>: "\x7f" != (missing)"#,
+            ]
+        );
+    }
+}
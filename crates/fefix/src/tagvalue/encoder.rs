@@ -1,10 +1,39 @@
-use super::Config;
+use super::{Config, FieldTransform, Message, TextError, TextPolicy};
 use crate::dict::IsFieldDefinition;
 use crate::field_types::CheckSum;
 use crate::{Buffer, BufferWriter, FieldType, GetConfig, SetField, TagU32};
+use nohash_hasher::IntMap;
+use std::borrow::Cow;
 use std::fmt::Write;
 use std::ops::Range;
 
+/// The type returned by [`Encoder::encode_with_begin_string`] when the
+/// `BeginString <8>` override is neither `FIX.x.y` nor `FIXT.1.1`. See
+/// [`Config::should_check_begin_string`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Implausible `BeginString <8>` value.")]
+pub struct BeginStringError;
+
+/// The type returned in the event of an error by [`EncoderHandle::try_done`].
+/// The offending fields or group entries are left out of the message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// A field was rejected by [`Encoder::set_text_policy`].
+    #[error(transparent)]
+    Text(#[from] TextError),
+    /// An entry of a repeating group doesn't start with the delimiter field,
+    /// i.e. the first field of the first entry. See
+    /// [`EncoderHandle::set_group`].
+    #[error("Group entry starts with field {tag} instead of delimiter field {delimiter}.")]
+    GroupEntry {
+        /// The tag of the first field of the first entry.
+        delimiter: u32,
+        /// The tag of the first field of the offending entry.
+        tag: u32,
+    },
+}
+
 /// A buffered, content-agnostic FIX encoder.
 ///
 /// [`Encoder`] is the fundamental building block for building higher-level
@@ -26,6 +55,8 @@ use std::ops::Range;
 #[derive(Debug, Clone, Default)]
 pub struct Encoder {
     config: Config,
+    field_transforms: IntMap<u32, FieldTransform>,
+    text_policy: Option<TextPolicy>,
 }
 
 impl Encoder {
@@ -52,6 +83,8 @@ impl Encoder {
             buffer,
             initial_buffer_len,
             body_start_i: 0,
+            last_field: None,
+            error: None,
         };
         state.set(8, begin_string);
         // The second field is supposed to be `BodyLength(9)`, but obviously
@@ -74,6 +107,195 @@ impl Encoder {
         state.set(35, msg_type);
         state
     }
+
+    /// Rewrites the values of all fields with `tag` (within repeating groups
+    /// too) with the encoding half of `transform`, replacing any previous
+    /// transform for `tag`.
+    ///
+    /// Values are transformed as soon as they're written, so `BodyLength <9>`
+    /// and `CheckSum <10>` are computed over the transformed message, as sent
+    /// on the wire. Transforms for `BeginString <8>`, `BodyLength <9>`,
+    /// `MsgType <35>`, and `CheckSum <10>` are ignored, and so are all
+    /// transforms by [`CompiledMessage`](super::CompiledMessage).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Encoder, FieldTransform};
+    /// use fefix::prelude::*;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// encoder.add_field_transform(
+    ///     55,
+    ///     FieldTransform::new(
+    ///         |symbol| Cow::Owned([symbol, b".N"].concat()),
+    ///         |symbol| Cow::Borrowed(symbol.strip_suffix(b".N").unwrap_or(symbol)),
+    ///     ),
+    /// );
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+    /// msg.set(fix44::SYMBOL, "ABC");
+    /// assert_eq!(msg.done().0, b"8=FIX.4.4|9=00000014|35=D|55=ABC.N|10=221|");
+    /// ```
+    pub fn add_field_transform(&mut self, tag: u32, transform: FieldTransform) {
+        self.field_transforms.insert(tag, transform);
+    }
+
+    /// Applies `policy` to the values of free-text fields as soon as they're
+    /// written, after [`Encoder::add_field_transform`]. Fields rejected
+    /// under [`ControlChars::Reject`] are left out of the message, and
+    /// [`EncoderHandle::try_done`] reports them, whereas
+    /// [`EncoderHandle::done`] only logs a warning. There's no text policy by
+    /// default.
+    ///
+    /// The `Length` fields of `data` fields are not updated, so `policy`
+    /// should only apply to `String` fields.
+    ///
+    /// [`ControlChars::Reject`]: super::ControlChars::Reject
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{ControlChars, Encoder, TextPolicy};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// encoder.set_text_policy(TextPolicy::new(ControlChars::Escape));
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"3");
+    /// msg.set(fix44::TEXT, "Bad|field\n");
+    /// let data = String::from_utf8(msg.done().0.to_vec()).unwrap();
+    /// assert!(data.contains("|58=Bad\\x7Cfield\\x0A|10="));
+    /// ```
+    pub fn set_text_policy(&mut self, policy: TextPolicy) {
+        self.text_policy = Some(policy);
+    }
+
+    /// Encodes a whole FIX message from `fields`, in the given order, and
+    /// appends it to `buffer`. `BeginString <8>` and `MsgType <35>` must be
+    /// among `fields`, but `BodyLength <9>` and `CheckSum <10>` are always
+    /// computed automatically. Returns [`None`] if either `BeginString <8>` or
+    /// `MsgType <35>` is missing.
+    ///
+    /// This is mostly useful for tests and simple tools. No [`Dictionary`]
+    /// is required.
+    ///
+    /// [`Dictionary`]: crate::Dictionary
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Encoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let (msg, _) = encoder
+    ///     .encode_fields(
+    ///         &mut buffer,
+    ///         &[(8, "FIX.4.2"), (35, "0"), (49, "A"), (56, "B"), (34, "1")],
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(msg, b"8=FIX.4.2|9=00000020|35=0|49=A|56=B|34=1|10=125|");
+    /// ```
+    pub fn encode_fields<'a, B, V>(
+        &'a mut self,
+        buffer: &'a mut B,
+        fields: &[(u32, V)],
+    ) -> Option<(&'a [u8], usize)>
+    where
+        B: Buffer,
+        V: AsRef<[u8]>,
+    {
+        let find = |tag| {
+            fields
+                .iter()
+                .find(|(t, _)| *t == tag)
+                .map(|(_, value)| value.as_ref())
+        };
+        let begin_string = find(8)?;
+        let msg_type = find(35)?;
+        let mut msg = self.start_message(begin_string, buffer, msg_type);
+        for (tag, value) in fields {
+            if !matches!(tag, 8 | 9 | 10 | 35) {
+                msg.set(*tag, value.as_ref());
+            }
+        }
+        Some(msg.done())
+    }
+
+    /// Re-encodes all fields of `msg` and appends the result to `buffer`, just
+    /// like [`EncoderHandle::set_fields`] does, but with `begin_string` as
+    /// `BeginString <8>` regardless of that of `msg`. `BodyLength <9>` and
+    /// `CheckSum <10>` are computed anew for the resulting bytes. This is
+    /// useful for bridging sessions with different FIX versions, and for tests
+    /// with mismatched versions.
+    ///
+    /// `begin_string` must be either `FIX.x.y` or `FIXT.1.1` unless
+    /// [`Config::should_check_begin_string`] is turned off.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{BeginStringError, Decoder, Encoder};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// let msg = decoder
+    ///     .decode(b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=1|10=038|")
+    ///     .unwrap();
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let (data, _) = encoder
+    ///     .encode_with_begin_string(&mut buffer, &msg, b"FIX.4.2")
+    ///     .unwrap();
+    /// assert_eq!(data, b"8=FIX.4.2|9=00000020|35=0|49=A|56=B|34=1|10=125|");
+    ///
+    /// let result = encoder.encode_with_begin_string(&mut buffer, &msg, b"FIX4.2");
+    /// assert_eq!(result, Err(BeginStringError));
+    /// ```
+    pub fn encode_with_begin_string<'a, B, T>(
+        &'a mut self,
+        buffer: &'a mut B,
+        msg: &Message<'_, T>,
+        begin_string: &[u8],
+    ) -> Result<(&'a [u8], usize), BeginStringError>
+    where
+        B: Buffer,
+    {
+        if self.config.should_check_begin_string && !is_plausible_begin_string(begin_string) {
+            return Err(BeginStringError);
+        }
+        let msg_type = msg.get_all(35).next().unwrap_or_default();
+        let mut handle = self.start_message(begin_string, buffer, msg_type);
+        handle.set_fields(msg.fields());
+        Ok(handle.done())
+    }
+}
+
+/// `FIX.x.y`, with any number of digits, or `FIXT.1.1`.
+fn is_plausible_begin_string(begin_string: &[u8]) -> bool {
+    if begin_string == b"FIXT.1.1" {
+        return true;
+    }
+    let version = match begin_string.strip_prefix(b"FIX.") {
+        Some(version) => version,
+        None => return false,
+    };
+    let mut parts = version.split(|byte| *byte == b'.');
+    let mut is_number = || {
+        parts
+            .next()
+            .is_some_and(|part| !part.is_empty() && part.iter().all(u8::is_ascii_digit))
+    };
+    is_number() && is_number() && parts.next().is_none()
 }
 
 impl GetConfig for Encoder {
@@ -96,6 +318,11 @@ pub struct EncoderHandle<'a, B> {
     buffer: &'a mut B,
     initial_buffer_len: usize,
     body_start_i: usize,
+    // The tag and the start of the last field, if there are any field
+    // transforms.
+    last_field: Option<(u32, usize)>,
+    // The first field or group entry which was left out.
+    error: Option<EncodeError>,
 }
 
 impl<'a, B> EncoderHandle<'a, B>
@@ -105,16 +332,265 @@ where
     /// Closes the current message writing operation and returns its byte
     /// representation, as well as its offset within the whole contents of the
     /// [`Buffer`].
+    ///
+    /// Fields rejected by [`Encoder::set_text_policy`] and invalid group
+    /// entries (see [`EncoderHandle::set_group`]) are left out, with nothing
+    /// but a warning under the `utils-tracing` feature. Use
+    /// [`EncoderHandle::try_done`] to refuse such messages instead, e.g. with
+    /// [`ControlChars::Reject`](super::ControlChars::Reject).
     pub fn done(mut self) -> (&'a [u8], usize) {
+        trace_span!("encode");
+        if self.error.is_some() {
+            trace_event!(
+                warn,
+                error = ?self.error,
+                "Fields were left out of the FIX message; see `EncoderHandle::try_done`"
+            );
+        }
         self.write_body_length();
         self.write_checksum();
+        trace_event!(
+            debug,
+            msg_type = %String::from_utf8_lossy(self.msg_type()),
+            body_length = self.body_length(),
+            len = self.buffer.len() - self.initial_buffer_len,
+            "FIX message encoded"
+        );
         (self.buffer.as_slice(), self.initial_buffer_len)
     }
 
+    /// Like [`EncoderHandle::done`], but fails if any field was rejected by
+    /// [`Encoder::set_text_policy`], or if any group entry was left out (see
+    /// [`EncoderHandle::set_group`]). In that case, the message is removed
+    /// from the [`Buffer`] altogether.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{ControlChars, EncodeError, Encoder, TextError, TextPolicy};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.set_text_policy(TextPolicy::new(ControlChars::Reject));
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"3");
+    /// msg.set(fix44::TEXT, "Bad\nfield");
+    /// assert_eq!(
+    ///     msg.try_done(),
+    ///     Err(EncodeError::Text(TextError { tag: 58, offset: 3 }))
+    /// );
+    /// assert!(buffer.is_empty());
+    /// ```
+    pub fn try_done(self) -> Result<(&'a [u8], usize), EncodeError> {
+        match self.error {
+            Some(err) => {
+                self.buffer.resize(self.initial_buffer_len, 0);
+                Err(err)
+            }
+            None => Ok(self.done()),
+        }
+    }
+
+    /// Writes a repeating group that starts with `num_in_group`. Entries are
+    /// added via [`EncoderGroupHandle::entry`] within `f`, and the value of
+    /// `num_in_group` is then computed automatically from the number of
+    /// entries. Nested groups are supported by means of calling
+    /// [`EncoderHandle::set_group`] inside an entry.
+    ///
+    /// Fields within each entry are written in the order in which they are
+    /// set, so the first of them must be the delimiter field of the
+    /// repeating group. The first field of the first entry is taken as the
+    /// delimiter, and later entries that start with any other field are left
+    /// out, as reported by [`EncoderHandle::try_done`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::Encoder;
+    /// use fefix::prelude::*;
+    ///
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+    /// msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+    ///     group.entry(|entry| {
+    ///         entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+    ///         entry.set(fix44::MD_ENTRY_PX, 1.5f32);
+    ///     });
+    ///     group.entry(|entry| {
+    ///         entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Offer);
+    ///         entry.set(fix44::MD_ENTRY_PX, 1.75f32);
+    ///     });
+    /// });
+    /// let data = String::from_utf8(msg.done().0.to_vec()).unwrap();
+    /// assert!(data.contains("|35=W|268=2|269=0|270=1.5|269=1|270=1.75|10="));
+    /// ```
+    pub fn set_group<K, F>(&mut self, num_in_group: K, f: F)
+    where
+        Self: SetField<K>,
+        F: FnOnce(&mut EncoderGroupHandle<'_, 'a, B>),
+    {
+        let start = self.buffer.len();
+        let mut group = EncoderGroupHandle {
+            message: self,
+            num_entries: 0,
+            delimiter: None,
+        };
+        f(&mut group);
+        let num_entries = group.num_entries;
+        let end_of_entries = self.buffer.len();
+        self.set(num_in_group, num_entries);
+        // The `NumInGroup` field is now at the very end. Move it before all
+        // group entries.
+        let num_in_group_len = self.buffer.len() - end_of_entries;
+        self.buffer.as_mut_slice()[start..].rotate_right(num_in_group_len);
+        self.last_field = None;
+    }
+
+    /// Appends raw `fields` in order, e.g. those returned by
+    /// [`Message::fields`](super::Message::fields). `BeginString <8>`,
+    /// `BodyLength <9>`, `MsgType <35>`, and `CheckSum <10>` are skipped, as
+    /// they're written by [`Encoder`] itself.
+    ///
+    /// This allows to re-encode a decoded message without first converting
+    /// it into an owned data structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, Encoder};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let inbound = b"8=FIX.4.4|9=44|35=D|49=A|56=HUB|34=2|128=B|11=x|55=EUR/USD|10=000|";
+    /// let inbound = decoder.decode(inbound).unwrap();
+    ///
+    /// // Forwards the message from `HUB` to `B`.
+    /// let mut encoder = Encoder::default();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+    /// msg.set(fix44::SENDER_COMP_ID, "HUB");
+    /// msg.set(fix44::TARGET_COMP_ID, "B");
+    /// msg.set(fix44::ON_BEHALF_OF_COMP_ID, "A");
+    /// msg.set_fields(
+    ///     inbound
+    ///         .fields()
+    ///         .filter(|(tag, _)| ![49, 56, 128].contains(&tag.get())),
+    /// );
+    /// let data = String::from_utf8(msg.done().0.to_vec()).unwrap();
+    /// assert!(data.contains("|35=D|49=HUB|56=B|115=A|34=2|11=x|55=EUR/USD|10="));
+    /// ```
+    pub fn set_fields<'f, I>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = (TagU32, &'f [u8])>,
+    {
+        for (tag, value) in fields {
+            if !matches!(tag.get(), 8 | 9 | 10 | 35) {
+                self.set(tag, value);
+            }
+        }
+    }
+
+    fn write_field<F>(&mut self, tag: u32, serialize_value: F)
+    where
+        F: FnOnce(&mut B) -> usize,
+    {
+        let field_start = self.buffer.len();
+        write!(BufferWriter(self.buffer), "{}=", tag).unwrap();
+        let value_start = self.buffer.len();
+        serialize_value(self.buffer);
+        if !self.encoder.field_transforms.is_empty() {
+            self.transform_value(tag, value_start);
+            self.last_field = Some((tag, field_start));
+        }
+        if self.encoder.text_policy.is_some()
+            && !self.apply_text_policy(tag, field_start, value_start)
+        {
+            self.last_field = None;
+            return;
+        }
+        self.buffer
+            .extend_from_slice(&[self.encoder.config().separator]);
+    }
+
+    fn transform_value(&mut self, tag: u32, value_start: usize) {
+        if matches!(tag, 8 | 9 | 10 | 35) {
+            return;
+        }
+        let transform = match self.encoder.field_transforms.get(&tag) {
+            Some(transform) => transform,
+            None => return,
+        };
+        let value = match transform.encode(&self.buffer.as_slice()[value_start..]) {
+            Some(value) => value,
+            None => return,
+        };
+        match (transform.length_tag(), self.last_field) {
+            // The `Length` field of `data` fields comes right before them.
+            (Some(length_tag), Some((last_tag, last_start))) if length_tag == last_tag => {
+                let separator = self.encoder.config.separator as char;
+                self.buffer.resize(last_start, 0);
+                write!(
+                    BufferWriter(self.buffer),
+                    "{}={}{}{}=",
+                    length_tag,
+                    value.len(),
+                    separator,
+                    tag
+                )
+                .unwrap();
+            }
+            _ => self.buffer.resize(value_start, 0),
+        }
+        self.buffer.extend_from_slice(&value);
+    }
+
+    /// Returns `false` if the field was rejected and removed.
+    fn apply_text_policy(&mut self, tag: u32, field_start: usize, value_start: usize) -> bool {
+        let policy = match self.encoder.text_policy.as_ref() {
+            Some(policy) if policy.applies_to(tag) && !matches!(tag, 8 | 9 | 10 | 35) => policy,
+            _ => return true,
+        };
+        let separator = self.encoder.config.separator;
+        match policy.sanitize(tag, &self.buffer.as_slice()[value_start..], separator) {
+            Ok(Cow::Borrowed(_)) => true,
+            Ok(Cow::Owned(value)) => {
+                self.buffer.resize(value_start, 0);
+                self.buffer.extend_from_slice(&value);
+                true
+            }
+            Err(err) => {
+                self.buffer.resize(field_start, 0);
+                self.error.get_or_insert(EncodeError::Text(err));
+                false
+            }
+        }
+    }
+
+    // The tag of the field which starts at `field_start`.
+    fn tag_at(&self, field_start: usize) -> Option<u32> {
+        let field = self.buffer.as_slice().get(field_start..)?;
+        let len = field.iter().position(|byte| *byte == b'=')?;
+        u32::deserialize(&field[..len]).ok()
+    }
+
     fn body_length_writable_range(&self) -> Range<usize> {
         self.body_start_i - 9..self.body_start_i - 1
     }
 
+    #[cfg(feature = "utils-tracing")]
+    fn msg_type(&self) -> &[u8] {
+        // `MsgType <35>` is always the first field after `BodyLength <9>`.
+        let field = &self.buffer.as_slice()[self.body_start_i + 3..];
+        let separator = self.encoder.config().separator;
+        let len = field.iter().position(|b| *b == separator).unwrap_or(0);
+        &field[..len]
+    }
+
     fn body_length(&self) -> usize {
         self.buffer.as_slice().len() - self.body_start_i
     }
@@ -142,10 +618,7 @@ where
     where
         V: FieldType<'s>,
     {
-        write!(BufferWriter(self.buffer), "{}=", tag).unwrap();
-        value.serialize_with(self.buffer, settings);
-        self.buffer
-            .extend_from_slice(&[self.encoder.config().separator]);
+        self.write_field(tag, |buffer| value.serialize_with(buffer, settings));
     }
 }
 
@@ -173,3 +646,292 @@ where
         self.set_with(field.tag(), value, settings)
     }
 }
+
+/// Appends all fields in the given order, exactly like repeated calls to
+/// [`SetField::set`]. Unlike [`EncoderHandle::set_fields`], no field is
+/// skipped.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::Encoder;
+/// use fefix::prelude::*;
+///
+/// let mut encoder = Encoder::default();
+/// encoder.config_mut().separator = b'|';
+/// let mut buffer = Vec::new();
+/// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+/// msg.extend(vec![(49, b"A".to_vec()), (56, b"B".to_vec())]);
+/// msg.extend([(34, 1)]);
+/// let data = msg.done().0;
+/// assert_eq!(data, b"8=FIX.4.4|9=00000020|35=0|49=A|56=B|34=1|10=127|");
+/// ```
+impl<'a, 'v, B, V> Extend<(u32, V)> for EncoderHandle<'a, B>
+where
+    B: Buffer,
+    V: FieldType<'v>,
+{
+    fn extend<I>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = (u32, V)>,
+    {
+        for (tag, value) in fields {
+            self.write_field(tag, |buffer| value.serialize(buffer));
+        }
+    }
+}
+
+/// A type returned by [`EncoderHandle::set_group`] to add entries to a
+/// repeating group.
+#[derive(Debug)]
+pub struct EncoderGroupHandle<'h, 'a, B> {
+    message: &'h mut EncoderHandle<'a, B>,
+    num_entries: usize,
+    // The tag of the first field of the first entry.
+    delimiter: Option<u32>,
+}
+
+impl<'h, 'a, B> EncoderGroupHandle<'h, 'a, B>
+where
+    B: Buffer,
+{
+    /// Adds a new entry to the repeating group. Fields are written via
+    /// `f`, starting with the delimiter field. The entry is left out if it
+    /// starts with any other field; see [`EncoderHandle::set_group`].
+    pub fn entry<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut EncoderHandle<'a, B>),
+    {
+        let start = self.message.buffer.len();
+        f(self.message);
+        match (self.delimiter, self.message.tag_at(start)) {
+            (None, tag) => self.delimiter = tag,
+            (Some(delimiter), Some(tag)) if tag != delimiter => {
+                self.message.buffer.resize(start, 0);
+                self.message.last_field = None;
+                let error = EncodeError::GroupEntry { delimiter, tag };
+                self.message.error.get_or_insert(error);
+                return;
+            }
+            _ => {}
+        }
+        self.num_entries += 1;
+    }
+
+    /// Returns the number of entries added so far.
+    pub fn len(&self) -> usize {
+        self.num_entries
+    }
+
+    /// Returns `true` if and only if no entries were added so far.
+    pub fn is_empty(&self) -> bool {
+        self.num_entries == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::tagvalue::Decoder;
+    use crate::{Dictionary, FieldMap, RepeatingGroup};
+
+    fn encoder() -> Encoder {
+        let mut encoder = Encoder::default();
+        encoder.config_mut().separator = b'|';
+        encoder
+    }
+
+    #[test]
+    fn begin_string_overrides_are_checked() {
+        for begin_string in [&b"FIX.4.4"[..], b"FIX.10.0", b"FIXT.1.1"] {
+            assert!(is_plausible_begin_string(begin_string));
+        }
+        for begin_string in [
+            &b""[..],
+            b"FIX.4",
+            b"FIX.4.4.1",
+            b"FIX.a.b",
+            b"FIX..4",
+            b"FIXT.1.2",
+        ] {
+            assert!(!is_plausible_begin_string(begin_string));
+        }
+
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=27|35=D|49=A|56=B|34=1|11=abc|10=000|")
+            .unwrap();
+        let mut encoder = encoder();
+        encoder.config_mut().should_check_begin_string = false;
+        let mut buffer = Vec::new();
+        let data = encoder
+            .encode_with_begin_string(&mut buffer, &msg, b"CUSTOM")
+            .unwrap()
+            .0
+            .to_vec();
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.get_raw(8), Some(b"CUSTOM" as &[u8]));
+        assert_eq!(msg.get_raw(11), Some(b"abc" as &[u8]));
+    }
+
+    #[test]
+    fn empty_group() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set_group(fix44::NO_MD_ENTRIES, |_group| {});
+        msg.set(fix44::MD_REQ_ID, "foo");
+        let data = msg.done().0;
+        assert_eq!(
+            data,
+            b"8=FIX.4.4|9=00000019|35=W|268=0|262=foo|10=176|" as &[u8]
+        );
+    }
+
+    #[test]
+    fn group_entries_must_start_with_the_delimiter() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+            group.entry(|entry| {
+                entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+                entry.set(fix44::MD_ENTRY_PX, 1.5f32);
+            });
+            group.entry(|entry| {
+                entry.set(fix44::MD_ENTRY_PX, 1.75f32);
+                entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Offer);
+            });
+            assert_eq!(group.len(), 1);
+        });
+        msg.set(fix44::MD_REQ_ID, "foo");
+        assert_eq!(
+            msg.try_done(),
+            Err(EncodeError::GroupEntry {
+                delimiter: 269,
+                tag: 270
+            })
+        );
+        assert!(buffer.is_empty());
+
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+            group.entry(|entry| entry.set(fix44::MD_ENTRY_TYPE, "0"));
+            group.entry(|entry| entry.set(fix44::MD_ENTRY_PX, 1.75f32));
+        });
+        assert_eq!(
+            msg.done().0,
+            b"8=FIX.4.4|9=00000017|35=W|268=1|269=0|10=162|" as &[u8]
+        );
+    }
+
+    #[test]
+    fn extend_with_mapped_inbound_fields() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let inbound = decoder
+            .decode(b"8=FIX.4.4|9=26|35=0|49=A|56=B|34=7|58=hi|10=000|" as &[u8])
+            .unwrap();
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        msg.extend(
+            (&inbound)
+                .into_iter()
+                .map(|(tag, value)| (tag.get(), value))
+                .filter(|(tag, _)| matches!(tag, 49 | 56 | 58)),
+        );
+        let data = msg.done().0;
+        assert!(data.starts_with(b"8=FIX.4.4|9=00000021|35=0|49=A|56=B|58=hi|10="));
+    }
+
+    #[test]
+    fn reencoded_message_is_identical() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set(fix44::MD_REQ_ID, "foo");
+        msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+            group.entry(|entry| {
+                entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+                entry.set(fix44::MD_ENTRY_PX, 1.5f32);
+            });
+        });
+        let original = msg.done().0.to_vec();
+        let message = decoder.decode(&original[..]).unwrap();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set_fields(message.fields());
+        assert_eq!(msg.done().0, &original[..]);
+    }
+
+    #[test]
+    fn encode_fields_keeps_order_and_value_types() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let fields: &[(u32, &[u8])] = &[
+            (8, b"FIX.4.4"),
+            (9, b"999"),
+            (35, b"0"),
+            (56, b"B"),
+            (49, b"A"),
+            (10, b"000"),
+        ];
+        let (msg, _) = encoder.encode_fields(&mut buffer, fields).unwrap();
+        assert_eq!(msg, b"8=FIX.4.4|9=00000015|35=0|56=B|49=A|10=050|");
+        let mut buffer = Vec::new();
+        let fields = [(8, b"FIX.4.4".to_vec()), (35, b"0".to_vec())];
+        assert!(encoder.encode_fields(&mut buffer, &fields).is_some());
+        assert!(encoder
+            .encode_fields(&mut Vec::new(), &[(8, "FIX.4.4"), (49, "A")])
+            .is_none());
+    }
+
+    #[test]
+    fn nested_groups() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set(fix44::MD_REQ_ID, "foo");
+        msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+            for i in 0..12u32 {
+                group.entry(|entry| {
+                    entry.set(fix44::MD_ENTRY_TYPE, fix44::MdEntryType::Bid);
+                    entry.set_group(fix44::NO_PARTY_I_DS, |parties| {
+                        for _ in 0..i % 3 {
+                            parties.entry(|party| {
+                                party.set(fix44::PARTY_ID, "BAR");
+                            });
+                        }
+                    });
+                    entry.set(fix44::MD_ENTRY_SIZE, i);
+                });
+            }
+            assert_eq!(group.len(), 12);
+        });
+        let data = msg.done().0.to_vec();
+        let expected_entries: String = (0..12)
+            .map(|i| {
+                let parties = "448=BAR|".repeat(i % 3);
+                format!("269=0|453={}|{}271={}|", i % 3, parties, i)
+            })
+            .collect();
+        let data = std::str::from_utf8(&data).unwrap();
+        assert!(data.contains(&format!("|262=foo|268=12|{}10=", expected_entries)));
+
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        let msg = decoder.decode(data.as_bytes()).unwrap();
+        let group = msg.group(fix44::NO_MD_ENTRIES).unwrap();
+        assert_eq!(group.len(), 12);
+        assert_eq!(group.get(0).unwrap().get(fix44::MD_ENTRY_TYPE), Ok(b"0"));
+    }
+}
@@ -40,26 +40,58 @@
 //! |[`Decoder`]           |`&[u8]`                  |[`Message`] |
 //! |[`DecoderStreaming`]   |data streams             |[`Message`] |
 
+use crate::field_types::IntSubtypeError;
+use crate::TagU32;
 use std::fmt::Debug;
 
+mod compiled;
 mod config;
 mod decoder;
+mod diff;
 mod encoder;
+mod multi_version;
 mod raw_decoder;
+mod repair;
+mod replay;
+mod retain;
+mod routing;
+mod signing;
+mod stream_encoder;
+mod text_policy;
+mod transform;
 mod utils;
 
+pub use compiled::{CompileError, CompiledMessage};
 pub use config::Config;
-pub use decoder::{Decoder, DecoderStreaming, Fields, Message, MessageGroup};
-pub use encoder::{Encoder, EncoderHandle};
+pub use decoder::{
+    Decoder, DecoderStreaming, FieldContext, FieldSpan, Fields, GroupCountError, GroupEntryContext,
+    HeaderView, Message, MessageGroup,
+};
+pub use diff::{FieldDiff, VOLATILE_TAGS};
+pub use encoder::{BeginStringError, EncodeError, Encoder, EncoderGroupHandle, EncoderHandle};
+pub use multi_version::MultiVersionDecoder;
 pub use raw_decoder::{RawDecoder, RawDecoderStreaming, RawFrame};
+pub use repair::FramingRepair;
+pub use replay::{FileReplay, ReplayFraming, ReplaySummary};
+pub use retain::RetainError;
+pub use routing::RoutingKey;
+pub use signing::SigningConvention;
+pub use stream_encoder::{StreamEncodeError, StreamEncoder};
+pub use text_policy::{ControlChars, TextError, TextPolicy};
+pub use transform::FieldTransform;
 
 #[cfg(feature = "utils-tokio")]
 mod tokio_decoder;
 #[cfg(feature = "utils-tokio")]
-pub use tokio_decoder::TokioDecoder;
+pub use tokio_decoder::{TokioDecoder, TokioRawDecoder};
 
 /// The type returned in the event of an error during message decoding.
+///
+/// New variants may be added in future releases, so matches must have a
+/// wildcard arm. [`DecodeError::is_incomplete`] and
+/// [`DecodeError::is_checksum_mismatch`] cover the most common cases.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum DecodeError {
     /// Mandatory field not found.
     #[error("Field not found.")]
@@ -67,10 +99,106 @@ pub enum DecodeError {
     /// Invalid FIX message syntax, `BodyLength <9>` value mismatch, or similar errors.
     #[error("Invalid FIX message syntax.")]
     Invalid,
+    /// A tag number is empty, zero, too long, or it contains non-digit
+    /// characters.
+    #[error("Malformed tag number at byte offset {offset}.")]
+    MalformedTag {
+        /// The position of the tag number within the whole message.
+        offset: usize,
+    },
+    /// A repeating group declares more entries than
+    /// [`Config::max_group_entries`], or more than could possibly fit in the
+    /// rest of the message.
+    #[error("Repeating group {tag} declares too many entries ({num_entries}).")]
+    GroupTooLarge {
+        /// The `NumInGroup` tag of the repeating group.
+        tag: TagU32,
+        /// The number of entries declared by the `NumInGroup` field.
+        num_entries: usize,
+    },
+    /// `BeginString <8>` is not in the
+    /// [`DictionaryRegistry`](crate::dict::DictionaryRegistry) of a
+    /// [`MultiVersionDecoder`], or it's not the same as in previous messages.
+    #[error("Unknown or unexpected `BeginString <8>`.")]
+    BeginString,
+    /// The message is shorter than `BodyLength <9>` implies, i.e. more data
+    /// is needed to decode it.
+    #[error("Incomplete FIX message: {len} bytes out of {expected_len}.")]
+    Incomplete {
+        /// The length of the data being decoded.
+        len: usize,
+        /// The length of the whole message according to `BodyLength <9>`.
+        expected_len: usize,
+    },
     /// Invalid `CheckSum <10>` FIX field value.
     #[error("Invalid `CheckSum <10>` FIX field value.")]
     CheckSum,
+    /// `CheckSum <10>` doesn't match the contents of the message. See
+    /// [`Config::verify_checksum`].
+    #[error("Wrong `CheckSum <10>` {actual}, expected {expected}.")]
+    CheckSumMismatch {
+        /// The checksum of the message contents.
+        expected: u8,
+        /// The `CheckSum <10>` field value.
+        actual: u8,
+    },
+    /// The message doesn't end with `CheckSum <10>`. See
+    /// [`Config::should_check_trailer`].
+    #[error("Missing `CheckSum <10>` at the end of the message.")]
+    MissingCheckSum,
+    /// One or more fields follow `CheckSum <10>`, which must be the last field
+    /// of the message. See [`Config::should_check_trailer`].
+    #[error("Field after `CheckSum <10>`.")]
+    FieldAfterCheckSum,
+    /// The value of an integer field doesn't respect the constraints of its
+    /// datatype. See [`Config::should_check_int_subtypes`].
+    #[error("Invalid value for field tag {tag}: {reason}")]
+    FieldValue {
+        /// The tag of the offending field.
+        tag: TagU32,
+        /// Why the field value is invalid.
+        reason: IntSubtypeError,
+    },
+    /// A field with datatype `data` is not immediately preceded by its
+    /// `Length` field. See [`Config::should_check_data_lengths`].
+    #[error("Data field {tag} without a preceding `Length` field.")]
+    DataLength {
+        /// The tag of the `data` field.
+        tag: TagU32,
+    },
+    /// `Signature <89>` is missing or it was rejected by the verifier. See
+    /// [`Decoder::set_signature_verifier`].
+    #[error("Invalid or missing `Signature <89>`.")]
+    SignatureInvalid,
+    /// A free-text field contains a control character. See
+    /// [`Decoder::set_text_policy`].
+    #[error(transparent)]
+    Text(#[from] TextError),
     /// I/O error.
     #[error("I/O error: {0}")]
     IO(#[from] std::io::Error),
 }
+
+impl DecodeError {
+    /// Returns `true` if more data is needed to decode the message, i.e. for
+    /// [`DecodeError::Incomplete`].
+    pub fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete { .. })
+    }
+
+    /// Returns `true` if `CheckSum <10>` is well-formed but wrong, i.e. for
+    /// [`DecodeError::CheckSumMismatch`].
+    pub fn is_checksum_mismatch(&self) -> bool {
+        matches!(self, Self::CheckSumMismatch { .. })
+    }
+
+    /// Returns the tag of the offending field, if known.
+    pub fn tag(&self) -> Option<TagU32> {
+        match self {
+            Self::GroupTooLarge { tag, .. }
+            | Self::FieldValue { tag, .. }
+            | Self::DataLength { tag } => Some(*tag),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use super::{Config, DecodeError, Decoder, Message};
+use crate::dict::DictionaryRegistry;
+use crate::GetConfig;
+
+/// A [`Decoder`] for FIX sessions which might use any of several FIX
+/// versions, e.g. on acceptors that serve many counterparties on the same
+/// port.
+///
+/// The [`Dictionary`](crate::Dictionary) is chosen from a
+/// [`DictionaryRegistry`] according to the `BeginString <8>` of the first
+/// message, and it's then used for all subsequent messages until
+/// [`MultiVersionDecoder::reset`]. Messages with an unknown or different
+/// `BeginString <8>` result in [`DecodeError::BeginString`] before any other
+/// decoding takes place.
+///
+/// The `ApplVerID <1128>` codes of the registry, e.g. those of FIX 5.0 and
+/// later versions over FIXT 1.1, are resolved per message as with
+/// [`Decoder::add_appl_ver_id`].
+///
+/// # Examples
+///
+/// ```
+/// use fefix::dict::{AppVersion, DictionaryRegistry};
+/// use fefix::tagvalue::{DecodeError, MultiVersionDecoder};
+/// use fefix::prelude::*;
+///
+/// let mut registry = DictionaryRegistry::new();
+/// registry.insert_app_version(AppVersion::Fix44);
+/// let mut decoder = MultiVersionDecoder::new(registry);
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+///
+/// let msg = b"8=FIX.4.2|9=20|35=A|49=A|56=B|34=1|10=000|";
+/// assert!(matches!(decoder.decode(msg), Err(DecodeError::BeginString)));
+/// let msg = b"8=FIX.4.4|9=20|35=A|49=A|56=B|34=1|10=000|";
+/// assert_eq!(decoder.decode(msg).unwrap().get_raw(35), Some(b"A" as &[u8]));
+/// assert_eq!(decoder.begin_string(), Some(b"FIX.4.4" as &[u8]));
+/// ```
+#[derive(Debug)]
+pub struct MultiVersionDecoder {
+    registry: DictionaryRegistry,
+    config: Config,
+    session: Option<(Vec<u8>, Decoder)>,
+}
+
+impl MultiVersionDecoder {
+    /// Creates a new [`MultiVersionDecoder`] which picks dictionaries from
+    /// `registry`, with [`Default`] configuration options.
+    pub fn new(registry: DictionaryRegistry) -> Self {
+        Self {
+            registry,
+            config: Config::default(),
+            session: None,
+        }
+    }
+
+    /// Returns the `BeginString <8>` chosen by the first message, if any.
+    pub fn begin_string(&self) -> Option<&[u8]> {
+        self.session
+            .as_ref()
+            .map(|(begin_string, _)| begin_string.as_slice())
+    }
+
+    /// Returns a mutable reference to the [`Decoder`] chosen by the first
+    /// message, if any. This allows for additional setup, e.g.
+    /// [`Decoder::add_appl_ver_id`].
+    pub fn decoder_mut(&mut self) -> Option<&mut Decoder> {
+        self.session.as_mut().map(|(_, decoder)| decoder)
+    }
+
+    /// Forgets the chosen [`Dictionary`](crate::Dictionary), e.g. at the
+    /// start of a new FIX session.
+    pub fn reset(&mut self) {
+        self.session = None;
+    }
+
+    /// Decodes `data` and returns an immutable reference to the obtained
+    /// message.
+    pub fn decode<'a, T>(&'a mut self, bytes: T) -> Result<Message<'a, T>, DecodeError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let begin_string =
+            peek_begin_string(bytes.as_ref(), self.config.separator).ok_or(DecodeError::Invalid)?;
+        let session = match self.session.take() {
+            Some(session) => session,
+            None => {
+                let dict = self
+                    .registry
+                    .get(begin_string)
+                    .ok_or(DecodeError::BeginString)?;
+                let mut decoder = Decoder::new(dict.as_ref());
+                for (appl_ver_id, dict) in self.registry.appl_ver_ids() {
+                    decoder.add_appl_ver_id(appl_ver_id.as_bytes(), dict.as_ref());
+                }
+                (begin_string.to_vec(), decoder)
+            }
+        };
+        let (session_begin_string, decoder) = self.session.insert(session);
+        if session_begin_string.as_slice() != begin_string {
+            return Err(DecodeError::BeginString);
+        }
+        *decoder.config_mut() = self.config;
+        decoder.decode(bytes)
+    }
+}
+
+impl GetConfig for MultiVersionDecoder {
+    type Config = Config;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut Self::Config {
+        &mut self.config
+    }
+}
+
+fn peek_begin_string(data: &[u8], separator: u8) -> Option<&[u8]> {
+    let data = data.strip_prefix(b"8=")?;
+    let len = data.iter().position(|byte| *byte == separator)?;
+    Some(&data[..len])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dict::AppVersion;
+
+    fn decoder() -> MultiVersionDecoder {
+        let mut registry = DictionaryRegistry::new();
+        registry.insert_app_version(AppVersion::Fix44);
+        let mut decoder = MultiVersionDecoder::new(registry);
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    #[cfg(feature = "fixt11")]
+    #[test]
+    fn session_keeps_first_begin_string() {
+        let mut decoder = decoder();
+        decoder
+            .decode(b"8=FIX.4.4|9=20|35=A|49=A|56=B|34=1|10=000|")
+            .unwrap();
+        // A known `BeginString <8>`, but not the one of the session.
+        decoder.registry.insert_app_version(AppVersion::Fixt11);
+        let result = decoder.decode(b"8=FIXT.1.1|9=20|35=0|49=A|56=B|34=2|10=000|");
+        assert!(matches!(result, Err(DecodeError::BeginString)));
+        decoder.reset();
+        assert_eq!(decoder.begin_string(), None);
+        decoder
+            .decode(b"8=FIXT.1.1|9=20|35=A|49=A|56=B|34=1|10=000|")
+            .unwrap();
+        assert_eq!(decoder.begin_string(), Some(b"FIXT.1.1" as &[u8]));
+    }
+
+    #[cfg(feature = "fixt11")]
+    #[test]
+    fn appl_ver_ids_are_resolved_per_message() {
+        let mut decoder = decoder();
+        decoder.registry.insert_app_version(AppVersion::Fixt11);
+        let msg = decoder
+            .decode(b"8=FIXT.1.1|9=27|35=D|49=A|56=B|34=2|1128=6|10=000|")
+            .unwrap();
+        assert_eq!(msg.app_version(), Some(AppVersion::Fix44));
+        let msg = decoder
+            .decode(b"8=FIXT.1.1|9=20|35=0|49=A|56=B|34=3|10=000|")
+            .unwrap();
+        assert_eq!(msg.app_version(), Some(AppVersion::Fixt11));
+    }
+
+    #[test]
+    fn unknown_begin_string_is_rejected_before_decoding() {
+        let mut decoder = decoder();
+        // The body length is wrong, too.
+        let result = decoder.decode(b"8=FIX.4.1|9=99|35=A|10=000|");
+        assert!(matches!(result, Err(DecodeError::BeginString)));
+        assert!(decoder.decoder_mut().is_none());
+        let result = decoder.decode(b"9=22|35=A|49=A|56=B|34=1|10=000|");
+        assert!(matches!(result, Err(DecodeError::Invalid)));
+    }
+}
@@ -121,18 +121,34 @@ impl RawDecoder {
     {
         let data = src.as_ref();
         let len = data.len();
-        if len < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES {
+        if len < utils::MIN_FIX_MESSAGE_LEN_IN_BYTES
+            || self.config.max_message_size.is_some_and(|max| len > max)
+        {
             return Err(DecodeError::Invalid);
         }
 
         let header_info =
             HeaderInfo::parse(data, self.config().separator).ok_or(DecodeError::Invalid)?;
 
-        utils::verify_body_length(
-            data,
-            header_info.field_1.end + 1,
-            header_info.nominal_body_len as usize,
-        )?;
+        let start_of_body = header_info.field_1.end + 1;
+        let end_of_body = start_of_body
+            .checked_add(header_info.nominal_body_len)
+            .ok_or(DecodeError::Invalid)?;
+        // A truncated body, unless `CheckSum <10>` shows that `BodyLength <9>`
+        // is simply wrong.
+        if end_of_body > len && !utils::contains_checksum_tag(data, self.config.separator) {
+            let expected_len = end_of_body.saturating_add(utils::FIELD_CHECKSUM_LEN_IN_BYTES);
+            let max_message_size = self.config.max_message_size;
+            return if max_message_size.is_some_and(|max| expected_len > max) {
+                Err(DecodeError::Invalid)
+            } else {
+                Err(DecodeError::Incomplete { len, expected_len })
+            };
+        }
+        if self.config.should_check_trailer {
+            utils::verify_trailer(data, end_of_body, self.config.separator)?;
+        }
+        utils::verify_body_length(data, start_of_body, header_info.nominal_body_len)?;
 
         if self.config.verify_checksum && self.config.separator == b'\x01' {
             utils::verify_checksum(data)?;
@@ -191,7 +207,11 @@ where
 
     fn num_bytes_required(&self) -> usize {
         match self.state {
-            ParserState::Empty => utils::MIN_FIX_MESSAGE_LEN_IN_BYTES,
+            // The header might be split across several reads, so we always ask
+            // for at least one more byte.
+            ParserState::Empty => {
+                utils::MIN_FIX_MESSAGE_LEN_IN_BYTES.max(self.buffer.as_slice().len() + 1)
+            }
             ParserState::Header(_, expected_len) => expected_len,
             ParserState::Failed => 0,
         }
@@ -200,21 +220,55 @@ where
     fn try_parse(&mut self) -> Result<Option<()>, Self::Error> {
         match self.state {
             ParserState::Empty => {
-                let header_info =
-                    HeaderInfo::parse(self.buffer.as_slice(), self.config().separator);
-                if let Some(header_info) = header_info {
-                    let expected_len_of_frame = header_info.field_1.end
-                        + 1
-                        + header_info.nominal_body_len
-                        + utils::FIELD_CHECKSUM_LEN_IN_BYTES;
-
-                    self.state = ParserState::Header(header_info, expected_len_of_frame);
-                    Ok(None)
-                } else {
-                    Err(DecodeError::Invalid)
+                let data = self.buffer.as_slice();
+                let separator = self.config().separator;
+                let max_message_size = self.config().max_message_size;
+                let header_info = match HeaderInfo::parse(data, separator) {
+                    Some(header_info) => header_info,
+                    // We need both `BeginString <8>` and `BodyLength <9>`
+                    // before we can tell how long the message is.
+                    None if is_header_prefix(data, separator)
+                        && max_message_size.is_none_or(|max| data.len() < max) =>
+                    {
+                        return Ok(None)
+                    }
+                    None => return Err(DecodeError::Invalid),
+                };
+                // `BodyLength <9>` comes from the counterparty, so we must be
+                // careful not to overflow or allocate too much memory.
+                let expected_len_of_frame = (header_info.field_1.end + 1)
+                    .checked_add(header_info.nominal_body_len)
+                    .and_then(|len| len.checked_add(utils::FIELD_CHECKSUM_LEN_IN_BYTES))
+                    .filter(|len| *len >= data.len())
+                    .filter(|len| max_message_size.is_none_or(|max| *len <= max))
+                    .ok_or(DecodeError::Invalid)?;
+
+                self.state = ParserState::Header(header_info, expected_len_of_frame);
+                self.try_parse()
+            }
+            ParserState::Header(_, expected_len) => {
+                let data = self.buffer.as_slice();
+                // Fields (e.g. `data` fields) can span over several reads, so
+                // we only look at the message once it's complete.
+                if data.len() < expected_len {
+                    return Ok(None);
+                }
+                if data.len() > expected_len {
+                    return Err(DecodeError::Invalid);
+                }
+                let end_of_body = expected_len - utils::FIELD_CHECKSUM_LEN_IN_BYTES;
+                if self.config.should_check_trailer {
+                    utils::verify_trailer(data, end_of_body, self.config.separator)?;
+                } else if !data[end_of_body..].starts_with(b"10=")
+                    || data.last() != Some(&self.config.separator)
+                {
+                    return Err(DecodeError::Invalid);
+                }
+                if self.config.verify_checksum && self.config.separator == b'\x01' {
+                    utils::verify_checksum(data)?;
                 }
+                Ok(Some(()))
             }
-            ParserState::Header(_, _) => Ok(Some(())),
             ParserState::Failed => panic!("Failed state"),
         }
     }
@@ -296,6 +350,22 @@ impl HeaderInfo {
     }
 }
 
+/// Returns `true` if `data` might be the start of a valid header, i.e. it
+/// doesn't contain `BodyLength <9>` in its entirety yet.
+pub(super) fn is_header_prefix(data: &[u8], separator: u8) -> bool {
+    data.iter().filter(|byte| **byte == separator).count() < 2
+}
+
+/// Returns the length of the frame at the start of `data`, as declared by its
+/// `BodyLength <9>`, if its header can be parsed. The frame might be longer
+/// than `data` itself.
+pub(super) fn frame_len(data: &[u8], separator: u8) -> Option<usize> {
+    let header_info = HeaderInfo::parse(data, separator)?;
+    (header_info.field_1.end + 1)
+        .checked_add(header_info.nominal_body_len)?
+        .checked_add(utils::FIELD_CHECKSUM_LEN_IN_BYTES)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -343,6 +413,22 @@ mod test {
         assert!(matches!(decoder.decode(msg), Err(DecodeError::Invalid)));
     }
 
+    #[test]
+    fn truncated_message_is_incomplete() {
+        let decoder = new_decoder();
+        let msg = b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=091|";
+        let err = decoder.decode(&msg[..40]).unwrap_err();
+        assert!(err.is_incomplete());
+        assert!(matches!(
+            err,
+            DecodeError::Incomplete {
+                len: 40,
+                expected_len: 62
+            }
+        ));
+        assert!(!decoder.decode(&msg[..10]).unwrap_err().is_incomplete());
+    }
+
     #[test]
     fn message_with_bad_checksum_is_invalid() {
         let mut decoder = new_decoder();
@@ -350,7 +436,11 @@ mod test {
         decoder.config_mut().verify_checksum = true;
         let msg =
             "8=FIX.4.2|9=40|35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|10=000|".replace('|', "\u{01}");
-        assert!(matches!(decoder.decode(&msg), Err(DecodeError::CheckSum)));
+        assert!(matches!(
+            decoder.decode(&msg),
+            Err(DecodeError::CheckSumMismatch { actual: 0, .. })
+        ));
+        assert!(decoder.decode(&msg).unwrap_err().is_checksum_mismatch());
     }
 
     #[test]
@@ -374,6 +464,30 @@ mod test {
         assert!(decoder.num_bytes_required() > 0);
     }
 
+    #[test]
+    fn streaming_decoder_rejects_garbage_header() {
+        let mut decoder = new_decoder().streaming(vec![]);
+        decoder.buffer().extend_from_slice(b"8=FIX.4.2");
+        assert!(matches!(decoder.try_parse(), Ok(None)));
+        decoder.buffer().extend_from_slice(b"|9=40|");
+        assert!(matches!(decoder.try_parse(), Ok(None)));
+        decoder.clear();
+        decoder.buffer().extend_from_slice(b"8=FIX.4.2|garbage|");
+        assert!(matches!(decoder.try_parse(), Err(DecodeError::Invalid)));
+    }
+
+    #[test]
+    fn streaming_decoder_checks_trailer() {
+        let mut decoder = new_decoder().streaming(vec![]);
+        decoder
+            .buffer()
+            .extend_from_slice(b"8=FIX.4.2|9=5|35=0|foobar|");
+        assert!(matches!(
+            decoder.try_parse(),
+            Err(DecodeError::MissingCheckSum)
+        ));
+    }
+
     #[test]
     fn new_streaming_decoder() {
         let stream = {
@@ -0,0 +1,212 @@
+use super::DecodeError;
+use crate::field_types::CheckSum;
+use crate::FieldType;
+
+/// A correction to the framing of a message, as applied by
+/// [`Decoder::decode`](super::Decoder::decode) when
+/// [`Config::should_repair_framing`](super::Config::should_repair_framing) is
+/// on. See [`Message::repairs`](super::Message::repairs).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FramingRepair {
+    /// `BodyLength <9>` was wrong, or missing altogether if `declared` is
+    /// [`None`].
+    BodyLength {
+        /// The original value of `BodyLength <9>`, if valid.
+        declared: Option<usize>,
+        /// The length of the body, which is now the value of
+        /// `BodyLength <9>`.
+        actual: usize,
+    },
+    /// `CheckSum <10>` was wrong, or missing altogether if `declared` is
+    /// [`None`].
+    CheckSum {
+        /// The original value of `CheckSum <10>`, if valid.
+        declared: Option<u8>,
+        /// The checksum of the (repaired) message, which is now the value of
+        /// `CheckSum <10>`.
+        actual: u8,
+    },
+    /// The separator after the last field was missing.
+    MissingSeparator,
+}
+
+/// Writes the message in `data` to `buffer` with `BodyLength <9>`,
+/// `CheckSum <10>`, and the separator at the end corrected if needed, and
+/// records all corrections in `repairs`. The body itself is copied as is.
+pub fn repair_framing(
+    data: &[u8],
+    separator: u8,
+    buffer: &mut Vec<u8>,
+    repairs: &mut Vec<FramingRepair>,
+) -> Result<(), DecodeError> {
+    buffer.clear();
+    repairs.clear();
+    if !data.starts_with(b"8=") {
+        return Err(DecodeError::Invalid);
+    }
+    let body_length_start = position(data, separator).ok_or(DecodeError::Invalid)? + 1;
+    let (declared_body_length, body_start) = match data[body_length_start..] {
+        [b'9', b'=', ..] => {
+            let value_start = body_length_start + 2;
+            let value_end = value_start
+                + position(&data[value_start..], separator).ok_or(DecodeError::Invalid)?;
+            (
+                usize::deserialize(&data[value_start..value_end]).ok(),
+                value_end + 1,
+            )
+        }
+        _ => (None, body_length_start),
+    };
+    let has_separator = data.len() > body_start && data.ends_with(&[separator]);
+    let content = if has_separator {
+        &data[body_start..data.len() - 1]
+    } else {
+        &data[body_start..]
+    };
+    // `CheckSum <10>` must be the last field, so any separator after it
+    // means that it's actually a field of the body.
+    let checksum_start = if content.starts_with(b"10=") {
+        Some(0)
+    } else {
+        content
+            .windows(4)
+            .rposition(|window| window == [separator, b'1', b'0', b'='])
+            .map(|i| i + 1)
+    };
+    let checksum_start = checksum_start.filter(|i| position(&content[*i..], separator).is_none());
+    let (body, declared_checksum) = match checksum_start {
+        Some(i) => {
+            let value = &content[i + 3..];
+            let declared = u32::deserialize(value)
+                .ok()
+                .filter(|_| value.len() == 3)
+                .and_then(|checksum| u8::try_from(checksum).ok());
+            (&content[..i], declared)
+        }
+        None => (content, None),
+    };
+    if !has_separator {
+        repairs.push(FramingRepair::MissingSeparator);
+    }
+
+    buffer.extend_from_slice(&data[..body_length_start]);
+    buffer.extend_from_slice(b"9=");
+    // The body lacks its last separator if `CheckSum <10>` is missing.
+    let needs_separator = checksum_start.is_none() && !body.is_empty();
+    let body_length = body.len() + usize::from(needs_separator);
+    match declared_body_length {
+        Some(declared) if declared == body_length => {
+            // Zero-padding is preserved.
+            buffer.extend_from_slice(&data[body_length_start + 2..body_start - 1]);
+        }
+        declared => {
+            trace_event!(
+                warn,
+                ?declared,
+                actual = body_length,
+                "Repaired `BodyLength <9>`"
+            );
+            repairs.push(FramingRepair::BodyLength {
+                declared,
+                actual: body_length,
+            });
+            body_length.serialize(buffer);
+        }
+    }
+    buffer.push(separator);
+    buffer.extend_from_slice(body);
+    if needs_separator {
+        buffer.push(separator);
+    }
+    let checksum = CheckSum::compute(buffer);
+    if declared_checksum != Some(checksum.0) {
+        trace_event!(
+            warn,
+            declared = ?declared_checksum,
+            actual = checksum.0,
+            "Repaired `CheckSum <10>`"
+        );
+        repairs.push(FramingRepair::CheckSum {
+            declared: declared_checksum,
+            actual: checksum.0,
+        });
+    }
+    buffer.extend_from_slice(b"10=");
+    checksum.serialize(buffer);
+    buffer.push(separator);
+    Ok(())
+}
+
+fn position(data: &[u8], separator: u8) -> Option<usize> {
+    data.iter().position(|byte| *byte == separator)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn repair(data: &[u8]) -> (String, Vec<FramingRepair>) {
+        let mut buffer = Vec::new();
+        let mut repairs = Vec::new();
+        repair_framing(data, b'|', &mut buffer, &mut repairs).unwrap();
+        (String::from_utf8(buffer).unwrap(), repairs)
+    }
+
+    #[test]
+    fn well_formed_messages_are_untouched() {
+        let data = "8=FIX.4.4|9=00000015|35=0|49=A|56=B|10=050|";
+        assert_eq!(repair(data.as_bytes()), (data.to_string(), vec![]));
+    }
+
+    #[test]
+    fn framing_errors_are_repaired() {
+        let repaired = "8=FIX.4.4|9=15|35=0|49=A|56=B|10=018|".to_string();
+        assert_eq!(
+            repair(b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=018"),
+            (repaired.clone(), vec![FramingRepair::MissingSeparator])
+        );
+        assert_eq!(
+            repair(b"8=FIX.4.4|9=15|35=0|49=A|56=B|"),
+            (
+                repaired.clone(),
+                vec![FramingRepair::CheckSum {
+                    declared: None,
+                    actual: 18
+                }]
+            )
+        );
+        assert_eq!(
+            repair(b"8=FIX.4.4|35=0|49=A|56=B|10=999|"),
+            (
+                repaired.clone(),
+                vec![
+                    FramingRepair::BodyLength {
+                        declared: None,
+                        actual: 15
+                    },
+                    FramingRepair::CheckSum {
+                        declared: None,
+                        actual: 18
+                    }
+                ]
+            )
+        );
+        let (data, repairs) = repair(b"8=FIX.4.4|9=99|35=0|49=A|56=B");
+        assert_eq!(data, repaired);
+        assert_eq!(repairs.len(), 3);
+    }
+
+    #[test]
+    fn checksum_must_be_last() {
+        let (data, _) = repair(b"8=FIX.4.4|9=1|35=0|10=000|58=x|");
+        assert_eq!(data, "8=FIX.4.4|9=17|35=0|10=000|58=x|10=143|");
+    }
+
+    #[test]
+    fn missing_begin_string() {
+        let mut buffer = Vec::new();
+        let mut repairs = Vec::new();
+        assert!(repair_framing(b"9=5|35=0|", b'|', &mut buffer, &mut repairs).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use super::{raw_decoder, DecodeError, Decoder, Message};
+use crate::GetConfig;
+use std::io;
+use std::path::Path;
+
+const BEGIN_STRING_PREFIX: &[u8] = b"8=FIX";
+const SOFH_LEN: usize = 6;
+
+/// How FIX messages are delimited within a [`FileReplay`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReplayFraming {
+    /// Messages are simply concatenated, and each frame is delimited by its
+    /// own `BodyLength <9>` and `CheckSum <10>`. The separator is taken from
+    /// the [`Decoder`] configuration, so `|`-delimited logs work too.
+    TagValue,
+    /// Each message is preceded by a *Simple Open Framing Header* (SOFH), i.e.
+    /// a 4-bytes big-endian message length (which includes the header
+    /// itself) and a 2-bytes encoding type.
+    Sofh,
+}
+
+/// Statistics about a whole [`FileReplay::run`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReplaySummary {
+    /// The number of messages which were decoded successfully.
+    pub num_messages: usize,
+    /// The number of malformed frames.
+    pub num_errors: usize,
+    /// The number of bytes that were skipped while looking for the next valid
+    /// frame.
+    pub num_skipped_bytes: usize,
+}
+
+/// Replays a captured FIX session, e.g. for testing or post-incident
+/// analysis.
+///
+/// Individual malformed frames don't abort the replay. They're reported to the
+/// handler and, if their end can't be found, [`FileReplay`] skips ahead to
+/// the next `8=FIX` (unless [`FileReplay::set_resync`] is turned off).
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{Decoder, FileReplay};
+/// use fefix::prelude::*;
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+/// let log = b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|10=000|garbage\
+///     8=FIX.4.4|9=25|35=0|49=B|56=A|34=3|3A=x|10=000|\
+///     8=FIX.4.4|9=20|35=0|49=B|56=A|34=4|10=000|".to_vec();
+///
+/// let mut replay = FileReplay::new(log, decoder);
+/// let mut senders = Vec::new();
+/// let summary = replay.run(|_index, _offset, msg| {
+///     if let Ok(msg) = msg {
+///         senders.push(msg.get::<&str>(fix44::SENDER_COMP_ID).unwrap().to_string());
+///     }
+/// });
+/// assert_eq!(senders, vec!["A", "B"]);
+/// assert_eq!(summary.num_errors, 1);
+/// assert_eq!(summary.num_skipped_bytes, "garbage".len());
+/// ```
+#[derive(Debug)]
+pub struct FileReplay {
+    data: Vec<u8>,
+    decoder: Decoder,
+    framing: ReplayFraming,
+    should_resync: bool,
+    should_skip_line_endings: bool,
+}
+
+impl FileReplay {
+    /// Creates a new [`FileReplay`] over the raw contents of a log.
+    pub fn new(data: Vec<u8>, decoder: Decoder) -> Self {
+        Self {
+            data,
+            decoder,
+            framing: ReplayFraming::TagValue,
+            should_resync: true,
+            should_skip_line_endings: false,
+        }
+    }
+
+    /// Reads the whole file at `path` and creates a new [`FileReplay`] over its
+    /// contents.
+    pub fn open<P>(path: P, decoder: Decoder) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::new(std::fs::read(path)?, decoder))
+    }
+
+    /// Sets the [`ReplayFraming`] of the log. It's
+    /// [`ReplayFraming::TagValue`] by default.
+    pub fn set_framing(&mut self, framing: ReplayFraming) {
+        self.framing = framing;
+    }
+
+    /// Determines whether or not to look for the next `8=FIX` when the end of
+    /// a malformed frame can't be found. If turned off, the replay stops at
+    /// the first such frame instead. It is enabled by default.
+    pub fn set_resync(&mut self, should_resync: bool) {
+        self.should_resync = should_resync;
+    }
+
+    /// Determines whether or not to silently skip `\r` and `\n` bytes between
+    /// frames, e.g. in logs with one message per line. Otherwise, they're
+    /// treated like any other garbage between frames. It is disabled by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, FileReplay};
+    /// use fefix::prelude::*;
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let log = b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|10=000|\r\n\
+    ///     8=FIX.4.4|9=20|35=0|49=B|56=A|34=3|10=000|\r\n".to_vec();
+    ///
+    /// let mut replay = FileReplay::new(log, decoder);
+    /// replay.set_resync(false);
+    /// replay.set_skip_line_endings(true);
+    /// let summary = replay.run(|_index, _offset, msg| assert!(msg.is_ok()));
+    /// assert_eq!(summary.num_messages, 2);
+    /// assert_eq!(summary.num_skipped_bytes, 0);
+    /// ```
+    pub fn set_skip_line_endings(&mut self, should_skip_line_endings: bool) {
+        self.should_skip_line_endings = should_skip_line_endings;
+    }
+
+    /// Decodes all messages in order, calling `handler` with the index of
+    /// each frame, its byte offset within the log, and the decoding result.
+    pub fn run<F>(&mut self, mut handler: F) -> ReplaySummary
+    where
+        F: FnMut(usize, usize, Result<Message<&[u8]>, DecodeError>),
+    {
+        let mut summary = ReplaySummary::default();
+        let mut offset = 0;
+        let mut index = 0;
+        while offset < self.data.len() {
+            if self.should_skip_line_endings {
+                offset += self.data[offset..]
+                    .iter()
+                    .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+                    .count();
+                if offset == self.data.len() {
+                    break;
+                }
+            }
+            let rest = &self.data[offset..];
+            let frame = self.frame_range(rest);
+            if frame.is_none() && !self.should_resync {
+                summary.num_errors += 1;
+                handler(index, offset, Err(DecodeError::Invalid));
+                break;
+            }
+            offset = match frame {
+                Some(frame) => {
+                    let result = self.decoder.decode(&rest[frame.clone()]);
+                    if result.is_ok() {
+                        summary.num_messages += 1;
+                    } else {
+                        summary.num_errors += 1;
+                    }
+                    handler(index, offset + frame.start, result);
+                    index += 1;
+                    offset + frame.end
+                }
+                None => {
+                    let skipped = self.next_frame_offset(rest, 0);
+                    if skipped == 0 {
+                        // Something that looks like a frame, but its end
+                        // can't be found.
+                        summary.num_errors += 1;
+                        handler(index, offset, Err(DecodeError::Invalid));
+                        index += 1;
+                        offset + self.next_frame_offset(rest, 1)
+                    } else {
+                        summary.num_skipped_bytes += skipped;
+                        offset + skipped
+                    }
+                }
+            };
+        }
+        summary
+    }
+
+    /// Returns the position of the frame at the start of `data`, excluding any
+    /// framing header, if it can be determined.
+    fn frame_range(&self, data: &[u8]) -> Option<std::ops::Range<usize>> {
+        let (start, end) = match self.framing {
+            ReplayFraming::TagValue => {
+                if !data.starts_with(BEGIN_STRING_PREFIX) {
+                    return None;
+                }
+                let separator = self.decoder.config().separator;
+                (0, raw_decoder::frame_len(data, separator)?)
+            }
+            ReplayFraming::Sofh => {
+                let len = data.get(..4)?;
+                let len = u32::from_be_bytes(len.try_into().ok()?) as usize;
+                if len < SOFH_LEN
+                    || !data[SOFH_LEN.min(data.len())..].starts_with(BEGIN_STRING_PREFIX)
+                {
+                    return None;
+                }
+                (SOFH_LEN, len)
+            }
+        };
+        if end > data.len() {
+            None
+        } else {
+            Some(start..end)
+        }
+    }
+
+    /// Returns the offset of the first plausible frame start within `data`,
+    /// not before `min_offset`, or `data.len()` if there's none.
+    fn next_frame_offset(&self, data: &[u8], min_offset: usize) -> usize {
+        let header_len = match self.framing {
+            ReplayFraming::TagValue => 0,
+            ReplayFraming::Sofh => SOFH_LEN,
+        };
+        data.windows(BEGIN_STRING_PREFIX.len())
+            .enumerate()
+            .skip(min_offset + header_len)
+            .find(|(_, window)| *window == BEGIN_STRING_PREFIX)
+            .map(|(i, _)| i - header_len)
+            .unwrap_or(data.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Dictionary, FieldMap};
+
+    const MSG_1: &[u8] = b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=1|10=000|";
+    const MSG_2: &[u8] = b"8=FIX.4.4|9=20|35=0|49=A|56=B|34=2|10=000|";
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    fn seq_nums(replay: &mut FileReplay) -> (Vec<(usize, usize, Option<u64>)>, ReplaySummary) {
+        let mut seq_nums = Vec::new();
+        let summary = replay.run(|index, offset, msg| {
+            seq_nums.push((index, offset, msg.ok().and_then(|msg| msg.get(34).ok())));
+        });
+        (seq_nums, summary)
+    }
+
+    fn sofh(msg: &[u8]) -> Vec<u8> {
+        let mut frame = ((msg.len() + SOFH_LEN) as u32).to_be_bytes().to_vec();
+        frame.extend_from_slice(&[0xF0, 0x00]);
+        frame.extend_from_slice(msg);
+        frame
+    }
+
+    #[test]
+    fn offsets_and_indices() {
+        let mut replay = FileReplay::new([MSG_1, MSG_2].concat(), decoder());
+        let (seq_nums, summary) = seq_nums(&mut replay);
+        assert_eq!(seq_nums, vec![(0, 0, Some(1)), (1, MSG_1.len(), Some(2))]);
+        assert_eq!(summary.num_messages, 2);
+    }
+
+    #[test]
+    fn truncated_frame_is_reported() {
+        let log = [MSG_1, MSG_2, &MSG_1[..30]].concat();
+        let mut replay = FileReplay::new(log, decoder());
+        let (seq_nums, summary) = seq_nums(&mut replay);
+        assert_eq!(
+            seq_nums,
+            vec![
+                (0, 0, Some(1)),
+                (1, MSG_1.len(), Some(2)),
+                (2, MSG_1.len() + MSG_2.len(), None)
+            ]
+        );
+        assert_eq!(summary.num_errors, 1);
+    }
+
+    #[test]
+    fn no_resync_stops_at_first_garbage() {
+        let log = [MSG_1, b"garbage", MSG_2].concat();
+        let mut replay = FileReplay::new(log, decoder());
+        replay.set_resync(false);
+        let (seq_nums, summary) = seq_nums(&mut replay);
+        assert_eq!(seq_nums, vec![(0, 0, Some(1)), (1, MSG_1.len(), None)]);
+        assert_eq!(summary.num_skipped_bytes, 0);
+    }
+
+    #[test]
+    fn newline_separated_log() {
+        let log = [MSG_1, b"\n", MSG_2, b"\r\n", MSG_1, b"\r\n\r\n"].concat();
+        let mut replay = FileReplay::new(log.clone(), decoder());
+        replay.set_resync(false);
+        replay.set_skip_line_endings(true);
+        let (seq_nums, summary) = seq_nums(&mut replay);
+        assert_eq!(
+            seq_nums,
+            vec![
+                (0, 0, Some(1)),
+                (1, MSG_1.len() + 1, Some(2)),
+                (2, MSG_1.len() + MSG_2.len() + 3, Some(1))
+            ]
+        );
+        assert_eq!(summary.num_errors, 0);
+        assert_eq!(summary.num_skipped_bytes, 0);
+        // Strict mode: line endings are garbage.
+        let mut replay = FileReplay::new(log, decoder());
+        let summary = replay.run(|_, _, _| {});
+        assert_eq!(summary.num_messages, 3);
+        assert_eq!(summary.num_skipped_bytes, 7);
+    }
+
+    #[test]
+    fn sofh_framing() {
+        let log = [sofh(MSG_1), b"garbage".to_vec(), sofh(MSG_2)].concat();
+        let mut replay = FileReplay::new(log, decoder());
+        replay.set_framing(ReplayFraming::Sofh);
+        let (seq_nums, summary) = seq_nums(&mut replay);
+        assert_eq!(
+            seq_nums,
+            vec![
+                (0, SOFH_LEN, Some(1)),
+                (1, 2 * SOFH_LEN + MSG_1.len() + 7, Some(2))
+            ]
+        );
+        assert_eq!(summary.num_skipped_bytes, 7);
+    }
+}
@@ -0,0 +1,140 @@
+use super::Message;
+use crate::TagU32;
+
+/// The type returned by [`Message::retain`] and [`Message::strip_tags`] when
+/// the remaining fields couldn't be decoded anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum RetainError {
+    /// A `data` field was kept without its `Length` field, or vice versa.
+    /// `data` fields may contain separators, so they can't be decoded
+    /// without their `Length` field.
+    #[error("`Length` field {length_tag} and `data` field {data_tag} must be retained together.")]
+    DataLength {
+        /// The tag of the `Length` field.
+        length_tag: TagU32,
+        /// The tag of the `data` field.
+        data_tag: TagU32,
+    },
+}
+
+impl<'a, T> Message<'a, T> {
+    /// Returns all fields of `self` for which `f` returns `true`, in
+    /// sequential order. This is useful for redacting messages before logging
+    /// them, or for forwarding only a subset of their fields.
+    ///
+    /// The result can be re-encoded with
+    /// [`EncoderHandle::set_fields`](super::EncoderHandle::set_fields), which
+    /// computes `BodyLength <9>` and `CheckSum <10>` anew. `data` fields (e.g.
+    /// `RawData <96>`) must be either retained or removed together with their
+    /// `Length` field (e.g. `RawDataLength <95>`), otherwise
+    /// [`RetainError::DataLength`] is returned. Note that removing a
+    /// `NumInGroup` field or the first field of its entries also breaks the
+    /// repeating group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fefix::tagvalue::{Decoder, Encoder, RetainError};
+    /// use fefix::prelude::*;
+    ///
+    /// const DATA: &[u8] = b"8=FIX.4.4|9=44|35=A|49=A|56=B|34=1|98=0|108=30|95=3|96=k|y|10=000|";
+    ///
+    /// let mut decoder = Decoder::new(Dictionary::fix44());
+    /// decoder.config_mut().separator = b'|';
+    /// decoder.config_mut().verify_checksum = false;
+    /// let message = decoder.decode(DATA).unwrap();
+    ///
+    /// let fields = message.retain(|tag, _| ![95, 96].contains(&tag.get())).unwrap();
+    /// let mut encoder = Encoder::new();
+    /// encoder.config_mut().separator = b'|';
+    /// let mut buffer = Vec::new();
+    /// let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"A");
+    /// msg.set_fields(fields);
+    /// assert!(msg.done().0.starts_with(b"8=FIX.4.4|9=00000032|35=A|49=A|56=B|34=1|98=0|108=30|10="));
+    ///
+    /// assert!(matches!(
+    ///     message.retain(|tag, _| tag.get() != 96),
+    ///     Err(RetainError::DataLength { .. })
+    /// ));
+    /// ```
+    pub fn retain<F>(&'a self, mut f: F) -> Result<Vec<(TagU32, &'a [u8])>, RetainError>
+    where
+        F: FnMut(TagU32, &[u8]) -> bool,
+    {
+        let mut is_retained = Vec::with_capacity(self.len());
+        let mut fields = Vec::with_capacity(self.len());
+        for (tag, value) in self.fields() {
+            let retain = f(tag, value);
+            is_retained.push(retain);
+            if retain {
+                fields.push((tag, value));
+            }
+        }
+        for &i in self.data_field_indices() {
+            if is_retained[i - 1] != is_retained[i] {
+                let mut tags = self.fields().skip(i - 1).map(|(tag, _)| tag);
+                if let (Some(length_tag), Some(data_tag)) = (tags.next(), tags.next()) {
+                    return Err(RetainError::DataLength {
+                        length_tag,
+                        data_tag,
+                    });
+                }
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Like [`Message::retain`], but removes all fields with tags in `tags`
+    /// and keeps everything else, e.g. `Password <554>` and
+    /// `NewPassword <925>` before logging a `Logon <A>` message.
+    pub fn strip_tags(&'a self, tags: &[u32]) -> Result<Vec<(TagU32, &'a [u8])>, RetainError> {
+        self.retain(|tag, _| !tags.contains(&tag.get()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Decoder;
+    use crate::{Dictionary, GetConfig};
+
+    fn decoder() -> Decoder {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder
+    }
+
+    fn tags(fields: &[(TagU32, &[u8])]) -> Vec<u32> {
+        fields.iter().map(|(tag, _)| tag.get()).collect()
+    }
+
+    #[test]
+    fn strip_tags_keeps_field_order() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=38|35=A|49=A|56=B|554=secret|98=0|108=30|10=000|")
+            .unwrap();
+        let fields = msg.strip_tags(&[554, 925]).unwrap();
+        assert_eq!(tags(&fields), vec![8, 35, 49, 56, 98, 108]);
+    }
+
+    #[test]
+    fn data_fields_must_be_retained_with_their_length_fields() {
+        let mut decoder = decoder();
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=27|35=0|49=A|56=B|95=3|96=a|b|10=000|")
+            .unwrap();
+        let expected_err = Err(RetainError::DataLength {
+            length_tag: TagU32::new(95).unwrap(),
+            data_tag: TagU32::new(96).unwrap(),
+        });
+        assert_eq!(msg.strip_tags(&[95]), expected_err);
+        assert_eq!(msg.strip_tags(&[96]), expected_err);
+        let fields = msg.strip_tags(&[95, 96]).unwrap();
+        assert_eq!(tags(&fields), vec![8, 35, 49, 56]);
+        let fields = msg.retain(|tag, _| tag.get() > 90).unwrap();
+        assert_eq!(fields[1], (TagU32::new(96).unwrap(), b"a|b" as &[u8]));
+    }
+}
@@ -0,0 +1,106 @@
+use super::Message;
+use crate::field_types::Exchange;
+use crate::FieldType;
+
+const SECURITY_EXCHANGE: u32 = 207;
+const TRADING_SESSION_ID: u32 = 336;
+const MARKET_SEGMENT_ID: u32 = 1300;
+
+/// The fields on which multi-market gateways usually route messages, as
+/// returned by [`Message::routing_key`]. It's meant as the key of dispatch
+/// tables; fields that are irrelevant for a given table can just be set to
+/// [`None`] before lookups.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::field_types::Exchange;
+/// use fefix::tagvalue::{Decoder, RoutingKey};
+/// use fefix::prelude::*;
+/// use std::collections::HashMap;
+///
+/// let mut routes = HashMap::new();
+/// let xeur = RoutingKey {
+///     security_exchange: Exchange::new(*b"XEUR"),
+///     ..RoutingKey::default()
+/// };
+/// routes.insert(xeur, "eurex-gateway");
+///
+/// let mut decoder = Decoder::new(Dictionary::fix44());
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+/// let msg = decoder
+///     .decode(b"8=FIX.4.4|9=37|35=D|49=A|56=B|34=2|207=XEUR|336=DAY|10=000|")
+///     .unwrap();
+///
+/// let mut key = msg.routing_key();
+/// assert_eq!(key.trading_session_id, Some("DAY"));
+/// key.trading_session_id = None;
+/// assert_eq!(routes.get(&key), Some(&"eurex-gateway"));
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RoutingKey<'a> {
+    /// `TradingSessionID <336>`.
+    pub trading_session_id: Option<&'a str>,
+    /// `MarketSegmentID <1300>`.
+    pub market_segment_id: Option<&'a str>,
+    /// `SecurityExchange <207>`.
+    pub security_exchange: Option<Exchange>,
+}
+
+impl<'a, T> Message<'a, T> {
+    /// Returns the value of `TradingSessionID <336>`, if present and valid
+    /// UTF-8.
+    pub fn trading_session_id(&self) -> Option<&'a str> {
+        self.field_value(TRADING_SESSION_ID)
+            .and_then(|value| <&str>::deserialize(value).ok())
+    }
+
+    /// Returns the value of `MarketSegmentID <1300>`, if present and valid
+    /// UTF-8.
+    pub fn market_segment_id(&self) -> Option<&'a str> {
+        self.field_value(MARKET_SEGMENT_ID)
+            .and_then(|value| <&str>::deserialize(value).ok())
+    }
+
+    /// Returns the value of `SecurityExchange <207>` as a market identifier
+    /// code, if present and valid.
+    pub fn security_exchange(&self) -> Option<Exchange> {
+        self.field_value(SECURITY_EXCHANGE)
+            .and_then(|value| Exchange::deserialize(value).ok())
+    }
+
+    /// Returns the [`RoutingKey`] of `self`. Like all other accessors, this
+    /// only looks at top-level fields, or at the fields of the current entry
+    /// for repeating group entries.
+    pub fn routing_key(&self) -> RoutingKey<'a> {
+        RoutingKey {
+            trading_session_id: self.trading_session_id(),
+            market_segment_id: self.market_segment_id(),
+            security_exchange: self.security_exchange(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tagvalue::{Decoder, RoutingKey};
+    use crate::{Dictionary, GetConfig};
+
+    #[test]
+    fn invalid_routing_fields_are_ignored() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        let msg = decoder
+            .decode(b"8=FIX.4.4|9=43|35=D|49=A|56=B|34=2|207=xeur|1300=EQ|336=\xff|10=000|")
+            .unwrap();
+        assert_eq!(
+            msg.routing_key(),
+            RoutingKey {
+                market_segment_id: Some("EQ"),
+                ..RoutingKey::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use super::utils::FIELD_CHECKSUM_LEN_IN_BYTES;
+use std::ops::Range;
+
+/// The byte range of messages which is covered by `Signature <89>`, as it
+/// varies by counterparty. See
+/// [`Config::signing_convention`](super::Config::signing_convention).
+///
+/// The signed range always starts at the beginning of the message, or at
+/// `MsgType <35>` without [`SigningConvention::include_framing`], and it
+/// always ends at `SignatureLength <93>`, after it with
+/// [`SigningConvention::include_signature_length`]. `CheckSum <10>` depends
+/// on the signature and is never signed, so messages without a signature are
+/// signed up to `CheckSum <10>`, as if `SignatureLength <93>` came next.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SigningConvention {
+    /// Whether `BeginString <8>` and `BodyLength <9>` are signed. As the
+    /// latter depends on the length of the signature, some counterparties
+    /// sign from `MsgType <35>` onwards.
+    ///
+    /// It is enabled by default.
+    pub include_framing: bool,
+    /// Whether `SignatureLength <93>` is signed, including its separator. This
+    /// only works with fixed-length signatures.
+    ///
+    /// It is disabled by default.
+    pub include_signature_length: bool,
+}
+
+impl SigningConvention {
+    /// Returns the signed byte range of a message of `len` bytes whose
+    /// payload, i.e. `MsgType <35>` and everything after it, starts at
+    /// `payload_start`. `signature_length` is the byte range of the whole
+    /// `SignatureLength <93>` field, if any.
+    pub(super) fn signed_range(
+        &self,
+        len: usize,
+        payload_start: usize,
+        signature_length: Option<Range<usize>>,
+    ) -> Range<usize> {
+        let start = if self.include_framing {
+            0
+        } else {
+            payload_start
+        };
+        let end = match signature_length {
+            Some(field) if self.include_signature_length => field.end,
+            Some(field) => field.start,
+            None => len - FIELD_CHECKSUM_LEN_IN_BYTES,
+        };
+        start..end
+    }
+}
+
+impl Default for SigningConvention {
+    fn default() -> Self {
+        Self {
+            include_framing: true,
+            include_signature_length: false,
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use super::Config;
+use crate::field_types::CheckSum;
+use crate::{Buffer, BufferWriter, FieldType, GetConfig};
+use std::fmt::Write;
+
+/// The type returned in the event of an error during
+/// [`StreamEncoder::finish`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum StreamEncodeError {
+    /// [`StreamEncoder::begin`] wasn't called.
+    #[error("No FIX message was started.")]
+    NotStarted,
+    /// [`StreamEncoder::push_group_entry`] was called outside of any repeating
+    /// group.
+    #[error("Group entry outside of any repeating group.")]
+    NoGroup,
+    /// A repeating group has fewer entries than declared.
+    #[error("Repeating group {tag} has {written} entries instead of {declared}.")]
+    GroupLength {
+        /// The `NumInGroup` tag of the repeating group.
+        tag: u32,
+        /// The number of entries declared by [`StreamEncoder::begin_group`].
+        declared: usize,
+        /// The number of entries that were actually written.
+        written: usize,
+    },
+    /// [`StreamEncoder::push_group_entry`] was called after all entries
+    /// declared by [`StreamEncoder::begin_group`] were written.
+    #[error("Repeating group {tag} has more than {declared} entries.")]
+    TooManyEntries {
+        /// The `NumInGroup` tag of the repeating group.
+        tag: u32,
+        /// The number of entries declared by [`StreamEncoder::begin_group`].
+        declared: usize,
+    },
+}
+
+#[derive(Debug, Copy, Clone)]
+struct OpenGroup {
+    tag: u32,
+    declared: usize,
+    written: usize,
+}
+
+/// A FIX encoder for very large messages, e.g. market data snapshots with
+/// thousands of repeating group entries.
+///
+/// Unlike [`Encoder`](super::Encoder), [`StreamEncoder`] requires the number
+/// of entries of each repeating group to be known in advance. In exchange,
+/// entries are appended one at a time and never need to be moved around. The
+/// body is kept in an internal buffer which is reused across messages, and
+/// [`StreamEncoder::finish`] writes the complete message, with the correct
+/// `BodyLength <9>` and `CheckSum <10>`.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::StreamEncoder;
+/// use fefix::prelude::*;
+///
+/// let mut encoder = StreamEncoder::new();
+/// encoder.config_mut().separator = b'|';
+/// encoder.begin(b"FIX.4.4", b"W");
+/// encoder.push_field(262, "foo");
+/// encoder.begin_group(268, 2);
+/// for price in [1.5f32, 1.75] {
+///     encoder.push_group_entry(|entry| {
+///         entry.push_field(269, "0");
+///         entry.push_field(270, price);
+///     });
+/// }
+///
+/// let mut buffer = Vec::new();
+/// let msg = encoder.finish(&mut buffer).unwrap();
+/// assert_eq!(
+///     msg,
+///     b"8=FIX.4.4|9=48|35=W|262=foo|268=2|269=0|270=1.5|269=0|270=1.75|10=171|"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StreamEncoder {
+    config: Config,
+    begin_string: Vec<u8>,
+    body: Vec<u8>,
+    // All repeating groups within the current entry (or outside of any group),
+    // even if complete, and those of its parent entries.
+    groups: Vec<OpenGroup>,
+    // The number of groups in `groups` that belong to parent entries.
+    entry_depth: usize,
+    error: Option<StreamEncodeError>,
+    is_started: bool,
+}
+
+impl StreamEncoder {
+    /// Creates a new [`StreamEncoder`] with [`Default`] configuration options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new FIX message, discarding any unfinished one.
+    pub fn begin(&mut self, begin_string: &[u8], msg_type: &[u8]) {
+        self.begin_string.clear();
+        self.begin_string.extend_from_slice(begin_string);
+        self.body.clear();
+        self.groups.clear();
+        self.entry_depth = 0;
+        self.error = None;
+        self.is_started = true;
+        self.push_field(35, msg_type);
+    }
+
+    /// Appends a field to the body of the current message.
+    pub fn push_field<'a, V>(&mut self, tag: u32, value: V)
+    where
+        V: FieldType<'a>,
+    {
+        write!(BufferWriter(&mut self.body), "{}=", tag).unwrap();
+        value.serialize(&mut self.body);
+        self.body.push(self.config.separator);
+    }
+
+    /// Appends the `num_in_group` field of a new repeating group with
+    /// `num_entries` entries. Exactly `num_entries` calls to
+    /// [`StreamEncoder::push_group_entry`] must follow, and entries can
+    /// contain nested repeating groups.
+    pub fn begin_group(&mut self, num_in_group: u32, num_entries: usize) {
+        self.push_field(num_in_group, num_entries);
+        self.groups.push(OpenGroup {
+            tag: num_in_group,
+            declared: num_entries,
+            written: 0,
+        });
+    }
+
+    /// Appends an entry to the last repeating group started by
+    /// [`StreamEncoder::begin_group`] at the same nesting level, i.e. within
+    /// the same parent entry. Its fields are written by `f`, starting with the
+    /// delimiter field.
+    pub fn push_group_entry<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let depth = self.groups.len();
+        if depth == self.entry_depth {
+            self.error.get_or_insert(StreamEncodeError::NoGroup);
+            return;
+        }
+        let group = self.groups[depth - 1];
+        if group.written == group.declared {
+            self.error.get_or_insert(StreamEncodeError::TooManyEntries {
+                tag: group.tag,
+                declared: group.declared,
+            });
+            return;
+        }
+        let parent_depth = std::mem::replace(&mut self.entry_depth, depth);
+        f(self);
+        self.entry_depth = parent_depth;
+        // Nested groups must be complete by the end of their parent entry.
+        if let Some(nested) = self.groups[depth..].iter().find(|g| g.written < g.declared) {
+            let error = group_length_error(nested);
+            self.error.get_or_insert(error);
+        }
+        self.groups.truncate(depth);
+        self.groups[depth - 1].written += 1;
+    }
+
+    /// Writes the current message to the end of `buffer`, complete with
+    /// `BeginString <8>`, `BodyLength <9>`, and `CheckSum <10>`, and returns
+    /// its byte contents.
+    pub fn finish<'b, B>(&mut self, buffer: &'b mut B) -> Result<&'b [u8], StreamEncodeError>
+    where
+        B: Buffer,
+    {
+        if !self.is_started {
+            return Err(StreamEncodeError::NotStarted);
+        }
+        self.is_started = false;
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        if let Some(group) = self.groups.iter().find(|g| g.written < g.declared) {
+            return Err(group_length_error(group));
+        }
+        let start = buffer.len();
+        let separator = self.config.separator;
+        buffer.extend_from_slice(b"8=");
+        buffer.extend_from_slice(&self.begin_string);
+        write!(
+            BufferWriter(buffer),
+            "{}9={}{}",
+            separator as char,
+            self.body.len(),
+            separator as char
+        )
+        .unwrap();
+        buffer.extend_from_slice(&self.body);
+        let checksum = CheckSum::compute(&buffer.as_slice()[start..]);
+        buffer.extend_from_slice(b"10=");
+        checksum.serialize(buffer);
+        buffer.extend_from_slice(&[separator]);
+        Ok(&buffer.as_slice()[start..])
+    }
+}
+
+fn group_length_error(group: &OpenGroup) -> StreamEncodeError {
+    StreamEncodeError::GroupLength {
+        tag: group.tag,
+        declared: group.declared,
+        written: group.written,
+    }
+}
+
+impl GetConfig for StreamEncoder {
+    type Config = Config;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn config_mut(&mut self) -> &mut Self::Config {
+        &mut self.config
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::definitions::fix44;
+    use crate::tagvalue::{Decoder, Encoder};
+    use crate::{Dictionary, FieldMap, RepeatingGroup, SetField};
+
+    fn encoder() -> StreamEncoder {
+        let mut encoder = StreamEncoder::new();
+        encoder.config_mut().separator = b'|';
+        encoder
+    }
+
+    #[test]
+    fn finish_without_begin_is_error() {
+        let mut buffer = Vec::new();
+        assert_eq!(
+            encoder().finish(&mut buffer),
+            Err(StreamEncodeError::NotStarted)
+        );
+    }
+
+    #[test]
+    fn missing_group_entries_are_detected() {
+        let mut encoder = encoder();
+        let mut buffer = Vec::new();
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 2);
+        encoder.push_group_entry(|entry| entry.push_field(269, "0"));
+        assert_eq!(
+            encoder.finish(&mut buffer),
+            Err(StreamEncodeError::GroupLength {
+                tag: 268,
+                declared: 2,
+                written: 1
+            })
+        );
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.push_group_entry(|entry| entry.push_field(269, "0"));
+        assert_eq!(encoder.finish(&mut buffer), Err(StreamEncodeError::NoGroup));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn incomplete_nested_group_is_detected() {
+        let mut encoder = encoder();
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 1);
+        encoder.push_group_entry(|entry| {
+            entry.push_field(269, "0");
+            entry.begin_group(453, 2);
+        });
+        assert!(matches!(
+            encoder.finish(&mut Vec::new()),
+            Err(StreamEncodeError::GroupLength { tag: 453, .. })
+        ));
+    }
+
+    #[test]
+    fn excess_group_entries_are_detected() {
+        let mut encoder = encoder();
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 1);
+        encoder.push_group_entry(|entry| entry.push_field(269, "0"));
+        encoder.push_group_entry(|entry| entry.push_field(269, "1"));
+        assert_eq!(
+            encoder.finish(&mut Vec::new()),
+            Err(StreamEncodeError::TooManyEntries {
+                tag: 268,
+                declared: 1
+            })
+        );
+
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 0);
+        encoder.push_group_entry(|entry| entry.push_field(269, "0"));
+        assert_eq!(
+            encoder.finish(&mut Vec::new()),
+            Err(StreamEncodeError::TooManyEntries {
+                tag: 268,
+                declared: 0
+            })
+        );
+    }
+
+    #[test]
+    fn excess_nested_group_entries_are_detected() {
+        let mut encoder = encoder();
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 2);
+        encoder.push_group_entry(|entry| {
+            entry.push_field(269, "0");
+            entry.begin_group(453, 1);
+            for party_id in ["A", "B"] {
+                entry.push_group_entry(|party| party.push_field(448, party_id));
+            }
+        });
+        encoder.push_group_entry(|entry| entry.push_field(269, "1"));
+        assert_eq!(
+            encoder.finish(&mut Vec::new()),
+            Err(StreamEncodeError::TooManyEntries {
+                tag: 453,
+                declared: 1
+            })
+        );
+
+        // Two consecutive nested groups within the same entry.
+        encoder.begin(b"FIX.4.4", b"W");
+        encoder.begin_group(268, 1);
+        encoder.push_group_entry(|entry| {
+            entry.push_field(269, "0");
+            entry.begin_group(453, 1);
+            entry.push_group_entry(|party| party.push_field(448, "A"));
+            entry.begin_group(454, 1);
+            entry.push_group_entry(|sub_id| sub_id.push_field(455, "B"));
+        });
+        let mut buffer = Vec::new();
+        assert_eq!(
+            encoder.finish(&mut buffer),
+            Ok(&b"8=FIX.4.4|9=41|35=W|268=1|269=0|453=1|448=A|454=1|455=B|10=191|"[..])
+        );
+    }
+
+    #[test]
+    fn same_message_as_encoder() {
+        const NUM_ENTRIES: usize = 10_000;
+
+        let mut stream_encoder = encoder();
+        stream_encoder.begin(b"FIX.4.4", b"W");
+        stream_encoder.push_field(262, "foo");
+        stream_encoder.begin_group(268, NUM_ENTRIES);
+        for i in 0..NUM_ENTRIES {
+            stream_encoder.push_group_entry(|entry| {
+                entry.push_field(269, "0");
+                entry.push_field(271, i);
+            });
+        }
+        let mut buffer = Vec::new();
+        let stream_encoded = stream_encoder.finish(&mut buffer).unwrap().to_vec();
+
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"W");
+        msg.set(fix44::MD_REQ_ID, "foo");
+        msg.set_group(fix44::NO_MD_ENTRIES, |group| {
+            for i in 0..NUM_ENTRIES {
+                group.entry(|entry| {
+                    entry.set(fix44::MD_ENTRY_TYPE, "0");
+                    entry.set(fix44::MD_ENTRY_SIZE, i);
+                });
+            }
+        });
+        let encoded = msg.done().0.to_vec();
+
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        decoder.config_mut().max_message_size = None;
+        let message = decoder.decode(&stream_encoded[..]).unwrap();
+        let group = message.group(268).unwrap();
+        assert_eq!(group.len(), NUM_ENTRIES);
+        assert_eq!(group.get(1234).unwrap().get(271), Ok(1234));
+        let stream_fields: Vec<_> = message
+            .fields()
+            .skip(1)
+            .map(|(t, v)| (t, v.to_vec()))
+            .collect();
+        let message = decoder.decode(&encoded[..]).unwrap();
+        let fields: Vec<_> = message
+            .fields()
+            .skip(1)
+            .map(|(t, v)| (t, v.to_vec()))
+            .collect();
+        assert_eq!(stream_fields, fields);
+    }
+}
@@ -0,0 +1,200 @@
+use nohash_hasher::IntSet;
+use std::borrow::Cow;
+
+/// What [`TextPolicy`] does with control characters in free-text fields.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ControlChars {
+    /// Fails with [`TextError`].
+    Reject,
+    /// Removes control characters from the value.
+    Strip,
+    /// Replaces each control character with its `\xHH` escape sequence,
+    /// e.g. `\x0A` for a newline. The escape sequences are meant for humans
+    /// and are never unescaped.
+    Escape,
+}
+
+/// The type returned in the event of a control character in a field which is
+/// subject to [`ControlChars::Reject`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Control character at byte {offset} of free-text field {tag}.")]
+pub struct TextError {
+    /// The tag of the offending field.
+    pub tag: u32,
+    /// The position of the first control character within the field value.
+    pub offset: usize,
+}
+
+/// Handling of control characters in free-text `String` fields, e.g.
+/// `Text <58>`, which often come from human operators and may contain
+/// newlines or even separators. See
+/// [`Encoder::set_text_policy`](super::Encoder::set_text_policy) and
+/// [`Decoder::set_text_policy`](super::Decoder::set_text_policy).
+///
+/// Control characters are all bytes below `0x20`, `0x7F`, and the field
+/// separator of [`Config`](super::Config). All other bytes, and UTF-8
+/// multibyte sequences in particular, are left as they are.
+///
+/// # Examples
+///
+/// ```
+/// use fefix::tagvalue::{ControlChars, TextPolicy};
+///
+/// let mut policy = TextPolicy::new(ControlChars::Escape);
+/// policy.add_tag(9000);
+/// assert!(policy.applies_to(58));
+/// assert_eq!(
+///     policy.sanitize(9000, "Héllo\nworld".as_bytes(), 0x1).unwrap(),
+///     "Héllo\\x0Aworld".as_bytes()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextPolicy {
+    control_chars: ControlChars,
+    tags: IntSet<u32>,
+}
+
+impl TextPolicy {
+    /// The free-text fields to which [`TextPolicy::new`] applies:
+    /// `Text <58>`, `SecurityDesc <107>`, `Subject <147>`, `Headline <148>`,
+    /// `ListStatusText <444>`, and `RejectText <1328>`.
+    pub const DEFAULT_TAGS: &'static [u32] = &[58, 107, 147, 148, 444, 1328];
+
+    /// Creates a new [`TextPolicy`] for [`TextPolicy::DEFAULT_TAGS`].
+    pub fn new(control_chars: ControlChars) -> Self {
+        Self::with_tags(control_chars, Self::DEFAULT_TAGS.iter().copied())
+    }
+
+    /// Creates a new [`TextPolicy`] for `tags` only.
+    pub fn with_tags<I>(control_chars: ControlChars, tags: I) -> Self
+    where
+        I: IntoIterator<Item = u32>,
+    {
+        Self {
+            control_chars,
+            tags: tags.into_iter().collect(),
+        }
+    }
+
+    /// Returns what `self` does with control characters.
+    pub fn control_chars(&self) -> ControlChars {
+        self.control_chars
+    }
+
+    /// Applies `self` to fields with `tag` as well.
+    pub fn add_tag(&mut self, tag: u32) {
+        self.tags.insert(tag);
+    }
+
+    /// Stops applying `self` to fields with `tag`.
+    pub fn remove_tag(&mut self, tag: u32) {
+        self.tags.remove(&tag);
+    }
+
+    /// Returns `true` if `self` applies to fields with `tag`.
+    pub fn applies_to(&self, tag: u32) -> bool {
+        self.tags.contains(&tag)
+    }
+
+    /// Applies `self` to `value`, regardless of [`TextPolicy::applies_to`].
+    /// Returns [`Cow::Borrowed`] if `value` has no control characters, given
+    /// `separator` as the field separator.
+    pub fn sanitize<'a>(
+        &self,
+        tag: u32,
+        value: &'a [u8],
+        separator: u8,
+    ) -> Result<Cow<'a, [u8]>, TextError> {
+        let is_control = |byte: u8| byte < 0x20 || byte == 0x7F || byte == separator;
+        let offset = match value.iter().position(|byte| is_control(*byte)) {
+            Some(offset) => offset,
+            None => return Ok(Cow::Borrowed(value)),
+        };
+        let mut sanitized = value[..offset].to_vec();
+        for &byte in &value[offset..] {
+            if !is_control(byte) {
+                sanitized.push(byte);
+                continue;
+            }
+            match self.control_chars {
+                ControlChars::Reject => return Err(TextError { tag, offset }),
+                ControlChars::Strip => {}
+                ControlChars::Escape => {
+                    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+                    sanitized.extend_from_slice(&[
+                        b'\\',
+                        b'x',
+                        HEX_DIGITS[usize::from(byte >> 4)],
+                        HEX_DIGITS[usize::from(byte & 0xF)],
+                    ]);
+                }
+            }
+        }
+        Ok(Cow::Owned(sanitized))
+    }
+}
+
+impl Default for TextPolicy {
+    fn default() -> Self {
+        Self::new(ControlChars::Escape)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{DecodeError, Decoder, Encoder};
+    use crate::{Dictionary, FieldMap, GetConfig, SetField};
+
+    #[test]
+    fn control_chars_are_handled_according_to_policy() {
+        let value = "a\x01b|c\n\u{e9}".as_bytes();
+        let sanitize = |control_chars| TextPolicy::new(control_chars).sanitize(58, value, b'|');
+        assert_eq!(
+            sanitize(ControlChars::Strip).unwrap(),
+            "abc\u{e9}".as_bytes()
+        );
+        assert_eq!(
+            sanitize(ControlChars::Escape).unwrap(),
+            "a\\x01b\\x7Cc\\x0A\u{e9}".as_bytes()
+        );
+        assert_eq!(
+            sanitize(ControlChars::Reject).unwrap_err(),
+            TextError { tag: 58, offset: 1 }
+        );
+        assert!(matches!(
+            TextPolicy::new(ControlChars::Reject).sanitize(58, b"ok", b'|'),
+            Ok(Cow::Borrowed(_))
+        ));
+    }
+
+    #[test]
+    fn operator_text_survives_re_encoding() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.set_text_policy(TextPolicy::new(ControlChars::Reject));
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        encoder.set_text_policy(TextPolicy::with_tags(ControlChars::Strip, [58, 9000]));
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"3");
+        msg.set(58, "line 1\nline 2|\u{2713}");
+        msg.set(9000, "\x01");
+        msg.set(9001, "x\ty");
+        let data = msg.try_done().unwrap().0.to_vec();
+        assert!(
+            String::from_utf8_lossy(&data).contains("|58=line 1line 2\u{2713}|9000=|9001=x\ty|")
+        );
+
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.get_raw(58), Some("line 1line 2\u{2713}".as_bytes()));
+        decoder.set_text_policy(TextPolicy::with_tags(ControlChars::Reject, [9001]));
+        assert!(matches!(
+            decoder.decode(&data[..]),
+            Err(DecodeError::Text(TextError {
+                tag: 9001,
+                offset: 1
+            }))
+        ));
+    }
+}
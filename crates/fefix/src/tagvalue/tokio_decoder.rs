@@ -1,12 +1,73 @@
-use super::{Config, DecodeError, Decoder, RawDecoder, RawFrame};
+use super::{raw_decoder, Config, DecodeError, Decoder, RawDecoder, RawFrame};
 use crate::{Dictionary, GetConfig};
 use bytes::{Bytes, BytesMut};
 use tokio_util::codec;
 
-#[derive(Debug)]
+/// A [`codec::Decoder`] of [`RawFrame`]s, e.g. for use with
+/// [`FramedRead`](tokio_util::codec::FramedRead).
+///
+/// Incomplete frames are left in the read buffer. As soon as the header of a
+/// frame is available, its length (as declared by `BodyLength <9>`) is
+/// retained, so that later calls only check whether enough bytes have
+/// arrived instead of scanning the frame again. This keeps the total work
+/// linear in the length of the frame, even when it's delivered in many small
+/// chunks.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+/// use fefix::tagvalue::TokioRawDecoder;
+/// use fefix::GetConfig;
+/// use tokio_util::codec::Decoder;
+///
+/// let mut decoder = TokioRawDecoder::new();
+/// decoder.config_mut().separator = b'|';
+/// decoder.config_mut().verify_checksum = false;
+///
+/// let mut buffer = BytesMut::from(&b"8=FIX.4.2|9=40|35=D|49=AFUNDMGR|"[..]);
+/// assert!(decoder.decode(&mut buffer).unwrap().is_none());
+/// buffer.extend_from_slice(b"56=ABROKER|15=USD|59=0|10=091|8=FIX.4.2|");
+/// let frame = decoder.decode(&mut buffer).unwrap().unwrap();
+/// assert_eq!(frame.payload(), b"35=D|49=AFUNDMGR|56=ABROKER|15=USD|59=0|");
+/// assert_eq!(&buffer[..], b"8=FIX.4.2|");
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(doc_cfg, doc(cfg(feature = "utils-tokio")))]
 pub struct TokioRawDecoder {
-    dict: Dictionary,
     raw_decoder: RawDecoder,
+    // The length of the frame at the start of the read buffer, once its
+    // header is available.
+    frame_len: Option<usize>,
+    #[cfg(test)]
+    num_scanned_bytes: usize,
+}
+
+impl TokioRawDecoder {
+    /// Creates a new [`TokioRawDecoder`] with default configuration options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn parse_frame_len(&mut self, data: &[u8]) -> Result<Option<usize>, DecodeError> {
+        let separator = self.config().separator;
+        let max_message_size = self.config().max_message_size;
+        #[cfg(test)]
+        {
+            self.num_scanned_bytes += data.len();
+        }
+        match raw_decoder::frame_len(data, separator) {
+            Some(len) if max_message_size.is_none_or(|max| len <= max) => Ok(Some(len)),
+            // We need both `BeginString <8>` and `BodyLength <9>` before we
+            // can tell how long the frame is.
+            None if raw_decoder::is_header_prefix(data, separator)
+                && max_message_size.is_none_or(|max| data.len() < max) =>
+            {
+                Ok(None)
+            }
+            _ => Err(DecodeError::Invalid),
+        }
+    }
 }
 
 impl codec::Decoder for TokioRawDecoder {
@@ -14,17 +75,32 @@ impl codec::Decoder for TokioRawDecoder {
     type Error = DecodeError;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        let split = src.split();
-        let result = self.raw_decoder.decode(split);
-        match result {
-            Ok(raw_frame) => Ok(Some(RawFrame {
-                data: raw_frame.data.freeze(),
-                begin_string: raw_frame.begin_string,
-                payload: raw_frame.payload,
-            })),
-            Err(DecodeError::Invalid) => Ok(None),
-            Err(e) => Err(e),
+        let frame_len = match self.frame_len {
+            Some(frame_len) => frame_len,
+            None => match self.parse_frame_len(src)? {
+                Some(frame_len) => {
+                    self.frame_len = Some(frame_len);
+                    frame_len
+                }
+                None => return Ok(None),
+            },
+        };
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
         }
+        self.frame_len = None;
+        let data = src.split_to(frame_len);
+        #[cfg(test)]
+        {
+            self.num_scanned_bytes += data.len();
+        }
+        let raw_frame = self.raw_decoder.decode(data)?;
+        Ok(Some(RawFrame {
+            data: raw_frame.data.freeze(),
+            begin_string: raw_frame.begin_string,
+            payload: raw_frame.payload,
+        }))
     }
 }
 
@@ -61,3 +137,71 @@ pub struct TokioDecoder {
 //        }
 //    }
 //}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::Encoder;
+    use crate::SetField;
+    use codec::Decoder as _;
+
+    fn decoder() -> TokioRawDecoder {
+        let mut decoder = TokioRawDecoder::new();
+        decoder.config_mut().separator = b'|';
+        decoder
+    }
+
+    fn large_message() -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"0");
+        for tag in 5000..5500u32 {
+            msg.set(tag, tag);
+        }
+        msg.done().0.to_vec()
+    }
+
+    #[test]
+    fn one_byte_at_a_time() {
+        let mut decoder = decoder();
+        let data = large_message();
+        let mut buffer = BytesMut::new();
+        for (i, byte) in data.iter().enumerate() {
+            buffer.extend_from_slice(&[*byte]);
+            let frame = decoder.decode(&mut buffer).unwrap();
+            assert_eq!(frame.is_some(), i == data.len() - 1);
+            if let Some(frame) = frame {
+                assert_eq!(frame.as_bytes(), &data[..]);
+            }
+        }
+        assert!(buffer.is_empty());
+        // The header is scanned once per byte until `BodyLength <9>` is
+        // complete, and the whole frame only once.
+        assert!(decoder.num_scanned_bytes < data.len() + 20 * 20);
+    }
+
+    #[test]
+    fn back_to_back_frames() {
+        let mut decoder = decoder();
+        let data = large_message();
+        let mut buffer = BytesMut::new();
+        buffer.extend_from_slice(&data);
+        buffer.extend_from_slice(&data[..100]);
+        assert!(decoder.decode(&mut buffer).unwrap().is_some());
+        assert!(decoder.decode(&mut buffer).unwrap().is_none());
+        buffer.extend_from_slice(&data[100..]);
+        let frame = decoder.decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(frame.as_bytes(), &data[..]);
+        assert!(decoder.decode(&mut buffer).unwrap().is_none());
+    }
+
+    #[test]
+    fn frames_above_max_message_size() {
+        let mut decoder = decoder();
+        decoder.config_mut().max_message_size = Some(1000);
+        let data = large_message();
+        let mut buffer = BytesMut::from(&data[..30]);
+        assert!(decoder.decode(&mut buffer).is_err());
+    }
+}
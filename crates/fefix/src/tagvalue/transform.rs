@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+type Transform = dyn Fn(&[u8]) -> Cow<[u8]> + Send + Sync;
+
+/// Rewrites the value of a specific field on the wire, e.g. the internal
+/// `Symbol <55>` `ABC` into a venue's `ABC.N` and back. See
+/// [`Encoder::add_field_transform`](super::Encoder::add_field_transform) and
+/// [`Decoder::add_field_transform`](super::Decoder::add_field_transform).
+///
+/// [`FieldTransform`] is cheap to clone, so that the same instance can be given
+/// to both an [`Encoder`](super::Encoder) and a [`Decoder`](super::Decoder).
+#[derive(Clone)]
+pub struct FieldTransform {
+    on_encode: Arc<Transform>,
+    on_decode: Arc<Transform>,
+    length_tag: Option<u32>,
+}
+
+impl FieldTransform {
+    /// Creates a new [`FieldTransform`] which applies `on_encode` to outbound
+    /// values and `on_decode` to inbound values. Either one can return
+    /// [`Cow::Borrowed`] to leave values as they are.
+    pub fn new<E, D>(on_encode: E, on_decode: D) -> Self
+    where
+        E: Fn(&[u8]) -> Cow<[u8]> + Send + Sync + 'static,
+        D: Fn(&[u8]) -> Cow<[u8]> + Send + Sync + 'static,
+    {
+        Self {
+            on_encode: Arc::new(on_encode),
+            on_decode: Arc::new(on_decode),
+            length_tag: None,
+        }
+    }
+
+    /// Declares the field to be of type `data`, and `length_tag` to be its
+    /// `Length` field, e.g. `SignatureLength <93>` for `Signature <89>`.
+    /// [`Encoder`](super::Encoder) then recomputes the `Length` field if it
+    /// immediately precedes the transformed field. This is not needed for
+    /// [`Decoder`](super::Decoder), which knows about `Length` fields from
+    /// its [`Dictionary`](crate::Dictionary).
+    pub fn with_length_tag(mut self, length_tag: u32) -> Self {
+        self.length_tag = Some(length_tag);
+        self
+    }
+
+    pub(super) fn length_tag(&self) -> Option<u32> {
+        self.length_tag
+    }
+
+    /// Returns the transformed `value` for encoding, or [`None`] if it's
+    /// unchanged.
+    pub(super) fn encode(&self, value: &[u8]) -> Option<Vec<u8>> {
+        changed(value, (self.on_encode)(value))
+    }
+
+    /// Like [`FieldTransform::encode`], but for decoding.
+    pub(super) fn decode(&self, value: &[u8]) -> Option<Vec<u8>> {
+        changed(value, (self.on_decode)(value))
+    }
+}
+
+fn changed(value: &[u8], transformed: Cow<[u8]>) -> Option<Vec<u8>> {
+    match transformed {
+        Cow::Borrowed(borrowed) if std::ptr::eq(borrowed, value) => None,
+        transformed => Some(transformed.into_owned()),
+    }
+}
+
+impl Debug for FieldTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FieldTransform")
+            .field("length_tag", &self.length_tag)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{Decoder, Encoder};
+    use crate::{Dictionary, FieldMap, GetConfig, SetField};
+
+    fn transforms() -> [(u32, FieldTransform); 2] {
+        [
+            (
+                55,
+                FieldTransform::new(
+                    |symbol| Cow::Owned([symbol, b".N"].concat()),
+                    |symbol| Cow::Borrowed(symbol.strip_suffix(b".N").unwrap_or(symbol)),
+                ),
+            ),
+            (
+                89,
+                FieldTransform::new(
+                    |signature| Cow::Owned(signature.repeat(2)),
+                    |signature| Cow::Borrowed(&signature[..signature.len() / 2]),
+                )
+                .with_length_tag(93),
+            ),
+        ]
+    }
+
+    #[test]
+    fn transforms_round_trip() {
+        let mut encoder = Encoder::new();
+        encoder.config_mut().separator = b'|';
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        for (tag, transform) in transforms() {
+            encoder.add_field_transform(tag, transform.clone());
+            decoder.add_field_transform(tag, transform);
+        }
+        let mut buffer = Vec::new();
+        let mut msg = encoder.start_message(b"FIX.4.4", &mut buffer, b"D");
+        msg.set(11, "foo");
+        msg.set(55, "ABC");
+        msg.set(93, 3);
+        msg.set(89, "x|z");
+        let data = msg.done().0.to_vec();
+        let data_str = String::from_utf8_lossy(&data);
+        assert!(data_str.contains("|11=foo|55=ABC.N|93=6|89=x|zx|z|10="));
+
+        let msg = decoder.decode(&data[..]).unwrap();
+        assert_eq!(msg.get_raw(11), Some(b"foo" as &[u8]));
+        assert_eq!(msg.get_raw(55), Some(b"ABC" as &[u8]));
+        assert_eq!(msg.get_raw(93), Some(b"3" as &[u8]));
+        assert_eq!(msg.get_raw(89), Some(b"x|z" as &[u8]));
+        assert_eq!(msg.as_bytes(), &data[..]);
+    }
+}
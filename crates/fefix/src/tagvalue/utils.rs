@@ -31,7 +31,16 @@ pub fn verify_checksum(headerless_msg: &[u8]) -> Result<(), DecodeError> {
     if nominal_checksum == actual_checksum {
         Ok(())
     } else {
-        Err(DecodeError::CheckSum)
+        trace_event!(
+            warn,
+            expected = actual_checksum.0,
+            actual = nominal_checksum.0,
+            "FIX message with invalid `CheckSum <10>`"
+        );
+        Err(DecodeError::CheckSumMismatch {
+            expected: actual_checksum.0,
+            actual: nominal_checksum.0,
+        })
     }
 }
 
@@ -54,10 +63,138 @@ pub fn verify_body_length(
     }
 }
 
+/// Verifies that `data` ends with `CheckSum <10>` right after the body, which
+/// ends at `end_of_body` according to `BodyLength <9>`.
+pub fn verify_trailer(data: &[u8], end_of_body: usize, separator: u8) -> Result<(), DecodeError> {
+    let trailer = data.get(end_of_body..).ok_or(DecodeError::Invalid)?;
+    if !trailer.starts_with(b"10=") {
+        // `CheckSum <10>` elsewhere means that `BodyLength <9>` is wrong,
+        // rather than the trailer.
+        return if contains_checksum_tag(data, separator) {
+            Err(DecodeError::Invalid)
+        } else {
+            Err(DecodeError::MissingCheckSum)
+        };
+    }
+    match trailer.iter().position(|byte| *byte == separator) {
+        // Messages must end with a separator.
+        None => Err(DecodeError::Invalid),
+        Some(i) if i + 1 < trailer.len() => Err(DecodeError::FieldAfterCheckSum),
+        Some(i)
+            if i + 1 == FIELD_CHECKSUM_LEN_IN_BYTES
+                && trailer[3..i].iter().all(u8::is_ascii_digit) =>
+        {
+            Ok(())
+        }
+        _ => Err(DecodeError::CheckSum),
+    }
+}
+
+/// Returns `true` if `data` contains a `CheckSum <10>` field anywhere but at
+/// its very start.
+pub fn contains_checksum_tag(data: &[u8], separator: u8) -> bool {
+    let checksum_tag = [separator, b'1', b'0', b'='];
+    data.windows(4).any(|window| window == checksum_tag)
+}
+
+/// Writes the hand-written FIX message `fixture` to `buffer` as a well-formed
+/// message with `separator`. Fields can be delimited by SOH, `|`, or `^A`, and
+/// `BodyLength <9>` and `CheckSum <10>` are computed anew (whether they're
+/// present or not).
+pub fn normalize_fixture(
+    fixture: &str,
+    separator: u8,
+    buffer: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    let delimiter = if fixture.contains('\x01') {
+        "\x01"
+    } else if fixture.contains("^A") {
+        "^A"
+    } else {
+        "|"
+    };
+    let mut fields = fixture
+        .trim()
+        .split(delimiter)
+        .filter(|field| !field.is_empty());
+    let begin_string = fields
+        .next()
+        .filter(|field| field.starts_with("8="))
+        .ok_or(DecodeError::Invalid)?;
+    let body: Vec<&str> = fields
+        .filter(|field| !field.starts_with("9=") && !field.starts_with("10="))
+        .collect();
+    let body_length: usize = body.iter().map(|field| field.len() + 1).sum();
+    buffer.clear();
+    buffer.extend_from_slice(begin_string.as_bytes());
+    buffer.push(separator);
+    buffer.extend_from_slice(b"9=");
+    body_length.serialize(buffer);
+    buffer.push(separator);
+    for field in body {
+        buffer.extend_from_slice(field.as_bytes());
+        buffer.push(separator);
+    }
+    let checksum = CheckSum::compute(buffer);
+    buffer.extend_from_slice(b"10=");
+    checksum.serialize(buffer);
+    buffer.push(separator);
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn trailer_errors() {
+        let verify = |data: &[u8], end_of_body| verify_trailer(data, end_of_body, b'|');
+        assert!(verify(b"35=0|10=123|", 5).is_ok());
+        assert!(matches!(
+            verify(b"35=0|", 5),
+            Err(DecodeError::MissingCheckSum)
+        ));
+        assert!(matches!(
+            verify(b"35=0|58=x|", 5),
+            Err(DecodeError::MissingCheckSum)
+        ));
+        assert!(matches!(
+            verify(b"35=0|10=123|58=x|", 5),
+            Err(DecodeError::FieldAfterCheckSum)
+        ));
+        assert!(matches!(
+            verify(b"35=0|10=12|", 5),
+            Err(DecodeError::CheckSum)
+        ));
+        assert!(matches!(
+            verify(b"35=0|10=1x3|", 5),
+            Err(DecodeError::CheckSum)
+        ));
+        assert!(matches!(
+            verify(b"35=0|10=123", 5),
+            Err(DecodeError::Invalid)
+        ));
+        // `BodyLength <9>` is too short.
+        assert!(matches!(
+            verify(b"35=0|10=123|", 3),
+            Err(DecodeError::Invalid)
+        ));
+    }
+
+    #[test]
+    fn fixtures_are_normalized() {
+        let mut buffer = Vec::new();
+        for fixture in [
+            "8=FIX.4.4|35=0|49=A|56=B|",
+            "8=FIX.4.4\x019=5\x0135=0\x0149=A\x0156=B\x0110=999\x01",
+            "8=FIX.4.4^A35=0^A49=A^A56=B\n",
+        ] {
+            normalize_fixture(fixture, b'|', &mut buffer).unwrap();
+            assert_eq!(buffer, b"8=FIX.4.4|9=15|35=0|49=A|56=B|10=018|");
+        }
+        assert!(normalize_fixture("35=0|49=A", b'|', &mut buffer).is_err());
+    }
+
     #[test]
     fn correct_retrieval_of_checksum_digits() {
         assert_eq!(
@@ -0,0 +1,151 @@
+//! An allocation-counting global allocator, to assert that performance
+//! sensitive code paths, e.g. decoding with a warmed up
+//! [`Decoder`](crate::tagvalue::Decoder), perform exactly as many allocations
+//! as expected.
+//!
+//! [`CountingAllocator`] must be installed as the global allocator of the test
+//! binary, otherwise [`count_allocs`] always returns zero.
+//!
+//! # Examples
+//!
+//! ```
+//! use fefix::test_alloc::{count_allocs, CountingAllocator};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator;
+//!
+//! fn main() {
+//!     let mut buffer: Vec<u8> = Vec::with_capacity(64);
+//!     assert_eq!(count_allocs(|| buffer.extend_from_slice(b"35=0|")), 0);
+//!     assert_eq!(count_allocs(|| vec![0u8; 1000]), 1);
+//! }
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    // Allocations are counted per thread, so that concurrently running tests
+    // don't interfere with each other.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record_allocation() {
+    // The thread-local may already be gone while the thread shuts down.
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// A [`GlobalAlloc`] which forwards to [`System`] and counts allocations,
+/// reallocations included, on each thread. See [`count_allocs`].
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Calls `f` and returns the number of allocations it performed on the
+/// current thread, reallocations included. `f` may return a value, e.g. to
+/// keep the code under test from being optimized away, which is dropped
+/// afterwards.
+///
+/// This only works if [`CountingAllocator`] is the global allocator.
+pub fn count_allocs<F, R>(f: F) -> usize
+where
+    F: FnOnce() -> R,
+{
+    let before = allocations();
+    let output = f();
+    let after = allocations();
+    drop(output);
+    after - before
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tagvalue::{CompiledMessage, Config, Decoder, Encoder};
+    use crate::{Dictionary, GetConfig, SetField};
+
+    const HEARTBEAT: &[u8] = b"8=FIX.4.4|9=42|35=0|49=A|56=B|34=12|52=20100304-07:59:30|10=185|";
+    const MARKET_DATA: &[u8] =
+        b"8=FIX.4.4|9=66|35=W|49=A|56=B|34=12|55=EUR/USD|268=2|269=0|270=1.5|269=1|270=1.6|10=000|";
+
+    #[test]
+    fn counts_allocations() {
+        assert_eq!(count_allocs(|| ()), 0);
+        assert_eq!(count_allocs(|| Vec::<u8>::with_capacity(8)), 1);
+        assert_eq!(
+            count_allocs(|| vec![String::from("a"), String::from("b")]),
+            3
+        );
+    }
+
+    #[test]
+    fn decoding_doesnt_allocate_in_steady_state() {
+        let mut decoder = Decoder::new(Dictionary::fix44());
+        decoder.config_mut().separator = b'|';
+        decoder.config_mut().verify_checksum = false;
+        for data in [HEARTBEAT, MARKET_DATA] {
+            decoder.decode(data).unwrap();
+        }
+        for data in [HEARTBEAT, MARKET_DATA] {
+            assert_eq!(count_allocs(|| decoder.decode(data).is_ok()), 0);
+        }
+    }
+
+    #[test]
+    fn encoding_into_a_buffer_doesnt_allocate_in_steady_state() {
+        let mut encoder = Encoder::default();
+        encoder.config_mut().separator = b'|';
+        let mut buffer = Vec::new();
+        let mut encode = |buffer: &mut Vec<u8>| {
+            buffer.clear();
+            let mut msg = encoder.start_message(b"FIX.4.4", buffer, b"0");
+            msg.set(49, "A");
+            msg.set(56, "B");
+            msg.set(34, 12u64);
+            msg.done().0.len()
+        };
+        encode(&mut buffer);
+        assert_eq!(count_allocs(|| encode(&mut buffer)), 0);
+    }
+
+    #[test]
+    fn compiled_messages_dont_allocate_with_enough_capacity() {
+        let mut config = Config::default();
+        config.separator = b'|';
+        let heartbeat = CompiledMessage::new(
+            &Dictionary::fix44(),
+            &config,
+            b"FIX.4.4",
+            b"0",
+            &[49, 56, 34],
+        )
+        .unwrap();
+        let mut buffer = Vec::with_capacity(256);
+        let values: [&[u8]; 3] = [b"A", b"B", b"12"];
+        assert_eq!(count_allocs(|| heartbeat.emit(&mut buffer, &values).1), 0);
+    }
+}
@@ -10,3 +10,27 @@ macro_rules! dbglog {
         }
     }}
 }
+
+/// Emits a [`tracing`](https://docs.rs/tracing) event at the given level, e.g.
+/// `trace_event!(debug, tag = 35, "...")`. It expands to nothing at all unless
+/// the `utils-tracing` feature is enabled, and field expressions are never
+/// evaluated in that case.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)*) => {{
+        #[cfg(feature = "utils-tracing")]
+        {
+            tracing::$level!($($arg)*);
+        }
+    }}
+}
+
+/// Enters a [`tracing`](https://docs.rs/tracing) span at the debug level
+/// until the end of the enclosing block, e.g.
+/// `trace_span!("decode", len = 42)`. Just like [`trace_event!`], it expands
+/// to nothing at all unless the `utils-tracing` feature is enabled.
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "utils-tracing")]
+        let _span = tracing::debug_span!($($arg)*).entered();
+    }
+}
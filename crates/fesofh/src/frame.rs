@@ -134,10 +134,13 @@ impl<'a> Frame<&'a [u8]> {
     /// ```
     pub fn deserialize(data: &'a [u8]) -> Result<Self, Error> {
         let header = Header::from_bytes(data)?;
-        Ok(Frame::new(
-            header.encoding_type,
-            &data[Header::LENGTH_IN_BYTES..header.nominal_message_length_in_bytes],
-        ))
+        let len = header.nominal_message_length_in_bytes;
+        let payload = data
+            .get(Header::LENGTH_IN_BYTES..len)
+            .ok_or_else(|| Error::Incomplete {
+                needed: len - data.len(),
+            })?;
+        Ok(Frame::new(header.encoding_type, payload))
     }
 }
 
@@ -201,6 +204,14 @@ mod test {
         ));
     }
 
+    #[test]
+    fn decode_incomplete_message() {
+        assert!(matches!(
+            Frame::<&[u8]>::deserialize(&[0, 0, 0, 10, 0, 0, 42]),
+            Err(Error::Incomplete { needed: 3 })
+        ));
+    }
+
     #[test]
     fn decode_empty_message() {
         let frame = Frame::<&[u8]>::deserialize(&[0, 0, 0, 6, 0, 0]).unwrap();
@@ -208,6 +219,12 @@ mod test {
         assert_eq!(frame.payload(), &[]);
     }
 
+    #[test]
+    fn decode_with_trailing_bytes() {
+        let frame = Frame::<&[u8]>::deserialize(&[0, 0, 0, 7, 0, 0, 42, 0, 0]).unwrap();
+        assert_eq!(frame.payload(), &[42]);
+    }
+
     #[test]
     fn encode_then_decode_should_have_no_effect() {
         fn prop(encoding_type: u16, payload: Vec<u8>) -> bool {
@@ -49,6 +49,7 @@ pub use tokio_codec::TokioCodec;
 /// The type returned in the event of an error when decoding SOFH-enclosed
 /// messages.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
     /// The SOFH-enclosed message's length is outside the legal range.
     #[error("The SOFH-enclosed message's length is outside the legal range.")]
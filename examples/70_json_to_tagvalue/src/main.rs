@@ -1,34 +1,16 @@
-use fefix::json::FieldOrGroup;
-use fefix::prelude::*;
-
 const JSON_FIX_MESSAGE: &str = include_str!("fix-example.json");
 
 fn main() {
-    let dictionary = fefix::Dictionary::fix42();
+    let dictionary = fefix::Dictionary::precompiled(fefix::dict::AppVersion::Fix42);
     let mut decoder = fefix::json::Decoder::new(dictionary.clone());
     let mut encoder = fefix::tagvalue::Encoder::new();
     let mut buffer = Vec::new();
 
     let json_msg = decoder.decode(JSON_FIX_MESSAGE.as_bytes()).unwrap();
-    let msg_type = json_msg.get(fix42::MSG_TYPE).unwrap();
-    let begin_string = json_msg.get(fix42::BEGIN_STRING).unwrap();
-
-    let mut fix_msg_builder = encoder.start_message(begin_string, &mut buffer, msg_type);
-
-    for (field_name, field_value) in json_msg.iter_fields() {
-        let field = dictionary
-            .field_by_name(field_name)
-            .expect("Invalid FIX.4.2 field!");
-
-        match field_value {
-            FieldOrGroup::Field(s) => {
-                fix_msg_builder.set(field.tag(), s.as_ref());
-            }
-            FieldOrGroup::Group(_g) => {}
-        }
-    }
-
-    let fix_msg = fix_msg_builder.done().0;
+    // Repeating groups, i.e. `NoMDEntries`, are converted as well.
+    let fix_msg = json_msg
+        .encode_tagvalue(&dictionary, &mut encoder, &mut buffer)
+        .expect("Invalid FIX.4.2 message!");
 
     println!("Successful conversion from JSON syntax to tag=value|.");
     println!();